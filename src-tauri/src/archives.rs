@@ -0,0 +1,242 @@
+// Archive inspection
+//
+// Old backups and exports sit in Downloads as multi-gigabyte .zip/.tar.gz
+// files. Listing what is inside (without extracting anything) lets the user
+// tell a redundant copy from the only one. Zip archives are read from their
+// central directory; tar archives have none, so their headers are streamed
+// and the data between them skipped, which for compressed tars still means
+// decompressing. That can take minutes, so every read checks the cancel
+// token.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use ts_rs::TS;
+
+/// Archives smaller than this aren't worth opening
+pub const INSPECT_MIN_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Compressed tars above this are skipped: listing them means decompressing all of it
+const MAX_COMPRESSED_TAR_SIZE: u64 = 8 * 1024 * 1024 * 1024;
+
+/// Stop listing after this many entries and mark the summary truncated
+const MAX_ENTRIES: usize = 100_000;
+
+/// Largest entries reported per archive
+const TOP_ENTRIES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchiveEntry {
+    pub name: String,
+    #[ts(type = "number")]
+    pub size: u64, // Uncompressed
+}
+
+/// What an archive contains, read without extracting it
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ArchiveSummary {
+    pub format: String, // "zip", "tar", "tar.gz"
+    pub entry_count: usize,
+    #[ts(type = "number")]
+    pub uncompressed_size: u64,
+    pub top_entries: Vec<ArchiveEntry>, // Largest files first
+    pub truncated: bool, // Listing stopped early; counts and sizes are partial
+}
+
+#[derive(Default)]
+struct Listing {
+    entry_count: usize,
+    uncompressed_size: u64,
+    top_entries: Vec<ArchiveEntry>,
+    truncated: bool,
+}
+
+impl Listing {
+    /// Count a file entry; false once the entry limit is reached
+    fn add(&mut self, name: String, size: u64) -> bool {
+        if self.entry_count >= MAX_ENTRIES {
+            self.truncated = true;
+            return false;
+        }
+        self.entry_count += 1;
+        self.uncompressed_size += size;
+
+        if self.top_entries.len() < TOP_ENTRIES || self.top_entries.last().map_or(true, |e| size > e.size) {
+            self.top_entries.push(ArchiveEntry { name, size });
+            self.top_entries.sort_by(|a, b| b.size.cmp(&a.size));
+            self.top_entries.truncate(TOP_ENTRIES);
+        }
+        true
+    }
+
+    fn into_summary(self, format: &str) -> ArchiveSummary {
+        ArchiveSummary {
+            format: format.to_string(),
+            entry_count: self.entry_count,
+            uncompressed_size: self.uncompressed_size,
+            top_entries: self.top_entries,
+            truncated: self.truncated,
+        }
+    }
+}
+
+fn is_cancelled(cancel: Option<&AtomicBool>) -> bool {
+    cancel.is_some_and(|c| c.load(Ordering::Relaxed))
+}
+
+/// Reader that fails once the cancel token is set, so a long decompression stops promptly
+struct Cancellable<'a, R> {
+    inner: R,
+    cancel: Option<&'a AtomicBool>,
+}
+
+impl<R: Read> Read for Cancellable<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if is_cancelled(self.cancel) {
+            return Err(io::Error::other("cancelled"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+/// Archive format from the file name, if it's one we can list
+fn archive_format(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        Some("zip")
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some("tar.gz")
+    } else if name.ends_with(".tar") {
+        Some("tar")
+    } else {
+        None
+    }
+}
+
+fn list_zip(file: File, cancel: Option<&AtomicBool>) -> Option<Listing> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file)).ok()?;
+    let mut listing = Listing::default();
+
+    for i in 0..archive.len() {
+        if is_cancelled(cancel) {
+            return None;
+        }
+        // Raw access reads only the directory record, never the data
+        let Ok(entry) = archive.by_index_raw(i) else { continue };
+        if entry.is_dir() {
+            continue;
+        }
+        if !listing.add(entry.name().to_string(), entry.size()) {
+            break;
+        }
+    }
+
+    Some(listing)
+}
+
+fn list_tar<R: Read>(reader: R) -> Option<Listing> {
+    let mut archive = tar::Archive::new(reader);
+    let mut listing = Listing::default();
+
+    for entry in archive.entries().ok()? {
+        // A corrupt or cut-off archive still reports what was read so far
+        let Ok(entry) = entry else {
+            listing.truncated = true;
+            break;
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry
+            .path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !listing.add(name, entry.size()) {
+            break;
+        }
+    }
+
+    Some(listing)
+}
+
+/// List a .zip, .tar or .tar.gz archive without extracting it
+///
+/// Returns None for other files, unreadable archives, compressed tars too
+/// large to stream through in reasonable time, and when cancelled.
+pub fn inspect_archive(path: &Path, cancel: Option<&AtomicBool>) -> Option<ArchiveSummary> {
+    let format = archive_format(path)?;
+    let file = File::open(path).ok()?;
+
+    let listing = match format {
+        "zip" => list_zip(file, cancel)?,
+        "tar" => list_tar(Cancellable { inner: BufReader::new(file), cancel })?,
+        _ => {
+            if file.metadata().ok()?.len() > MAX_COMPRESSED_TAR_SIZE {
+                return None;
+            }
+            let reader = Cancellable { inner: BufReader::new(file), cancel };
+            list_tar(flate2::read::GzDecoder::new(reader))?
+        }
+    };
+
+    // A cancelled tar listing looks truncated; don't report it as the contents
+    if is_cancelled(cancel) {
+        return None;
+    }
+    Some(listing.into_summary(format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_inspect_zip_and_tar_gz() {
+        let dir = TestDir::new("archives-test");
+        let files: &[(&str, usize)] = &[("a.txt", 10), ("big/b.bin", 5000), ("c.log", 300)];
+
+        let zip_path = dir.join("backup.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let stored = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        writer.add_directory("big/", stored).unwrap();
+        for (name, size) in files {
+            writer.start_file(*name, stored).unwrap();
+            writer.write_all(&vec![b'x'; *size]).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let tar_path = dir.join("backup.tar.gz");
+        let encoder = flate2::write::GzEncoder::new(File::create(&tar_path).unwrap(), flate2::Compression::fast());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, size) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(*size as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, &vec![b'x'; *size][..]).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        for (path, format) in [(&zip_path, "zip"), (&tar_path, "tar.gz")] {
+            let summary = inspect_archive(path, None).unwrap();
+            assert_eq!(summary.format, format);
+            assert_eq!(summary.entry_count, 3);
+            assert_eq!(summary.uncompressed_size, 5310);
+            assert_eq!(summary.top_entries[0], ArchiveEntry { name: "big/b.bin".to_string(), size: 5000 });
+            assert!(!summary.truncated);
+        }
+
+        assert!(inspect_archive(&dir.join("notes.txt"), None).is_none());
+
+        let cancelled = AtomicBool::new(true);
+        assert!(inspect_archive(&zip_path, Some(&cancelled)).is_none());
+        assert!(inspect_archive(&tar_path, Some(&cancelled)).is_none());
+    }
+}
@@ -0,0 +1,301 @@
+// Platform capability report
+//
+// Many operations depend on the OS and on external tools being installed
+// (diskpart, parted, resize2fs, ...). This module probes for them once per
+// session so the UI can hide or disable actions that would fail.
+//
+// Disk enumeration (WMI, lsblk, diskutil) works without elevation. Only
+// features that open the raw disk device (sector reads, table backups,
+// secure wipe) need it; they check `require_raw_disk_access` first.
+
+use crate::partition::OperationError;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::command;
+
+/// External tools the partition and cleaner features shell out to
+const PROBED_TOOLS: &[&str] = &[
+    "diskpart",
+    "parted",
+    "resize2fs",
+    "ntfsresize",
+    "smartctl",
+    "diskutil",
+    "lsblk",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolAvailability {
+    pub name: String,
+    pub available: bool,
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OperationSupport {
+    pub enumerate_disks: bool,
+    pub expand_partition: bool,
+    pub shrink_partition: bool,
+    pub create_partition: bool,
+    pub delete_partition: bool,
+    pub mount_partition: bool,
+    pub unmount_partition: bool,
+    pub smart_status: bool,
+    pub scan_junk: bool,
+    pub clean_junk: bool,
+    /// Raw sector access to physical disks (partition table reads/backups, secure wipe)
+    pub raw_disk_access: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Capabilities {
+    /// "windows", "linux" or "macos"
+    pub platform: String,
+    pub tools: Vec<ToolAvailability>,
+    pub operations: OperationSupport,
+    /// Whether the app can open physical disks directly (admin/root)
+    pub elevated: bool,
+    /// Windows Storage PowerShell cmdlets are installed; partition changes
+    /// use them instead of diskpart
+    #[serde(default)]
+    pub storage_cmdlets: bool,
+    /// Human-readable explanations for unsupported operations
+    pub notes: Vec<String>,
+}
+
+lazy_static! {
+    static ref CAPABILITIES: Capabilities = probe_capabilities();
+}
+
+/// Locate an executable on PATH
+pub fn find_tool(name: &str) -> Option<PathBuf> {
+    let path_var = std::env::var_os("PATH")?;
+
+    for dir in std::env::split_paths(&path_var) {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let candidate = dir.join(format!("{}.exe", name));
+            if candidate.is_file() {
+                return Some(candidate);
+            }
+        }
+    }
+
+    None
+}
+
+/// Whether an executable is available on PATH
+pub fn tool_available(name: &str) -> bool {
+    find_tool(name).is_some()
+}
+
+/// Whether this process can open a physical disk for reading
+///
+/// Tries the first disk device rather than checking group membership, since
+/// e.g. the Linux `disk` group grants access without root.
+fn probe_raw_disk_access() -> bool {
+    first_disk_device()
+        .map(|device| std::fs::File::open(device).is_ok())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+fn first_disk_device() -> Option<PathBuf> {
+    Some(PathBuf::from(r"\\.\PhysicalDrive0"))
+}
+
+#[cfg(target_os = "linux")]
+fn first_disk_device() -> Option<PathBuf> {
+    let mut names: Vec<String> = std::fs::read_dir("/sys/block")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with("loop") && !name.starts_with("ram") && !name.starts_with("zram"))
+        .collect();
+    names.sort();
+    names.first().map(|name| PathBuf::from("/dev").join(name))
+}
+
+#[cfg(target_os = "macos")]
+fn first_disk_device() -> Option<PathBuf> {
+    Some(PathBuf::from("/dev/rdisk0"))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn first_disk_device() -> Option<PathBuf> {
+    None
+}
+
+/// Whether the app runs elevated (admin/root)
+pub fn is_elevated() -> bool {
+    CAPABILITIES.elevated
+}
+
+/// Whether Windows partition changes should go through the Storage cmdlets
+pub fn use_storage_cmdlets() -> bool {
+    CAPABILITIES.storage_cmdlets
+}
+
+/// Fail with `RequiresElevation` unless raw disk access is available
+///
+/// Call before any feature that opens the disk device directly.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn require_raw_disk_access(feature: &str) -> Result<(), OperationError> {
+    if CAPABILITIES.elevated {
+        Ok(())
+    } else {
+        Err(OperationError::RequiresElevation {
+            feature: feature.to_string(),
+        })
+    }
+}
+
+fn probe_capabilities() -> Capabilities {
+    let tools: Vec<ToolAvailability> = PROBED_TOOLS
+        .iter()
+        .map(|name| {
+            let path = find_tool(name);
+            ToolAvailability {
+                name: name.to_string(),
+                available: path.is_some(),
+                path: path.map(|p| p.to_string_lossy().to_string()),
+            }
+        })
+        .collect();
+
+    let has = |name: &str| tools.iter().any(|t| t.name == name && t.available);
+    let mut notes = Vec::new();
+    let elevated = probe_raw_disk_access();
+    let storage_cmdlets = crate::partition::storage_cmdlets::probe();
+
+    #[cfg(target_os = "windows")]
+    let operations = {
+        let diskpart = has("diskpart");
+        if !diskpart && !storage_cmdlets {
+            notes.push("Neither the Storage PowerShell module nor diskpart was found; partition changes are unavailable.".to_string());
+        } else if !storage_cmdlets {
+            notes.push(
+                "The Storage PowerShell module was not found; falling back to diskpart, which needs a drive letter \
+                 to resize or delete a partition."
+                    .to_string(),
+            );
+        }
+        notes.push("Automatic mounting is not implemented on Windows.".to_string());
+
+        OperationSupport {
+            enumerate_disks: true, // WMI
+            expand_partition: storage_cmdlets || diskpart,
+            shrink_partition: storage_cmdlets || diskpart,
+            create_partition: diskpart,
+            delete_partition: storage_cmdlets || diskpart,
+            mount_partition: false,
+            unmount_partition: diskpart,
+            smart_status: true, // WMI fallback when smartctl is missing
+            scan_junk: true,
+            clean_junk: true,
+            raw_disk_access: false, // Filled in from the elevation probe
+        }
+    };
+
+    #[cfg(target_os = "linux")]
+    let operations = {
+        let parted = has("parted");
+        let fs_resize = has("resize2fs") || has("ntfsresize");
+        if !parted {
+            notes.push("parted was not found; partition table changes are unavailable.".to_string());
+        }
+        if !fs_resize {
+            notes.push("Neither resize2fs nor ntfsresize was found; filesystems cannot be resized.".to_string());
+        }
+        notes.push("Automatic mounting is not implemented on Linux.".to_string());
+
+        OperationSupport {
+            enumerate_disks: has("lsblk"),
+            expand_partition: parted && fs_resize,
+            shrink_partition: fs_resize,
+            create_partition: parted,
+            delete_partition: parted,
+            mount_partition: false,
+            unmount_partition: true,
+            smart_status: has("smartctl"),
+            scan_junk: true,
+            clean_junk: true,
+            raw_disk_access: false, // Filled in from the elevation probe
+        }
+    };
+
+    #[cfg(target_os = "macos")]
+    let operations = {
+        let diskutil = has("diskutil");
+
+        OperationSupport {
+            enumerate_disks: diskutil,
+            expand_partition: diskutil,
+            shrink_partition: diskutil,
+            create_partition: diskutil,
+            delete_partition: diskutil,
+            mount_partition: diskutil,
+            unmount_partition: diskutil,
+            smart_status: has("smartctl"),
+            scan_junk: true,
+            clean_junk: true,
+            raw_disk_access: false, // Filled in from the elevation probe
+        }
+    };
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    let operations = {
+        notes.push("Partition management is not supported on this platform.".to_string());
+
+        OperationSupport {
+            enumerate_disks: false,
+            expand_partition: false,
+            shrink_partition: false,
+            create_partition: false,
+            delete_partition: false,
+            mount_partition: false,
+            unmount_partition: false,
+            smart_status: has("smartctl"),
+            scan_junk: true,
+            clean_junk: true,
+            raw_disk_access: false, // Filled in from the elevation probe
+        }
+    };
+
+    if !has("smartctl") {
+        notes.push("smartctl was not found; SMART details may be limited.".to_string());
+    }
+
+    if !elevated {
+        notes.push(
+            "Not running as administrator/root. Disks and partitions are still listed, but raw partition table reads, \
+             table backups and secure wipe are unavailable, and partition changes may be refused."
+                .to_string(),
+        );
+    }
+
+    Capabilities {
+        platform: std::env::consts::OS.to_string(),
+        tools,
+        operations: OperationSupport {
+            raw_disk_access: elevated,
+            ..operations
+        },
+        elevated,
+        storage_cmdlets,
+        notes,
+    }
+}
+
+/// Report which tools and operations are available on this machine
+/// The probe runs once per session and is cached
+#[command]
+pub fn get_capabilities() -> Capabilities {
+    CAPABILITIES.clone()
+}
@@ -1,32 +1,130 @@
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 use std::path::{Path, PathBuf};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fs;
-use std::time::SystemTime;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use crate::archives::{self, ArchiveSummary};
+use crate::container_cache;
+use crate::shadow_copies;
+use crate::paths::{display_path, long_path};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
 pub struct JunkItem {
     pub path: String,
     pub name: String,
+    #[ts(type = "number")]
     pub size: u64,
     pub description: String,
     pub age_days: Option<u32>, // Age in days since last modified
+    #[serde(default)]
+    pub requires_reboot: bool, // In use by Windows; deletion is scheduled for the next restart
+    #[serde(default)]
+    pub safety: SafetyLevel, // How sure the scan is that deleting this breaks nothing
+    #[serde(default)]
+    pub archive: Option<ArchiveSummary>, // Contents of a large archive, when `inspect_archives` is set
+    #[serde(default)]
+    pub age_basis: AgeBasis, // Timestamp `age_days` was measured from; Modified when access times weren't usable
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Confidence that deleting a junk item won't log the user out or break an app
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, TS)]
+#[ts(export)]
+pub enum SafetyLevel {
+    /// Temp files, logs and caches that are rebuilt on demand
+    Safe,
+    /// Rebuildable, but costly to regenerate or possibly still wanted
+    #[default]
+    Caution,
+    /// Looks like app state (sessions, logins, saved windows); only delete on purpose
+    Risky,
+}
+
+/// Which timestamp an item's age is measured from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, TS)]
+#[ts(export)]
+pub enum AgeBasis {
+    /// Last modification; always available
+    #[default]
+    Modified,
+    /// Last read, where the filesystem records one (noatime/relatime mounts often don't)
+    Accessed,
+}
+
+/// Name fragments of files apps keep state or credentials in
+const RISKY_NAME_PATTERNS: &[&str] = &[
+    "session", "state", "login", "cookie", "credential", "token", "keychain", "password",
+];
+
+/// Developer directories whose entries are installed or archived, not cached
+const RISKY_DEVELOPER_PARENTS: &[&str] = &["extensions", "Archives"];
+
+/// Classify a junk item by its name, extension and category
+fn classify_item(path: &Path, category_id: &str) -> SafetyLevel {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if RISKY_NAME_PATTERNS.iter().any(|p| name.contains(p)) {
+        return SafetyLevel::Risky;
+    }
+
+    let extension = Path::new(&name)
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    if DISPOSABLE_EXTENSIONS.contains(&extension.as_str()) {
+        return SafetyLevel::Safe;
+    }
+
+    match category_id {
+        "temp_files" | "system_logs" | "browser_cache" | "package_cache" => SafetyLevel::Safe,
+        "developer_cache"
+            if path
+                .parent()
+                .and_then(|p| p.file_name())
+                .is_some_and(|p| RISKY_DEVELOPER_PARENTS.iter().any(|r| p.to_string_lossy() == *r)) =>
+        {
+            SafetyLevel::Risky
+        }
+        _ => SafetyLevel::Caution,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
 pub struct JunkCategory {
     pub id: String,
     pub name: String,
     pub description: String,
     pub items: Vec<JunkItem>,
+    #[ts(type = "number")]
     pub total_size: u64,
     pub icon: String,
+    #[ts(type = "number")]
+    pub filtered_size: u64, // Size of entries excluded by filters (e.g. too new)
+    pub filtered_count: usize, // Number of entries excluded by filters
+    #[serde(default)]
+    pub unreadable_count: usize, // Entries that couldn't be read; sizes are partial when non-zero
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct CleaningOptions {
     pub min_age_days: Option<u32>, // Only delete files older than this
     pub dry_run: bool, // If true, don't actually delete, just return what would be deleted
     pub skip_errors: bool, // If true, continue on errors instead of stopping
+    pub prune_empty_dirs: bool, // Remove directories left empty under the affected cleaning roots
+    pub preserve_root_dirs: bool, // Never prune a cleaning root itself, even when empty
+    pub inspect_archives: bool, // List the contents of large archives in Downloads (read-only, slower)
+    pub use_trash: bool, // Move items to the OS trash / Recycle Bin instead of deleting them permanently
+    pub age_basis: AgeBasis, // Measure `min_age_days` from last modification or last access
+    #[serde(skip)]
+    pub now: Option<SystemTime>, // Reference time for age checks (None = current time)
 }
 
 impl Default for CleaningOptions {
@@ -35,26 +133,136 @@ impl Default for CleaningOptions {
             min_age_days: None,
             dry_run: false,
             skip_errors: true,
+            prune_empty_dirs: false,
+            preserve_root_dirs: true,
+            inspect_archives: false,
+            use_trash: false,
+            age_basis: AgeBasis::Modified,
+            now: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A named cleaning preset: options plus the categories it touches
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CleaningProfile {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub options: CleaningOptions,
+    #[serde(default)]
+    pub category_ids: Vec<String>, // Categories to include; empty means all
+    #[serde(default)]
+    pub min_item_size: u64, // Items smaller than this are left alone (0 = no limit)
+}
+
+/// Built-in presets, from least to most aggressive
+pub fn builtin_profiles() -> Vec<CleaningProfile> {
+    const MB: u64 = 1024 * 1024;
+
+    let options = |min_age_days| CleaningOptions {
+        min_age_days,
+        ..CleaningOptions::default()
+    };
+    let categories = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect();
+
+    vec![
+        CleaningProfile {
+            id: "conservative".to_string(),
+            name: "Conservative".to_string(),
+            description: "Temporary files and trash older than 30 days".to_string(),
+            options: options(Some(30)),
+            category_ids: categories(&["temp_files", "trash"]),
+            min_item_size: 0,
+        },
+        CleaningProfile {
+            id: "balanced".to_string(),
+            name: "Balanced".to_string(),
+            description: "Temporary files, trash, logs and system/browser caches older than a week".to_string(),
+            options: options(Some(7)),
+            category_ids: categories(&["temp_files", "trash", "system_logs", "system_cache", "browser_cache"]),
+            min_item_size: MB,
+        },
+        CleaningProfile {
+            id: "aggressive".to_string(),
+            name: "Aggressive".to_string(),
+            description: "Every cache category, regardless of age".to_string(),
+            options: options(None),
+            category_ids: Vec::new(),
+            min_item_size: 0,
+        },
+    ]
+}
+
+/// Scan with a profile's options, keeping only its categories and item sizes
+pub fn scan_with_profile(profile: &CleaningProfile, cancel: Option<Arc<AtomicBool>>) -> Vec<JunkCategory> {
+    let categories = scan_junk_items_with_options(profile.options.clone(), cancel);
+    apply_profile_filters(categories, profile)
+}
+
+fn apply_profile_filters(categories: Vec<JunkCategory>, profile: &CleaningProfile) -> Vec<JunkCategory> {
+    categories
+        .into_iter()
+        .filter(|c| profile.category_ids.is_empty() || profile.category_ids.contains(&c.id))
+        .map(|mut category| {
+            let (kept, small): (Vec<_>, Vec<_>) = category
+                .items
+                .into_iter()
+                .partition(|item| item.size >= profile.min_item_size);
+            for item in &small {
+                category.total_size -= item.size;
+                category.filtered_size += item.size;
+                category.filtered_count += 1;
+            }
+            category.items = kept;
+            category
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
 pub struct DeletionResult {
     pub deleted_count: usize,
     pub deleted_size: u64,
     pub failed_count: usize,
     pub errors: Vec<String>,
     pub skipped_count: usize, // Files skipped due to age filter
+    #[serde(default)]
+    pub deferred_count: usize, // Scheduled for deletion on the next restart
+    #[serde(default)]
+    pub deferred_size: u64, // Bytes freed after the next restart
+    #[serde(default)]
+    pub pruned_dir_count: usize, // Empty directories removed after deletion (prune_empty_dirs)
+    #[serde(default)]
+    pub trashed_count: usize, // Of deleted_count, items moved to the trash (use_trash) and still recoverable
+    #[serde(default)]
+    pub permanently_deleted: Vec<String>, // With use_trash, items the trash refused that were deleted permanently instead
 }
 
-#[derive(Debug, Clone)]
+impl DeletionResult {
+    /// Fold the counts of another (partial) run into this one
+    pub fn merge(&mut self, other: DeletionResult) {
+        self.deleted_count += other.deleted_count;
+        self.deleted_size += other.deleted_size;
+        self.failed_count += other.failed_count;
+        self.errors.extend(other.errors);
+        self.skipped_count += other.skipped_count;
+        self.deferred_count += other.deferred_count;
+        self.deferred_size += other.deferred_size;
+        self.pruned_dir_count += other.pruned_dir_count;
+        self.trashed_count += other.trashed_count;
+        self.permanently_deleted.extend(other.permanently_deleted);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
 struct CleaningPath {
     category_id: &'static str,
     category_name: &'static str,
     path_template: &'static str,
     description: &'static str,
-    supports_wildcards: bool,
+    supports_wildcards: bool, // `*`/`?` components in path_template are globbed; every matching directory is scanned
+    requires_reboot: bool, // Contents are held open by the system and can only go on restart
 }
 
 // macOS cleaning paths
@@ -67,14 +275,14 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Caches",
             path_template: "~/Library/Caches",
             description: "User application caches",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_cache",
             category_name: "System Caches",
             path_template: "/Library/Caches",
             description: "System-wide application caches",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // System Logs
@@ -83,21 +291,21 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Logs",
             path_template: "~/Library/Logs",
             description: "User application logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_logs",
             category_name: "System Logs",
             path_template: "/Library/Logs",
             description: "System application logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_logs",
             category_name: "System Logs",
             path_template: "/private/var/log",
             description: "System logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Temporary Files
@@ -106,51 +314,38 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Temporary Files",
             path_template: "/tmp",
             description: "Temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "temp_files",
             category_name: "Temporary Files",
             path_template: "/var/tmp",
             description: "Persistent temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "temp_files",
             category_name: "Temporary Files",
             path_template: "~/Library/Saved Application State",
             description: "Application state files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Browser Caches
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "~/Library/Caches/Google/Chrome/Default",
-            description: "Chrome browser cache",
-            supports_wildcards: false,
-        },
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "~/Library/Caches/Firefox/Profiles",
-            description: "Firefox browser cache",
-            supports_wildcards: false,
-        },
         CleaningPath {
             category_id: "browser_cache",
             category_name: "Browser Caches",
             path_template: "~/Library/Containers/com.apple.Safari/Data/Library/Caches/com.apple.Safari/WebKitCache",
             description: "Safari browser cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "browser_cache",
             category_name: "Browser Caches",
-            path_template: "~/Library/Caches/Microsoft Edge/Default/Cache",
-            description: "Edge browser cache",
-            supports_wildcards: false,
+            path_template: "~/Library/Caches/Firefox/Profiles/*/cache2",
+            description: "Firefox disk cache (every profile)",
+            supports_wildcards: true,
+            ..Default::default()
         },
         
         // Developer Tools
@@ -159,77 +354,77 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Developer Caches",
             path_template: "~/Library/Developer/Xcode/DerivedData",
             description: "Xcode build artifacts",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/Library/Developer/Xcode/Archives",
             description: "Xcode archives",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/Library/Developer/CoreSimulator/Caches",
             description: "iOS Simulator caches",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.gradle/caches",
             description: "Gradle build cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.npm",
             description: "npm package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.yarn/cache",
             description: "Yarn package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.cache/yarn",
             description: "Yarn cache (alternative)",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/Library/Caches/com.apple.dt.Xcode",
             description: "Xcode caches",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.android/build-cache",
             description: "Android build cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.vscode/extensions",
             description: "VS Code extensions cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.cargo/registry",
             description: "Rust cargo cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
     ]
 }
@@ -244,21 +439,21 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Temporary Files",
             path_template: "%TEMP%",
             description: "User temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "temp_files",
             category_name: "Temporary Files",
             path_template: "%LOCALAPPDATA%\\Temp",
             description: "Local AppData temp files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "temp_files",
             category_name: "Temporary Files",
             path_template: "C:\\Windows\\Temp",
             description: "Windows system temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // System Caches
@@ -267,35 +462,37 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Caches",
             path_template: "C:\\Windows\\Prefetch",
             description: "Prefetch files",
-            supports_wildcards: false,
+            requires_reboot: true,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_cache",
             category_name: "System Caches",
             path_template: "C:\\Windows\\SoftwareDistribution\\Download",
             description: "Windows Update cache",
-            supports_wildcards: false,
+            requires_reboot: true,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_cache",
             category_name: "System Caches",
             path_template: "%LOCALAPPDATA%\\Microsoft\\Windows\\Explorer\\ThumbCacheToDelete",
             description: "Thumbnail cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_cache",
             category_name: "System Caches",
             path_template: "%LOCALAPPDATA%\\Microsoft\\Windows\\INetCache",
             description: "Internet Explorer cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_cache",
             category_name: "System Caches",
             path_template: "%LOCALAPPDATA%\\CrashDumps",
             description: "Crash dump files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // System Logs
@@ -304,37 +501,24 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Logs",
             path_template: "C:\\Windows\\Logs",
             description: "Windows logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_logs",
             category_name: "System Logs",
             path_template: "C:\\Windows\\Panther",
             description: "Windows installation logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Browser Caches
         CleaningPath {
             category_id: "browser_cache",
             category_name: "Browser Caches",
-            path_template: "%LOCALAPPDATA%\\Google\\Chrome\\User Data\\Default\\Cache",
-            description: "Chrome browser cache",
-            supports_wildcards: false,
-        },
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "%APPDATA%\\Mozilla\\Firefox\\Profiles",
-            description: "Firefox browser cache",
-            supports_wildcards: false,
-        },
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "%LOCALAPPDATA%\\Microsoft\\Edge\\User Data\\Default\\Cache",
-            description: "Edge browser cache",
-            supports_wildcards: false,
+            path_template: "%LOCALAPPDATA%\\Mozilla\\Firefox\\Profiles\\*\\cache2",
+            description: "Firefox disk cache (every profile)",
+            supports_wildcards: true,
+            ..Default::default()
         },
         
         // Developer Tools
@@ -343,49 +527,49 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Developer Caches",
             path_template: "%USERPROFILE%\\.gradle\\caches",
             description: "Gradle build cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%LOCALAPPDATA%\\npm-cache",
             description: "npm package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%LOCALAPPDATA%\\Yarn\\cache",
             description: "Yarn package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%APPDATA%\\Code\\Cache",
             description: "VS Code cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%APPDATA%\\Code\\CachedExtensionVSIXs",
             description: "VS Code extensions cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%LOCALAPPDATA%\\Android\\build-cache",
             description: "Android build cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "%USERPROFILE%\\.cargo\\registry",
             description: "Rust cargo cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
     ]
 }
@@ -400,7 +584,7 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Caches",
             path_template: "~/.cache",
             description: "User application caches",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Temporary Files
@@ -409,14 +593,14 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Temporary Files",
             path_template: "/tmp",
             description: "Temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "temp_files",
             category_name: "Temporary Files",
             path_template: "/var/tmp",
             description: "Persistent temporary files",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // System Logs
@@ -425,14 +609,14 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "System Logs",
             path_template: "/var/log",
             description: "System logs",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "system_logs",
             category_name: "System Logs",
             path_template: "~/.xsession-errors",
             description: "X session errors",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Trash
@@ -441,30 +625,17 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Trash",
             path_template: "~/.local/share/Trash",
             description: "User trash",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Browser Caches
         CleaningPath {
             category_id: "browser_cache",
             category_name: "Browser Caches",
-            path_template: "~/.cache/google-chrome/Default/Cache",
-            description: "Chrome browser cache",
-            supports_wildcards: false,
-        },
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "~/.cache/mozilla/firefox",
-            description: "Firefox browser cache",
-            supports_wildcards: false,
-        },
-        CleaningPath {
-            category_id: "browser_cache",
-            category_name: "Browser Caches",
-            path_template: "~/.cache/chromium/Default/Cache",
-            description: "Chromium browser cache",
-            supports_wildcards: false,
+            path_template: "~/.cache/mozilla/firefox/*/cache2",
+            description: "Firefox disk cache (every profile)",
+            supports_wildcards: true,
+            ..Default::default()
         },
         
         // Package Manager Caches
@@ -473,21 +644,21 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Package Manager Caches",
             path_template: "/var/cache/apt/archives",
             description: "APT package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "package_cache",
             category_name: "Package Manager Caches",
             path_template: "/var/cache/dnf",
             description: "DNF package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "package_cache",
             category_name: "Package Manager Caches",
             path_template: "/var/cache/yum",
             description: "YUM package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         
         // Developer Tools
@@ -496,61 +667,66 @@ fn get_cleaning_paths() -> Vec<CleaningPath> {
             category_name: "Developer Caches",
             path_template: "~/.gradle/caches",
             description: "Gradle build cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.npm",
             description: "npm package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.cache/yarn",
             description: "Yarn package cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.cache/pip",
             description: "Python pip cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.cargo/registry",
             description: "Rust cargo cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.m2/repository",
             description: "Maven repository cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.vscode/extensions",
             description: "VS Code extensions",
-            supports_wildcards: false,
+            ..Default::default()
         },
         CleaningPath {
             category_id: "developer_cache",
             category_name: "Developer Caches",
             path_template: "~/.config/Code/CachedData",
             description: "VS Code cache",
-            supports_wildcards: false,
+            ..Default::default()
         },
     ]
 }
 
 fn expand_path(path: &str) -> Option<PathBuf> {
+    Some(expand_template(path)).filter(|p| p.exists())
+}
+
+/// `~` and environment variables in `path` replaced, whether or not the result exists
+fn expand_template(path: &str) -> PathBuf {
     use std::env;
     
     let mut expanded = path.to_string();
@@ -559,7 +735,7 @@ fn expand_path(path: &str) -> Option<PathBuf> {
     if expanded.starts_with('~') {
         if let Some(home_dir) = dirs::home_dir() {
             if expanded == "~" {
-                return Some(home_dir);
+                return home_dir;
             }
             expanded = expanded.replacen("~", &home_dir.to_string_lossy(), 1);
         }
@@ -597,211 +773,2200 @@ fn expand_path(path: &str) -> Option<PathBuf> {
         }
     }
     
-    let path_buf = PathBuf::from(expanded);
-    if path_buf.exists() {
-        Some(path_buf)
-    } else {
-        None
+    PathBuf::from(expanded)
+}
+
+/// Existing directories a cleaning path refers to
+///
+/// Without `supports_wildcards` this is `expand_path`. With it, the part of
+/// the template before the first wildcard component is expanded as usual
+/// (and escaped, so a `[` in a user name isn't read as a pattern) and the
+/// rest is globbed.
+fn expand_paths(cleaning_path: &CleaningPath) -> Vec<PathBuf> {
+    if !cleaning_path.supports_wildcards {
+        return expand_path(cleaning_path.path_template).into_iter().collect();
+    }
+    glob_template(cleaning_path.path_template)
+}
+
+fn glob_template(template: &str) -> Vec<PathBuf> {
+    let components: Vec<&str> = template.split(['/', '\\']).collect();
+    let first_wildcard = components
+        .iter()
+        .position(|c| c.contains(['*', '?']))
+        .unwrap_or(components.len());
+    let (prefix, rest) = components.split_at(first_wildcard);
+
+    let base = expand_template(&prefix.join(std::path::MAIN_SEPARATOR_STR));
+    if !base.is_dir() {
+        return Vec::new();
+    }
+    let mut pattern = glob::Pattern::escape(&base.to_string_lossy());
+    for component in rest {
+        pattern.push(std::path::MAIN_SEPARATOR);
+        pattern.push_str(component);
     }
+
+    let Ok(matches) = glob::glob(&pattern) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = matches.flatten().filter(|p| p.is_dir()).collect();
+    dirs.sort();
+    dirs
+}
+
+/// Size of a directory tree, summed from whatever could be read
+#[derive(Debug, Clone, Copy, Default)]
+struct DirSize {
+    bytes: u64,
+    unreadable: usize, // Entries that couldn't be read (e.g. permission denied) and aren't counted
+    last_accessed: Option<SystemTime>, // Newest file access recorded after that file's last write
+}
+
+/// Walk a directory and sum file sizes, skipping entries that can't be read
+/// rather than giving up on the whole tree
+fn calculate_dir_size(path: &Path) -> DirSize {
+    calculate_dir_size_cancellable(path, None)
+}
+
+/// `calculate_dir_size` that stops early, with a partial total, once `cancel` is set
+fn calculate_dir_size_cancellable(path: &Path, cancel: Option<&AtomicBool>) -> DirSize {
+    walk_dir_size(path, cancel, None)
 }
 
-fn calculate_dir_size(path: &Path) -> u64 {
-    match fs_extra::dir::get_size(path) {
-        Ok(size) => size,
-        Err(_) => 0,
+/// Sum a directory tree, stopping early once `cancel` is set or a file was
+/// used after `used_after`; the total is partial in either case
+fn walk_dir_size(path: &Path, cancel: Option<&AtomicBool>, used_after: Option<SystemTime>) -> DirSize {
+    let mut size = DirSize::default();
+
+    for (idx, entry) in walkdir::WalkDir::new(long_path(path)).min_depth(1).into_iter().enumerate() {
+        if idx % 256 == 0 && cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+            break;
+        }
+        match entry.and_then(|e| e.metadata()) {
+            Ok(meta) if meta.is_file() => {
+                size.bytes += meta.len();
+                if let Some(accessed) = recorded_access(&meta) {
+                    size.last_accessed = size.last_accessed.max(Some(accessed));
+                    if used_after.is_some_and(|cutoff| accessed > cutoff) {
+                        break;
+                    }
+                }
+            }
+            Ok(_) => {}
+            Err(_) => size.unreadable += 1,
+        }
     }
+
+    size
+}
+
+/// Access time, when the filesystem recorded a read after the last write
+///
+/// With noatime the access time never moves, and with relatime it is only
+/// bumped when it is older than the modification time, so an access time that
+/// isn't later than the modification time says nothing about use.
+fn recorded_access(metadata: &fs::Metadata) -> Option<SystemTime> {
+    let accessed = metadata.accessed().ok()?;
+    (accessed > metadata.modified().ok()?).then_some(accessed)
 }
 
-fn get_file_age_days(metadata: &fs::Metadata) -> Option<u32> {
-    metadata
-        .modified()
+fn days_since(time: SystemTime, now: SystemTime) -> Option<u32> {
+    now.duration_since(time)
         .ok()
-        .and_then(|modified| {
-            SystemTime::now()
-                .duration_since(modified)
-                .ok()
-        })
         .map(|duration| (duration.as_secs() / 86400) as u32)
 }
 
-pub fn scan_junk_items() -> Vec<JunkCategory> {
-    scan_junk_items_with_options(CleaningOptions::default())
+/// Age of a file by `basis`, with the basis actually used
+///
+/// Falls back to the modification time when no access was recorded.
+fn get_file_age_days(metadata: &fs::Metadata, now: SystemTime, basis: AgeBasis) -> (Option<u32>, AgeBasis) {
+    if basis == AgeBasis::Accessed {
+        if let Some(accessed) = recorded_access(metadata) {
+            return (days_since(accessed, now), AgeBasis::Accessed);
+        }
+    }
+    (metadata.modified().ok().and_then(|m| days_since(m, now)), AgeBasis::Modified)
+}
+
+/// Age of a directory by `basis`, with the basis actually used
+///
+/// A directory's own access time is bumped by listing it (this scan included),
+/// so access age comes from the newest file access found inside instead.
+fn dir_age_days(metadata: &fs::Metadata, size: &DirSize, now: SystemTime, basis: AgeBasis) -> (Option<u32>, AgeBasis) {
+    match (basis, size.last_accessed) {
+        (AgeBasis::Accessed, Some(accessed)) => {
+            let used = metadata.modified().map_or(accessed, |m| m.max(accessed));
+            (days_since(used, now), AgeBasis::Accessed)
+        }
+        _ => get_file_age_days(metadata, now, AgeBasis::Modified),
+    }
+}
+
+/// Whether an item of the given age passes the `min_age_days` filter
+/// Items whose age can't be determined are excluded when a filter is set
+fn passes_age_filter(age_days: Option<u32>, min_age_days: Option<u32>) -> bool {
+    match (min_age_days, age_days) {
+        (None, _) => true,
+        (Some(min_age), Some(age)) => age >= min_age,
+        (Some(_), None) => false,
+    }
+}
+
+/// Scan every cleaning location
+///
+/// `cancel` is checked between locations and between their entries; once it
+/// is set the scan stops and returns the categories collected so far. The
+/// entry being sized at that moment is left out, since its size is partial.
+pub fn scan_junk_items_with_options(options: CleaningOptions, cancel: Option<Arc<AtomicBool>>) -> Vec<JunkCategory> {
+    scan_junk_items_reporting(options, cancel, |_| {})
 }
 
-pub fn scan_junk_items_with_options(options: CleaningOptions) -> Vec<JunkCategory> {
+/// `scan_junk_items_with_options`, calling `on_category` with each category as soon as it is complete
+///
+/// A category is complete once every location feeding it has been scanned,
+/// so each is reported exactly once, in final form. Categories cut short by
+/// cancellation are returned but not reported.
+pub fn scan_junk_items_reporting(
+    options: CleaningOptions,
+    cancel: Option<Arc<AtomicBool>>,
+    mut on_category: impl FnMut(&JunkCategory),
+) -> Vec<JunkCategory> {
     let mut categories: Vec<JunkCategory> = Vec::new();
     let cleaning_paths = get_cleaning_paths();
+    let now = options.now.unwrap_or_else(SystemTime::now);
+    let cancel = cancel.as_deref();
+    let cancelled = || cancel.is_some_and(|c| c.load(Ordering::Relaxed));
+
+    // Browser caches also come from browser profiles, so that category is only complete after them
+    let browser_id = "browser_cache";
+    let last_index: HashMap<&str, usize> = cleaning_paths
+        .iter()
+        .enumerate()
+        .map(|(index, cp)| (cp.category_id, index))
+        .collect();
     
-    for cleaning_path in cleaning_paths {
-        if let Some(path) = expand_path(cleaning_path.path_template) {
-            if !path.exists() {
-                continue;
-            }
-            
+    for (index, cleaning_path) in cleaning_paths.iter().enumerate() {
+        if cancelled() {
+            return categories;
+        }
+        for path in expand_paths(cleaning_path) {
             let mut items = Vec::new();
             let mut total_size = 0;
+            let mut filtered_size = 0;
+            let mut filtered_count = 0;
+            let mut unreadable_count = 0;
             
             // Scan directory contents
-            if let Ok(read_dir) = fs::read_dir(&path) {
+            if let Ok(read_dir) = fs::read_dir(long_path(&path)) {
                 for entry in read_dir.flatten() {
+                    if cancelled() {
+                        break;
+                    }
                     if let Ok(meta) = entry.metadata() {
-                        // Calculate age
-                        let age_days = get_file_age_days(&meta);
-                        
-                        // Apply age filter if specified
-                        if let Some(min_age) = options.min_age_days {
-                            if let Some(age) = age_days {
-                                if age < min_age {
-                                    continue; // Skip files that are too new
-                                }
-                            } else {
-                                continue; // Skip if we can't determine age
+                        let (size, (age_days, age_basis)) = if meta.is_dir() {
+                            let dir_size = calculate_dir_size_cancellable(&entry.path(), cancel);
+                            if cancelled() {
+                                break;
                             }
-                        }
-                        
-                        let size = if meta.is_dir() {
-                            calculate_dir_size(&entry.path())
+                            unreadable_count += dir_size.unreadable;
+                            (dir_size.bytes, dir_age_days(&meta, &dir_size, now, options.age_basis))
                         } else {
-                            meta.len()
+                            (meta.len(), get_file_age_days(&meta, now, options.age_basis))
                         };
                         
+                        // Apply age filter if specified
+                        if !passes_age_filter(age_days, options.min_age_days) {
+                            // Too new or of unknown age; counted so the UI can show what was held back
+                            filtered_size += size;
+                            filtered_count += 1;
+                            continue;
+                        }
+                        
                         total_size += size;
                         
                         items.push(JunkItem {
-                            path: entry.path().to_string_lossy().to_string(),
+                            path: display_path(&entry.path()).to_string_lossy().to_string(),
                             name: entry.file_name().to_string_lossy().to_string(),
                             size,
                             description: cleaning_path.description.to_string(),
                             age_days,
+                            requires_reboot: cleaning_path.requires_reboot,
+                            safety: classify_item(&entry.path(), cleaning_path.category_id),
+                            archive: None,
+                            age_basis,
                         });
                     }
                 }
             }
             
-            if !items.is_empty() {
-                // Check if category already exists
-                if let Some(cat) = categories.iter_mut().find(|c| c.id == cleaning_path.category_id) {
-                    cat.items.extend(items);
-                    cat.total_size += total_size;
-                } else {
-                    categories.push(JunkCategory {
-                        id: cleaning_path.category_id.to_string(),
-                        name: cleaning_path.category_name.to_string(),
-                        description: format!("Files in {}", cleaning_path.category_name),
-                        items,
-                        total_size,
-                        icon: cleaning_path.category_id.to_string(),
-                    });
-                }
+            // Keep the category even when everything was filtered out
+            if let Some(cat) = categories.iter_mut().find(|c| c.id == cleaning_path.category_id) {
+                cat.items.extend(items);
+                cat.total_size += total_size;
+                cat.filtered_size += filtered_size;
+                cat.filtered_count += filtered_count;
+                cat.unreadable_count += unreadable_count;
+            } else {
+                categories.push(JunkCategory {
+                    id: cleaning_path.category_id.to_string(),
+                    name: cleaning_path.category_name.to_string(),
+                    description: format!("Files in {}", cleaning_path.category_name),
+                    items,
+                    total_size,
+                    icon: cleaning_path.category_id.to_string(),
+                    filtered_size,
+                    filtered_count,
+                    unreadable_count,
+                });
+            }
+        }
+
+        let id = cleaning_path.category_id;
+        if last_index[id] == index && id != browser_id && !cancelled() {
+            if let Some(cat) = categories.iter().find(|c| c.id == id) {
+                on_category(cat);
             }
         }
     }
+
+    if cancelled() {
+        return categories;
+    }
+
+    // Browser caches are found per profile rather than from fixed paths
+    let browsers = scan_browser_caches(options);
+    if let Some(cat) = categories.iter_mut().find(|c| c.id == browsers.id) {
+        // A profile cache found here is one directory; wildcard cleaning paths list its entries
+        let (duplicates, kept): (Vec<JunkItem>, Vec<JunkItem>) = std::mem::take(&mut cat.items)
+            .into_iter()
+            .partition(|item| browsers.items.iter().any(|b| Path::new(&item.path).starts_with(&b.path)));
+        cat.total_size -= duplicates.iter().map(|item| item.size).sum::<u64>();
+        cat.items = kept;
+        cat.items.extend(browsers.items);
+        cat.total_size += browsers.total_size;
+        cat.filtered_size += browsers.filtered_size;
+        cat.filtered_count += browsers.filtered_count;
+        cat.unreadable_count += browsers.unreadable_count;
+    } else if !browsers.items.is_empty() || browsers.filtered_count > 0 {
+        categories.push(browsers);
+    }
+    if let Some(cat) = categories.iter().find(|c| c.id == browser_id) {
+        on_category(cat);
+    }
+
+    if cancelled() {
+        return categories;
+    }
+    for category in container_cache::scan_container_cache().into_iter().chain(shadow_copies::scan_shadow_copies()) {
+        on_category(&category);
+        categories.push(category);
+    }
     
     categories
 }
 
-pub fn delete_junk_items(paths: Vec<String>) -> Result<(), String> {
-    let result = delete_junk_items_with_options(paths, CleaningOptions::default())?;
-    
-    if result.failed_count > 0 {
-        Err(result.errors.join("\n"))
-    } else {
-        Ok(())
-    }
+/// Installer and archive types that pile up in Downloads
+const DOWNLOAD_JUNK_EXTENSIONS: &[(&str, &str)] = &[
+    ("dmg", "Disk image installer"),
+    ("pkg", "Installer package"),
+    ("exe", "Installer/executable"),
+    ("msi", "Installer package"),
+    ("iso", "Disk image"),
+    ("zip", "Archive"),
+    ("tar", "Archive"),
+    ("gz", "Compressed archive"),
+    ("tgz", "Compressed archive"),
+];
+
+/// Downloads younger than this are kept unless `min_age_days` says otherwise
+const DOWNLOADS_DEFAULT_MIN_AGE_DAYS: u32 = 30;
+
+/// Scan the user's Downloads folder for old installers and archives
+///
+/// Downloads is user data, so this is opt-in and not part of `get_cleaning_paths`.
+/// Returns None when there's no Downloads folder. Once cancelled it returns
+/// the items found so far.
+pub fn scan_downloads_junk(options: CleaningOptions, cancel: Option<Arc<AtomicBool>>) -> Option<JunkCategory> {
+    let dir = dirs::download_dir().filter(|d| d.is_dir())?;
+    Some(scan_downloads_dir(&dir, &options, cancel.as_deref()))
 }
 
-pub fn delete_junk_items_with_options(
-    paths: Vec<String>,
-    options: CleaningOptions,
-) -> Result<DeletionResult, String> {
-    let mut deleted_count = 0;
-    let mut deleted_size = 0;
-    let mut failed_count = 0;
-    let mut skipped_count = 0;
-    let mut errors = Vec::new();
-    
-    for path in paths {
-        let p = Path::new(&path);
-        
-        if !p.exists() {
-            if !options.skip_errors {
-                return Err(format!("Path does not exist: {}", path));
-            }
-            errors.push(format!("Path does not exist: {}", path));
-            failed_count += 1;
-            continue;
+fn scan_downloads_dir(dir: &Path, options: &CleaningOptions, cancel: Option<&AtomicBool>) -> JunkCategory {
+    let now = options.now.unwrap_or_else(SystemTime::now);
+    let min_age_days = Some(options.min_age_days.unwrap_or(DOWNLOADS_DEFAULT_MIN_AGE_DAYS));
+
+    let mut category = JunkCategory {
+        id: "downloads".to_string(),
+        name: "Old Downloads".to_string(),
+        description: "Installers and archives in your Downloads folder. These are your own files: \
+                      review each one before deleting."
+            .to_string(),
+        items: Vec::new(),
+        total_size: 0,
+        icon: "downloads".to_string(),
+        filtered_size: 0,
+        filtered_count: 0,
+        unreadable_count: 0,
+    };
+
+    let Ok(read_dir) = fs::read_dir(dir) else {
+        return category;
+    };
+
+    for entry in read_dir.flatten() {
+        if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+            break;
         }
-        
-        // Get metadata for age check and size
-        let metadata = match fs::metadata(p) {
-            Ok(m) => m,
-            Err(e) => {
-                if !options.skip_errors {
-                    return Err(format!("Failed to get metadata for {}: {}", path, e));
-                }
-                errors.push(format!("Failed to get metadata for {}: {}", path, e));
-                failed_count += 1;
-                continue;
-            }
-        };
-        
-        // Apply age filter if specified
-        if let Some(min_age) = options.min_age_days {
-            if let Some(age) = get_file_age_days(&metadata) {
-                if age < min_age {
-                    skipped_count += 1;
-                    continue; // Skip files that are too new
-                }
-            } else {
-                skipped_count += 1;
-                continue; // Skip if we can't determine age
-            }
+        let Ok(meta) = entry.metadata() else { continue };
+        if !meta.is_file() {
+            continue;
         }
-        
-        // Calculate size before deletion
-        let size = if metadata.is_dir() {
-            calculate_dir_size(p)
-        } else {
-            metadata.len()
+
+        let path = entry.path();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let Some((_, kind)) = DOWNLOAD_JUNK_EXTENSIONS.iter().find(|(ext, _)| *ext == extension) else {
+            continue;
         };
-        
-        // Dry run mode - don't actually delete
-        if options.dry_run {
-            deleted_count += 1;
-            deleted_size += size;
+
+        let (age_days, age_basis) = get_file_age_days(&meta, now, options.age_basis);
+        if !passes_age_filter(age_days, min_age_days) {
+            category.filtered_size += meta.len();
+            category.filtered_count += 1;
             continue;
         }
-        
-        // Perform actual deletion
-        let result = if p.is_file() {
-            fs::remove_file(p)
-        } else if p.is_dir() {
-            fs::remove_dir_all(p)
-        } else {
-            skipped_count += 1;
-            continue;
+
+        let archive = (options.inspect_archives && meta.len() >= archives::INSPECT_MIN_SIZE)
+            .then(|| archives::inspect_archive(&path, cancel))
+            .flatten();
+        let description = match &archive {
+            Some(summary) => format!(
+                "{} in Downloads holding {} files (review before deleting)",
+                kind, summary.entry_count
+            ),
+            None => format!("{} in Downloads (review before deleting)", kind),
         };
-        
-        match result {
-            Ok(_) => {
-                deleted_count += 1;
-                deleted_size += size;
+
+        category.total_size += meta.len();
+        category.items.push(JunkItem {
+            path: path.to_string_lossy().to_string(),
+            name: entry.file_name().to_string_lossy().to_string(),
+            size: meta.len(),
+            description,
+            age_days,
+            requires_reboot: false,
+            safety: SafetyLevel::Caution,
+            archive,
+            age_basis,
+        });
+    }
+
+    category.items.sort_by(|a, b| b.size.cmp(&a.size));
+    category
+}
+
+/// How a browser lays out its profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BrowserFamily {
+    Chromium,
+    Firefox,
+}
+
+impl BrowserFamily {
+    /// File every real profile directory contains (rules out Crashpad, ShaderCache, ...)
+    fn profile_marker(self) -> &'static str {
+        match self {
+            BrowserFamily::Chromium => "Preferences",
+            BrowserFamily::Firefox => "prefs.js",
+        }
+    }
+
+    /// Subdirectories the browser rebuilds on its own, with what they hold
+    ///
+    /// This is an allow-list: cookies, Login Data, History, Local Storage and
+    /// IndexedDB sit next to these and deleting them logs the user out.
+    fn cache_dirs(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            BrowserFamily::Chromium => &[
+                ("Cache", "disk cache"),
+                ("Code Cache", "compiled script cache"),
+                ("GPUCache", "GPU shader cache"),
+                ("Service Worker/CacheStorage", "service worker cache"),
+                ("Service Worker/ScriptCache", "service worker script cache"),
+            ],
+            BrowserFamily::Firefox => &[
+                ("cache2", "disk cache"),
+                ("startupCache", "startup cache"),
+                ("thumbnails", "page thumbnails"),
+            ],
+        }
+    }
+}
+
+/// Where a browser keeps its profiles
+#[derive(Debug, Clone)]
+struct BrowserLocation {
+    name: &'static str,
+    family: BrowserFamily,
+    data_root: &'static str, // Holds the profile list (Local State / profiles.ini) and the profiles
+    cache_root: Option<&'static str>, // Mirrors the profile layout for disk caches, when kept apart
+}
+
+#[cfg(target_os = "macos")]
+fn browser_locations() -> Vec<BrowserLocation> {
+    vec![
+        BrowserLocation {
+            name: "Chrome",
+            family: BrowserFamily::Chromium,
+            data_root: "~/Library/Application Support/Google/Chrome",
+            cache_root: Some("~/Library/Caches/Google/Chrome"),
+        },
+        BrowserLocation {
+            name: "Chromium",
+            family: BrowserFamily::Chromium,
+            data_root: "~/Library/Application Support/Chromium",
+            cache_root: Some("~/Library/Caches/Chromium"),
+        },
+        BrowserLocation {
+            name: "Edge",
+            family: BrowserFamily::Chromium,
+            data_root: "~/Library/Application Support/Microsoft Edge",
+            cache_root: Some("~/Library/Caches/Microsoft Edge"),
+        },
+        BrowserLocation {
+            name: "Firefox",
+            family: BrowserFamily::Firefox,
+            data_root: "~/Library/Application Support/Firefox",
+            cache_root: Some("~/Library/Caches/Firefox"),
+        },
+    ]
+}
+
+#[cfg(target_os = "windows")]
+fn browser_locations() -> Vec<BrowserLocation> {
+    vec![
+        BrowserLocation {
+            name: "Chrome",
+            family: BrowserFamily::Chromium,
+            data_root: "%LOCALAPPDATA%\\Google\\Chrome\\User Data",
+            cache_root: None,
+        },
+        BrowserLocation {
+            name: "Edge",
+            family: BrowserFamily::Chromium,
+            data_root: "%LOCALAPPDATA%\\Microsoft\\Edge\\User Data",
+            cache_root: None,
+        },
+        BrowserLocation {
+            name: "Firefox",
+            family: BrowserFamily::Firefox,
+            data_root: "%APPDATA%\\Mozilla\\Firefox",
+            cache_root: Some("%LOCALAPPDATA%\\Mozilla\\Firefox"),
+        },
+    ]
+}
+
+#[cfg(target_os = "linux")]
+fn browser_locations() -> Vec<BrowserLocation> {
+    vec![
+        BrowserLocation {
+            name: "Chrome",
+            family: BrowserFamily::Chromium,
+            data_root: "~/.config/google-chrome",
+            cache_root: Some("~/.cache/google-chrome"),
+        },
+        BrowserLocation {
+            name: "Chromium",
+            family: BrowserFamily::Chromium,
+            data_root: "~/.config/chromium",
+            cache_root: Some("~/.cache/chromium"),
+        },
+        BrowserLocation {
+            name: "Edge",
+            family: BrowserFamily::Chromium,
+            data_root: "~/.config/microsoft-edge",
+            cache_root: Some("~/.cache/microsoft-edge"),
+        },
+        BrowserLocation {
+            name: "Firefox",
+            family: BrowserFamily::Firefox,
+            data_root: "~/.mozilla/firefox",
+            cache_root: Some("~/.cache/mozilla/firefox"),
+        },
+    ]
+}
+
+/// A browser profile found on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BrowserProfile {
+    name: String, // Name the user gave the profile, or its directory name
+    path: PathBuf, // Relative to the data root, or absolute (Firefox `IsRelative=0`)
+}
+
+/// Profiles listed in a Chromium `Local State` file (`profile.info_cache`)
+fn chromium_profiles(data_root: &Path) -> Vec<BrowserProfile> {
+    let Ok(text) = fs::read_to_string(long_path(&data_root.join("Local State"))) else {
+        return Vec::new();
+    };
+    let Ok(state) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Vec::new();
+    };
+    let Some(info_cache) = state.pointer("/profile/info_cache").and_then(|v| v.as_object()) else {
+        return Vec::new();
+    };
+
+    info_cache
+        .iter()
+        .map(|(dir, info)| BrowserProfile {
+            name: info.get("name").and_then(|n| n.as_str()).unwrap_or(dir).to_string(),
+            path: PathBuf::from(dir),
+        })
+        .collect()
+}
+
+/// Profiles listed in a Firefox `profiles.ini`
+fn parse_profiles_ini(text: &str) -> Vec<BrowserProfile> {
+    let mut sections: Vec<HashMap<&str, &str>> = Vec::new();
+    let mut in_profile = false;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            // [Install...] and [General] sections don't describe profiles
+            in_profile = header.starts_with("Profile");
+            if in_profile {
+                sections.push(HashMap::new());
             }
-            Err(e) => {
-                if !options.skip_errors {
-                    return Err(format!("Failed to delete {}: {}", path, e));
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_profile) {
+            if let Some(section) = sections.last_mut() {
+                section.insert(key.trim(), value.trim());
+            }
+        }
+    }
+
+    sections
+        .into_iter()
+        .filter_map(|section| {
+            let raw = *section.get("Path")?;
+            let path = if section.get("IsRelative") == Some(&"0") {
+                PathBuf::from(raw)
+            } else {
+                // Relative paths always use '/', even on Windows
+                raw.split('/').collect()
+            };
+            Some(BrowserProfile {
+                name: section.get("Name").copied().unwrap_or(raw).to_string(),
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Profile directories recognised by their marker file, for when the profile
+/// list hasn't been written yet (fresh install) or can't be parsed
+fn marker_profiles(data_root: &Path, family: BrowserFamily) -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+
+    for parent in [PathBuf::new(), PathBuf::from("Profiles")] {
+        let Ok(read_dir) = fs::read_dir(long_path(&data_root.join(&parent))) else { continue };
+        for entry in read_dir.flatten() {
+            if entry.path().join(family.profile_marker()).is_file() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                profiles.push(BrowserProfile { path: parent.join(&name), name });
+            }
+        }
+    }
+
+    profiles
+}
+
+/// Every profile of the browser whose data lives in `data_root`
+fn discover_profiles(data_root: &Path, family: BrowserFamily) -> Vec<BrowserProfile> {
+    let listed = match family {
+        BrowserFamily::Chromium => chromium_profiles(data_root),
+        BrowserFamily::Firefox => fs::read_to_string(long_path(&data_root.join("profiles.ini")))
+            .map(|text| parse_profiles_ini(&text))
+            .unwrap_or_default(),
+    };
+
+    let mut profiles = if listed.is_empty() { marker_profiles(data_root, family) } else { listed };
+    profiles.retain(|p| data_root.join(&p.path).is_dir());
+    profiles.sort_by(|a, b| a.path.cmp(&b.path));
+    profiles.dedup_by(|a, b| a.path == b.path);
+    profiles
+}
+
+/// An allow-listed cache directory that exists in some profile
+struct ProfileCacheDir {
+    path: PathBuf,
+    subdir: &'static str,
+    what: &'static str,
+    profile: String,
+}
+
+/// Cache directories of every profile of the browser at `data_root`
+fn profile_cache_dirs(family: BrowserFamily, data_root: &Path, cache_root: Option<&Path>) -> Vec<ProfileCacheDir> {
+    let mut dirs = Vec::new();
+
+    for profile in discover_profiles(data_root, family) {
+        let mut roots = vec![data_root.join(&profile.path)];
+        // An absolute profile path has no counterpart under the cache root
+        if let Some(cache_root) = cache_root.filter(|_| profile.path.is_relative()) {
+            roots.push(cache_root.join(&profile.path));
+        }
+
+        for (subdir, what) in family.cache_dirs() {
+            for root in &roots {
+                let path = subdir.split('/').fold(root.clone(), |p, part| p.join(part));
+                if path.is_dir() {
+                    dirs.push(ProfileCacheDir {
+                        path,
+                        subdir,
+                        what,
+                        profile: profile.name.clone(),
+                    });
                 }
-                errors.push(format!("Failed to delete {}: {}", path, e));
-                failed_count += 1;
             }
         }
     }
-    
-    Ok(DeletionResult {
-        deleted_count,
-        deleted_size,
-        failed_count,
-        errors,
-        skipped_count,
-    })
+
+    dirs
+}
+
+/// Cache directories of every detected browser profile
+fn browser_cache_roots() -> Vec<PathBuf> {
+    browser_locations()
+        .into_iter()
+        .filter_map(|location| {
+            let data_root = expand_path(location.data_root)?;
+            let cache_root = location.cache_root.and_then(expand_path);
+            Some(profile_cache_dirs(location.family, &data_root, cache_root.as_deref()))
+        })
+        .flatten()
+        .map(|dir| dir.path)
+        .collect()
+}
+
+/// Scan every detected browser profile for cache data that is safe to delete
+///
+/// Profiles come from Chromium's `Local State` and Firefox's `profiles.ini`,
+/// so non-default profiles are covered too. Only the allow-listed cache
+/// subdirectories of each profile are returned; cookies, saved logins and
+/// history are never included.
+pub fn scan_browser_caches(options: CleaningOptions) -> JunkCategory {
+    let mut category = JunkCategory {
+        id: "browser_cache".to_string(),
+        name: "Browser Caches".to_string(),
+        description: "Cache data browsers rebuild on their own. Cookies, saved logins and history are left alone."
+            .to_string(),
+        items: Vec::new(),
+        total_size: 0,
+        icon: "browser_cache".to_string(),
+        filtered_size: 0,
+        filtered_count: 0,
+        unreadable_count: 0,
+    };
+
+    for location in browser_locations() {
+        let Some(data_root) = expand_path(location.data_root) else { continue };
+        let cache_root = location.cache_root.and_then(expand_path);
+        collect_browser_caches(&mut category, &location, &data_root, cache_root.as_deref(), &options);
+    }
+
+    category.items.sort_by(|a, b| b.size.cmp(&a.size));
+    category
+}
+
+fn collect_browser_caches(
+    category: &mut JunkCategory,
+    location: &BrowserLocation,
+    data_root: &Path,
+    cache_root: Option<&Path>,
+    options: &CleaningOptions,
+) {
+    let now = options.now.unwrap_or_else(SystemTime::now);
+
+    for dir in profile_cache_dirs(location.family, data_root, cache_root) {
+        let Ok(meta) = fs::metadata(long_path(&dir.path)) else { continue };
+
+        let size = calculate_dir_size(&dir.path);
+        category.unreadable_count += size.unreadable;
+
+        let (age_days, age_basis) = dir_age_days(&meta, &size, now, options.age_basis);
+        if !passes_age_filter(age_days, options.min_age_days) {
+            category.filtered_size += size.bytes;
+            category.filtered_count += 1;
+            continue;
+        }
+
+        category.total_size += size.bytes;
+        category.items.push(JunkItem {
+            path: display_path(&dir.path).to_string_lossy().to_string(),
+            name: dir.subdir.to_string(),
+            size: size.bytes,
+            description: format!("{} {} ({} profile)", location.name, dir.what, dir.profile),
+            age_days,
+            requires_reboot: false,
+            safety: classify_item(&dir.path, "browser_cache"),
+            archive: None,
+            age_basis,
+        });
+    }
+}
+
+/// All files of one extension across the cleaning roots
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+pub struct ExtensionCleanOpportunity {
+    pub extension: String, // Lowercase, without the dot
+    pub safety: SafetyLevel, // Safe for logs, temp files and dumps; Caution for other cache data
+    pub file_count: usize,
+    #[ts(type = "number")]
+    pub total_size: u64,
+    pub category_ids: Vec<String>, // Categories the files were found in
+    pub paths: Vec<String>, // The largest files, at most MAX_BUCKET_PATHS; the counts above cover all of them
+}
+
+/// Paths kept per extension bucket; a cache can hold hundreds of thousands of files
+const MAX_BUCKET_PATHS: usize = 100;
+
+/// Extensions that are throwaway wherever they appear inside a cache
+const DISPOSABLE_EXTENSIONS: &[&str] = &[
+    "log", "tmp", "temp", "crash", "old", "bak", "dmp", "etl", "trace",
+];
+
+/// Never `Risky`: that is judged per item, from app-state names, not per extension
+fn extension_safety(extension: &str) -> SafetyLevel {
+    if DISPOSABLE_EXTENSIONS.contains(&extension) {
+        SafetyLevel::Safe
+    } else {
+        SafetyLevel::Caution
+    }
+}
+
+/// Bucket the files under every cleaning root by extension, largest first
+///
+/// A cross-category view of the same roots `scan_junk_items_with_options`
+/// lists: "3.2 GB of .log files across caches". Files failing the age
+/// filter are left out. Roots only cleanable on restart are skipped.
+pub fn scan_extension_opportunities(options: CleaningOptions) -> Vec<ExtensionCleanOpportunity> {
+    let mut roots: Vec<(PathBuf, &'static str)> = get_cleaning_paths()
+        .into_iter()
+        .filter(|cp| !cp.requires_reboot)
+        .flat_map(|cp| expand_paths(&cp).into_iter().map(move |path| (path, cp.category_id)))
+        .chain(browser_cache_roots().into_iter().map(|path| (path, "browser_cache")))
+        .collect();
+    // Wildcard cleaning paths can name the same profile caches the browser scan finds
+    roots.sort();
+    roots.dedup_by(|a, b| a.0 == b.0);
+
+    extension_opportunities(&roots, &options)
+}
+
+fn extension_opportunities(roots: &[(PathBuf, &str)], options: &CleaningOptions) -> Vec<ExtensionCleanOpportunity> {
+    let now = options.now.unwrap_or_else(SystemTime::now);
+    // Each bucket keeps its largest paths in a min-heap, so the smallest is evicted first
+    let mut buckets: HashMap<String, (ExtensionCleanOpportunity, BinaryHeap<Reverse<(u64, String)>>)> = HashMap::new();
+    let mut seen = HashSet::new();
+
+    for (root, category_id) in roots {
+        for entry in walkdir::WalkDir::new(long_path(root)).min_depth(1).into_iter().flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Some(extension) = entry.path().extension().map(|e| e.to_string_lossy().to_lowercase()) else {
+                continue;
+            };
+            let Ok(meta) = entry.metadata() else { continue };
+            if !passes_age_filter(get_file_age_days(&meta, now, options.age_basis).0, options.min_age_days) {
+                continue;
+            }
+
+            // Nested cleaning roots (e.g. a browser cache inside ~/.cache) would count twice
+            let path = display_path(entry.path()).to_string_lossy().to_string();
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+
+            let (bucket, largest) = buckets.entry(extension.clone()).or_insert_with(|| {
+                let bucket = ExtensionCleanOpportunity {
+                    safety: extension_safety(&extension),
+                    extension,
+                    file_count: 0,
+                    total_size: 0,
+                    category_ids: Vec::new(),
+                    paths: Vec::new(),
+                };
+                (bucket, BinaryHeap::new())
+            });
+            bucket.file_count += 1;
+            bucket.total_size += meta.len();
+            largest.push(Reverse((meta.len(), path)));
+            if largest.len() > MAX_BUCKET_PATHS {
+                largest.pop();
+            }
+            if !bucket.category_ids.iter().any(|id| id == category_id) {
+                bucket.category_ids.push(category_id.to_string());
+            }
+        }
+    }
+
+    let mut buckets: Vec<ExtensionCleanOpportunity> = buckets
+        .into_values()
+        .map(|(mut bucket, largest)| {
+            // Ascending order of Reverse is largest first
+            bucket.paths = largest.into_sorted_vec().into_iter().map(|Reverse((_, path))| path).collect();
+            bucket
+        })
+        .collect();
+    buckets.sort_by(|a, b| b.total_size.cmp(&a.total_size).then_with(|| a.extension.cmp(&b.extension)));
+    buckets
+}
+
+pub fn delete_junk_items(paths: Vec<String>) -> Result<(), String> {
+    let result = delete_junk_items_with_options(paths, CleaningOptions::default())?;
+    
+    if result.failed_count > 0 {
+        Err(result.errors.join("\n"))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn delete_junk_items_with_options(
+    paths: Vec<String>,
+    options: CleaningOptions,
+) -> Result<DeletionResult, String> {
+    let plan = DeletePlan::new(&options);
+    let mut result = plan.delete(dedupe_delete_set(paths), &options)?;
+    result.pruned_dir_count = plan.prune_affected(&options);
+    Ok(result)
+}
+
+/// The parts of a deletion run that don't depend on the individual path
+///
+/// Built once per run, so a selection deleted in chunks (or per category)
+/// doesn't resolve the cleaning roots and the delete guard again each time.
+pub struct DeletePlan {
+    now: SystemTime,
+    reboot_roots: Vec<PathBuf>,
+    guard: DeleteGuard,
+    cleaning_roots: Vec<PathBuf>, // Pruned after deletion; empty unless prune_empty_dirs is set
+    affected_roots: Mutex<Vec<PathBuf>>,
+}
+
+impl DeletePlan {
+    pub fn new(options: &CleaningOptions) -> Self {
+        DeletePlan {
+            now: options.now.unwrap_or_else(SystemTime::now),
+            reboot_roots: reboot_only_roots(),
+            guard: DeleteGuard::new(),
+            cleaning_roots: if options.prune_empty_dirs {
+                get_cleaning_paths()
+                    .into_iter()
+                    .flat_map(|cp| expand_paths(&cp))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+            affected_roots: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Remove empty directories left in the cleaning roots that deletions so far touched
+    ///
+    /// Returns how many were removed. Roots are only pruned once; later
+    /// deletions start a new set.
+    pub fn prune_affected(&self, options: &CleaningOptions) -> usize {
+        let roots = self.affected_roots.lock().map(|mut roots| std::mem::take(&mut *roots)).unwrap_or_default();
+        roots
+            .iter()
+            .map(|root| prune_empty_dirs(root, options.preserve_root_dirs))
+            .sum()
+    }
+
+    /// What deleting `path` with `options` would do, decided without changing anything
+    ///
+    /// `path` must already be past `dedupe_delete_set`. Shared by `delete` and
+    /// `preview_deletion`, so a preview shows what a run would actually do.
+    fn resolve(&self, path: &str, options: &CleaningOptions) -> ItemPlan {
+        let unmeasured = |action| ItemPlan { action, size: 0, age_days: None, age_basis: AgeBasis::Modified };
+
+        // Container storage and shadow copies are removed through their own
+        // tools, never deleted file by file
+        if path.starts_with(container_cache::CONTAINER_SCHEME) || path.starts_with(shadow_copies::SHADOW_SCHEME) {
+            return unmeasured(ItemAction::Reclaim);
+        }
+
+        let p = Path::new(path);
+        if let Err(e) = self.guard.check(p) {
+            return unmeasured(ItemAction::Refused(e));
+        }
+        let metadata = match fs::metadata(long_path(p)) {
+            Ok(m) => m,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return unmeasured(ItemAction::Unreadable("No longer exists".to_string()));
+            }
+            Err(e) => return unmeasured(ItemAction::Unreadable(format!("Can't be read: {}", e))),
+        };
+
+        let (size, (age_days, age_basis)) = measure_item(p, &metadata, self.now, options);
+        let action = if !passes_age_filter(age_days, options.min_age_days) {
+            let min_age = options.min_age_days.unwrap_or_default();
+            let what = match age_basis {
+                AgeBasis::Modified => "modified",
+                AgeBasis::Accessed => "used",
+            };
+            ItemAction::Skip(match age_days {
+                Some(age) => format!("Last {} {} days ago, newer than the {}-day minimum age", what, age, min_age),
+                None => format!("Age unknown, so the {}-day minimum age can't be checked", min_age),
+            })
+        } else if !metadata.is_file() && !metadata.is_dir() {
+            ItemAction::Skip("Not a regular file or folder".to_string())
+        } else if self.reboot_roots.iter().any(|root| p.starts_with(root)) {
+            // In-use system caches are scheduled for the next restart instead of
+            // failing now with sharing violations
+            ItemAction::DeferUntilRestart
+        } else {
+            ItemAction::Remove
+        };
+
+        ItemPlan { action, size, age_days, age_basis }
+    }
+
+    /// Delete `paths`, which must already be free of overlap (`dedupe_delete_set`)
+    ///
+    /// Empty directories are left for `prune_affected`, so `pruned_dir_count` is 0.
+    pub fn delete(&self, paths: Vec<String>, options: &CleaningOptions) -> Result<DeletionResult, String> {
+        let mut result = DeletionResult::default();
+
+        for path in paths {
+            let item = self.resolve(&path, options);
+            let outcome = match item.action {
+                ItemAction::Reclaim => reclaim(&path, options.dry_run).map(|freed| {
+                    result.deleted_count += 1;
+                    result.deleted_size += freed;
+                }),
+                // Never stop the run for this: it only catches a bad path from the caller
+                ItemAction::Refused(e) => {
+                    result.errors.push(e);
+                    result.failed_count += 1;
+                    Ok(())
+                }
+                ItemAction::Unreadable(reason) => Err(format!("{}: {}", path, reason)),
+                ItemAction::Skip(_) => {
+                    result.skipped_count += 1;
+                    Ok(())
+                }
+                ItemAction::DeferUntilRestart => {
+                    let scheduled = if options.dry_run { Ok(()) } else { schedule_delete_on_reboot(Path::new(&path)) };
+                    scheduled
+                        .map(|()| {
+                            result.deferred_count += 1;
+                            result.deferred_size += item.size;
+                        })
+                        .map_err(|e| format!("Failed to schedule {} for deletion on restart: {}", path, e))
+                }
+                // Dry run mode - don't actually delete
+                ItemAction::Remove if options.dry_run => {
+                    result.deleted_count += 1;
+                    result.deleted_size += item.size;
+                    Ok(())
+                }
+                ItemAction::Remove => self.remove(&path, options).map(|trashed| {
+                    result.deleted_count += 1;
+                    result.deleted_size += item.size;
+                    if trashed {
+                        result.trashed_count += 1;
+                    } else if options.use_trash {
+                        result.permanently_deleted.push(path.clone());
+                    }
+                }),
+            };
+
+            if let Err(e) = outcome {
+                if !options.skip_errors {
+                    return Err(e);
+                }
+                result.errors.push(e);
+                result.failed_count += 1;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Delete or trash one path; true if it went to the trash
+    fn remove(&self, path: &str, options: &CleaningOptions) -> Result<bool, String> {
+        let p = Path::new(path);
+        let long = long_path(p);
+        let trashed = if options.use_trash {
+            move_to_trash(p, &long, options.skip_errors)?
+        } else {
+            remove_permanently(&long).map_err(|e| format!("Failed to delete {}: {}", path, e))?;
+            false
+        };
+
+        // The most specific cleaning root holding the item gets pruned afterwards
+        if let Some(root) = self
+            .cleaning_roots
+            .iter()
+            .filter(|root| p.starts_with(root))
+            .max_by_key(|root| root.components().count())
+        {
+            if let Ok(mut affected) = self.affected_roots.lock() {
+                if !affected.contains(root) {
+                    affected.push(root.clone());
+                }
+            }
+        }
+        Ok(trashed)
+    }
+}
+
+/// What a deletion run does with one path
+enum ItemAction {
+    /// Container storage or shadow copies, removed through their own tools
+    Reclaim,
+    /// Refused by the delete guard; a failure, but never one that stops the run
+    Refused(String),
+    /// Missing or unreadable; a failure that stops the run unless `skip_errors` is set
+    Unreadable(String),
+    /// Left alone: newer than the minimum age, or not a regular file or folder
+    Skip(String),
+    /// In-use system cache, removed on the next restart
+    DeferUntilRestart,
+    /// Deleted, or moved to the trash with `use_trash`
+    Remove,
+}
+
+/// One resolved path of a deletion run
+struct ItemPlan {
+    action: ItemAction,
+    size: u64, // 0 for Reclaim and for paths that couldn't be read
+    age_days: Option<u32>,
+    age_basis: AgeBasis,
+}
+
+/// Run (or, with `dry_run`, measure) the tool that reclaims a container storage or shadow copy path
+fn reclaim(path: &str, dry_run: bool) -> Result<u64, String> {
+    if path.starts_with(container_cache::CONTAINER_SCHEME) {
+        container_cache::prune(path, dry_run)
+    } else {
+        shadow_copies::delete_oldest(path, dry_run)
+    }
+}
+
+/// Size and age of a path about to be deleted; a directory's access age comes from its contents
+///
+/// `min_age_days` is applied while measuring, so a directory too new to
+/// delete isn't walked in full: its own modification time bounds its age
+/// under either basis, and an access age walk stops at the first file used
+/// within the minimum age. A directory that fails this way reports size 0.
+fn measure_item(path: &Path, metadata: &fs::Metadata, now: SystemTime, options: &CleaningOptions) -> (u64, (Option<u32>, AgeBasis)) {
+    if !metadata.is_dir() {
+        return (metadata.len(), get_file_age_days(metadata, now, options.age_basis));
+    }
+
+    let modified_age = get_file_age_days(metadata, now, AgeBasis::Modified);
+    if !passes_age_filter(modified_age.0, options.min_age_days) {
+        return (0, modified_age);
+    }
+
+    let used_after = match (options.age_basis, options.min_age_days) {
+        (AgeBasis::Accessed, Some(min_age)) => now.checked_sub(Duration::from_secs(u64::from(min_age) * 86400)),
+        _ => None,
+    };
+    let dir_size = walk_dir_size(path, None, used_after);
+    let age = dir_age_days(metadata, &dir_size, now, options.age_basis);
+    if !passes_age_filter(age.0, options.min_age_days) {
+        return (0, age);
+    }
+    (dir_size.bytes, age)
+}
+
+/// What deleting a path with the given options would do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum PreviewAction {
+    Delete,
+    Trash,
+    DeferUntilRestart, // In-use system cache, removed on the next restart
+    Reclaim,           // Container storage or shadow copies, removed through their own tools
+    Skip,
+}
+
+/// One resolved path of a deletion preview
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DeletionPreviewItem {
+    pub path: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    pub age_days: Option<u32>,
+    pub age_basis: AgeBasis,
+    pub action: PreviewAction,
+    pub skip_reason: Option<String>, // Set when action is Skip
+}
+
+/// Every path a deletion with `options` would touch or leave alone, read from disk now
+///
+/// Resolves each path with the same `DeletePlan` a deletion uses, after the
+/// same overlap removal, but changes nothing, so a confirmation dialog can
+/// show current sizes instead of the possibly stale scan snapshot.
+pub fn preview_deletion(paths: Vec<String>, options: &CleaningOptions) -> Vec<DeletionPreviewItem> {
+    let plan = DeletePlan::new(options);
+
+    let kept = dedupe_delete_set(paths.clone());
+    let kept_set: HashSet<&String> = kept.iter().collect();
+
+    let skipped = |path: &str, reason: String| DeletionPreviewItem {
+        path: path.to_string(),
+        size: 0,
+        age_days: None,
+        age_basis: AgeBasis::Modified,
+        action: PreviewAction::Skip,
+        skip_reason: Some(reason),
+    };
+
+    let mut items = Vec::with_capacity(paths.len());
+    let mut seen = HashSet::new();
+    for path in &paths {
+        if !seen.insert(path) {
+            continue;
+        }
+        if !kept_set.contains(path) {
+            items.push(skipped(path, "Inside another selected folder, which is deleted with it".to_string()));
+            continue;
+        }
+
+        let item = plan.resolve(path, options);
+        let (action, skip_reason) = match item.action {
+            ItemAction::Reclaim => {
+                items.push(match reclaim(path, true) {
+                    Ok(size) => DeletionPreviewItem {
+                        path: path.clone(),
+                        size,
+                        age_days: None,
+                        age_basis: AgeBasis::Modified,
+                        action: PreviewAction::Reclaim,
+                        skip_reason: None,
+                    },
+                    Err(e) => skipped(path, e),
+                });
+                continue;
+            }
+            ItemAction::Refused(reason) | ItemAction::Unreadable(reason) | ItemAction::Skip(reason) => {
+                (PreviewAction::Skip, Some(reason))
+            }
+            ItemAction::DeferUntilRestart => (PreviewAction::DeferUntilRestart, None),
+            ItemAction::Remove if options.use_trash => (PreviewAction::Trash, None),
+            ItemAction::Remove => (PreviewAction::Delete, None),
+        };
+
+        items.push(DeletionPreviewItem {
+            path: path.clone(),
+            size: item.size,
+            age_days: item.age_days,
+            age_basis: item.age_basis,
+            action,
+            skip_reason,
+        });
+    }
+
+    items
+}
+
+fn remove_permanently(long: &Path) -> std::io::Result<()> {
+    if long.is_dir() {
+        fs::remove_dir_all(long)
+    } else {
+        fs::remove_file(long)
+    }
+}
+
+/// Last line of defence against deleting the OS or a whole user profile
+///
+/// Refuses filesystem roots, a fixed list of system and profile directories,
+/// and anything that contains a cleaning location instead of lying inside
+/// one. Paths are compared in canonical form, so `..` and symlinks don't get
+/// around it.
+struct DeleteGuard {
+    critical: Vec<PathBuf>,
+    cleaning_roots: Vec<PathBuf>,
+}
+
+impl DeleteGuard {
+    fn new() -> Self {
+        let roots = get_cleaning_paths()
+            .into_iter()
+            .flat_map(|cp| expand_paths(&cp))
+            .chain(browser_cache_roots());
+        Self::with_paths(critical_paths(), roots)
+    }
+
+    fn with_paths(critical: impl IntoIterator<Item = PathBuf>, cleaning_roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        let canonical = |p: PathBuf| fs::canonicalize(&p).unwrap_or(p);
+        Self {
+            critical: critical.into_iter().map(canonical).collect(),
+            cleaning_roots: cleaning_roots.into_iter().map(canonical).collect(),
+        }
+    }
+
+    fn check(&self, path: &Path) -> Result<(), String> {
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let refuse = |why: &str| Err(format!("Refusing to delete {}: {}", path.display(), why));
+
+        if canonical.parent().is_none() {
+            return refuse("it is the root of a filesystem");
+        }
+        if self.critical.contains(&canonical) {
+            return refuse("it is a protected system or user folder");
+        }
+        // An item inside one cleaning location may hold another (~/.cache/mozilla
+        // holds the Firefox cache root); only a folder above all of them is refused
+        let inside_root = self
+            .cleaning_roots
+            .iter()
+            .any(|root| canonical.starts_with(root) && *root != canonical);
+        if !inside_root {
+            if let Some(root) = self.cleaning_roots.iter().find(|root| root.starts_with(&canonical) && **root != canonical) {
+                return refuse(&format!("it contains the cleaning location {}", root.display()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Folders the cleaner must never delete as a whole
+fn critical_paths() -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let fixed: Vec<PathBuf> = ["SystemRoot", "ProgramFiles", "ProgramFiles(x86)", "ProgramData", "PUBLIC"]
+        .iter()
+        .filter_map(|var| std::env::var_os(var).map(PathBuf::from))
+        .chain(std::env::var_os("SystemDrive").map(|drive| Path::new(&drive).join("\\Users")))
+        .collect();
+    #[cfg(not(target_os = "windows"))]
+    let fixed: Vec<PathBuf> = [
+        "/System", "/Library", "/Applications", "/Users", "/private", "/usr", "/bin", "/sbin", "/lib",
+        "/lib64", "/etc", "/var", "/opt", "/boot", "/home", "/root", "/dev", "/proc", "/sys",
+    ]
+    .iter()
+    .map(PathBuf::from)
+    .collect();
+
+    fixed.into_iter().chain(dirs::home_dir()).collect()
+}
+
+/// Move `path` to the trash; true if it went there, false if it was deleted permanently
+///
+/// Trashing can fail where the trash isn't reachable (e.g. a Linux volume
+/// with no trash directory of its own). With `skip_errors` the item is then
+/// deleted permanently and listed in `DeletionResult::permanently_deleted`;
+/// otherwise the failure is returned.
+fn move_to_trash(path: &Path, long: &Path, skip_errors: bool) -> Result<bool, String> {
+    // The trash APIs take the plain path, not the \\?\ long form
+    match trash::delete(path) {
+        Ok(()) => Ok(true),
+        Err(e) if skip_errors => {
+            log::warn!("Couldn't move {} to the trash ({}); deleting it permanently", path.display(), e);
+            remove_permanently(long)
+                .map(|()| false)
+                .map_err(|de| format!("Failed to delete {}: {} (moving it to the trash also failed: {})", path.display(), de, e))
+        }
+        Err(e) => Err(format!("Failed to move {} to the trash: {}", path.display(), e)),
+    }
+}
+
+/// Split an overlap-free delete set across (category id, paths) groups
+///
+/// A path selected under several categories, or inside a directory selected
+/// under another, goes to the first category that claims it.
+pub fn claim_category_paths(groups: Vec<(String, Vec<String>)>) -> Vec<(String, Vec<String>)> {
+    let mut unclaimed: HashSet<String> = dedupe_delete_set(
+        groups.iter().flat_map(|(_, paths)| paths.iter().cloned()).collect(),
+    )
+    .into_iter()
+    .collect();
+
+    groups
+        .into_iter()
+        .map(|(category_id, paths)| {
+            let paths = paths.into_iter().filter(|p| unclaimed.remove(p)).collect();
+            (category_id, paths)
+        })
+        .collect()
+}
+
+/// Drop duplicates and paths inside another selected directory
+///
+/// Deleting a parent already removes its children, so a child listed alongside
+/// it would otherwise fail with "does not exist". Paths are compared in
+/// canonical form (symlinks and `..` resolved); ones that can't be
+/// canonicalized, e.g. because they're missing, are compared as given.
+pub fn dedupe_delete_set(paths: Vec<String>) -> Vec<String> {
+    let mut keyed: Vec<(PathBuf, String)> = paths
+        .into_iter()
+        .map(|path| (fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path)), path))
+        .collect();
+
+    // Component-wise ordering puts every parent before its descendants
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut kept: Vec<(PathBuf, String)> = Vec::with_capacity(keyed.len());
+    for (canonical, path) in keyed {
+        if kept.last().is_some_and(|(parent, _)| canonical.starts_with(parent)) {
+            continue;
+        }
+        kept.push((canonical, path));
+    }
+
+    kept.into_iter().map(|(_, path)| path).collect()
+}
+
+/// Remove directories under `root` that are empty, deepest first
+///
+/// A directory holding anything at all - including hidden or system files -
+/// is kept. Symlinks aren't followed. Returns how many were removed.
+fn prune_empty_dirs(root: &Path, keep_root: bool) -> usize {
+    let root = long_path(root);
+    let min_depth = if keep_root { 1 } else { 0 };
+    let mut pruned = 0;
+
+    for entry in walkdir::WalkDir::new(&root).min_depth(min_depth).contents_first(true) {
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+
+        let is_empty = fs::read_dir(entry.path())
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        if is_empty && fs::remove_dir(entry.path()).is_ok() {
+            pruned += 1;
+        }
+    }
+
+    pruned
+}
+
+/// Expanded cleaning paths whose contents can only be removed on restart
+fn reboot_only_roots() -> Vec<PathBuf> {
+    get_cleaning_paths()
+        .into_iter()
+        .filter(|cp| cp.requires_reboot)
+        .flat_map(|cp| expand_paths(&cp))
+        .collect()
+}
+
+/// Register a file or directory tree for deletion on the next restart
+///
+/// MoveFileEx only removes empty directories at boot, so contents are
+/// registered before their parent. Needs administrator rights.
+#[cfg(target_os = "windows")]
+fn schedule_delete_on_reboot(path: &Path) -> Result<(), String> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{MoveFileExW, MOVEFILE_DELAY_UNTIL_REBOOT};
+
+    for entry in walkdir::WalkDir::new(long_path(path)).contents_first(true) {
+        let entry = entry.map_err(|e| e.to_string())?;
+        let wide: Vec<u16> = entry.path().as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+        unsafe { MoveFileExW(PCWSTR(wide.as_ptr()), PCWSTR::null(), MOVEFILE_DELAY_UNTIL_REBOOT) }
+            .map_err(|e| format!("{}: {}", entry.path().display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn schedule_delete_on_reboot(_path: &Path) -> Result<(), String> {
+    Err("Deletion on restart is only supported on Windows".to_string())
+}
+
+/// Estimated vs actual space freed for one category
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryReclaim {
+    pub category_id: String,
+    pub estimated_freed: u64, // Sum of the scanned sizes of the items sent for deletion
+    pub actual_freed: u64, // Bytes actually deleted (failed and skipped items excluded)
+    pub deferred_freed: u64, // Bytes scheduled for deletion on the next restart
+    pub deleted_count: usize,
+    pub failed_count: usize,
+    pub skipped_count: usize,
+    pub deferred_count: usize,
+    pub trashed_count: usize, // Of deleted_count, items moved to the trash and still recoverable
+    pub permanently_deleted: Vec<String>, // Items the trash refused that were deleted permanently instead
+    pub pruned_dir_count: usize, // Empty directories removed after this category's items
+    pub errors: Vec<String>,
+}
+
+impl CategoryReclaim {
+    pub fn new(category_id: String, estimated_freed: u64, result: DeletionResult) -> Self {
+        CategoryReclaim {
+            category_id,
+            estimated_freed,
+            actual_freed: result.deleted_size,
+            deferred_freed: result.deferred_size,
+            deleted_count: result.deleted_count,
+            failed_count: result.failed_count,
+            skipped_count: result.skipped_count,
+            deferred_count: result.deferred_count,
+            trashed_count: result.trashed_count,
+            permanently_deleted: result.permanently_deleted,
+            pruned_dir_count: result.pruned_dir_count,
+            errors: result.errors,
+        }
+    }
+}
+
+/// Per-category outcome of a cleaning run
+///
+/// `actual_freed` can fall short of `estimated_freed` because of failures,
+/// skipped items or files that changed since the scan; the volume delta can
+/// differ again because of hardlinks, snapshots or other activity on the disk.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ReclaimReport {
+    pub categories: Vec<CategoryReclaim>,
+    pub estimated_freed: u64,
+    pub actual_freed: u64,
+    pub volume_free_delta: Option<i64>, // Change in free space on the affected volumes (None if unknown or dry run)
+}
+
+impl ReclaimReport {
+    pub fn push(&mut self, category: CategoryReclaim) {
+        self.estimated_freed += category.estimated_freed;
+        self.actual_freed += category.actual_freed;
+        self.categories.push(category);
+    }
+}
+
+/// Each category's item paths, claimed across categories as by `claim_category_paths`,
+/// with the scanned size of the items sent for deletion
+pub fn category_delete_groups(categories: &[JunkCategory]) -> Vec<(String, Vec<String>, u64)> {
+    let sizes: HashMap<&str, u64> = categories
+        .iter()
+        .flat_map(|c| c.items.iter().map(|i| (i.path.as_str(), i.size)))
+        .collect();
+    let groups = categories
+        .iter()
+        .map(|c| (c.id.clone(), c.items.iter().map(|i| i.path.clone()).collect()))
+        .collect();
+
+    claim_category_paths(groups)
+        .into_iter()
+        .map(|(id, paths)| {
+            let estimated = paths.iter().map(|p| sizes.get(p.as_str()).copied().unwrap_or(0)).sum();
+            (id, paths, estimated)
+        })
+        .collect()
+}
+
+/// Total free space on the volumes holding the items of `categories`, for `volume_free_delta`
+pub fn categories_free_space(categories: &[JunkCategory]) -> Option<u64> {
+    let paths: Vec<&str> = categories
+        .iter()
+        .flat_map(|c| c.items.iter().map(|i| i.path.as_str()))
+        .collect();
+    volume_free_space(&paths)
+}
+
+/// Total free space on the volumes holding `paths`
+fn volume_free_space(paths: &[&str]) -> Option<u64> {
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+
+    // The volume of a path is the one with the longest matching mount point
+    let mut volumes: Vec<usize> = paths
+        .iter()
+        .filter_map(|path| {
+            disks
+                .iter()
+                .enumerate()
+                .filter(|(_, d)| Path::new(path).starts_with(d.mount_point()))
+                .max_by_key(|(_, d)| d.mount_point().as_os_str().len())
+                .map(|(index, _)| index)
+        })
+        .collect();
+    volumes.sort_unstable();
+    volumes.dedup();
+
+    if volumes.is_empty() {
+        return None;
+    }
+    Some(volumes.iter().map(|&i| disks[i].available_space()).sum())
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeletableCheck {
+    pub path: String,
+    pub deletable: bool, // Deletion is likely to succeed for this process
+    pub reason: Option<String>, // Why deletion would fail
+}
+
+/// Predict whether each path could be deleted by this process, without deleting anything
+pub fn check_deletable(paths: &[String]) -> Vec<DeletableCheck> {
+    paths
+        .iter()
+        .map(|path| {
+            let reason = deletion_blocker(Path::new(path)).err();
+            DeletableCheck {
+                path: path.clone(),
+                deletable: reason.is_none(),
+                reason,
+            }
+        })
+        .collect()
+}
+
+fn deletion_blocker(path: &Path) -> Result<(), String> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| format!("Cannot read: {}", e))?;
+    let parent = path
+        .parent()
+        .ok_or_else(|| "Cannot delete a filesystem root".to_string())?;
+
+    check_parent_writable(parent, &metadata)?;
+
+    // Windows refuses to delete read-only files; Unix only cares about the parent
+    #[cfg(windows)]
+    if metadata.permissions().readonly() {
+        return Err("File is read-only".to_string());
+    }
+
+    Ok(())
+}
+
+/// Removing a directory entry needs write access to its parent; in sticky
+/// directories (e.g. /tmp) the entry must also belong to us
+#[cfg(unix)]
+fn check_parent_writable(parent: &Path, metadata: &fs::Metadata) -> Result<(), String> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+
+    let c_parent = CString::new(parent.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    if unsafe { libc::access(c_parent.as_ptr(), libc::W_OK) } != 0 {
+        return Err(format!("No write permission on {}", parent.display()));
+    }
+
+    let parent_meta = fs::metadata(parent).map_err(|e| e.to_string())?;
+    let euid = unsafe { libc::geteuid() };
+    if parent_meta.mode() & 0o1000 != 0 && euid != 0 && metadata.uid() != euid && parent_meta.uid() != euid {
+        return Err("Owned by another user in a shared directory".to_string());
+    }
+
+    Ok(())
+}
+
+/// Only the read-only attribute is checked; ACLs aren't evaluated
+#[cfg(not(unix))]
+fn check_parent_writable(parent: &Path, _metadata: &fs::Metadata) -> Result<(), String> {
+    let parent_meta = fs::metadata(parent).map_err(|e| e.to_string())?;
+    if parent_meta.permissions().readonly() {
+        return Err(format!("{} is read-only", parent.display()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+    use std::time::Duration;
+
+    const DAY: Duration = Duration::from_secs(86400);
+
+    #[test]
+    fn test_age_filter_boundary_is_included() {
+        assert!(passes_age_filter(Some(7), Some(7)));
+        assert!(!passes_age_filter(Some(6), Some(7)));
+        assert!(passes_age_filter(Some(0), None));
+    }
+
+    #[test]
+    fn test_age_filter_excludes_unknown_age() {
+        assert!(!passes_age_filter(None, Some(7)));
+        assert!(passes_age_filter(None, None));
+    }
+
+    #[test]
+    fn test_delete_respects_injected_clock() {
+        let dir = TestDir::new("cleaner-test");
+        let file = dir.join("old.log");
+        fs::write(&file, b"junk").unwrap();
+
+        let modified = fs::metadata(&file).unwrap().modified().unwrap();
+        let options = |now: SystemTime| CleaningOptions {
+            min_age_days: Some(7),
+            dry_run: true,
+            now: Some(now),
+            ..CleaningOptions::default()
+        };
+        let paths = vec![file.to_string_lossy().to_string()];
+
+        // Exactly at the boundary: included
+        let result = delete_junk_items_with_options(paths.clone(), options(modified + 7 * DAY)).unwrap();
+        assert_eq!(result.deleted_count, 1);
+        assert_eq!(result.skipped_count, 0);
+
+        // One day short: skipped
+        let result = delete_junk_items_with_options(paths, options(modified + 6 * DAY)).unwrap();
+        assert_eq!(result.deleted_count, 0);
+        assert_eq!(result.skipped_count, 1);
+    }
+
+    #[test]
+    fn test_in_use_paths_are_deferred_until_restart() {
+        let dir = TestDir::new("cleaner-test");
+        let prefetch = dir.join("Prefetch");
+        fs::create_dir_all(&prefetch).unwrap();
+        let held = prefetch.join("APP.pf");
+        fs::write(&held, b"in use").unwrap();
+        let free = dir.join("free.log");
+        fs::write(&free, b"junk").unwrap();
+
+        let options = CleaningOptions {
+            dry_run: true,
+            ..CleaningOptions::default()
+        };
+        let mut plan = DeletePlan::new(&options);
+        plan.reboot_roots = vec![prefetch.clone()];
+        let path = |p: &Path| p.to_string_lossy().to_string();
+
+        assert!(matches!(plan.resolve(&path(&held), &options).action, ItemAction::DeferUntilRestart));
+        assert!(matches!(plan.resolve(&path(&free), &options).action, ItemAction::Remove));
+
+        let result = plan.delete(vec![path(&held), path(&free)], &options).unwrap();
+        assert_eq!(result.deferred_count, 1);
+        assert_eq!(result.deferred_size, 6);
+        assert_eq!(result.deleted_count, 1);
+        assert_eq!(result.deleted_size, 4);
+        assert!(held.exists());
+    }
+
+    #[test]
+    fn test_access_age_falls_back_to_modified() {
+        let dir = TestDir::new("cleaner-test");
+        let file = dir.join("cache.bin");
+        fs::write(&file, b"junk").unwrap();
+
+        let now = SystemTime::now();
+        let set_times = |accessed: SystemTime| {
+            let times = fs::FileTimes::new().set_modified(now - 30 * DAY).set_accessed(accessed);
+            fs::File::options().write(true).open(&file).unwrap().set_times(times).unwrap();
+        };
+
+        // Read yesterday, written a month ago
+        set_times(now - DAY);
+        let meta = fs::metadata(&file).unwrap();
+        assert_eq!(get_file_age_days(&meta, now, AgeBasis::Modified), (Some(30), AgeBasis::Modified));
+        assert_eq!(get_file_age_days(&meta, now, AgeBasis::Accessed), (Some(1), AgeBasis::Accessed));
+
+        // The directory itself was just written, which counts as use too
+        let size = calculate_dir_size(&dir);
+        assert!(size.last_accessed.is_some());
+        let dir_meta = fs::metadata(&dir).unwrap();
+        assert_eq!(dir_age_days(&dir_meta, &size, now, AgeBasis::Accessed), (Some(0), AgeBasis::Accessed));
+
+        // No read recorded since the last write (noatime, relatime): modification time is used
+        set_times(now - 30 * DAY);
+        let meta = fs::metadata(&file).unwrap();
+        assert_eq!(get_file_age_days(&meta, now, AgeBasis::Accessed), (Some(30), AgeBasis::Modified));
+    }
+
+    #[test]
+    fn test_age_filter_skips_new_directories_without_measuring_them() {
+        let dir = TestDir::new("cleaner-test");
+        let cache = dir.join("cache");
+        fs::create_dir_all(&cache).unwrap();
+        let file = cache.join("a.bin");
+        fs::write(&file, vec![0u8; 100]).unwrap();
+
+        let created = fs::metadata(&cache).unwrap().modified().unwrap();
+        let times = fs::FileTimes::new().set_modified(created - 30 * DAY).set_accessed(created + 6 * DAY);
+        fs::File::options().write(true).open(&file).unwrap().set_times(times).unwrap();
+        let resolve = |age_basis, now| {
+            let options = CleaningOptions {
+                min_age_days: Some(7),
+                age_basis,
+                now: Some(now),
+                ..CleaningOptions::default()
+            };
+            DeletePlan::new(&options).resolve(&cache.to_string_lossy(), &options)
+        };
+
+        // The folder itself changed 3 days ago: skipped before its contents are read
+        let item = resolve(AgeBasis::Modified, created + 3 * DAY);
+        assert!(matches!(item.action, ItemAction::Skip(_)));
+        assert_eq!(item.size, 0);
+
+        let item = resolve(AgeBasis::Modified, created + 8 * DAY);
+        assert!(matches!(item.action, ItemAction::Remove));
+        assert_eq!(item.size, 100);
+
+        // Old enough by modification, but a file inside was used 2 days ago
+        let item = resolve(AgeBasis::Accessed, created + 8 * DAY);
+        assert!(matches!(item.action, ItemAction::Skip(_)));
+        assert_eq!((item.size, item.age_days, item.age_basis), (0, Some(2), AgeBasis::Accessed));
+    }
+
+    #[test]
+    fn test_preview_deletion_explains_skips() {
+        let dir = TestDir::new("cleaner-test");
+        let cache = dir.join("cache");
+        fs::create_dir_all(&cache).unwrap();
+        let inner = cache.join("entry.tmp");
+        fs::write(&inner, b"junk").unwrap();
+        let log = dir.join("new.log");
+        fs::write(&log, b"fresh").unwrap();
+
+        let modified = fs::metadata(&log).unwrap().modified().unwrap();
+        let options = CleaningOptions {
+            min_age_days: Some(7),
+            now: Some(modified + 3 * DAY),
+            ..CleaningOptions::default()
+        };
+        let path = |p: &Path| p.to_string_lossy().to_string();
+        let paths = vec![path(&inner), path(&cache), path(&log), path(&dir.join("gone.tmp"))];
+
+        let preview = preview_deletion(paths, &options);
+        assert_eq!(preview.len(), 4);
+        assert_eq!(preview[0].action, PreviewAction::Skip); // Covered by cache/
+        assert_eq!(preview[2].action, PreviewAction::Skip);
+        assert_eq!(preview[2].size, 5);
+        assert_eq!(preview[2].age_days, Some(3));
+        assert!(preview[2].skip_reason.as_deref().unwrap().contains("7-day"));
+        assert_eq!(preview[3].skip_reason.as_deref(), Some("No longer exists"));
+
+        // Nothing was touched
+        assert!(inner.exists() && log.exists());
+    }
+
+    #[test]
+    fn test_delete_overlapping_selection_once() {
+        let dir = TestDir::new("cleaner-test");
+        let parent = dir.join("cache");
+        fs::create_dir_all(&parent).unwrap();
+        let child = parent.join("entry.tmp");
+        fs::write(&child, b"junk").unwrap();
+
+        let paths = vec![
+            child.to_string_lossy().to_string(),
+            parent.to_string_lossy().to_string(),
+        ];
+        let result = delete_junk_items_with_options(paths, CleaningOptions::default()).unwrap();
+
+        assert_eq!(result.deleted_count, 1);
+        assert_eq!(result.failed_count, 0);
+        assert!(!parent.exists());
+    }
+
+    #[test]
+    fn test_extension_opportunities_bucket_old_files() {
+        let dir = TestDir::new("extensions-test");
+        fs::create_dir_all(dir.join("app")).unwrap();
+        fs::write(dir.join("a.log"), vec![0u8; 10]).unwrap();
+        fs::write(dir.join("app").join("b.LOG"), vec![0u8; 20]).unwrap();
+        fs::write(dir.join("app").join("data.bin"), vec![0u8; 5]).unwrap();
+        fs::write(dir.join("README"), b"no extension").unwrap();
+
+        let modified = fs::metadata(dir.join("a.log")).unwrap().modified().unwrap();
+        let options = |now: SystemTime| CleaningOptions {
+            min_age_days: Some(7),
+            now: Some(now),
+            ..CleaningOptions::default()
+        };
+        let roots = vec![(dir.to_path_buf(), "system_logs")];
+
+        let buckets = extension_opportunities(&roots, &options(modified + 8 * DAY));
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].extension, "log");
+        assert_eq!(buckets[0].safety, SafetyLevel::Safe);
+        assert_eq!(buckets[0].file_count, 2);
+        assert_eq!(buckets[0].total_size, 30);
+        assert_eq!(buckets[0].category_ids, vec!["system_logs"]);
+        assert_eq!(buckets[0].paths.len(), 2);
+        assert!(buckets[0].paths[0].ends_with("b.LOG")); // Largest first
+        assert_eq!(buckets[1].safety, SafetyLevel::Caution);
+
+        // Too new for the age filter
+        assert!(extension_opportunities(&roots, &options(modified + DAY)).is_empty());
+    }
+
+    #[test]
+    fn test_extension_bucket_keeps_only_the_largest_paths() {
+        let dir = TestDir::new("extensions-test");
+        for i in 0..MAX_BUCKET_PATHS + 5 {
+            fs::write(dir.join(format!("{}.tmp", i)), vec![0u8; i + 1]).unwrap();
+        }
+        let roots = vec![(dir.to_path_buf(), "temp_files")];
+
+        let buckets = extension_opportunities(&roots, &CleaningOptions::default());
+        assert_eq!(buckets[0].file_count, MAX_BUCKET_PATHS + 5);
+        assert_eq!(buckets[0].paths.len(), MAX_BUCKET_PATHS);
+        // Path::ends_with matches whole components, so "0.tmp" doesn't match "10.tmp"
+        let kept = |i: usize| buckets[0].paths.iter().any(|p| Path::new(p).ends_with(format!("{}.tmp", i)));
+        assert!(Path::new(&buckets[0].paths[0]).ends_with(format!("{}.tmp", MAX_BUCKET_PATHS + 4)));
+        assert!(!kept(0) && !kept(4) && kept(5));
+    }
+
+    #[test]
+    fn test_check_deletable_reports_missing_and_present() {
+        let dir = TestDir::new("deletable-test");
+        let file = dir.join("cache.tmp");
+        fs::write(&file, b"junk").unwrap();
+
+        let paths = vec![
+            file.to_string_lossy().to_string(),
+            dir.join("missing").to_string_lossy().to_string(),
+        ];
+        let checks = check_deletable(&paths);
+
+        assert!(checks[0].deletable);
+        assert!(!checks[1].deletable);
+        assert!(checks[1].reason.is_some());
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn test_downloads_flags_old_installers_only() {
+        let dir = TestDir::new("downloads-test");
+        fs::create_dir_all(dir.join("folder.zip")).unwrap();
+        fs::write(dir.join("setup.EXE"), b"installer").unwrap();
+        fs::write(dir.join("photos.zip"), b"zip").unwrap();
+        fs::write(dir.join("report.pdf"), b"document").unwrap();
+
+        let modified = fs::metadata(dir.join("setup.EXE")).unwrap().modified().unwrap();
+        let options = |now: SystemTime| CleaningOptions {
+            now: Some(now),
+            ..CleaningOptions::default()
+        };
+
+        // Fresh downloads are held back by the default threshold
+        let category = scan_downloads_dir(&dir, &options(modified + DAY), None);
+        assert!(category.items.is_empty());
+        assert_eq!(category.filtered_count, 2);
+
+        let category = scan_downloads_dir(&dir, &options(modified + 31 * DAY), None);
+        let mut names: Vec<_> = category.items.iter().map(|i| i.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["photos.zip", "setup.EXE"]);
+        assert_eq!(category.total_size, 12);
+    }
+
+    #[test]
+    fn test_parse_profiles_ini_lists_every_profile() {
+        let ini = "[Install4F96D1932A9F858E]\nDefault=Profiles/abc.default-release\n\n\
+                   [Profile1]\nName=work\nIsRelative=1\nPath=Profiles/xyz.work\n\n\
+                   [Profile0]\nName=default-release\nIsRelative=1\nPath=Profiles/abc.default-release\nDefault=1\n\n\
+                   [Profile2]\nName=usb\nIsRelative=0\nPath=/media/usb/firefox\n\n\
+                   [General]\nStartWithLastProfile=1\n";
+
+        let profiles = parse_profiles_ini(ini);
+        let names: Vec<_> = profiles.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["work", "default-release", "usb"]);
+        assert_eq!(profiles[0].path, Path::new("Profiles").join("xyz.work"));
+        assert_eq!(profiles[2].path, PathBuf::from("/media/usb/firefox"));
+    }
+
+    #[test]
+    fn test_browser_caches_cover_all_profiles_and_skip_logins() {
+        let dir = TestDir::new("browser-test");
+        let profiles = dir.join("config");
+        let caches = dir.join("cache");
+        for profile in ["Default", "Profile 1"] {
+            fs::create_dir_all(profiles.join(profile)).unwrap();
+            fs::write(profiles.join(profile).join("Preferences"), b"{}").unwrap();
+            fs::write(profiles.join(profile).join("Login Data"), b"secret").unwrap();
+            fs::write(profiles.join(profile).join("Cookies"), b"secret").unwrap();
+        }
+        fs::create_dir_all(profiles.join("Default").join("GPUCache")).unwrap();
+        fs::write(profiles.join("Default").join("GPUCache").join("data_0"), b"gpu").unwrap();
+        fs::create_dir_all(profiles.join("Profile 1").join("Service Worker").join("CacheStorage")).unwrap();
+        fs::create_dir_all(caches.join("Profile 1").join("Cache")).unwrap();
+        fs::write(caches.join("Profile 1").join("Cache").join("f_000001"), b"cached").unwrap();
+        // Not a profile: no Preferences file
+        fs::create_dir_all(profiles.join("Crashpad").join("Cache")).unwrap();
+
+        let location = BrowserLocation {
+            name: "Chrome",
+            family: BrowserFamily::Chromium,
+            data_root: "",
+            cache_root: None,
+        };
+        let mut category = JunkCategory {
+            id: "browser_cache".to_string(),
+            name: "Browser Caches".to_string(),
+            description: String::new(),
+            items: Vec::new(),
+            total_size: 0,
+            icon: "browser_cache".to_string(),
+            filtered_size: 0,
+            filtered_count: 0,
+            unreadable_count: 0,
+        };
+        collect_browser_caches(&mut category, &location, &profiles, Some(&caches), &CleaningOptions::default());
+
+        let mut found: Vec<_> = category.items.iter().map(|i| i.description.as_str()).collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                "Chrome GPU shader cache (Default profile)",
+                "Chrome disk cache (Profile 1 profile)",
+                "Chrome service worker cache (Profile 1 profile)",
+            ]
+        );
+        assert_eq!(category.total_size, 9);
+        assert!(category.items.iter().all(|i| !i.path.contains("Login Data") && !i.path.contains("Cookies")));
+
+        // Once Local State lists the profiles, it decides which ones exist and their names
+        fs::write(
+            profiles.join("Local State"),
+            r#"{"profile":{"info_cache":{"Profile 1":{"name":"Work"}}}}"#,
+        )
+        .unwrap();
+        let found: Vec<_> = profile_cache_dirs(BrowserFamily::Chromium, &profiles, Some(&caches))
+            .into_iter()
+            .map(|d| (d.profile, d.subdir))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Work".to_string(), "Cache"),
+                ("Work".to_string(), "Service Worker/CacheStorage"),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dir_size_skips_unreadable_subdirectory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TestDir::new("dirsize-test");
+        let locked = dir.join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(dir.join("a.bin"), vec![0u8; 100]).unwrap();
+        fs::write(locked.join("b.bin"), vec![0u8; 50]).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let size = calculate_dir_size(&dir);
+
+        if fs::read_dir(&locked).is_ok() {
+            // Running as root: permissions don't apply
+            assert_eq!(size.bytes, 150);
+        } else {
+            assert_eq!(size.bytes, 100);
+            assert_eq!(size.unreadable, 1);
+        }
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[test]
+    fn test_cancelled_scan_returns_early() {
+        let dir = TestDir::new("dirsize-test");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("a.bin"), vec![0u8; 100]).unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        assert_eq!(calculate_dir_size_cancellable(&dir, Some(&cancel)).bytes, 0);
+        assert_eq!(calculate_dir_size_cancellable(&dir, None).bytes, 100);
+        assert!(scan_junk_items_with_options(CleaningOptions::default(), Some(cancel)).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_template_matches_each_profile() {
+        let dir = TestDir::new("glob-[test]");
+        for profile in ["a.default", "b.work"] {
+            fs::create_dir_all(dir.join("Profiles").join(profile).join("cache2")).unwrap();
+        }
+        fs::create_dir_all(dir.join("Profiles").join("c.empty")).unwrap();
+
+        let template = format!("{}/Profiles/*/cache2", dir.to_string_lossy());
+        let found = glob_template(&template);
+
+        assert_eq!(found, vec![
+            dir.join("Profiles").join("a.default").join("cache2"),
+            dir.join("Profiles").join("b.work").join("cache2"),
+        ]);
+        assert!(glob_template(&format!("{}/Missing/*/cache2", dir.to_string_lossy())).is_empty());
+    }
+
+    #[test]
+    fn test_delete_by_category_reports_each_group() {
+        let dir = TestDir::new("clean-test");
+        fs::create_dir_all(dir.join("cache")).unwrap();
+        fs::write(dir.join("cache").join("a.bin"), vec![0u8; 100]).unwrap();
+        fs::write(dir.join("b.log"), vec![0u8; 30]).unwrap();
+        let path = |name: &str| dir.join(name).to_string_lossy().to_string();
+        let a = dir.join("cache").join("a.bin").to_string_lossy().to_string();
+
+        let groups = vec![
+            ("system_cache".to_string(), vec![path("cache")]),
+            // Inside a directory the first group already covers
+            ("temp_files".to_string(), vec![a, path("b.log")]),
+        ];
+        let options = CleaningOptions { dry_run: true, ..CleaningOptions::default() };
+        let plan = DeletePlan::new(&options);
+        let results: HashMap<String, DeletionResult> = claim_category_paths(groups)
+            .into_iter()
+            .map(|(id, paths)| (id, plan.delete(paths, &options).unwrap()))
+            .collect();
+
+        assert_eq!(results["system_cache"].deleted_size, 100);
+        assert_eq!(results["temp_files"].deleted_size, 30);
+        assert_eq!(results["temp_files"].deleted_count, 1);
+        assert_eq!(results.values().map(|r| r.deleted_size).sum::<u64>(), 130);
+    }
+
+    #[test]
+    fn test_delete_guard_refuses_roots_and_ancestors_of_cleaning_paths() {
+        let dir = TestDir::new("guard-test");
+        let cache = dir.join("home").join(".cache");
+        fs::create_dir_all(cache.join("app")).unwrap();
+
+        let guard = DeleteGuard::with_paths(vec![dir.join("home")], vec![cache.clone()]);
+
+        assert!(guard.check(&cache.join("app")).is_ok());
+        assert!(guard.check(&cache).is_ok());
+        assert!(guard.check(&dir.join("home")).unwrap_err().contains("protected"));
+        assert!(guard.check(&dir).unwrap_err().contains("contains the cleaning location"));
+        assert!(guard.check(&cache.join("..").join("..")).is_err());
+        assert!(guard.check(Path::new("/")).unwrap_err().contains("root of a filesystem"));
+
+        // A scanned item inside ~/.cache that holds a nested root (browser cache)
+        let browser_root = cache.join("mozilla").join("firefox").join("profile").join("cache2");
+        fs::create_dir_all(&browser_root).unwrap();
+        let guard = DeleteGuard::with_paths(vec![dir.join("home")], vec![cache.clone(), browser_root.clone()]);
+        assert!(guard.check(&cache.join("mozilla")).is_ok());
+        assert!(guard.check(&browser_root).is_ok());
+        assert!(guard.check(&dir).unwrap_err().contains("contains the cleaning location"));
+
+        // Counted as a failure even when errors aren't skipped
+        let options = CleaningOptions { dry_run: true, skip_errors: false, ..CleaningOptions::default() };
+        let result = delete_junk_items_with_options(vec!["/".to_string()], options).unwrap();
+        assert_eq!(result.failed_count, 1);
+        assert_eq!(result.deleted_count, 0);
+    }
+
+    #[test]
+    fn test_classify_item_flags_state_and_installed_files() {
+        let classify = |path: &str, category: &str| classify_item(Path::new(path), category);
+
+        assert_eq!(classify("/tmp/build-1234.tmp", "system_cache"), SafetyLevel::Safe);
+        assert_eq!(classify("/tmp/npm-cache", "temp_files"), SafetyLevel::Safe);
+        assert_eq!(classify("/cache/app/Session Storage", "system_cache"), SafetyLevel::Risky);
+        assert_eq!(classify("/state/com.apple.Terminal.savedState", "temp_files"), SafetyLevel::Risky);
+        assert_eq!(classify("/home/u/.vscode/extensions/ms-python.python-1.0", "developer_cache"), SafetyLevel::Risky);
+        assert_eq!(classify("/home/u/.gradle/caches/modules-2", "developer_cache"), SafetyLevel::Caution);
+        assert_eq!(classify("/home/u/.cache/fontconfig", "system_cache"), SafetyLevel::Caution);
+    }
+
+    #[test]
+    fn test_profile_filters_categories_and_small_items() {
+        let item = |size| JunkItem {
+            path: format!("/tmp/item-{}", size),
+            name: format!("item-{}", size),
+            size,
+            description: String::new(),
+            age_days: None,
+            requires_reboot: false,
+            safety: SafetyLevel::Safe,
+            archive: None,
+            age_basis: AgeBasis::Modified,
+        };
+        let category = |id: &str, items: Vec<JunkItem>| JunkCategory {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            total_size: items.iter().map(|i| i.size).sum(),
+            items,
+            icon: id.to_string(),
+            filtered_size: 0,
+            filtered_count: 0,
+            unreadable_count: 0,
+        };
+
+        let profile = CleaningProfile {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: String::new(),
+            options: CleaningOptions::default(),
+            category_ids: vec!["temp_files".to_string()],
+            min_item_size: 100,
+        };
+        let categories = vec![
+            category("temp_files", vec![item(50), item(500)]),
+            category("developer_cache", vec![item(1000)]),
+        ];
+
+        let filtered = apply_profile_filters(categories, &profile);
+
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].items.len(), 1);
+        assert_eq!(filtered[0].total_size, 500);
+        assert_eq!(filtered[0].filtered_size, 50);
+        assert_eq!(filtered[0].filtered_count, 1);
+    }
+
+    #[test]
+    fn test_reclaim_report_subtracts_failed_items() {
+        let dir = TestDir::new("reclaim-test");
+        let file = dir.join("cache.bin");
+        fs::write(&file, vec![0u8; 10]).unwrap();
+
+        let item = |path: &Path, size| JunkItem {
+            path: path.to_string_lossy().to_string(),
+            name: String::new(),
+            size,
+            description: String::new(),
+            age_days: None,
+            requires_reboot: false,
+            safety: SafetyLevel::Safe,
+            archive: None,
+            age_basis: AgeBasis::Modified,
+        };
+        let category = JunkCategory {
+            id: "temp_files".to_string(),
+            name: "Temporary Files".to_string(),
+            description: String::new(),
+            items: vec![item(&file, 10), item(&dir.join("gone.bin"), 5)],
+            total_size: 15,
+            icon: String::new(),
+            filtered_size: 0,
+            filtered_count: 0,
+            unreadable_count: 0,
+        };
+
+        let options = CleaningOptions::default();
+        let plan = DeletePlan::new(&options);
+        let mut report = ReclaimReport::default();
+        for (id, paths, estimated) in category_delete_groups(&[category]) {
+            report.push(CategoryReclaim::new(id, estimated, plan.delete(paths, &options).unwrap()));
+        }
+
+        assert_eq!(report.estimated_freed, 15);
+        assert_eq!(report.actual_freed, 10);
+        assert_eq!(report.categories[0].failed_count, 1);
+        assert!(!file.exists());
+    }
+
+    #[test]
+    fn test_prune_empty_dirs_keeps_root_and_non_empty() {
+        let root = TestDir::new("cleaner-test");
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        fs::create_dir_all(root.join("hidden")).unwrap();
+        fs::write(root.join("hidden").join(".keep"), b"").unwrap();
+
+        assert_eq!(prune_empty_dirs(&root, true), 2);
+        assert!(root.exists());
+        assert!(!root.join("a").exists());
+        assert!(root.join("hidden").join(".keep").exists());
+
+        fs::remove_file(root.join("hidden").join(".keep")).unwrap();
+        assert_eq!(prune_empty_dirs(&root, false), 2);
+        assert!(!root.exists());
+    }
 }
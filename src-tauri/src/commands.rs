@@ -1,17 +1,93 @@
 use tauri::{command, AppHandle, Emitter};
-use crate::scanner::{scan_directory, FileNode, ScanStats};
+use crate::scanner::{self, combine_roots, scan_directory, scan_directory_incremental, DirStamps, FileNode, ScanOptions, ScanStats, SizeBasis};
 use crate::cleaner::{self, JunkCategory};
+use crate::confirmation::{self, ConfirmationToken, DestructiveOp};
+use crate::config::{self, CacheSettings, DEFAULT_CACHE_ENTRIES, DEFAULT_CACHE_TTL};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, RwLock};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::{SystemTime, Duration};
 use lazy_static::lazy_static;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use sysinfo::Disks;
+use notify_debouncer_mini::{new_debouncer, DebounceEventResult, Debouncer};
+use notify_debouncer_mini::notify::{RecommendedWatcher, RecursiveMode};
 
 struct CacheEntry {
     node: FileNode,
     timestamp: SystemTime,
+    size_basis: SizeBasis,
+    stamps: Arc<DirStamps>, // Directory mtimes of the scan this came from; only `rescan_incremental` records them
+    depth: usize, // Levels of children listed in `node`
+}
+
+impl CacheEntry {
+    /// Younger than the cache TTL
+    fn is_fresh(&self) -> bool {
+        let ttl = CACHE_TTL.load(Ordering::Relaxed);
+        self.timestamp.elapsed().map(|e| e.as_secs() < ttl).unwrap_or(false)
+    }
+}
+
+/// Scan results by normalized path, capped at `capacity` entries
+///
+/// Inserting past the cap evicts the least recently used entry. Each entry
+/// holds a whole subtree, so an unbounded map grows with every directory a
+/// long session browses. Eviction scans for the oldest use, which is cheap at
+/// the few hundred entries the cache holds.
+struct ScanCache {
+    entries: HashMap<String, (CacheEntry, u64)>, // Entry and the tick of its last use
+    tick: u64,
+    capacity: usize,
+}
+
+impl ScanCache {
+    fn new(capacity: usize) -> Self {
+        ScanCache { entries: HashMap::new(), tick: 0, capacity }
+    }
+
+    /// Look up `key` and count it as a use
+    fn get(&mut self, key: &str) -> Option<&CacheEntry> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(entry, used)| {
+            *used = tick;
+            &*entry
+        })
+    }
+
+    /// Look up `key` without counting it as a use
+    fn peek(&self, key: &str) -> Option<&CacheEntry> {
+        self.entries.get(key).map(|(entry, _)| entry)
+    }
+
+    fn insert(&mut self, key: String, entry: CacheEntry) {
+        self.tick += 1;
+        self.entries.insert(key, (entry, self.tick));
+        self.evict();
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&String) -> bool) {
+        self.entries.retain(|key, _| keep(key));
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(key, _)| key.clone()) else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+    }
 }
 
 // Global state to manage cancellation
@@ -20,13 +96,35 @@ struct ScanState {
 }
 
 lazy_static! {
-    static ref SCAN_CACHE: Mutex<HashMap<String, CacheEntry>> = Mutex::new(HashMap::new());
+    static ref SCAN_CACHE: Mutex<ScanCache> = Mutex::new(ScanCache::new(DEFAULT_CACHE_ENTRIES));
     static ref SCAN_STATE: RwLock<ScanState> = RwLock::new(ScanState { 
         cancel_token: Arc::new(AtomicBool::new(false)) 
     });
+    static ref DELETE_STATE: RwLock<ScanState> = RwLock::new(ScanState {
+        cancel_token: Arc::new(AtomicBool::new(false))
+    });
+    static ref WARM_STATE: RwLock<ScanState> = RwLock::new(ScanState {
+        cancel_token: Arc::new(AtomicBool::new(false))
+    });
+    static ref JUNK_SCAN_STATE: RwLock<ScanState> = RwLock::new(ScanState {
+        cancel_token: Arc::new(AtomicBool::new(false))
+    });
+    static ref DUPLICATES_STATE: RwLock<ScanState> = RwLock::new(ScanState {
+        cancel_token: Arc::new(AtomicBool::new(false))
+    });
+    static ref WATCHERS: Mutex<HashMap<String, Debouncer<RecommendedWatcher>>> = Mutex::new(HashMap::new());
 }
 
-const CACHE_TTL: u64 = 60 * 60; 
+static CACHE_TTL: AtomicU64 = AtomicU64::new(DEFAULT_CACHE_TTL);
+
+/// Directories scanned at once by `scan_dirs`; each scan is already parallel inside
+const MAX_PARALLEL_SCANS: usize = 3;
+
+/// Directories watched at once by `watch_dir`; each holds OS watch handles
+const MAX_WATCHERS: usize = 8;
+
+/// Quiet period before a burst of filesystem events is reported as one change
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
 
 fn normalize_path(path: &str) -> String {
     let mut s = path.to_string();
@@ -47,14 +145,115 @@ struct ScanProgress {
     errors: u64,
 }
 
+/// Scan `path`, listing `depth` levels of children (default `options.max_depth`, normally 2)
+///
+/// A depth of 1 lists only the immediate children, with their full sizes, for
+/// a quick first view; a larger depth fills a whole treemap at once.
 #[command]
-pub async fn scan_dir(app: AppHandle, path: String) -> Result<FileNode, String> {
-    scan_dir_internal(app, path, false).await
+pub async fn scan_dir(app: AppHandle, path: String, options: Option<ScanOptions>, depth: Option<usize>) -> Result<FileNode, String> {
+    let mut options = options.unwrap_or_default();
+    if let Some(depth) = depth {
+        options.max_depth = depth;
+    }
+    scan_dir_internal(app, path, options, false).await
 }
 
 #[command]
-pub async fn refresh_scan(app: AppHandle, path: String) -> Result<FileNode, String> {
-    scan_dir_internal(app, path, true).await
+pub async fn refresh_scan(app: AppHandle, path: String, options: Option<ScanOptions>) -> Result<FileNode, String> {
+    scan_dir_internal(app, path, options.unwrap_or_default(), true).await
+}
+
+/// Refresh a scan by re-walking only the subdirectories that changed since
+/// the cached scan, reusing cached sizes for the rest
+///
+/// Change is detected from directory mtimes, which move when entries are
+/// created, deleted or renamed but not when a file is rewritten in place;
+/// `refresh_scan` is the exact (and slower) alternative. Without a usable
+/// cached scan this is a full scan; only this command records directory
+/// mtimes, so the first incremental rescan of a path is always a full one.
+#[command]
+pub async fn rescan_incremental(app: AppHandle, path: String, options: Option<ScanOptions>) -> Result<FileNode, String> {
+    let options = options.unwrap_or_default();
+    let key = normalize_path(&path);
+
+    let previous = {
+        let mut cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
+        cache
+            .get(&key)
+            .filter(|e| e.size_basis == options.size_basis && e.node.is_estimate == options.cluster_estimate)
+            // Reused children have to be listed as deep as this scan lists them
+            .filter(|e| e.depth == options.max_depth.max(1))
+            .map(|e| (e.node.clone(), e.stamps.clone()))
+    };
+
+    let (cancel_token, stats, is_done) = start_scan(&app, path.clone());
+
+    let scan_options = options.clone();
+    let (result, stamps) = tauri::async_runtime::spawn_blocking(move || {
+        let previous = previous.as_ref().map(|(node, stamps)| (node, stamps.as_ref()));
+        scan_directory_incremental(&path, &scan_options, previous, Some(stats), Some(cancel_token))
+    }).await.map_err(|e| e.to_string())??;
+
+    is_done.store(true, Ordering::Relaxed);
+
+    cache_scan(&key, &result, &options, stamps)?;
+
+    Ok(result)
+}
+
+/// Rough file count and duration of a scan of `path`, without running it
+#[command]
+pub async fn estimate_scan(path: String, options: Option<ScanOptions>) -> Result<scanner::ScanEstimate, String> {
+    let options = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || scanner::estimate_scan(&path, &options))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Biggest files modified in the last `since_hours`; answers "what just filled the disk?"
+///
+/// Cancelled by `cancel_scan` like a regular scan.
+#[command]
+pub async fn find_recent_large_files(
+    path: String,
+    since_hours: u64,
+    limit: usize,
+    options: Option<ScanOptions>,
+) -> Result<Vec<FileNode>, String> {
+    let options = options.unwrap_or_default();
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut state) = SCAN_STATE.write() {
+        state.cancel_token = cancel_token.clone();
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        scanner::find_recent_large_files(&path, since_hours, limit, &options, Some(cancel_token))
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Groups of identical files under `path`, for keeping one copy and deleting the rest
+///
+/// Cancelled by `cancel_find_duplicates`. It has its own token, so a
+/// directory scan started meanwhile can't leave it uncancellable.
+#[command]
+pub async fn find_duplicates(path: String, options: Option<ScanOptions>) -> Result<Vec<scanner::DuplicateGroup>, String> {
+    let options = options.unwrap_or_default();
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut state) = DUPLICATES_STATE.write() {
+        state.cancel_token = cancel_token.clone();
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        scanner::find_duplicates(&path, &options, Some(cancel_token))
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Stop the running `find_duplicates`
+#[command]
+pub fn cancel_find_duplicates() {
+    if let Ok(state) = DUPLICATES_STATE.read() {
+        state.cancel_token.store(true, Ordering::Relaxed);
+    }
 }
 
 #[command]
@@ -64,21 +263,126 @@ pub fn cancel_scan() {
     }
 }
 
-async fn scan_dir_internal(app: AppHandle, path: String, force_refresh: bool) -> Result<FileNode, String> {
+async fn scan_dir_internal(app: AppHandle, path: String, options: ScanOptions, force_refresh: bool) -> Result<FileNode, String> {
     let key = normalize_path(&path);
 
     // Check cache
     if !force_refresh {
-        let cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
-        if let Some(entry) = cache.get(&key) {
-            if let Ok(elapsed) = entry.timestamp.elapsed() {
-                if elapsed.as_secs() < CACHE_TTL {
-                    return Ok(entry.node.clone());
+        if let Some(node) = cached_scan(&key, &options)? {
+            return Ok(node);
+        }
+    }
+
+    let (cancel_token, stats, is_done) = start_scan(&app, path.clone());
+
+    let path_clone = path.clone();
+    let scan_options = options.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        scan_directory(&path_clone, &scan_options, Some(stats), Some(cancel_token))
+    }).await.map_err(|e| e.to_string())??;
+
+    is_done.store(true, Ordering::Relaxed);
+    
+    cache_scan(&key, &result, &options, DirStamps::new())?;
+
+    Ok(result)
+}
+
+/// Scan several directories concurrently and return them under one combined root
+#[command]
+pub async fn scan_dirs(app: AppHandle, paths: Vec<String>, options: Option<ScanOptions>) -> Result<FileNode, String> {
+    use futures_util::stream::{self, StreamExt, TryStreamExt};
+
+    let options = options.unwrap_or_default();
+    let mut keys: Vec<String> = paths.iter().map(|p| normalize_path(p)).collect();
+    keys.sort();
+    keys.dedup();
+
+    let (cancel_token, stats, is_done) = start_scan(&app, keys.join(", "));
+
+    let roots: Result<Vec<FileNode>, String> = stream::iter(keys)
+        .map(|key| {
+            let options = options.clone();
+            let stats = stats.clone();
+            let cancel_token = cancel_token.clone();
+            async move {
+                if let Some(node) = cached_scan(&key, &options)? {
+                    return Ok(node);
                 }
+
+                let path = key.clone();
+                let scan_options = options.clone();
+                let node = tauri::async_runtime::spawn_blocking(move || {
+                    scan_directory(&path, &scan_options, Some(stats), Some(cancel_token))
+                }).await.map_err(|e| e.to_string())??;
+
+                cache_scan(&key, &node, &options, DirStamps::new())?;
+                Ok(node)
             }
+        })
+        .buffered(MAX_PARALLEL_SCANS)
+        .try_collect()
+        .await;
+
+    is_done.store(true, Ordering::Relaxed);
+
+    Ok(combine_roots(roots?))
+}
+
+/// A fresh cached scan of `key` made with the same size basis, listed at least as deep as requested
+fn cached_scan(key: &str, options: &ScanOptions) -> Result<Option<FileNode>, String> {
+    let mut cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
+    Ok(cache
+        .get(key)
+        .filter(|e| e.size_basis == options.size_basis)
+        .filter(|e| e.depth >= options.max_depth.max(1))
+        // An estimate doesn't answer a request for exact sizes
+        .filter(|e| options.cluster_estimate || !e.node.is_estimate)
+        .filter(|e| e.is_fresh())
+        .map(|e| e.node.clone()))
+}
+
+/// Cache a scan result and its immediate children
+fn cache_scan(key: &str, result: &FileNode, options: &ScanOptions, stamps: DirStamps) -> Result<(), String> {
+    let mut cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
+    let now = SystemTime::now();
+    let size_basis = options.size_basis;
+    let depth = options.max_depth.max(1);
+    // Children share the root's stamps; a rescan only looks at the ones under its own path
+    let stamps = Arc::new(stamps);
+    
+    // At depth 1 the children have no children of their own listed and can't be served
+    if let Some(children) = result.children.as_ref().filter(|_| depth > 1) {
+        // A summarized child has no children to serve; opening it rescans it with a fresh budget
+        for child in children.iter().filter(|c| !c.summarized) {
+            let child_key = normalize_path(&child.path);
+            cache.insert(child_key, CacheEntry {
+                node: child.clone(),
+                timestamp: now,
+                size_basis,
+                stamps: stamps.clone(),
+                depth: depth - 1,
+            });
         }
     }
 
+    // Inserted last so it is the most recent use and outlives its children when the cache fills
+    cache.insert(key.to_string(), CacheEntry {
+        node: result.clone(),
+        timestamp: now,
+        size_basis,
+        stamps,
+        depth,
+    });
+
+    Ok(())
+}
+
+/// Register a new cancellable scan and emit its progress until it's done
+fn start_scan(app: &AppHandle, path_report: String) -> (Arc<AtomicBool>, Arc<ScanStats>, Arc<AtomicBool>) {
+    // A user-initiated scan takes priority over background warming
+    cancel_warm_cache();
+
     // Reset cancellation
     let cancel_token = Arc::new(AtomicBool::new(false));
     if let Ok(mut state) = SCAN_STATE.write() {
@@ -97,7 +401,6 @@ async fn scan_dir_internal(app: AppHandle, path: String, force_refresh: bool) ->
     // Spawn progress emitter
     let stats_clone = stats.clone();
     let app_handle = app.clone();
-    let path_report = path.clone();
     let cancel_clone = cancel_token.clone();
     let is_done_clone = is_done.clone();
     
@@ -125,33 +428,61 @@ async fn scan_dir_internal(app: AppHandle, path: String, force_refresh: bool) ->
         }
     });
 
-    let path_clone = path.clone();
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        scan_directory(&path_clone, Some(stats), Some(cancel_token))
-    }).await.map_err(|e| e.to_string())??;
+    (cancel_token, stats, is_done)
+}
 
-    is_done.store(true, Ordering::Relaxed);
-    
-    // Update cache
-    let mut cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
-    let now = SystemTime::now();
-    
-    cache.insert(key.clone(), CacheEntry {
-        node: result.clone(),
-        timestamp: now,
-    });
-    
-    if let Some(children) = &result.children {
-        for child in children {
-            let child_key = normalize_path(&child.path);
-            cache.insert(child_key, CacheEntry {
-                node: child.clone(),
-                timestamp: now,
-            });
+/// Pre-scan directories in the background so the first real scan is served from the cache
+///
+/// Defaults to the home directory and the drives from `get_drives`. Runs one
+/// path at a time on a single thread, skips paths that are already cached,
+/// and stops as soon as a user scan starts or `cancel_warm_cache` is called.
+#[command]
+pub fn warm_cache(paths: Option<Vec<String>>, options: Option<ScanOptions>) {
+    let paths = paths.unwrap_or_else(default_warm_paths);
+    let mut options = options.unwrap_or_default();
+    // Background work: one thread unless the caller asked for more
+    options.concurrency.get_or_insert(1);
+
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut state) = WARM_STATE.write() {
+        state.cancel_token.store(true, Ordering::Relaxed);
+        state.cancel_token = cancel_token.clone();
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        for path in paths {
+            if cancel_token.load(Ordering::Relaxed) {
+                break;
+            }
+
+            let key = normalize_path(&path);
+            if matches!(cached_scan(&key, &options), Ok(Some(_))) {
+                continue;
+            }
+
+            match scan_directory(&key, &options, None, Some(cancel_token.clone())) {
+                Ok(node) => {
+                    let _ = cache_scan(&key, &node, &options, DirStamps::new());
+                }
+                Err(e) => log::debug!("Cache warm-up of {} stopped: {}", key, e),
+            }
         }
+    });
+}
+
+#[command]
+pub fn cancel_warm_cache() {
+    if let Ok(state) = WARM_STATE.read() {
+        state.cancel_token.store(true, Ordering::Relaxed);
     }
+}
 
-    Ok(result)
+fn default_warm_paths() -> Vec<String> {
+    dirs::home_dir()
+        .map(|home| home.to_string_lossy().to_string())
+        .into_iter()
+        .chain(get_drives().into_iter().map(|drive| drive.path))
+        .collect()
 }
 
 #[command]
@@ -161,6 +492,145 @@ pub fn clear_cache() {
     }
 }
 
+/// Apply the configured TTL and size to the scan cache (at startup and on config changes)
+pub fn apply_cache_settings(settings: &CacheSettings) {
+    CACHE_TTL.store(settings.ttl_seconds, Ordering::Relaxed);
+    if let Ok(mut cache) = SCAN_CACHE.lock() {
+        cache.set_capacity(settings.max_entries);
+    }
+}
+
+/// How long scans stay cached, in seconds (default one hour; 0 turns caching off)
+///
+/// Applies to entries already cached, since freshness is checked on every lookup.
+/// Saved to the config, so it survives a restart.
+#[command]
+pub fn set_cache_ttl(app: AppHandle, seconds: u64) -> Result<(), String> {
+    let config = config::modify_config(&app, |c| c.cache.ttl_seconds = seconds)?;
+    apply_cache_settings(&config.cache);
+    Ok(())
+}
+
+/// Most scans kept cached (default 256); the least recently used go first
+///
+/// Saved to the config, so it survives a restart.
+#[command]
+pub fn set_cache_capacity(app: AppHandle, entries: usize) -> Result<(), String> {
+    let config = config::modify_config(&app, |c| c.cache.max_entries = entries)?;
+    apply_cache_settings(&config.cache);
+    Ok(())
+}
+
+/// Evict what a change to `path` makes stale and keep the rest of the cache warm
+///
+/// That is `path`'s own entry, entries below it, and every ancestor, whose
+/// totals include it. Keys are compared in `normalize_path` form, the way
+/// they are stored.
+#[command]
+pub fn invalidate_path(path: String) {
+    invalidate_cached(&[PathBuf::from(normalize_path(&path))]);
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ScanInvalidated {
+    root: String,
+    paths: Vec<String>,
+}
+
+/// Drop cache entries that a change to `changed` makes stale: the path itself,
+/// anything below it, and every ancestor whose totals include it
+pub(crate) fn invalidate_cached(changed: &[PathBuf]) {
+    if let Ok(mut cache) = SCAN_CACHE.lock() {
+        cache.retain(|key| {
+            let key = Path::new(key);
+            !changed.iter().any(|p| p.starts_with(key) || key.starts_with(p))
+        });
+    }
+}
+
+/// Watch `path` and emit `scan-invalidated` when anything below it changes
+///
+/// Events are debounced so a large copy produces one notification, not thousands.
+/// The stale cache entries are dropped before the event, so a re-fetch rescans them.
+/// Setting up a recursive watch walks the whole tree, so it runs off the async
+/// runtime and without holding the watcher lock.
+#[command]
+pub async fn watch_dir(app: AppHandle, path: String) -> Result<(), String> {
+    let key = normalize_path(&path);
+    {
+        let watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
+        if watchers.contains_key(&key) {
+            return Ok(());
+        }
+        check_watcher_limit(watchers.len())?;
+    }
+
+    let root = key.clone();
+    let debouncer = tauri::async_runtime::spawn_blocking(move || {
+        let watched = root.clone();
+        let mut debouncer = new_debouncer(WATCH_DEBOUNCE, move |result: DebounceEventResult| {
+            let events = match result {
+                Ok(events) => events,
+                Err(e) => {
+                    log::warn!("Watch error under {}: {}", root, e);
+                    return;
+                }
+            };
+
+            let mut changed: Vec<PathBuf> = events.into_iter().map(|event| event.path).collect();
+            changed.sort();
+            changed.dedup();
+            if changed.is_empty() {
+                return;
+            }
+
+            invalidate_cached(&changed);
+            let _ = app.emit("scan-invalidated", ScanInvalidated {
+                root: root.clone(),
+                paths: changed.iter().map(|p| p.to_string_lossy().to_string()).collect(),
+            });
+        })
+        .map_err(|e| e.to_string())?;
+
+        debouncer
+            .watcher()
+            .watch(Path::new(&watched), RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", path, e))?;
+        Ok::<_, String>(debouncer)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    // Another call may have watched the same path, or filled the last slot,
+    // while this one was setting up; the debouncer is dropped in that case
+    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
+    if watchers.contains_key(&key) {
+        return Ok(());
+    }
+    check_watcher_limit(watchers.len())?;
+    watchers.insert(key, debouncer);
+    Ok(())
+}
+
+fn check_watcher_limit(watching: usize) -> Result<(), String> {
+    if watching >= MAX_WATCHERS {
+        return Err(format!(
+            "Already watching {} directories; stop watching one first",
+            MAX_WATCHERS
+        ));
+    }
+    Ok(())
+}
+
+/// Stop watching a directory passed to `watch_dir`
+#[command]
+pub fn unwatch_dir(path: String) -> Result<(), String> {
+    let mut watchers = WATCHERS.lock().map_err(|e| e.to_string())?;
+    // Dropping the debouncer removes the OS watch
+    watchers.remove(&normalize_path(&path));
+    Ok(())
+}
+
 #[command]
 pub fn reveal_in_explorer(path: String) {
     #[cfg(target_os = "windows")]
@@ -225,8 +695,57 @@ pub fn open_file(path: String) {
     }
 }
 
+/// Issue a short-lived token that `op` on `target` must present before it runs
+///
+/// The token's description says exactly what will be destroyed, so the
+/// confirmation dialog can show it.
 #[command]
-pub fn delete_item(path: String) -> Result<(), String> {
+pub fn prepare_destructive(op: DestructiveOp, target: String) -> Result<ConfirmationToken, String> {
+    let description = match op {
+        DestructiveOp::DeletePartition => {
+            let partition = crate::partition::get_partition_info(&target).map_err(|e| e.to_string())?;
+            format!("Delete partition {}. All data on it will be lost.", partition.display_name())
+        }
+        DestructiveOp::DeleteItem => {
+            let metadata = std::fs::symlink_metadata(&target).map_err(|_| "Path does not exist".to_string())?;
+            if metadata.is_dir() {
+                format!("Permanently delete the folder {} and everything in it.", target)
+            } else {
+                format!("Permanently delete {} ({} bytes).", target, metadata.len())
+            }
+        }
+        DestructiveOp::CleanJunk => {
+            return Err("Junk selections are confirmed with prepare_clean_junk".to_string());
+        }
+    };
+
+    Ok(confirmation::issue(op, &target, description))
+}
+
+/// Issue the token `clean_junk` and the other junk deletions need for exactly `paths`
+#[command]
+pub fn prepare_clean_junk(paths: Vec<String>, use_trash: Option<bool>) -> ConfirmationToken {
+    let target = confirmation::selection_target(&paths);
+    let description = if use_trash.unwrap_or(false) {
+        format!("Move {} selected junk item(s) to the trash.", paths.len())
+    } else {
+        format!("Permanently delete {} selected junk item(s). This cannot be undone.", paths.len())
+    };
+    confirmation::issue(DestructiveOp::CleanJunk, &target, description)
+}
+
+/// Consume the `prepare_clean_junk` token for `paths`; dry runs delete nothing and need none
+fn consume_junk_token(confirmation: Option<&str>, paths: &[String], dry_run: bool) -> Result<(), String> {
+    if dry_run {
+        return Ok(());
+    }
+    confirmation::consume(confirmation, DestructiveOp::CleanJunk, &confirmation::selection_target(paths))
+}
+
+#[command]
+pub fn delete_item(path: String, confirmation: Option<String>) -> Result<(), String> {
+    confirmation::consume(confirmation.as_deref(), DestructiveOp::DeleteItem, &path)?;
+
     let p = Path::new(&path);
     if !p.exists() {
         return Err("Path does not exist".to_string());
@@ -235,16 +754,160 @@ pub fn delete_item(path: String) -> Result<(), String> {
     if p.is_dir() {
         std::fs::remove_dir_all(p).map_err(|e| e.to_string())?;
     } else {
-        std::fs::remove_file(p).map_err(|e| e.to_string())?;
+        std::fs::remove_file(p).map_err(|e| {
+            crate::system_files::protected_reason(p).unwrap_or_else(|| e.to_string())
+        })?;
     }
     
-    // Invalidate cache for parent or just clear all for safety?
-    // Let's clear for now to be safe as size calc up the tree changes.
-    clear_cache();
+    // Size calc up the tree changes; unrelated scans stay cached
+    invalidate_path(path);
     
     Ok(())
 }
 
+#[derive(Clone, serde::Serialize)]
+struct DeleteProgress {
+    path: String,
+    removed: u64,
+    total: u64,
+}
+
+/// Emit delete progress every this many removed entries
+const DELETE_PROGRESS_INTERVAL: u64 = 200;
+
+/// Delete a file or directory tree entry by entry, reporting progress
+/// Unlike `delete_item` this can be cancelled with `cancel_delete`; entries
+/// removed before cancellation stay removed.
+#[command]
+pub async fn delete_item_with_progress(
+    app: AppHandle,
+    path: String,
+    confirmation: Option<String>,
+) -> Result<(), String> {
+    confirmation::consume(confirmation.as_deref(), DestructiveOp::DeleteItem, &path)?;
+
+    if !Path::new(&path).exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut state) = DELETE_STATE.write() {
+        state.cancel_token = cancel_token.clone();
+    }
+
+    let root = path.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let total = walkdir::WalkDir::new(&root).into_iter().flatten().count() as u64;
+        let mut removed = 0u64;
+
+        // Children before parents so each directory is empty when we reach it
+        for entry in walkdir::WalkDir::new(&root).contents_first(true) {
+            if cancel_token.load(Ordering::Relaxed) {
+                return Err(format!("Delete cancelled after removing {} of {} entries", removed, total));
+            }
+
+            let entry = entry.map_err(|e| e.to_string())?;
+            if entry.file_type().is_dir() {
+                std::fs::remove_dir(entry.path())
+            } else {
+                std::fs::remove_file(entry.path())
+            }
+            .map_err(|e| format!("Failed to delete {}: {}", entry.path().display(), e))?;
+
+            removed += 1;
+            if removed % DELETE_PROGRESS_INTERVAL == 0 || removed == total {
+                let _ = app.emit("delete-progress", DeleteProgress {
+                    path: root.clone(),
+                    removed,
+                    total,
+                });
+            }
+        }
+
+        Ok(())
+    }).await.map_err(|e| e.to_string())?;
+
+    // Sizes up the tree changed even if we stopped part way
+    invalidate_path(path);
+
+    result
+}
+
+#[command]
+pub fn cancel_delete() {
+    if let Ok(state) = DELETE_STATE.read() {
+        state.cancel_token.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Ancestor chain from the volume root down to `path`, sized from the scan cache
+/// Levels that haven't been scanned come back with `size: 0` and `size_unknown` set
+#[command]
+pub fn get_breadcrumb(path: String) -> Result<Vec<FileNode>, String> {
+    let cache = SCAN_CACHE.lock().map_err(|e| e.to_string())?;
+    let disks = Disks::new_with_refreshed_list();
+
+    let mut chain: Vec<&Path> = Path::new(&path).ancestors().collect();
+    chain.reverse();
+
+    Ok(chain
+        .into_iter()
+        .map(|level| breadcrumb_node(&cache, &disks, level))
+        .collect())
+}
+
+fn breadcrumb_node(cache: &ScanCache, disks: &Disks, path: &Path) -> FileNode {
+    let path_str = path.to_string_lossy().to_string();
+    let key = normalize_path(&path_str);
+
+    let fresh = |entry: &&CacheEntry| entry.is_fresh();
+
+    // Cached as its own entry
+    if let Some(entry) = cache.peek(&key).filter(fresh) {
+        return FileNode { children: None, ..entry.node.clone() };
+    }
+
+    // Listed among a cached parent's children
+    let from_parent = path
+        .parent()
+        .and_then(|parent| cache.peek(&normalize_path(&parent.to_string_lossy())))
+        .filter(fresh)
+        .and_then(|entry| entry.node.children.as_ref())
+        .and_then(|children| children.iter().find(|c| normalize_path(&c.path) == key));
+    if let Some(node) = from_parent {
+        return FileNode { children: None, ..node.clone() };
+    }
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.clone());
+
+    // Volume roots: used space is cheap to read
+    let used = disks
+        .iter()
+        .find(|d| d.mount_point() == path)
+        .map(|d| d.total_space().saturating_sub(d.available_space()));
+
+    FileNode {
+        name,
+        path: path_str,
+        size: used.unwrap_or(0),
+        own_size: 0,
+        logical_size: used.unwrap_or(0),
+        allocated_size: used.unwrap_or(0),
+        is_dir: true,
+        children: None,
+        last_modified: 0,
+        file_count: 0,
+        skipped_paths: Vec::new(),
+        size_unknown: used.is_none(),
+        is_cloud_placeholder: false,
+        is_estimate: false,
+        summarized: false,
+    }
+}
+
 #[command]
 pub fn get_drives() -> Vec<FileNode> {
     let mut drives = Vec::new();
@@ -284,20 +947,48 @@ pub fn get_drives() -> Vec<FileNode> {
             name: final_name,
             path: mount_point,
             size: used,
+            own_size: 0,
+            logical_size: used,
+            allocated_size: used,
             is_dir: true,
             children: None,
             last_modified,
             file_count: 0,
+            skipped_paths: Vec::new(),
+            size_unknown: false,
+            is_cloud_placeholder: false,
+            is_estimate: false,
+            summarized: false,
         });
     }
     drives
 }
 
+/// The user's saved cleaning options, used when a command isn't given any
+fn default_cleaning_options(app: &AppHandle) -> cleaner::CleaningOptions {
+    config::load_config(app)
+        .map(|config| config.cleaning_options)
+        .unwrap_or_else(|e| {
+            log::warn!("Couldn't load the config, using default cleaning options: {}", e);
+            cleaner::CleaningOptions::default()
+        })
+}
+
+/// Register a new junk scan for `cancel_junk_scan`
+fn start_junk_scan() -> Arc<AtomicBool> {
+    let cancel_token = Arc::new(AtomicBool::new(false));
+    if let Ok(mut state) = JUNK_SCAN_STATE.write() {
+        state.cancel_token = cancel_token.clone();
+    }
+    cancel_token
+}
+
 #[command]
-pub async fn scan_junk() -> Result<Vec<JunkCategory>, String> {
-    // This could also be spawned blocking if it takes time
+pub async fn scan_junk(app: AppHandle) -> Result<Vec<JunkCategory>, String> {
+    let cancel_token = start_junk_scan();
+    let options = default_cleaning_options(&app);
     let result = tauri::async_runtime::spawn_blocking(move || {
-        cleaner::scan_junk_items()
+        cleaner::scan_junk_items_with_options(options, Some(cancel_token))
     }).await.map_err(|e| e.to_string())?;
     
     Ok(result)
@@ -305,33 +996,300 @@ pub async fn scan_junk() -> Result<Vec<JunkCategory>, String> {
 
 #[command]
 pub async fn scan_junk_with_options(options: cleaner::CleaningOptions) -> Result<Vec<JunkCategory>, String> {
+    let cancel_token = start_junk_scan();
     let result = tauri::async_runtime::spawn_blocking(move || {
-        cleaner::scan_junk_items_with_options(options)
+        cleaner::scan_junk_items_with_options(options, Some(cancel_token))
     }).await.map_err(|e| e.to_string())?;
     
     Ok(result)
 }
 
+#[derive(Clone, serde::Serialize)]
+struct JunkScanComplete {
+    category_count: usize,
+    total_size: u64,
+    cancelled: bool, // Stopped by `cancel_junk_scan`; categories still in progress were not reported
+}
+
+/// Scan for junk like `scan_junk_with_options`, reporting categories as they complete
+///
+/// Events:
+/// - `junk-category-found`: a `JunkCategory` payload, once per category, as
+///   soon as every location feeding it has been scanned
+/// - `junk-scan-complete`: `{ category_count, total_size, cancelled }` once
+///   the scan ends, whether it finished or was cancelled
+///
+/// Also returns the full list, so callers that only await the result still work.
 #[command]
-pub async fn clean_junk(paths: Vec<String>) -> Result<(), String> {
+pub async fn scan_junk_items_streaming(
+    app: AppHandle,
+    options: Option<cleaner::CleaningOptions>,
+) -> Result<Vec<JunkCategory>, String> {
+    let cancel_token = start_junk_scan();
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+
     let result = tauri::async_runtime::spawn_blocking(move || {
-        cleaner::delete_junk_items(paths)
-    }).await.map_err(|e| e.to_string())??;
+        let categories = cleaner::scan_junk_items_reporting(options, Some(cancel_token.clone()), |category| {
+            let _ = app.emit("junk-category-found", category);
+        });
+        let _ = app.emit("junk-scan-complete", JunkScanComplete {
+            category_count: categories.len(),
+            total_size: categories.iter().map(|c| c.total_size).sum(),
+            cancelled: cancel_token.load(Ordering::Relaxed),
+        });
+        categories
+    }).await.map_err(|e| e.to_string())?;
+
+    Ok(result)
+}
+
+/// Stop the running junk scan; it returns the categories found so far
+#[command]
+pub fn cancel_junk_scan() {
+    if let Ok(state) = JUNK_SCAN_STATE.read() {
+        state.cancel_token.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Built-in cleaning profiles followed by the user's saved ones
+#[command]
+pub fn list_profiles(app: AppHandle) -> Result<Vec<cleaner::CleaningProfile>, String> {
+    let mut profiles = cleaner::builtin_profiles();
+    profiles.extend(config::load_config(&app)?.cleaning_profiles);
+    Ok(profiles)
+}
+
+/// Scan using a profile from `list_profiles`
+#[command]
+pub async fn scan_with_profile(app: AppHandle, profile_id: String) -> Result<Vec<JunkCategory>, String> {
+    let profile = list_profiles(app)?
+        .into_iter()
+        .find(|p| p.id == profile_id)
+        .ok_or_else(|| format!("Unknown cleaning profile: {}", profile_id))?;
+
+    let cancel_token = start_junk_scan();
+    tauri::async_runtime::spawn_blocking(move || {
+        cleaner::scan_with_profile(&profile, Some(cancel_token))
+    }).await.map_err(|e| e.to_string())
+}
+
+/// Opt-in scan of the Downloads folder for old installers and archives
+/// `options.min_age_days` overrides the default 30-day threshold
+/// Cancelled by `cancel_junk_scan`, which also stops archive inspection
+#[command]
+pub async fn scan_downloads_junk(
+    app: AppHandle,
+    options: Option<cleaner::CleaningOptions>,
+) -> Result<Option<JunkCategory>, String> {
+    let cancel_token = start_junk_scan();
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+    tauri::async_runtime::spawn_blocking(move || {
+        cleaner::scan_downloads_junk(options, Some(cancel_token))
+    }).await.map_err(|e| e.to_string())
+}
+
+/// Safe-to-delete cache directories of every detected browser profile
+#[command]
+pub async fn scan_browser_caches(app: AppHandle, options: Option<cleaner::CleaningOptions>) -> Result<JunkCategory, String> {
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+    tauri::async_runtime::spawn_blocking(move || {
+        cleaner::scan_browser_caches(options)
+    }).await.map_err(|e| e.to_string())
+}
+
+/// Cap Windows shadow storage on `volume` (e.g. "C:") at `max_percent` of the
+/// volume, dropping the oldest restore points that no longer fit. Returns the bytes freed.
+#[command]
+pub async fn resize_shadow_storage(volume: String, max_percent: u8) -> Result<u64, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::shadow_copies::resize_storage(&volume, max_percent)
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Sizes of the page/swap and hibernation files, with the supported way to reclaim each
+#[command]
+pub async fn get_system_files_report() -> Result<crate::system_files::SystemFilesReport, String> {
+    tauri::async_runtime::spawn_blocking(crate::system_files::system_files_report)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Files under the cleaning roots grouped by extension, for cross-category cleanup
+#[command]
+pub async fn scan_extension_opportunities(
+    app: AppHandle,
+    options: Option<cleaner::CleaningOptions>,
+) -> Result<Vec<cleaner::ExtensionCleanOpportunity>, String> {
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+    tauri::async_runtime::spawn_blocking(move || {
+        cleaner::scan_extension_opportunities(options)
+    }).await.map_err(|e| e.to_string())
+}
+
+/// Report which paths this process could delete, without deleting anything
+#[command]
+pub fn check_deletable(paths: Vec<String>) -> Vec<cleaner::DeletableCheck> {
+    cleaner::check_deletable(&paths)
+}
+
+/// Junk paths deleted per blocking task
+const JUNK_DELETE_CHUNK: usize = 64;
+
+#[derive(Clone, serde::Serialize)]
+struct JunkDeleteProgress {
+    processed: usize,
+    total: usize,
+    deleted_size: u64,
+}
+
+/// Deletes a selection a chunk at a time on the blocking pool, emitting
+/// "junk-delete-progress" after each chunk
+///
+/// Yielding between chunks keeps one huge selection from holding a runtime
+/// thread for the whole run, so other commands keep being served. The
+/// `DeletePlan` is built once and shared by every chunk.
+struct ChunkedJunkDelete {
+    app: AppHandle,
+    plan: Arc<cleaner::DeletePlan>,
+    options: cleaner::CleaningOptions,
+    progress: JunkDeleteProgress,
+}
+
+impl ChunkedJunkDelete {
+    /// `total` is the number of paths all `delete` calls will be given together
+    fn new(app: &AppHandle, options: cleaner::CleaningOptions, total: usize) -> Self {
+        ChunkedJunkDelete {
+            app: app.clone(),
+            plan: Arc::new(cleaner::DeletePlan::new(&options)),
+            options,
+            progress: JunkDeleteProgress { processed: 0, total, deleted_size: 0 },
+        }
+    }
+
+    /// Delete overlap-free `paths` (`dedupe_delete_set`), then prune the directories they leave empty
+    async fn delete(&mut self, paths: Vec<String>) -> Result<cleaner::DeletionResult, String> {
+        let mut result = cleaner::DeletionResult::default();
+
+        for chunk in paths.chunks(JUNK_DELETE_CHUNK) {
+            let chunk = chunk.to_vec();
+            let chunk_len = chunk.len();
+            let plan = self.plan.clone();
+            let options = self.options.clone();
+            let partial = tauri::async_runtime::spawn_blocking(move || plan.delete(chunk, &options))
+                .await
+                .map_err(|e| e.to_string())??;
+
+            self.progress.processed += chunk_len;
+            self.progress.deleted_size += partial.deleted_size;
+            result.merge(partial);
+            let _ = self.app.emit("junk-delete-progress", self.progress.clone());
+
+            tokio::task::yield_now().await;
+        }
+
+        let plan = self.plan.clone();
+        let options = self.options.clone();
+        result.pruned_dir_count = tauri::async_runtime::spawn_blocking(move || plan.prune_affected(&options))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(result)
+    }
+}
+
+/// Delete junk paths in chunks, removing overlap across the whole selection first
+async fn delete_junk_chunked(
+    app: &AppHandle,
+    paths: Vec<String>,
+    options: cleaner::CleaningOptions,
+) -> Result<cleaner::DeletionResult, String> {
+    let paths = cleaner::dedupe_delete_set(paths);
+    ChunkedJunkDelete::new(app, options, paths.len()).delete(paths).await
+}
+
+#[command]
+pub async fn clean_junk(app: AppHandle, paths: Vec<String>, confirmation: Option<String>) -> Result<(), String> {
+    let options = default_cleaning_options(&app);
+    consume_junk_token(confirmation.as_deref(), &paths, options.dry_run)?;
+    let result = delete_junk_chunked(&app, paths, options).await?;
     
     // Invalidate main scan cache just in case we deleted something overlapping
     clear_cache();
     
+    if result.failed_count > 0 {
+        return Err(result.errors.join("\n"));
+    }
     Ok(())
 }
 
+/// Clean the given categories and report estimated vs actual freed space per category
+///
+/// Summing the per-category counts gives the totals `clean_junk_with_options` would report.
+#[command]
+pub async fn delete_junk_by_category(
+    app: AppHandle,
+    categories: Vec<JunkCategory>,
+    options: Option<cleaner::CleaningOptions>,
+    confirmation: Option<String>,
+) -> Result<cleaner::ReclaimReport, String> {
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+    let dry_run = options.dry_run;
+    let paths: Vec<String> = categories
+        .iter()
+        .flat_map(|c| c.items.iter().map(|item| item.path.clone()))
+        .collect();
+    consume_junk_token(confirmation.as_deref(), &paths, dry_run)?;
+
+    let categories = Arc::new(categories);
+    let free_space = |categories: &Arc<Vec<JunkCategory>>| {
+        let categories = categories.clone();
+        tauri::async_runtime::spawn_blocking(move || cleaner::categories_free_space(&categories))
+    };
+    let free_before = if dry_run { None } else { free_space(&categories).await.map_err(|e| e.to_string())? };
+
+    let groups = cleaner::category_delete_groups(&categories);
+    let total = groups.iter().map(|(_, paths, _)| paths.len()).sum();
+    let mut deleter = ChunkedJunkDelete::new(&app, options, total);
+    let mut report = cleaner::ReclaimReport::default();
+    for (category_id, paths, estimated_freed) in groups {
+        let result = deleter.delete(paths).await?;
+        report.push(cleaner::CategoryReclaim::new(category_id, estimated_freed, result));
+    }
+
+    if let Some(before) = free_before {
+        if let Some(after) = free_space(&categories).await.map_err(|e| e.to_string())? {
+            report.volume_free_delta = Some(after as i64 - before as i64);
+        }
+    }
+
+    if !dry_run {
+        clear_cache();
+    }
+
+    Ok(report)
+}
+
+/// What cleaning `paths` with `options` would do to each one, read from disk without changing anything
+#[command]
+pub async fn preview_deletion(
+    app: AppHandle,
+    paths: Vec<String>,
+    options: Option<cleaner::CleaningOptions>,
+) -> Result<Vec<cleaner::DeletionPreviewItem>, String> {
+    let options = options.unwrap_or_else(|| default_cleaning_options(&app));
+    tauri::async_runtime::spawn_blocking(move || cleaner::preview_deletion(paths, &options))
+        .await
+        .map_err(|e| e.to_string())
+}
+
 #[command]
 pub async fn clean_junk_with_options(
+    app: AppHandle,
     paths: Vec<String>,
     options: cleaner::CleaningOptions,
+    confirmation: Option<String>,
 ) -> Result<cleaner::DeletionResult, String> {
-    let result = tauri::async_runtime::spawn_blocking(move || {
-        cleaner::delete_junk_items_with_options(paths, options)
-    }).await.map_err(|e| e.to_string())??;
+    consume_junk_token(confirmation.as_deref(), &paths, options.dry_run)?;
+    let result = delete_junk_chunked(&app, paths, options).await?;
     
     // Invalidate main scan cache if not dry run
     if !result.errors.is_empty() || result.deleted_count > 0 {
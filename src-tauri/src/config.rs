@@ -0,0 +1,210 @@
+// Persistent application configuration
+//
+// All user-tunable options live in a single `ToolkitConfig` stored as JSON
+// under the Tauri app config directory. Writes are atomic (write-temp-then-rename)
+// and missing/older files are migrated by filling in defaults.
+
+use crate::cleaner::{CleaningOptions, CleaningProfile};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+
+/// Current on-disk config format version
+pub const CONFIG_VERSION: u32 = 1;
+
+const CONFIG_FILE_NAME: &str = "toolkit-config.json";
+
+/// Seconds a cached scan is served before it is rescanned
+pub const DEFAULT_CACHE_TTL: u64 = 60 * 60;
+
+/// Most scans kept in the scan cache
+pub const DEFAULT_CACHE_ENTRIES: usize = 256;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ToolkitConfig {
+    /// Format version of the file this config was loaded from
+    pub version: u32,
+    /// Scan cache settings, applied to the scan cache at startup
+    pub cache: CacheSettings,
+    /// Options the cleaning commands use when the caller passes none
+    pub cleaning_options: CleaningOptions,
+    /// User-defined cleaning presets shown after the built-in ones
+    pub cleaning_profiles: Vec<CleaningProfile>,
+}
+
+impl Default for ToolkitConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            cache: CacheSettings::default(),
+            cleaning_options: CleaningOptions::default(),
+            cleaning_profiles: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CacheSettings {
+    /// How long a scan result stays valid, in seconds
+    pub ttl_seconds: u64,
+    /// Maximum number of cached directory entries
+    pub max_entries: usize,
+}
+
+impl Default for CacheSettings {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: DEFAULT_CACHE_TTL,
+            max_entries: DEFAULT_CACHE_ENTRIES,
+        }
+    }
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_config_dir().map_err(|e| e.to_string())?;
+    Ok(dir.join(CONFIG_FILE_NAME))
+}
+
+/// Parse a config file's contents, filling defaults for anything missing
+pub fn parse_config(contents: &str) -> Result<ToolkitConfig, String> {
+    let mut config: ToolkitConfig = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+
+    if config.version < CONFIG_VERSION {
+        log::info!("Migrating config from version {} to {}", config.version, CONFIG_VERSION);
+        config.version = CONFIG_VERSION;
+    }
+
+    Ok(config)
+}
+
+/// Load the config from `path`, returning defaults if the file is absent
+pub fn load_config_from(path: &Path) -> Result<ToolkitConfig, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => parse_config(&contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(ToolkitConfig::default()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Save the config to `path` atomically (write to a temp file, then rename)
+pub fn save_config_to(path: &Path, config: &ToolkitConfig) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    let tmp_path = path.with_extension("json.tmp");
+
+    fs::write(&tmp_path, json).map_err(|e| e.to_string())?;
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        e.to_string()
+    })
+}
+
+/// Load the app's config
+pub fn load_config(app: &AppHandle) -> Result<ToolkitConfig, String> {
+    load_config_from(&config_path(app)?)
+}
+
+/// Change the stored config with `update` and persist it
+pub fn modify_config(app: &AppHandle, update: impl FnOnce(&mut ToolkitConfig)) -> Result<ToolkitConfig, String> {
+    let path = config_path(app)?;
+    let mut config = load_config_from(&path)?;
+    update(&mut config);
+
+    save_config_to(&path, &config)?;
+    Ok(config)
+}
+
+/// Apply a JSON merge patch (RFC 7386) to `target`
+fn merge_patch(target: &mut Value, patch: &Value) {
+    match patch {
+        Value::Object(patch_map) => {
+            if !target.is_object() {
+                *target = Value::Object(serde_json::Map::new());
+            }
+            let target_map = target.as_object_mut().unwrap();
+            for (key, value) in patch_map {
+                if value.is_null() {
+                    target_map.remove(key);
+                } else {
+                    merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+                }
+            }
+        }
+        _ => *target = patch.clone(),
+    }
+}
+
+/// Apply a partial update to a config, re-filling defaults for removed keys
+pub fn apply_patch(config: &ToolkitConfig, patch: &Value) -> Result<ToolkitConfig, String> {
+    let mut value = serde_json::to_value(config).map_err(|e| e.to_string())?;
+    merge_patch(&mut value, patch);
+
+    let mut updated: ToolkitConfig = serde_json::from_value(value).map_err(|e| e.to_string())?;
+    updated.version = CONFIG_VERSION;
+    Ok(updated)
+}
+
+#[command]
+pub fn get_config(app: AppHandle) -> Result<ToolkitConfig, String> {
+    load_config(&app)
+}
+
+/// Merge `patch` into the stored config and persist it
+///
+/// Cache settings take effect immediately.
+#[command]
+pub fn update_config(app: AppHandle, patch: Value) -> Result<ToolkitConfig, String> {
+    let path = config_path(&app)?;
+    let current = load_config_from(&path)?;
+    let updated = apply_patch(&current, &patch)?;
+
+    save_config_to(&path, &updated)?;
+    crate::commands::apply_cache_settings(&updated.cache);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+
+    #[test]
+    fn test_parse_older_config_fills_defaults() {
+        let config = parse_config(r#"{ "version": 0, "cache": { "max_entries": 64 } }"#).unwrap();
+
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.cache.max_entries, 64);
+        assert_eq!(config.cache.ttl_seconds, CacheSettings::default().ttl_seconds);
+    }
+
+    #[test]
+    fn test_apply_patch_merges_nested_fields() {
+        let config = ToolkitConfig::default();
+        let patch = serde_json::json!({ "cache": { "ttl_seconds": 120 } });
+
+        let updated = apply_patch(&config, &patch).unwrap();
+
+        assert_eq!(updated.cache.ttl_seconds, 120);
+        assert_eq!(updated.cache.max_entries, config.cache.max_entries);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = TestDir::new("config-test");
+        let path = dir.join(CONFIG_FILE_NAME);
+
+        let mut config = ToolkitConfig::default();
+        config.cleaning_options.min_age_days = Some(14);
+        save_config_to(&path, &config).unwrap();
+
+        let loaded = load_config_from(&path).unwrap();
+        assert_eq!(loaded.cleaning_options.min_age_days, Some(14));
+    }
+}
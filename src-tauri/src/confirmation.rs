@@ -0,0 +1,159 @@
+// Two-step confirmation for destructive commands
+//
+// A single IPC call must not be able to destroy data: a frontend bug would
+// otherwise wipe a partition or a folder with no second factor. The frontend
+// first asks `prepare_destructive` for a token, which describes exactly what
+// will happen and is bound to one operation and target. The destructive
+// command then consumes that token and rejects missing, expired, reused or
+// mismatched ones.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use ts_rs::TS;
+
+/// How long a token stays valid after it is issued
+pub const TOKEN_TTL: Duration = Duration::from_secs(60);
+
+/// Operations that need a confirmation token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum DestructiveOp {
+    DeletePartition,
+    DeleteItem,
+    CleanJunk, // Target is `selection_target` of the paths, see `prepare_clean_junk`
+}
+
+/// A single-use permission to run one destructive operation on one target
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct ConfirmationToken {
+    pub token: String,
+    pub op: DestructiveOp,
+    pub target: String, // Partition id or path the token is bound to
+    pub description: String, // What will happen, for the confirmation dialog
+    #[ts(type = "number")]
+    pub expires_in_secs: u64,
+}
+
+struct Pending {
+    op: DestructiveOp,
+    target: String,
+    expires_at: Instant,
+}
+
+lazy_static! {
+    static ref PENDING: Mutex<HashMap<String, Pending>> = Mutex::new(HashMap::new());
+}
+
+/// Issue a token for `op` on `target`
+pub fn issue(op: DestructiveOp, target: &str, description: String) -> ConfirmationToken {
+    issue_with_ttl(op, target, description, TOKEN_TTL)
+}
+
+fn issue_with_ttl(op: DestructiveOp, target: &str, description: String, ttl: Duration) -> ConfirmationToken {
+    let token = uuid::Uuid::new_v4().to_string();
+    let now = Instant::now();
+
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.retain(|_, p| p.expires_at > now);
+        pending.insert(
+            token.clone(),
+            Pending {
+                op,
+                target: target.to_string(),
+                expires_at: now + ttl,
+            },
+        );
+    }
+
+    ConfirmationToken {
+        token,
+        op,
+        target: target.to_string(),
+        description,
+        expires_in_secs: ttl.as_secs(),
+    }
+}
+
+/// Target a token for deleting several paths at once is bound to
+///
+/// Order and repeats don't matter; any other change to the selection gives
+/// a different target, so a token can't be replayed for more paths.
+pub fn selection_target(paths: &[String]) -> String {
+    let mut sorted: Vec<&str> = paths.iter().map(String::as_str).collect();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let mut hasher = blake3::Hasher::new();
+    for path in &sorted {
+        hasher.update(path.as_bytes());
+        hasher.update(&[0]);
+    }
+    format!("{} paths ({})", sorted.len(), &hasher.finalize().to_hex()[..16])
+}
+
+/// Use up `token`, failing unless it was issued for exactly `op` on `target` and hasn't expired
+pub fn consume(token: Option<&str>, op: DestructiveOp, target: &str) -> Result<(), String> {
+    let token = token.ok_or_else(|| {
+        let prepare = match op {
+            DestructiveOp::CleanJunk => "prepare_clean_junk",
+            DestructiveOp::DeletePartition | DestructiveOp::DeleteItem => "prepare_destructive",
+        };
+        format!("{:?} requires a confirmation token; call {} first", op, prepare)
+    })?;
+
+    // Removed up front so a token can never be used twice, even after a mismatch
+    let pending = PENDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(token)
+        .ok_or_else(|| "Unknown or already used confirmation token".to_string())?;
+
+    if pending.expires_at <= Instant::now() {
+        return Err("Confirmation token expired; confirm the operation again".to_string());
+    }
+    if pending.op != op || pending.target != target {
+        return Err(format!(
+            "Confirmation token was issued for {:?} on {}, not {:?} on {}",
+            pending.op, pending.target, op, target
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_single_use_and_bound_to_target() {
+        let token = issue(DestructiveOp::DeleteItem, "/tmp/a", String::new());
+        assert!(consume(Some(&token.token), DestructiveOp::DeleteItem, "/tmp/a").is_ok());
+        assert!(consume(Some(&token.token), DestructiveOp::DeleteItem, "/tmp/a").is_err());
+
+        let token = issue(DestructiveOp::DeleteItem, "/tmp/a", String::new());
+        assert!(consume(Some(&token.token), DestructiveOp::DeleteItem, "/tmp/b").is_err());
+
+        let token = issue(DestructiveOp::DeleteItem, "sda1", String::new());
+        assert!(consume(Some(&token.token), DestructiveOp::DeletePartition, "sda1").is_err());
+
+        let token = issue_with_ttl(DestructiveOp::DeletePartition, "sda1", String::new(), Duration::ZERO);
+        assert!(consume(Some(&token.token), DestructiveOp::DeletePartition, "sda1").is_err());
+
+        assert!(consume(None, DestructiveOp::DeletePartition, "sda1").is_err());
+    }
+
+    #[test]
+    fn test_selection_target_ignores_order_but_not_contents() {
+        let paths = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        let target = selection_target(&paths(&["/tmp/a", "/tmp/b"]));
+
+        assert_eq!(target, selection_target(&paths(&["/tmp/b", "/tmp/a", "/tmp/a"])));
+        assert_ne!(target, selection_target(&paths(&["/tmp/a", "/tmp/b", "/tmp/c"])));
+        assert!(target.starts_with("2 paths"));
+    }
+}
@@ -0,0 +1,253 @@
+// Docker / Podman reclaimable space
+//
+// Container engines keep images, stopped containers, volumes and build cache
+// in their own storage (e.g. /var/lib/docker/overlay2). Deleting those files
+// directly corrupts the engine, so this category is measured with
+// `<engine> system df` and cleaned with the engine's own prune commands.
+// Its items carry `container://<engine>/<resource>` paths instead of real ones.
+
+use crate::cleaner::{JunkCategory, AgeBasis, JunkItem, SafetyLevel};
+use std::process::Command;
+
+/// Path prefix of junk items that are pruned through a container engine
+pub const CONTAINER_SCHEME: &str = "container://";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Docker,
+    Podman,
+}
+
+const ENGINES: [Engine; 2] = [Engine::Docker, Engine::Podman];
+
+impl Engine {
+    fn binary(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker",
+            Engine::Podman => "podman",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Engine::Docker => "Docker",
+            Engine::Podman => "Podman",
+        }
+    }
+}
+
+/// A kind of storage reported by `system df`, and the prune that frees it
+struct Resource {
+    df_type: &'static str, // TYPE column of `system df`
+    key: &'static str, // Last segment of the item path
+    description: &'static str,
+    safety: SafetyLevel,
+    prune: &'static [&'static str],
+}
+
+const RESOURCES: &[Resource] = &[
+    Resource {
+        df_type: "Images",
+        key: "images",
+        description: "Images not used by any container (pulled again when next needed)",
+        safety: SafetyLevel::Safe,
+        prune: &["image", "prune", "--all", "--force"],
+    },
+    Resource {
+        df_type: "Containers",
+        key: "containers",
+        description: "Stopped containers and their writable layers",
+        safety: SafetyLevel::Caution,
+        prune: &["container", "prune", "--force"],
+    },
+    Resource {
+        df_type: "Local Volumes",
+        key: "volumes",
+        description: "Volumes not attached to any container. These can hold data such as databases: review before deleting.",
+        safety: SafetyLevel::Risky,
+        prune: &["volume", "prune", "--force"],
+    },
+    Resource {
+        df_type: "Build Cache",
+        key: "build-cache",
+        description: "Build cache layers",
+        safety: SafetyLevel::Safe,
+        prune: &["builder", "prune", "--force"],
+    },
+];
+
+/// Parse a size as printed by Docker/Podman ("1.2GB", "512kB", "3.4MiB", "0B")
+fn parse_size(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+
+    let multiplier: f64 = match unit.trim() {
+        "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    Some((number * multiplier).round() as u64)
+}
+
+/// Parse `system df --format "{{.Type}}\t{{.Reclaimable}}"` into (type, reclaimable bytes)
+fn parse_df(output: &str) -> Vec<(String, u64)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (kind, reclaimable) = line.split_once('\t')?;
+            // "1.2GB (50%)": the percentage isn't needed
+            let size = parse_size(reclaimable.split_whitespace().next()?)?;
+            Some((kind.trim().to_string(), size))
+        })
+        .collect()
+}
+
+/// The "Total reclaimed space: 1.2GB" summary printed by prune commands
+fn parse_reclaimed(output: &str) -> Option<u64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Total reclaimed space:"))
+        .and_then(parse_size)
+}
+
+/// Reclaimable space per resource type, or None when the engine isn't installed or running
+fn system_df(engine: Engine) -> Option<Vec<(String, u64)>> {
+    let output = Command::new(engine.binary())
+        .args(["system", "df", "--format", "{{.Type}}\t{{.Reclaimable}}"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(parse_df(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn reclaimable(usage: &[(String, u64)], resource: &Resource) -> Option<u64> {
+    usage.iter().find(|(kind, _)| kind == resource.df_type).map(|(_, size)| *size)
+}
+
+/// Reclaimable container storage of every running engine, or None if there is none
+pub fn scan_container_cache() -> Option<JunkCategory> {
+    let mut category = JunkCategory {
+        id: "container_cache".to_string(),
+        name: "Docker & Podman".to_string(),
+        description: "Unused images, stopped containers, volumes and build cache, removed with the engine's own prune commands."
+            .to_string(),
+        items: Vec::new(),
+        total_size: 0,
+        icon: "container_cache".to_string(),
+        filtered_size: 0,
+        filtered_count: 0,
+        unreadable_count: 0,
+    };
+    let mut seen: Vec<Vec<(String, u64)>> = Vec::new();
+
+    for engine in ENGINES {
+        let Some(usage) = system_df(engine) else { continue };
+        // With podman-docker, `docker` is podman: count that storage once
+        if seen.contains(&usage) {
+            continue;
+        }
+
+        for resource in RESOURCES {
+            let Some(size) = reclaimable(&usage, resource).filter(|&s| s > 0) else { continue };
+            category.total_size += size;
+            category.items.push(JunkItem {
+                path: format!("{}{}/{}", CONTAINER_SCHEME, engine.binary(), resource.key),
+                name: format!("{} {}", engine.label(), resource.df_type.to_lowercase()),
+                size,
+                description: resource.description.to_string(),
+                age_days: None,
+                requires_reboot: false,
+                safety: resource.safety,
+                archive: None,
+                age_basis: AgeBasis::Modified,
+            });
+        }
+        seen.push(usage);
+    }
+
+    (!seen.is_empty()).then_some(category)
+}
+
+/// Run the prune behind a `container://` item path, returning the bytes it freed
+///
+/// A dry run reports what `system df` currently considers reclaimable.
+pub fn prune(path: &str, dry_run: bool) -> Result<u64, String> {
+    let (engine, key) = path
+        .strip_prefix(CONTAINER_SCHEME)
+        .and_then(|rest| rest.split_once('/'))
+        .ok_or_else(|| format!("Not a container cache item: {}", path))?;
+    let engine = ENGINES
+        .into_iter()
+        .find(|e| e.binary() == engine)
+        .ok_or_else(|| format!("Unknown container engine: {}", engine))?;
+    let resource = RESOURCES
+        .iter()
+        .find(|r| r.key == key)
+        .ok_or_else(|| format!("Unknown container resource: {}", key))?;
+
+    let before = system_df(engine)
+        .ok_or_else(|| format!("{} is not running", engine.label()))?;
+    let before = reclaimable(&before, resource).unwrap_or(0);
+    if dry_run {
+        return Ok(before);
+    }
+
+    let output = Command::new(engine.binary())
+        .args(resource.prune)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", engine.binary(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} {} failed: {}",
+            engine.binary(),
+            resource.prune.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // Not every prune prints a total (podman lists removed IDs); fall back to the df delta
+    Ok(parse_reclaimed(&String::from_utf8_lossy(&output.stdout)).unwrap_or_else(|| {
+        let after = system_df(engine)
+            .and_then(|usage| reclaimable(&usage, resource))
+            .unwrap_or(before);
+        before.saturating_sub(after)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_and_prune_output() {
+        let df = "Images\t2.5GB (62%)\nContainers\t1.1kB (100%)\nLocal Volumes\t0B (0%)\nBuild Cache\t300MB\n";
+        assert_eq!(
+            parse_df(df),
+            vec![
+                ("Images".to_string(), 2_500_000_000),
+                ("Containers".to_string(), 1_100),
+                ("Local Volumes".to_string(), 0),
+                ("Build Cache".to_string(), 300_000_000),
+            ]
+        );
+
+        let prune = "Deleted Images:\nuntagged: alpine:latest\ndeleted: sha256:abc\n\nTotal reclaimed space: 7.8MB\n";
+        assert_eq!(parse_reclaimed(prune), Some(7_800_000));
+        assert_eq!(parse_reclaimed("abc123\ndef456\n"), None);
+        assert_eq!(parse_size("1.5GiB"), Some(1_610_612_736));
+        assert_eq!(parse_size("N/A"), None);
+    }
+}
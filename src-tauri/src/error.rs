@@ -0,0 +1,101 @@
+// Top-level error type returned by Tauri commands
+//
+// Commands traditionally return `Result<T, String>`. `ToolkitError` keeps the
+// typed cause on the Rust side and still serializes as the plain message, so
+// the frontend's `String(error)` handling is unchanged. The one exception is
+// a compound operation that stopped partway: it serializes as
+// `{ message, partial_failure }` so the UI can show what is still in effect.
+
+use crate::partition::{OperationError, PartialFailure, ResizeError};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ToolkitError {
+    #[error(transparent)]
+    Resize(#[from] ResizeError),
+
+    #[error(transparent)]
+    Operation(#[from] OperationError),
+
+    #[error(transparent)]
+    PartialFailure(#[from] PartialFailure),
+
+    #[error("{0} is not supported on this platform")]
+    Unsupported(String),
+
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<anyhow::Error> for ToolkitError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<PartialFailure>() {
+            Ok(failure) => ToolkitError::PartialFailure(failure),
+            Err(e) => ToolkitError::Other(e.to_string()),
+        }
+    }
+}
+
+impl From<String> for ToolkitError {
+    fn from(message: String) -> Self {
+        ToolkitError::Other(message)
+    }
+}
+
+impl From<&str> for ToolkitError {
+    fn from(message: &str) -> Self {
+        ToolkitError::Other(message.to_string())
+    }
+}
+
+impl Serialize for ToolkitError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ToolkitError::PartialFailure(failure) => {
+                let mut state = serializer.serialize_struct("ToolkitError", 2)?;
+                state.serialize_field("message", &self.to_string())?;
+                state.serialize_field("partial_failure", failure)?;
+                state.end()
+            }
+            _ => serializer.serialize_str(&self.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resize_error_serializes_as_message() {
+        let err: ToolkitError = ResizeError::ExceedsCapacity {
+            requested: 2048,
+            maximum: 1024,
+        }
+        .into();
+
+        let json = serde_json::to_string(&err).unwrap();
+        assert!(json.contains("exceeds available contiguous space"));
+        assert!(matches!(err, ToolkitError::Resize(ResizeError::ExceedsCapacity { .. })));
+    }
+
+    #[test]
+    fn test_partial_failure_keeps_its_fields_through_anyhow() {
+        let failure = PartialFailure {
+            operation: "Split of /dev/sda2".to_string(),
+            failed_step: "create the new partition".to_string(),
+            reason: "parted failed".to_string(),
+            completed: vec![],
+            rolled_back: vec!["shrink /dev/sda2".to_string()],
+            manual_steps: vec![],
+            table_backups: vec![],
+        };
+        let err: ToolkitError = anyhow::Error::from(failure).into();
+
+        let json = serde_json::to_value(&err).unwrap();
+        assert!(json["message"].as_str().unwrap().contains("failed at \"create the new partition\""));
+        assert_eq!(json["partial_failure"]["rolled_back"][0], "shrink /dev/sda2");
+    }
+}
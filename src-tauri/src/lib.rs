@@ -2,12 +2,25 @@ mod scanner;
 mod commands;
 mod ai;
 mod ai_commands;
+mod archives;
+mod capabilities;
 mod cleaner;
+mod config;
+mod confirmation;
+mod container_cache;
+mod error;
 mod mcp;
 mod mcp_commands_native; // Native Rust MCP implementation (replaces subprocess)
+mod mounts;
 mod system_tools;
 mod partition;
 mod partition_commands;
+mod paths;
+mod recycle;
+mod shadow_copies;
+mod system_files;
+#[cfg(test)]
+mod test_support;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -20,6 +33,17 @@ pub fn run() {
             .build(),
         )?;
       }
+
+      // Scripts left behind by a run that crashed mid-operation
+      let removed = partition::diskpart::sweep_stale_scripts();
+      if removed > 0 {
+        log::info!("Removed {} stale diskpart script(s) from the temp directory", removed);
+      }
+
+      match config::load_config(app.handle()) {
+        Ok(config) => commands::apply_cache_settings(&config.cache),
+        Err(e) => log::warn!("Couldn't load the config, using default cache settings: {}", e),
+      }
       Ok(())
     })
     .manage(ai_commands::InferenceState::default())
@@ -27,12 +51,30 @@ pub fn run() {
     .invoke_handler(tauri::generate_handler![
         commands::scan_dir,
         commands::refresh_scan,
+        commands::rescan_incremental,
+        commands::scan_dirs,
         commands::clear_cache,
+        commands::set_cache_ttl,
+        commands::invalidate_path,
+        commands::set_cache_capacity,
+        commands::watch_dir,
+        commands::unwatch_dir,
         commands::reveal_in_explorer,
         commands::open_file,
+        commands::prepare_destructive,
+        commands::prepare_clean_junk,
         commands::delete_item,
+        commands::delete_item_with_progress,
+        commands::cancel_delete,
         commands::get_drives,
+        commands::get_breadcrumb,
         commands::cancel_scan,
+        commands::find_recent_large_files,
+        commands::find_duplicates,
+        commands::cancel_find_duplicates,
+        commands::estimate_scan,
+        commands::warm_cache,
+        commands::cancel_warm_cache,
         ai_commands::get_ai_providers_status,
         ai_commands::get_provider_models,
         ai_commands::run_ai_inference,
@@ -41,8 +83,24 @@ pub fn run() {
         ai_commands::download_model,
         commands::scan_junk,
         commands::scan_junk_with_options,
+        commands::scan_junk_items_streaming,
+        commands::cancel_junk_scan,
+        commands::scan_downloads_junk,
+        commands::scan_browser_caches,
+        commands::resize_shadow_storage,
+        commands::get_system_files_report,
+        commands::scan_extension_opportunities,
+        commands::list_profiles,
+        commands::scan_with_profile,
         commands::clean_junk,
+        commands::check_deletable,
         commands::clean_junk_with_options,
+        commands::delete_junk_by_category,
+        commands::preview_deletion,
+        recycle::list_trashed,
+        recycle::restore_trashed,
+        config::get_config,
+        config::update_config,
         mcp_commands_native::initialize_mcp,
         mcp_commands_native::get_mcp_tools,
         mcp_commands_native::execute_mcp_tool,
@@ -61,15 +119,28 @@ pub fn run() {
         system_tools::kill_process,
         system_tools::get_security_logs,
         system_tools::get_open_ports,
+        capabilities::get_capabilities,
         // Partition Management
         partition_commands::get_disks,
         partition_commands::get_partitions,
+        partition_commands::get_mounted_volumes,
+        partition_commands::get_disk_health_summary,
         partition_commands::get_partition_info,
+        partition_commands::inspect_partition,
+        partition_commands::analyze_fragmentation,
+        partition_commands::get_media_type,
         partition_commands::validate_expand_partition,
         partition_commands::validate_shrink_partition,
+        partition_commands::propose_shrink_target,
         partition_commands::expand_partition,
         partition_commands::shrink_partition,
+        partition_commands::cancel_resize,
+        partition_commands::split_partition,
+        partition_commands::supported_create_filesystems,
         partition_commands::create_space_reallocation_plan,
+        partition_commands::validate_reallocation_plan,
+        partition_commands::execute_reallocation_plan,
+        partition_commands::cleanup_stale_scripts,
         partition_commands::unmount_partition,
         partition_commands::mount_partition,
         partition_commands::validate_delete_partition,
@@ -0,0 +1,157 @@
+// Mount table
+//
+// On Linux the same files can be reachable through several mount points:
+// bind mounts re-expose a directory elsewhere, overlay mounts (containers)
+// layer directories that already live under /var/lib, and loop mounts (snaps,
+// ISOs) expose the contents of an image file that is counted on its own.
+// /proc/self/mountinfo tells these apart from the mount that owns the data.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum MountKind {
+    /// The mount that owns the filesystem's data
+    #[default]
+    Normal,
+    /// Another view of a directory already mounted elsewhere
+    Bind,
+    /// Union of directories from other filesystems (container layers)
+    Overlay,
+    /// Contents of an image file attached through a loop device
+    Loop,
+}
+
+/// One line of the mount table
+#[derive(Debug, Clone)]
+pub struct MountEntry {
+    pub mount_point: PathBuf,
+    pub fs_type: String,
+    /// Device or source the filesystem came from (e.g. /dev/sda1, /dev/loop3)
+    pub source: String,
+    pub kind: MountKind,
+}
+
+/// Undo the octal escapes mountinfo uses for whitespace and backslashes
+fn unescape(field: &str) -> String {
+    field
+        .replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+/// Parse the contents of /proc/self/mountinfo
+///
+/// Line format: `id parent major:minor root mount_point options [optional...] - fstype source super_options`.
+/// A mount is a bind when it exposes a subdirectory of its filesystem (root
+/// other than `/`) or repeats a device+root pair mounted earlier; btrfs
+/// subvolume mounts also have a non-`/` root but own their data.
+pub fn parse_mountinfo(text: &str) -> Vec<MountEntry> {
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    let mut entries = Vec::new();
+
+    for line in text.lines() {
+        let Some((mount, fs)) = line.split_once(" - ") else {
+            continue;
+        };
+        let mount: Vec<&str> = mount.split_whitespace().collect();
+        let fs: Vec<&str> = fs.split_whitespace().collect();
+        if mount.len() < 5 || fs.is_empty() {
+            continue;
+        }
+
+        let device = mount[2].to_string();
+        let root = unescape(mount[3]);
+        let fs_type = fs[0].to_string();
+        let source = fs.get(1).map(|s| unescape(s)).unwrap_or_default();
+        let super_options = fs.get(2).copied().unwrap_or("");
+
+        let is_subvolume = fs_type == "btrfs"
+            && super_options.split(',').any(|o| o.strip_prefix("subvol=") == Some(root.as_str()));
+
+        let kind = if fs_type == "overlay" || fs_type == "fuse-overlayfs" {
+            MountKind::Overlay
+        } else if source.starts_with("/dev/loop") {
+            MountKind::Loop
+        } else if !seen.insert((device, root.clone())) || (root != "/" && !is_subvolume) {
+            MountKind::Bind
+        } else {
+            MountKind::Normal
+        };
+
+        entries.push(MountEntry {
+            mount_point: PathBuf::from(unescape(mount[4])),
+            fs_type,
+            source,
+            kind,
+        });
+    }
+
+    entries
+}
+
+/// Current mount table; empty if it can't be read
+#[cfg(target_os = "linux")]
+pub fn mount_table() -> Vec<MountEntry> {
+    std::fs::read_to_string("/proc/self/mountinfo")
+        .map(|text| parse_mountinfo(&text))
+        .unwrap_or_default()
+}
+
+/// Current mount table; other platforms have no bind/overlay/loop distinction
+/// worth tracking, so every volume sysinfo reports is a normal mount
+#[cfg(not(target_os = "linux"))]
+pub fn mount_table() -> Vec<MountEntry> {
+    sysinfo::Disks::new_with_refreshed_list()
+        .iter()
+        .map(|disk| MountEntry {
+            mount_point: disk.mount_point().to_path_buf(),
+            fs_type: disk.file_system().to_string_lossy().to_string(),
+            source: disk.name().to_string_lossy().to_string(),
+            kind: MountKind::Normal,
+        })
+        .collect()
+}
+
+/// The mount `path` lives on (the one with the longest matching mount point)
+pub fn mount_for_path<'a>(table: &'a [MountEntry], path: &Path) -> Option<&'a MountEntry> {
+    table
+        .iter()
+        .filter(|m| path.starts_with(&m.mount_point))
+        .max_by_key(|m| m.mount_point.as_os_str().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTINFO: &str = "\
+22 1 8:2 / / rw,relatime shared:1 - ext4 /dev/sda2 rw
+23 22 0:21 / /proc rw,nosuid shared:5 - proc proc rw
+30 22 8:3 /@home /home rw,relatime shared:2 - btrfs /dev/sda3 rw,subvol=/@home
+31 22 8:2 /srv/data /mnt/data\\040copy rw,relatime shared:1 - ext4 /dev/sda2 rw
+32 22 8:2 / /mnt/root rw,relatime shared:1 - ext4 /dev/sda2 rw
+40 22 7:3 / /snap/core/123 ro,nodev shared:9 - squashfs /dev/loop3 ro
+50 22 0:52 / /var/lib/docker/overlay2/abc/merged rw - overlay overlay rw,lowerdir=/x
+";
+
+    #[test]
+    fn test_parse_mountinfo_classifies_mounts() {
+        let table = parse_mountinfo(MOUNTINFO);
+        let kind = |mp: &str| table.iter().find(|m| m.mount_point == Path::new(mp)).unwrap().kind;
+
+        assert_eq!(table.len(), 7);
+        assert_eq!(kind("/"), MountKind::Normal);
+        assert_eq!(kind("/proc"), MountKind::Normal);
+        assert_eq!(kind("/home"), MountKind::Normal);
+        assert_eq!(kind("/mnt/data copy"), MountKind::Bind);
+        assert_eq!(kind("/mnt/root"), MountKind::Bind);
+        assert_eq!(kind("/snap/core/123"), MountKind::Loop);
+        assert_eq!(kind("/var/lib/docker/overlay2/abc/merged"), MountKind::Overlay);
+
+        let home = mount_for_path(&table, Path::new("/home/user/file")).unwrap();
+        assert_eq!(home.fs_type, "btrfs");
+    }
+}
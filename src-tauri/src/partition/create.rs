@@ -0,0 +1,426 @@
+// Partition creation operations
+//
+// Creates a new partition inside unallocated space and formats it.
+// Used by the split workflow and the reallocation wizard's recreate step.
+
+use crate::partition::types::*;
+use crate::partition::exec::RunCommand;
+use anyhow::{anyhow, Result};
+use std::process::Command;
+
+/// MBR partition tables have four slots for primary/extended partitions
+pub const MBR_MAX_PRIMARY_PARTITIONS: usize = 4;
+
+/// Round an offset up to the next alignment boundary
+pub fn align_up(offset: u64) -> u64 {
+    offset.div_ceil(PARTITION_ALIGNMENT) * PARTITION_ALIGNMENT
+}
+
+/// Create a partition at `offset` with `size` bytes and format it
+pub fn create_partition(
+    disk: &DiskInfo,
+    offset: u64,
+    size: u64,
+    filesystem: FilesystemType,
+    label: Option<&str>,
+) -> Result<()> {
+    if size == 0 {
+        return Err(anyhow!("New partition size must be greater than zero"));
+    }
+
+    if offset + size > disk.total_size {
+        return Err(anyhow!(
+            "New partition would extend past the end of the disk"
+        ));
+    }
+
+    check_mbr_primary_limit(disk)?;
+
+    create_platform(disk, offset, size, filesystem, label)
+}
+
+/// mkfs binary and label flag for each filesystem Linux can create
+#[cfg(target_os = "linux")]
+const MKFS_TOOLS: &[(FilesystemType, &str, &str)] = &[
+    (FilesystemType::Ext4, "mkfs.ext4", "-L"),
+    (FilesystemType::Ext3, "mkfs.ext3", "-L"),
+    (FilesystemType::Ext2, "mkfs.ext2", "-L"),
+    (FilesystemType::Btrfs, "mkfs.btrfs", "-L"),
+    (FilesystemType::XFS, "mkfs.xfs", "-L"),
+    (FilesystemType::FAT32, "mkfs.vfat", "-n"),
+    (FilesystemType::ExFAT, "mkfs.exfat", "-n"),
+    (FilesystemType::NTFS, "mkfs.ntfs", "-L"),
+];
+
+/// Filesystems that `create_partition` can format on this machine
+///
+/// Windows formats through diskpart and macOS through diskutil; on Linux each
+/// filesystem needs its own mkfs binary, so those are probed individually.
+pub fn supported_create_filesystems() -> Vec<FilesystemType> {
+    #[cfg(target_os = "windows")]
+    {
+        if crate::capabilities::tool_available("diskpart") {
+            vec![FilesystemType::NTFS, FilesystemType::ExFAT, FilesystemType::FAT32]
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        if !crate::capabilities::tool_available("parted") {
+            return Vec::new();
+        }
+
+        MKFS_TOOLS
+            .iter()
+            .filter(|(_, mkfs, _)| crate::capabilities::tool_available(mkfs))
+            .map(|(fs, _, _)| *fs)
+            .collect()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        if crate::capabilities::tool_available("diskutil") {
+            vec![FilesystemType::APFS, FilesystemType::HFSPlus, FilesystemType::ExFAT, FilesystemType::FAT32]
+        } else {
+            Vec::new()
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        Vec::new()
+    }
+}
+
+/// Reject creating another primary partition on an MBR disk with no free slots
+///
+/// Platforms don't always report the MBR type (lsblk reports everything as
+/// `Normal`), so untyped partitions numbered 1-4 are counted too: MBR numbers
+/// primary/extended slots 1-4 and logical partitions from 5.
+pub fn check_mbr_primary_limit(disk: &DiskInfo) -> Result<()> {
+    if disk.table_type != PartitionTableType::MBR {
+        return Ok(());
+    }
+
+    let used_slots = disk
+        .partitions
+        .iter()
+        .filter(|p| match p.partition_type {
+            PartitionType::Primary | PartitionType::Extended => true,
+            PartitionType::Logical => false,
+            PartitionType::Normal | PartitionType::Unknown => p.number <= 4,
+        })
+        .count();
+
+    if used_slots >= MBR_MAX_PRIMARY_PARTITIONS {
+        return Err(anyhow!(
+            "This MBR disk already has {} primary partitions (the maximum). \
+             Create a logical partition inside an extended partition instead, or convert the disk to GPT.",
+            MBR_MAX_PRIMARY_PARTITIONS
+        ));
+    }
+
+    Ok(())
+}
+
+/// Windows partition creation using diskpart
+#[cfg(target_os = "windows")]
+fn create_platform(
+    disk: &DiskInfo,
+    offset: u64,
+    size: u64,
+    filesystem: FilesystemType,
+    label: Option<&str>,
+) -> Result<()> {
+    use crate::partition::diskpart::ScriptFile;
+    use std::os::windows::process::CommandExt;
+
+    let fs_name = match filesystem {
+        FilesystemType::NTFS => "ntfs",
+        FilesystemType::FAT32 => "fat32",
+        FilesystemType::ExFAT => "exfat",
+        other => {
+            return Err(anyhow!(
+                "Cannot create {} partitions on Windows",
+                other.display_name()
+            ))
+        }
+    };
+
+    let label_arg = label
+        .map(|l| format!(" label=\"{}\"", l.replace('"', "")))
+        .unwrap_or_default();
+
+    // diskpart expects size in MB and offset in KB
+    let script = format!(
+        "select disk {}\ncreate partition primary size={} offset={}\nformat fs={} quick{}\nassign\n",
+        disk_number(disk),
+        size / (1024 * 1024),
+        offset / 1024,
+        fs_name,
+        label_arg
+    );
+
+    let script_file = ScriptFile::create(&script, "create")?;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = Command::new("diskpart")
+        .arg("/s")
+        .arg(script_file.path())
+        .creation_flags(CREATE_NO_WINDOW)
+        .run()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "Diskpart create failed: {}",
+            String::from_utf8_lossy(&output.stdout)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the diskpart disk number for a disk ("disk-N" or "\\.\PhysicalDriveN")
+#[cfg(target_os = "windows")]
+fn disk_number(disk: &DiskInfo) -> String {
+    if let Some(stripped) = disk.id.strip_prefix("disk-") {
+        stripped.to_string()
+    } else {
+        disk.device_path.replace("\\\\.\\PhysicalDrive", "")
+    }
+}
+
+/// Linux partition creation using parted + mkfs
+#[cfg(target_os = "linux")]
+fn create_platform(
+    disk: &DiskInfo,
+    offset: u64,
+    size: u64,
+    filesystem: FilesystemType,
+    label: Option<&str>,
+) -> Result<()> {
+    let (_, mkfs, label_flag) = *MKFS_TOOLS
+        .iter()
+        .find(|(fs, _, _)| *fs == filesystem)
+        .ok_or_else(|| anyhow!("Cannot create {} partitions on Linux", filesystem.display_name()))?;
+
+    // parted end is inclusive
+    let end = offset + size - 1;
+
+    let output = Command::new("parted")
+        .arg(&disk.device_path)
+        .arg("--script")
+        .arg("unit")
+        .arg("B")
+        .arg("mkpart")
+        .arg("primary")
+        .arg(format!("{}B", offset))
+        .arg(format!("{}B", end))
+        .run()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "parted mkpart failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    // Make sure the kernel sees the new partition before formatting
+    let _ = Command::new("partprobe").arg(&disk.device_path).run();
+
+    let number = find_partition_number_at(&disk.device_path, offset)?;
+    let device = partition_device_path(&disk.device_path, number);
+
+    let mut cmd = Command::new(mkfs);
+    if filesystem == FilesystemType::NTFS {
+        cmd.arg("--quick");
+    }
+    if let Some(label) = label {
+        cmd.arg(label_flag).arg(label);
+    }
+
+    let output = cmd.arg(&device).run()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "{} failed on {}: {}",
+            mkfs,
+            device,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// Find the partition number that starts at `offset` using parted's machine output
+#[cfg(target_os = "linux")]
+fn find_partition_number_at(disk_path: &str, offset: u64) -> Result<u32> {
+    let output = Command::new("parted")
+        .arg("-m")
+        .arg(disk_path)
+        .arg("unit")
+        .arg("B")
+        .arg("print")
+        .run()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // Machine format: "number:start:end:size:fs:name:flags;"
+    for line in stdout.lines() {
+        let fields: Vec<&str> = line.split(':').collect();
+        if fields.len() < 2 {
+            continue;
+        }
+
+        let number = fields[0].parse::<u32>();
+        let start = fields[1].trim_end_matches('B').parse::<u64>();
+
+        if let (Ok(number), Ok(start)) = (number, start) {
+            if start == offset {
+                return Ok(number);
+            }
+        }
+    }
+
+    Err(anyhow!("Could not find newly created partition at offset {}", offset))
+}
+
+/// Build a partition device path (e.g., /dev/sda + 3 -> /dev/sda3, /dev/nvme0n1 + 3 -> /dev/nvme0n1p3)
+#[cfg(target_os = "linux")]
+fn partition_device_path(disk_path: &str, number: u32) -> String {
+    if disk_path.chars().last().map(|c| c.is_ascii_digit()).unwrap_or(false) {
+        format!("{}p{}", disk_path, number)
+    } else {
+        format!("{}{}", disk_path, number)
+    }
+}
+
+/// macOS partition creation using diskutil
+#[cfg(target_os = "macos")]
+fn create_platform(
+    disk: &DiskInfo,
+    offset: u64,
+    size: u64,
+    filesystem: FilesystemType,
+    label: Option<&str>,
+) -> Result<()> {
+    let personality = match filesystem {
+        FilesystemType::APFS => "APFS",
+        FilesystemType::HFSPlus => "JHFS+",
+        FilesystemType::FAT32 => "FAT32",
+        FilesystemType::ExFAT => "ExFAT",
+        other => {
+            return Err(anyhow!(
+                "Cannot create {} partitions on macOS",
+                other.display_name()
+            ))
+        }
+    };
+
+    let anchor = diskutil_anchor(disk, offset)?;
+
+    let output = Command::new("diskutil")
+        .arg("addPartition")
+        .arg(&anchor.device_path)
+        .arg(personality)
+        .arg(label.unwrap_or("Untitled"))
+        .arg(format!("{}B", size))
+        .run()?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "diskutil addPartition failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    Ok(())
+}
+
+/// The partition `diskutil addPartition` must be given to create one at `offset`
+///
+/// diskutil takes no start offset: it puts the new partition at the start of
+/// the free space right after the partition it's given. So `offset` has to
+/// be where an existing partition ends (give or take alignment); anything
+/// else is refused rather than silently created somewhere else.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn diskutil_anchor(disk: &DiskInfo, offset: u64) -> Result<&PartitionInfo> {
+    let preceding = disk
+        .partitions
+        .iter()
+        .filter(|p| p.start_offset + p.total_size <= offset)
+        .max_by_key(|p| p.start_offset + p.total_size)
+        .ok_or_else(|| anyhow!("On macOS a new partition can only be created right after an existing one"))?;
+
+    let free_start = preceding.start_offset + preceding.total_size;
+    if offset - free_start >= PARTITION_ALIGNMENT {
+        return Err(anyhow!(
+            "On macOS a new partition can only start where the free space after {} begins (offset {}), not at offset {}",
+            preceding.display_name(),
+            free_start,
+            offset
+        ));
+    }
+
+    Ok(preceding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{mock_disk, mock_partition, GB};
+
+    fn mbr_disk(primaries: u32) -> DiskInfo {
+        let partitions = (1..=primaries)
+            .map(|number| PartitionInfo {
+                number,
+                start_offset: number as u64 * GB,
+                total_size: GB,
+                ..mock_partition(&format!("sda{}", number))
+            })
+            .collect();
+        DiskInfo {
+            table_type: PartitionTableType::MBR,
+            ..mock_disk("/dev/sda", partitions)
+        }
+    }
+
+    #[test]
+    fn test_mbr_rejects_fifth_primary() {
+        let disk = mbr_disk(4);
+
+        assert!(check_mbr_primary_limit(&disk).is_err());
+        assert!(create_partition(&disk, 50 * GB, GB, FilesystemType::Ext4, None).is_err());
+    }
+
+    #[test]
+    fn test_mbr_limit_skipped_for_gpt_and_free_slots() {
+        assert!(check_mbr_primary_limit(&mbr_disk(3)).is_ok());
+
+        let mut gpt = mbr_disk(4);
+        gpt.table_type = PartitionTableType::GPT;
+        assert!(check_mbr_primary_limit(&gpt).is_ok());
+    }
+
+    #[test]
+    fn test_align_up() {
+        assert_eq!(align_up(0), 0);
+        assert_eq!(align_up(1), PARTITION_ALIGNMENT);
+        assert_eq!(align_up(PARTITION_ALIGNMENT), PARTITION_ALIGNMENT);
+        assert_eq!(align_up(PARTITION_ALIGNMENT + 1), 2 * PARTITION_ALIGNMENT);
+    }
+
+    #[test]
+    fn test_diskutil_anchor_requires_offset_right_after_a_partition() {
+        let disk = mbr_disk(2); // sda1 at 1-2 GB, sda2 at 2-3 GB
+
+        assert_eq!(diskutil_anchor(&disk, 3 * GB).unwrap().id, "sda2");
+        assert_eq!(diskutil_anchor(&disk, 3 * GB + 4096).unwrap().id, "sda2");
+        assert!(diskutil_anchor(&disk, 10 * GB).is_err());
+        assert!(diskutil_anchor(&disk, GB / 2).is_err());
+    }
+}
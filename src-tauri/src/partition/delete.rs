@@ -4,38 +4,46 @@
 // DANGEROUS: Deleting partitions destroys all data - use with extreme caution!
 
 use crate::partition::types::*;
+use crate::partition::exec::RunCommand;
 use anyhow::{anyhow, Result};
 use std::process::Command;
 
 /// Delete a partition (platform-specific)
 /// WARNING: This will destroy all data on the partition!
+///
+/// `disk` is the disk holding `partition`; parted is addressed through it.
 #[cfg(target_os = "windows")]
-pub fn delete_partition(partition: &PartitionInfo) -> Result<()> {
+pub fn delete_partition(partition: &PartitionInfo, _disk: &DiskInfo) -> Result<()> {
     delete_windows(partition)
 }
 
 #[cfg(target_os = "macos")]
-pub fn delete_partition(partition: &PartitionInfo) -> Result<()> {
+pub fn delete_partition(partition: &PartitionInfo, _disk: &DiskInfo) -> Result<()> {
     delete_macos(partition)
 }
 
 #[cfg(target_os = "linux")]
-pub fn delete_partition(partition: &PartitionInfo) -> Result<()> {
-    delete_linux(partition)
+pub fn delete_partition(partition: &PartitionInfo, disk: &DiskInfo) -> Result<()> {
+    delete_linux(partition, disk)
 }
 
-/// Windows partition deletion using diskpart
+/// Windows partition deletion using the Storage cmdlets, or diskpart without them
 #[cfg(target_os = "windows")]
 fn delete_windows(partition: &PartitionInfo) -> Result<()> {
-    use std::fs;
-    use std::io::Write;
+    use crate::partition::diskpart::ScriptFile;
+
+    // Remove-Partition finds the partition by disk and offset, so it needn't be mounted
+    if crate::capabilities::use_storage_cmdlets() {
+        return crate::partition::storage_cmdlets::remove_partition(partition);
+    }
 
     // Get drive letter or use partition number
     let delete_command = if let Some(mount_point) = &partition.mount_point {
-        // If partition is mounted, select by volume letter
-        let drive_letter = mount_point.chars().next()
-            .ok_or_else(|| anyhow!("Invalid mount point format"))?;
-        format!("select volume {}\ndelete volume\n", drive_letter)
+        // If partition is mounted, select by drive letter or folder mount path
+        format!(
+            "select volume {}\ndelete volume\n",
+            crate::partition::diskpart::volume_selector(mount_point)
+        )
     } else {
         // If unmounted, we need to select by disk and partition number
         // Parse device_path to get disk number and partition number
@@ -43,23 +51,17 @@ fn delete_windows(partition: &PartitionInfo) -> Result<()> {
 
         // For now, require the partition to be mounted or provide better identification
         return Err(anyhow!(
-            "Cannot delete unmounted partition on Windows. Please assign a drive letter first or use Disk Management."
+            "Cannot delete an unmounted partition with diskpart. Assign a drive letter first, or use Disk Management."
         ));
     };
 
-    let script_path = std::env::temp_dir().join("delete_partition.txt");
-    let mut file = fs::File::create(&script_path)?;
-    file.write_all(delete_command.as_bytes())?;
-    drop(file);
+    let script_file = ScriptFile::create(&delete_command, "delete")?;
 
     // Execute diskpart
     let output = Command::new("diskpart")
         .arg("/s")
-        .arg(&script_path)
-        .output()?;
-
-    // Clean up script file
-    let _ = fs::remove_file(&script_path);
+        .arg(script_file.path())
+        .run()?;
 
     if !output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -71,15 +73,8 @@ fn delete_windows(partition: &PartitionInfo) -> Result<()> {
         ));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.contains("successfully") || stdout.contains("deleted") || stdout.contains("removed") {
-        Ok(())
-    } else if stdout.contains("error") || stdout.contains("failed") {
-        Err(anyhow!("Delete operation failed. Output: {}", stdout))
-    } else {
-        // Even if we're not sure, if status.success() we'll accept it
-        Ok(())
-    }
+    // diskpart exits non-zero when a command fails; its messages are localized
+    Ok(())
 }
 
 /// macOS partition deletion using diskutil
@@ -90,7 +85,7 @@ fn delete_macos(partition: &PartitionInfo) -> Result<()> {
         .arg("free")
         .arg("free")
         .arg(&partition.device_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -101,28 +96,17 @@ fn delete_macos(partition: &PartitionInfo) -> Result<()> {
 }
 
 /// Linux partition deletion using parted
+///
+/// The disk comes from `disk` rather than the partition's device name, which
+/// doesn't end in the bare number for nvme0n1p2, mmcblk0p1 or loop0p1.
 #[cfg(target_os = "linux")]
-fn delete_linux(partition: &PartitionInfo) -> Result<()> {
-    // Extract partition number from device path (e.g., /dev/sda1 -> 1)
-    let partition_num = partition.device_path
-        .chars()
-        .rev()
-        .take_while(|c| c.is_numeric())
-        .collect::<String>()
-        .chars()
-        .rev()
-        .collect::<String>();
-
-    // Get disk device (e.g., /dev/sda1 -> /dev/sda)
-    let disk_device = partition.device_path
-        .trim_end_matches(&partition_num);
-
+fn delete_linux(partition: &PartitionInfo, disk: &DiskInfo) -> Result<()> {
     let output = Command::new("parted")
-        .arg(disk_device)
+        .arg(&disk.device_path)
         .arg("--script")
         .arg("rm")
-        .arg(&partition_num)
-        .output()?;
+        .arg(partition.number.to_string())
+        .run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -136,6 +120,10 @@ fn delete_linux(partition: &PartitionInfo) -> Result<()> {
 pub fn validate_delete(partition: &PartitionInfo) -> Result<Vec<String>> {
     let mut warnings = Vec::new();
 
+    if partition.is_system_volume {
+        warnings.push("⚠️ CRITICAL: This is the RUNNING SYSTEM volume! It can't be deleted from the running OS; use recovery or installation media.".to_string());
+    }
+
     // Check if it's a system/boot partition
     if partition.flags.contains(&PartitionFlag::Boot) {
         warnings.push("⚠️ CRITICAL: This is a BOOT partition! Deleting it will make your system UNBOOTABLE!".to_string());
@@ -145,6 +133,14 @@ pub fn validate_delete(partition: &PartitionInfo) -> Result<Vec<String>> {
         warnings.push("⚠️ CRITICAL: This is a SYSTEM/EFI partition! Deleting it will make your system UNBOOTABLE!".to_string());
     }
 
+    if partition.flags.contains(&PartitionFlag::Reserved) {
+        warnings.push("⚠️ CRITICAL: This is a RESERVED partition (Microsoft Reserved or BIOS boot). Windows or the bootloader may stop working without it!".to_string());
+    }
+
+    if partition.filesystem == FilesystemType::Swap {
+        warnings.push("⚠️ This is a SWAP partition. Disable it (swapoff) and remove it from /etc/fstab before deleting, or the system may fail to boot.".to_string());
+    }
+
     // Check if partition has data
     if let Some(used_space) = partition.used_space {
         if used_space > 0 {
@@ -168,3 +164,27 @@ pub fn validate_delete(partition: &PartitionInfo) -> Result<Vec<String>> {
 
     Ok(warnings)
 }
+
+#[cfg(all(test, feature = "dry-run-exec", target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::partition::exec::dry_run;
+    use crate::test_support::{mock_disk, mock_partition};
+
+    #[test]
+    fn test_delete_addresses_parted_by_disk_and_number() {
+        for (disk_path, device_path, number) in [
+            ("/dev/sda", "/dev/sda1", 1),
+            ("/dev/nvme0n1", "/dev/nvme0n1p3", 3),
+            ("/dev/mmcblk0", "/dev/mmcblk0p2", 2),
+        ] {
+            let partition = PartitionInfo { number, ..mock_partition(device_path.trim_start_matches("/dev/")) };
+            let disk = mock_disk(disk_path, vec![partition.clone()]);
+
+            delete_partition(&partition, &disk).unwrap();
+
+            let number = number.to_string();
+            assert_eq!(dry_run::take_log(), vec![vec!["parted", disk_path, "--script", "rm", number.as_str()]]);
+        }
+    }
+}
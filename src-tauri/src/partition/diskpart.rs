@@ -0,0 +1,312 @@
+// diskpart script execution and output analysis
+//
+// diskpart stops at the first failing command of a script and exits non-zero,
+// so success is judged from the exit status alone: its messages are localized.
+// The (English) per-command confirmation lines are only used to name the
+// step that failed when they are present.
+
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Every script written by this app starts with this, so stale ones can be found
+const SCRIPT_PREFIX: &str = "diskpart_";
+
+/// Fixed names used by older versions, swept as well
+const LEGACY_SCRIPT_NAMES: &[&str] = &["delete_partition.txt", "unmount_partition.txt", "shrink_partition.txt"];
+
+/// Scripts younger than this may belong to another running instance
+const STALE_SCRIPT_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// A diskpart script in the temp directory, removed when dropped
+///
+/// The name includes the pid and a UUID so concurrent operations never share
+/// a file, and the drop guard cleans up on every error path.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub struct ScriptFile {
+    path: PathBuf,
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+impl ScriptFile {
+    /// Write `script` to a fresh file named after `purpose` (e.g. "expand")
+    pub fn create(script: &str, purpose: &str) -> std::io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "{}{}_{}_{}.txt",
+            SCRIPT_PREFIX,
+            purpose,
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::write(&path, script)?;
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for ScriptFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Remove diskpart scripts left in the temp directory by crashed runs
+///
+/// Returns the number of files removed. Scripts from this process and recent
+/// ones (which may belong to another instance mid-operation) are kept.
+pub fn sweep_stale_scripts() -> usize {
+    sweep_stale_scripts_in(&std::env::temp_dir(), SystemTime::now())
+}
+
+fn sweep_stale_scripts_in(dir: &Path, now: SystemTime) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let own_marker = format!("_{}_", std::process::id());
+    let mut removed = 0;
+
+    for entry in entries.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_script = (name.starts_with(SCRIPT_PREFIX) && name.ends_with(".txt"))
+            || LEGACY_SCRIPT_NAMES.contains(&name.as_str());
+        if !is_script || name.contains(&own_marker) {
+            continue;
+        }
+
+        let is_stale = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .map(|age| age >= STALE_SCRIPT_AGE)
+            .unwrap_or(false);
+
+        if is_stale && std::fs::remove_file(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+
+    removed
+}
+
+/// A diskpart script step that failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskpartFailure {
+    /// The script line that failed (e.g. "extend size=1024")
+    pub step: String,
+
+    /// diskpart's explanation for the failure
+    pub message: String,
+}
+
+impl std::fmt::Display for DiskpartFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let verb = self.step.split_whitespace().next().unwrap_or("diskpart");
+        write!(f, "{} failed: {}", verb, self.message)
+    }
+}
+
+/// Whether an output line confirms that a script command completed
+fn is_confirmation(line: &str) -> bool {
+    let line = line.to_lowercase();
+    line.contains("successfully") || line.contains("is now the selected") || line.contains("is the selected")
+}
+
+/// Work out which script command (if any) failed from diskpart's output
+///
+/// Every successful command prints one confirmation line, so the number of
+/// confirmations tells us how far the script got. The text after the last
+/// confirmation is diskpart's error for the next command.
+pub fn analyze_output(script: &str, stdout: &str) -> std::result::Result<(), DiskpartFailure> {
+    let steps: Vec<&str> = script
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .collect();
+
+    let lines: Vec<&str> = stdout.lines().map(|l| l.trim()).collect();
+    let confirmations: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| is_confirmation(l))
+        .map(|(i, _)| i)
+        .collect();
+
+    if confirmations.len() >= steps.len() {
+        return Ok(());
+    }
+
+    let failed_step = steps[confirmations.len()];
+    let error_start = confirmations.last().map(|i| i + 1).unwrap_or(0);
+
+    let message = lines[error_start..]
+        .iter()
+        .filter(|l| !l.is_empty())
+        .filter(|l| !l.starts_with("Microsoft DiskPart") && !l.starts_with("Copyright") && !l.starts_with("On computer"))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Err(DiskpartFailure {
+        step: failed_step.to_string(),
+        message: if message.is_empty() {
+            "no output from diskpart".to_string()
+        } else {
+            message
+        },
+    })
+}
+
+/// Run a diskpart script and fail with the name of the step that failed
+///
+/// `purpose` names the temp script file (e.g. "expand").
+#[cfg(target_os = "windows")]
+pub fn run_script(script: &str, purpose: &str) -> Result<String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    use crate::partition::exec::RunCommand;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let script_file = ScriptFile::create(script, purpose)?;
+
+    let output = Command::new("diskpart")
+        .arg("/s")
+        .arg(script_file.path())
+        .creation_flags(CREATE_NO_WINDOW)
+        .run()?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if output.status.success() {
+        return Ok(stdout);
+    }
+
+    match analyze_output(script, &stdout) {
+        Err(failure) => Err(anyhow!("{}", failure)),
+        // Localized output has no confirmations we can count
+        Ok(()) => Err(anyhow!(
+            "diskpart failed ({}): {}",
+            exit_code_meaning(output.status.code()),
+            stdout.trim()
+        )),
+    }
+}
+
+/// What diskpart's documented exit codes mean
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn exit_code_meaning(code: Option<i32>) -> &'static str {
+    match code {
+        Some(1) => "fatal exception",
+        Some(2) => "invalid command-line arguments",
+        Some(3) => "could not open the script file",
+        Some(4) => "a disk service call failed",
+        Some(5) => "command syntax error",
+        _ => "unknown error",
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn run_script(_script: &str, _purpose: &str) -> Result<String> {
+    Err(anyhow!("diskpart is only available on Windows"))
+}
+
+/// Drive letter of a Windows mount point like "E:" or "E:\"; None for folder mounts
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn drive_letter(mount_point: &str) -> Option<char> {
+    let rest = mount_point.trim_end_matches('\\');
+    let mut chars = rest.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(letter), Some(':'), None) if letter.is_ascii_alphabetic() => Some(letter.to_ascii_uppercase()),
+        _ => None,
+    }
+}
+
+/// `select volume` argument for a mount point: the letter for drive letters,
+/// the quoted folder path (with its trailing backslash) for folder mounts
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn volume_selector(mount_point: &str) -> String {
+    match drive_letter(mount_point) {
+        Some(letter) => letter.to_string(),
+        None => format!("\"{}\\\"", mount_point.trim_end_matches('\\')),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+
+    #[test]
+    fn test_volume_selector_handles_folder_mounts() {
+        assert_eq!(drive_letter("e:"), Some('E'));
+        assert_eq!(drive_letter("E:\\"), Some('E'));
+        assert_eq!(drive_letter("D:\\Mounts\\Data"), None);
+
+        assert_eq!(volume_selector("E:\\"), "E");
+        assert_eq!(volume_selector("D:\\Mounts\\Data"), "\"D:\\Mounts\\Data\\\"");
+    }
+
+    const SCRIPT: &str = "select volume D\nextend size=1024\n";
+
+    #[test]
+    fn test_analyze_output_success() {
+        let stdout = "\nVolume 3 is the selected volume.\n\nDiskPart successfully extended the volume.\n";
+        assert!(analyze_output(SCRIPT, stdout).is_ok());
+    }
+
+    #[test]
+    fn test_analyze_output_names_failed_step() {
+        let stdout = "\nVolume 3 is the selected volume.\n\nVirtual Disk Service error:\nThere is not enough usable space for this operation.\n";
+        let failure = analyze_output(SCRIPT, stdout).unwrap_err();
+
+        assert_eq!(failure.step, "extend size=1024");
+        assert!(failure.message.contains("not enough usable space"));
+        assert!(failure.to_string().starts_with("extend failed:"));
+    }
+
+    #[test]
+    fn test_analyze_output_select_failure() {
+        let stdout = "\nThe volume you selected is not valid or does not exist.\n";
+        let failure = analyze_output(SCRIPT, stdout).unwrap_err();
+
+        assert_eq!(failure.step, "select volume D");
+    }
+
+    #[test]
+    fn test_script_files_are_unique_and_removed_on_drop() {
+        let a = ScriptFile::create("select volume D\n", "test").unwrap();
+        let b = ScriptFile::create("select volume D\n", "test").unwrap();
+        assert_ne!(a.path(), b.path());
+
+        let path = a.path().to_path_buf();
+        assert!(path.exists());
+        drop(a);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_sweep_removes_only_stale_foreign_scripts() {
+        let dir = TestDir::new("diskpart-test");
+
+        let foreign = dir.join("diskpart_expand_999999999_x.txt");
+        let own = dir.join(format!("diskpart_expand_{}_x.txt", std::process::id()));
+        let unrelated = dir.join("notes.txt");
+        for path in [&foreign, &own, &unrelated] {
+            std::fs::write(path, "").unwrap();
+        }
+
+        // Nothing is old enough yet
+        assert_eq!(sweep_stale_scripts_in(&dir, SystemTime::now()), 0);
+
+        let later = SystemTime::now() + STALE_SCRIPT_AGE + Duration::from_secs(1);
+        assert_eq!(sweep_stale_scripts_in(&dir, later), 1);
+        assert!(!foreign.exists());
+        assert!(own.exists());
+        assert!(unrelated.exists());
+    }
+}
@@ -0,0 +1,59 @@
+// Errors for refused or conflicting partition operations
+
+use thiserror::Error;
+
+/// Reasons a partition operation was refused before running
+#[derive(Debug, Error)]
+pub enum OperationError {
+    /// The target partition hosts the running app or its temp directory
+    #[error(
+        "{partition} hosts {what} ({path}). Modifying it could crash the app mid-operation. \
+         Pass allow_self_disk to proceed anyway."
+    )]
+    SelfDisk {
+        partition: String,
+        what: String,
+        path: String,
+    },
+
+    /// The partition holds the running operating system
+    #[error(
+        "{partition} is the running system volume. Trying to {operation} it from the running OS \
+         would fail or leave the system unbootable. Boot from recovery or installation media to do this, \
+         or pass force to proceed anyway."
+    )]
+    SystemVolume {
+        partition: String,
+        operation: String,
+    },
+
+    /// The partition has a drive letter or mount point that doesn't respond
+    #[error(
+        "{partition} is assigned to {mount_point} but is not accessible. \
+         The device may be failing or offline; check its connection and health before retrying."
+    )]
+    Inaccessible {
+        partition: String,
+        mount_point: String,
+    },
+
+    /// The feature opens the disk device directly and needs elevation
+    #[error("{feature} needs raw disk access. Restart the app as administrator (or root) to use it.")]
+    RequiresElevation { feature: String },
+
+    /// On battery with too little charge to finish a long operation safely
+    #[error(
+        "The computer is on battery ({}), below the {minimum}% needed to start a resize. Plug in the charger: \
+         losing power mid-resize can corrupt the partition. To proceed anyway, tick \"Resize on battery power anyway\".",
+        .percent.map(|p| format!("{}%", p)).unwrap_or_else(|| "unknown charge".to_string())
+    )]
+    LowBattery { percent: Option<u8>, minimum: u8 },
+
+    /// Another mutating operation on the same partition hasn't finished
+    #[error("{partition} is busy with another operation. Wait for it to finish before retrying.")]
+    Busy { partition: String },
+
+    /// Another operation is changing the partition table of the same disk
+    #[error("{disk} is busy with another operation that changes its partition table. Wait for it to finish before retrying.")]
+    DiskBusy { disk: String },
+}
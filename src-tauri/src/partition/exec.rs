@@ -0,0 +1,226 @@
+// Command execution for partition operations
+//
+// Every operation that modifies disks runs its external tools (diskpart,
+// parted, resize2fs, ...) through `RunCommand::run`, or `run_streaming` when
+// the tool's progress output is shown as it runs. With the `dry-run-exec`
+// feature the command line is recorded instead of spawned and reports success,
+// so the expand/shrink/delete orchestration can be tested without real disks.
+//
+// Tools run under the C locale so their messages are the English ones the
+// parsers expect; success itself is judged from the exit status.
+
+use std::io;
+use std::process::{Child, Command, Output};
+use std::sync::Mutex;
+
+/// Run a command to completion, capturing its output
+pub trait RunCommand {
+    fn run(&mut self) -> io::Result<Output>;
+
+    /// Like `run`, also passing stdout to `on_output` as it arrives
+    #[cfg_attr(target_os = "windows", allow(dead_code))]
+    fn run_streaming(&mut self, on_output: &mut dyn FnMut(&str)) -> io::Result<Output>;
+
+    /// Like `run`, keeping the child in `slot` so another thread can kill it
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    fn run_killable(&mut self, slot: &ChildSlot) -> io::Result<Output>;
+}
+
+/// The child of a `run_killable` call, while it runs
+#[derive(Debug, Default)]
+pub struct ChildSlot(Mutex<Option<Child>>);
+
+impl ChildSlot {
+    /// Kill the child if one is running; false when there was nothing to kill
+    pub fn kill(&self) -> bool {
+        let mut child = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        child.as_mut().is_some_and(|c| c.kill().is_ok())
+    }
+}
+
+/// Force untranslated output from Unix tools whose output gets parsed
+///
+/// Has no effect on Windows tools, which don't read these variables.
+pub trait CLocale {
+    fn c_locale(&mut self) -> &mut Self;
+}
+
+impl CLocale for Command {
+    fn c_locale(&mut self) -> &mut Self {
+        self.env("LC_ALL", "C").env("LANG", "C")
+    }
+}
+
+impl RunCommand for Command {
+    #[cfg(not(feature = "dry-run-exec"))]
+    fn run(&mut self) -> io::Result<Output> {
+        self.c_locale().output()
+    }
+
+    #[cfg(not(feature = "dry-run-exec"))]
+    fn run_streaming(&mut self, on_output: &mut dyn FnMut(&str)) -> io::Result<Output> {
+        use std::io::Read;
+        use std::process::Stdio;
+
+        let mut child = self.c_locale().stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+
+        // Drain stderr on its own thread so a full pipe can't stall the tool
+        let stderr_pipe = child.stderr.take();
+        let stderr_reader = std::thread::spawn(move || {
+            let mut stderr = Vec::new();
+            if let Some(mut pipe) = stderr_pipe {
+                let _ = pipe.read_to_end(&mut stderr);
+            }
+            stderr
+        });
+
+        // Progress bars are redrawn with \r rather than newlines, so pass on
+        // whatever has arrived instead of waiting for whole lines
+        let mut stdout = Vec::new();
+        if let Some(mut pipe) = child.stdout.take() {
+            let mut chunk = [0u8; 4096];
+            loop {
+                let read = match pipe.read(&mut chunk) {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        // Don't leave a zombie or a blocked stderr thread behind
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        let _ = stderr_reader.join();
+                        return Err(e);
+                    }
+                };
+                stdout.extend_from_slice(&chunk[..read]);
+                on_output(&String::from_utf8_lossy(&chunk[..read]));
+            }
+        }
+
+        let status = child.wait()?;
+        let stderr = stderr_reader.join().unwrap_or_default();
+        Ok(Output { status, stdout, stderr })
+    }
+
+    #[cfg(not(feature = "dry-run-exec"))]
+    fn run_killable(&mut self, slot: &ChildSlot) -> io::Result<Output> {
+        use std::io::Read;
+        use std::process::Stdio;
+        use std::time::Duration;
+
+        let mut child = self.c_locale().stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        let drain = |pipe: Option<Box<dyn Read + Send>>| {
+            std::thread::spawn(move || {
+                let mut buf = Vec::new();
+                if let Some(mut pipe) = pipe {
+                    let _ = pipe.read_to_end(&mut buf);
+                }
+                buf
+            })
+        };
+        let stdout_reader = drain(child.stdout.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+        let stderr_reader = drain(child.stderr.take().map(|p| Box::new(p) as Box<dyn Read + Send>));
+
+        // Poll instead of blocking in wait(), which would hold the lock kill() needs
+        *slot.0.lock().unwrap_or_else(|e| e.into_inner()) = Some(child);
+        let status = loop {
+            let mut guard = slot.0.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some(status) = guard.as_mut().map(Child::try_wait).transpose()?.flatten() {
+                guard.take();
+                break status;
+            }
+            drop(guard);
+            std::thread::sleep(Duration::from_millis(50));
+        };
+
+        Ok(Output {
+            status,
+            stdout: stdout_reader.join().unwrap_or_default(),
+            stderr: stderr_reader.join().unwrap_or_default(),
+        })
+    }
+
+    #[cfg(feature = "dry-run-exec")]
+    fn run(&mut self) -> io::Result<Output> {
+        dry_run::record(self)
+    }
+
+    #[cfg(feature = "dry-run-exec")]
+    fn run_streaming(&mut self, on_output: &mut dyn FnMut(&str)) -> io::Result<Output> {
+        let output = dry_run::record(self)?;
+        on_output(&String::from_utf8_lossy(&output.stdout));
+        Ok(output)
+    }
+
+    #[cfg(feature = "dry-run-exec")]
+    fn run_killable(&mut self, _slot: &ChildSlot) -> io::Result<Output> {
+        dry_run::record(self)
+    }
+}
+
+#[cfg(feature = "dry-run-exec")]
+pub mod dry_run {
+    use std::cell::RefCell;
+    use std::io;
+    use std::process::{Command, ExitStatus, Output};
+
+    thread_local! {
+        // Per thread so parallel tests don't see each other's commands
+        static LOG: RefCell<Vec<Vec<String>>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Record the command's argv and return a canned success
+    pub fn record(cmd: &Command) -> io::Result<Output> {
+        let argv: Vec<String> = std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|a| a.to_string_lossy().to_string())
+            .collect();
+
+        let stdout = canned_stdout(&argv);
+        LOG.with(|log| log.borrow_mut().push(argv));
+
+        Ok(Output {
+            status: success(),
+            stdout,
+            stderr: Vec::new(),
+        })
+    }
+
+    /// Commands recorded on this thread since the last call
+    pub fn take_log() -> Vec<Vec<String>> {
+        LOG.with(|log| std::mem::take(&mut *log.borrow_mut()))
+    }
+
+    /// diskpart's output names the failed step, so confirm every script line
+    fn canned_stdout(argv: &[String]) -> Vec<u8> {
+        if argv[0] != "diskpart" {
+            return Vec::new();
+        }
+
+        let script = argv
+            .iter()
+            .position(|a| a == "/s")
+            .and_then(|i| argv.get(i + 1))
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .unwrap_or_default();
+
+        script
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|_| "DiskPart successfully completed the operation.\n")
+            .collect::<String>()
+            .into_bytes()
+    }
+
+    #[cfg(unix)]
+    fn success() -> ExitStatus {
+        use std::os::unix::process::ExitStatusExt;
+        ExitStatus::from_raw(0)
+    }
+
+    #[cfg(windows)]
+    fn success() -> ExitStatus {
+        use std::os::windows::process::ExitStatusExt;
+        ExitStatus::from_raw(0)
+    }
+}
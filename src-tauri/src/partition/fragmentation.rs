@@ -0,0 +1,198 @@
+// Filesystem fragmentation analysis
+//
+// Runs the platform's analysis-only defragmenter (`defrag /A` on Windows,
+// `e4defrag -c` on Linux ext4) and turns its output into a report. Heavy
+// fragmentation near the end of an NTFS volume can block shrinking, so the
+// shrink workflow uses this to explain failures up front.
+
+#[cfg(target_os = "linux")]
+use crate::partition::exec::CLocale;
+use crate::partition::media::media_type_for_device;
+use crate::partition::types::*;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+use std::process::Command;
+
+/// e4defrag's score scale: 0-30 no problem, 31-55 a little fragmented, 56+ needs defrag
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const E4DEFRAG_NEEDS_DEFRAG_SCORE: u8 = 56;
+
+/// Above this, recommend defragmenting when `defrag` doesn't say either way
+const NTFS_NEEDS_DEFRAG_PERCENT: u8 = 10;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FragmentationReport {
+    pub partition_id: String,
+
+    /// Fragmented share of the volume (Windows) or e4defrag's 0-100 score (ext4)
+    pub fragmentation_percent: Option<u8>,
+
+    pub needs_defrag: bool,
+
+    /// None when the media type couldn't be determined
+    pub is_ssd: Option<bool>,
+
+    pub recommendation: String,
+}
+
+/// Analyze fragmentation without changing anything on disk
+pub fn analyze_fragmentation(disk: &DiskInfo, partition: &PartitionInfo) -> FragmentationReport {
+    let mut report = FragmentationReport {
+        partition_id: partition.id.clone(),
+        fragmentation_percent: None,
+        needs_defrag: false,
+        is_ssd: media_type_for_device(&disk.device_path).is_ssd(),
+        recommendation: String::new(),
+    };
+
+    if report.is_ssd == Some(true) {
+        report.recommendation =
+            "Defragmentation is not recommended for SSDs; fragmentation doesn't affect their performance.".to_string();
+        return report;
+    }
+
+    match analyze_platform(partition) {
+        Ok((percent, needs_defrag)) => {
+            report.fragmentation_percent = Some(percent);
+            report.needs_defrag = needs_defrag;
+            report.recommendation = if needs_defrag {
+                "This volume is fragmented. Defragmenting it first makes shrinking more likely to succeed.".to_string()
+            } else {
+                "Fragmentation is low; no defragmentation needed.".to_string()
+            };
+        }
+        Err(reason) => report.recommendation = reason,
+    }
+
+    report
+}
+
+/// Returns (percent, needs_defrag) or a reason the analysis couldn't run
+#[cfg(target_os = "windows")]
+fn analyze_platform(partition: &PartitionInfo) -> Result<(u8, bool), String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    if partition.filesystem != FilesystemType::NTFS {
+        return Err(format!(
+            "Fragmentation analysis isn't available for {} volumes.",
+            partition.filesystem.display_name()
+        ));
+    }
+
+    let drive = partition
+        .mount_point
+        .as_deref()
+        .and_then(crate::partition::diskpart::drive_letter)
+        .ok_or("Assign a drive letter to analyze fragmentation.")?;
+
+    let output = Command::new("defrag")
+        .arg(format!("{}:", drive))
+        .arg("/A")
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map_err(|e| format!("Failed to run defrag: {}", e))?;
+
+    parse_defrag_analysis(&String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| "defrag didn't report a fragmentation figure (it needs administrator rights).".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn analyze_platform(partition: &PartitionInfo) -> Result<(u8, bool), String> {
+    if partition.filesystem != FilesystemType::Ext4 {
+        return Err(format!(
+            "Fragmentation analysis isn't available for {} volumes.",
+            partition.filesystem.display_name()
+        ));
+    }
+
+    // e4defrag -c works on a mounted filesystem or its device
+    let target = partition.mount_point.as_deref().unwrap_or(&partition.device_path);
+    let output = Command::new("e4defrag")
+        .c_locale()
+        .arg("-c")
+        .arg(target)
+        .output()
+        .map_err(|e| format!("Failed to run e4defrag: {}", e))?;
+
+    parse_e4defrag_score(&String::from_utf8_lossy(&output.stdout))
+        .map(|score| (score, score >= E4DEFRAG_NEEDS_DEFRAG_SCORE))
+        .ok_or_else(|| "e4defrag didn't report a fragmentation score.".to_string())
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+fn analyze_platform(_partition: &PartitionInfo) -> Result<(u8, bool), String> {
+    Err("Fragmentation analysis isn't available on this platform.".to_string())
+}
+
+/// Parse `defrag /A` output: "Total fragmented space = 12%" plus its advice line
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_defrag_analysis(output: &str) -> Option<(u8, bool)> {
+    let percent = output.lines().find_map(|line| {
+        let line = line.trim();
+        if !line.to_lowercase().starts_with("total fragmented space") {
+            return None;
+        }
+        let value = line.split('=').nth(1)?.trim().trim_end_matches('%').trim();
+        value.parse::<u8>().ok()
+    })?;
+
+    let lower = output.to_lowercase();
+    let needs_defrag = if lower.contains("you do not need to defragment") {
+        false
+    } else if lower.contains("recommended that you defragment") {
+        true
+    } else {
+        percent > NTFS_NEEDS_DEFRAG_PERCENT
+    };
+
+    Some((percent, needs_defrag))
+}
+
+/// Parse the "Fragmentation score" line from `e4defrag -c`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn parse_e4defrag_score(output: &str) -> Option<u8> {
+    output.lines().find_map(|line| {
+        let rest = line.trim().strip_prefix("Fragmentation score")?;
+        rest.split_whitespace().next()?.parse::<u8>().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defrag_analysis() {
+        let output = "\
+Post Defragmentation Report:
+
+    Volume Information:
+        Volume size                 = 475.80 GB
+        Free space                  = 120.15 GB
+        Total fragmented space      = 17%
+        Largest free space size     = 60.02 GB
+
+    It is recommended that you defragment this volume.
+";
+        assert_eq!(parse_defrag_analysis(output), Some((17, true)));
+
+        let output = "Total fragmented space = 2%\nYou do not need to defragment this volume.\n";
+        assert_eq!(parse_defrag_analysis(output), Some((2, false)));
+
+        assert_eq!(parse_defrag_analysis("Access denied"), None);
+    }
+
+    #[test]
+    fn test_parse_e4defrag_score() {
+        let output = "\
+<Fragmented files>                             now/best       size/ext
+ Total/best extents                             41830/40771
+ Average size per extent                        218 KB
+ Fragmentation score                            1
+ [0-30 no problem: 31-55 a little bit fragmented: 56- needs defrag]
+ This device (/dev/sda1) does not need defragmentation.
+";
+        assert_eq!(parse_e4defrag_score(output), Some(1));
+        assert_eq!(parse_e4defrag_score("e4defrag: permission denied"), None);
+    }
+}
@@ -0,0 +1,91 @@
+// Used-space probing for unmounted filesystems
+//
+// `df` only knows about mounted filesystems. For unmounted ones the
+// filesystem's own tools can report usage from the superblock/bitmap without
+// mounting: `dumpe2fs -h` for ext2/3/4 and `ntfsresize --info` for NTFS. Both
+// are read-only. Anything else (or a probe failure, e.g. without root) leaves
+// the used space unknown.
+
+use super::exec::CLocale;
+use super::types::FilesystemType;
+use std::process::Command;
+
+/// Used space of an unmounted filesystem, if its tools can report it
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn probe_used_space(device: &str, filesystem: &FilesystemType) -> Option<u64> {
+    match filesystem {
+        FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4 => {
+            let output = Command::new("dumpe2fs").c_locale().arg("-h").arg(device).output().ok()?;
+            if !output.status.success() {
+                log::debug!("dumpe2fs failed for {}: {}", device, String::from_utf8_lossy(&output.stderr).trim());
+                return None;
+            }
+            parse_dumpe2fs_used(&String::from_utf8_lossy(&output.stdout))
+        }
+        FilesystemType::NTFS => {
+            let output = Command::new("ntfsresize")
+                .c_locale()
+                .args(["--info", "--force", "--no-action"])
+                .arg(device)
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                log::debug!("ntfsresize --info failed for {}: {}", device, String::from_utf8_lossy(&output.stderr).trim());
+                return None;
+            }
+            parse_ntfsresize_used(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => None,
+    }
+}
+
+/// Used bytes from `dumpe2fs -h` superblock fields
+fn parse_dumpe2fs_used(output: &str) -> Option<u64> {
+    let field = |name: &str| -> Option<u64> {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name))
+            .and_then(|rest| rest.trim_start_matches(':').trim().parse().ok())
+    };
+
+    let blocks = field("Block count")?;
+    let free = field("Free blocks")?;
+    let block_size = field("Block size")?;
+    Some(blocks.checked_sub(free)? * block_size)
+}
+
+/// Used bytes from the "Space in use" line of `ntfsresize --info`
+///
+/// ntfsresize reports decimal megabytes, e.g. "Space in use       : 5181 MB (12.1%)".
+fn parse_ntfsresize_used(output: &str) -> Option<u64> {
+    let rest = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Space in use"))?;
+    let mb: u64 = rest.trim_start_matches([' ', ':']).split_whitespace().next()?.parse().ok()?;
+    Some(mb * 1_000_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dumpe2fs_used() {
+        let output = "Filesystem volume name:   data\n\
+                      Block count:              1000\n\
+                      Reserved block count:     50\n\
+                      Free blocks:              400\n\
+                      Block size:               4096\n";
+        assert_eq!(parse_dumpe2fs_used(output), Some(600 * 4096));
+        assert_eq!(parse_dumpe2fs_used("Block count: 10\n"), None);
+    }
+
+    #[test]
+    fn test_parse_ntfsresize_used() {
+        let output = "ntfsresize v2022.10.3\n\
+                      Current volume size: 42947571200 bytes (42948 MB)\n\
+                      Space in use       : 5181 MB (12.1%)\n";
+        assert_eq!(parse_ntfsresize_used(output), Some(5_181_000_000));
+        assert_eq!(parse_ntfsresize_used("nothing useful"), None);
+    }
+}
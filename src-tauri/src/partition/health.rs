@@ -0,0 +1,305 @@
+// Per-disk health overview
+//
+// Combines the disk layout, SMART data and mounted-volume usage into one
+// summary per disk, with the warnings that apply and an overall status that
+// is the worst of them. The frontend shows this as-is instead of correlating
+// several calls itself.
+
+use super::info::{get_all_disks, get_mounted_volumes};
+use super::types::*;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use ts_rs::TS;
+
+/// Free space below this share of a volume is a warning
+const LOW_SPACE_PERCENT: u64 = 10;
+
+/// Free space below this share of a volume is critical
+const CRITICAL_SPACE_PERCENT: u64 = 3;
+
+/// Drive temperatures (Celsius) at or above these levels are warned about
+const WARM_TEMPERATURE: f32 = 60.0;
+const HOT_TEMPERATURE: f32 = 70.0;
+
+/// SSD wear (percent of rated endurance used) at or above this is a warning
+const WEAR_WARNING_PERCENT: u8 = 90;
+
+/// One finding about a disk
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct HealthWarning {
+    pub severity: HealthStatus,
+    pub message: String,
+}
+
+/// Everything the health overview shows for one disk
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct DiskHealthSummary {
+    pub disk_id: String,
+    pub device_path: String,
+    pub model: String,
+    #[ts(type = "number")]
+    pub total_size: u64,
+    pub partition_count: usize,
+    /// Used and free space across the disk's mounted volumes
+    #[ts(type = "number")]
+    pub used_space: u64,
+    #[ts(type = "number")]
+    pub free_space: u64,
+    pub smart: Option<SmartStatus>,
+    pub temperature: Option<f32>,
+    pub warnings: Vec<HealthWarning>,
+    /// Worst of the SMART verdict and the warnings; Unknown when SMART couldn't be read and nothing is wrong
+    pub status: HealthStatus,
+}
+
+/// Rank for picking the worst status; Unknown sits between Good and Warning
+fn severity(status: HealthStatus) -> u8 {
+    match status {
+        HealthStatus::Good => 0,
+        HealthStatus::Unknown => 1,
+        HealthStatus::Warning => 2,
+        HealthStatus::Critical => 3,
+    }
+}
+
+fn worst(a: HealthStatus, b: HealthStatus) -> HealthStatus {
+    if severity(b) > severity(a) { b } else { a }
+}
+
+/// Mount point without its trailing separator, so "C:\" and "C:" compare equal
+fn normalize_mount(mount_point: &str) -> &str {
+    let trimmed = mount_point.trim_end_matches(['/', '\\']);
+    if trimmed.is_empty() { mount_point } else { trimmed }
+}
+
+/// Build the summary for `disk`
+///
+/// `locked` holds device paths and mount points of encrypted volumes that
+/// haven't been unlocked.
+pub fn summarize_disk(
+    disk: &DiskInfo,
+    volumes: &[VolumeUsage],
+    smart: Option<SmartStatus>,
+    locked: &HashSet<String>,
+) -> DiskHealthSummary {
+    let mut warnings = Vec::new();
+    let mut used_space = 0;
+    let mut free_space = 0;
+
+    for partition in &disk.partitions {
+        let name = partition.display_name();
+
+        if locked.contains(&partition.device_path)
+            || partition.mount_point.as_deref().is_some_and(|mp| locked.contains(normalize_mount(mp)))
+        {
+            warnings.push(HealthWarning {
+                severity: HealthStatus::Warning,
+                message: format!("{} is encrypted and locked; its contents and free space can't be read until it is unlocked", name),
+            });
+            continue;
+        }
+
+        let Some(mount_point) = partition.mount_point.as_deref() else { continue };
+        let Some(volume) = volumes
+            .iter()
+            .find(|v| normalize_mount(&v.mount_point) == normalize_mount(mount_point))
+        else {
+            continue;
+        };
+        used_space += volume.used_space;
+        free_space += volume.free_space;
+
+        if volume.total_space == 0 {
+            continue;
+        }
+        let free_percent = volume.free_space.saturating_mul(100) / volume.total_space;
+        let severity = if free_percent < CRITICAL_SPACE_PERCENT {
+            HealthStatus::Critical
+        } else if free_percent < LOW_SPACE_PERCENT {
+            HealthStatus::Warning
+        } else {
+            continue;
+        };
+        warnings.push(HealthWarning {
+            severity,
+            message: format!("{} is low on space ({}% free)", name, free_percent),
+        });
+    }
+
+    let mut status = HealthStatus::Good;
+    match &smart {
+        Some(smart) => {
+            if smart.health == HealthStatus::Critical {
+                warnings.push(HealthWarning {
+                    severity: HealthStatus::Critical,
+                    message: "SMART reports the drive is failing; back up its data now".to_string(),
+                });
+            }
+            status = worst(status, smart.health);
+
+            if let Some(temperature) = smart.temperature.filter(|t| *t >= WARM_TEMPERATURE) {
+                warnings.push(HealthWarning {
+                    severity: if temperature >= HOT_TEMPERATURE { HealthStatus::Critical } else { HealthStatus::Warning },
+                    message: format!("Drive temperature is {:.0} °C", temperature),
+                });
+            }
+            if let Some(used) = smart.percent_used.filter(|p| *p >= WEAR_WARNING_PERCENT) {
+                warnings.push(HealthWarning {
+                    severity: if used >= 100 { HealthStatus::Critical } else { HealthStatus::Warning },
+                    message: format!("{}% of the drive's rated write endurance is used", used),
+                });
+            }
+        }
+        None => status = HealthStatus::Unknown,
+    }
+    status = warnings.iter().fold(status, |acc, w| worst(acc, w.severity));
+
+    DiskHealthSummary {
+        disk_id: disk.id.clone(),
+        device_path: disk.device_path.clone(),
+        model: disk.model.clone(),
+        total_size: disk.total_size,
+        partition_count: disk.partitions.len(),
+        used_space,
+        free_space,
+        temperature: smart.as_ref().and_then(|s| s.temperature),
+        smart,
+        warnings,
+        status,
+    }
+}
+
+/// Device paths of LUKS containers with nothing opened on top of them
+#[cfg(target_os = "linux")]
+fn locked_encrypted_volumes() -> HashSet<String> {
+    use super::exec::CLocale;
+    use std::process::Command;
+
+    let Ok(output) = Command::new("lsblk").c_locale().args(["-J", "-o", "PATH,FSTYPE"]).output() else {
+        return HashSet::new();
+    };
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&output.stdout) else {
+        return HashSet::new();
+    };
+
+    fn collect(devices: &serde_json::Value, locked: &mut HashSet<String>) {
+        for device in devices.as_array().into_iter().flatten() {
+            let opened = device["children"].as_array().is_some_and(|c| !c.is_empty());
+            if device["fstype"].as_str() == Some("crypto_LUKS") && !opened {
+                if let Some(path) = device["path"].as_str() {
+                    locked.insert(path.to_string());
+                }
+            }
+            collect(&device["children"], locked);
+        }
+    }
+
+    let mut locked = HashSet::new();
+    collect(&json["blockdevices"], &mut locked);
+    locked
+}
+
+/// Mount points ("D:") of BitLocker volumes that are locked
+///
+/// Get-BitLockerVolume needs an elevated process; without one nothing is reported.
+#[cfg(target_os = "windows")]
+fn locked_encrypted_volumes() -> HashSet<String> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let Ok(output) = Command::new("powershell")
+        .args([
+            "-NoProfile",
+            "-NonInteractive",
+            "-Command",
+            "Get-BitLockerVolume | Where-Object LockStatus -eq 'Locked' | ForEach-Object { $_.MountPoint }",
+        ])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return HashSet::new();
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| normalize_mount(line.trim()).to_string())
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+fn locked_encrypted_volumes() -> HashSet<String> {
+    HashSet::new()
+}
+
+/// Health summary for every disk
+pub fn get_disk_health_summary() -> Result<Vec<DiskHealthSummary>> {
+    let disks = get_all_disks()?;
+    let volumes = get_mounted_volumes();
+    let locked = locked_encrypted_volumes();
+
+    Ok(disks
+        .iter()
+        .map(|disk| summarize_disk(disk, &volumes, disk.status.smart_status.clone(), &locked))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mounts::MountKind;
+    use crate::test_support::{mock_disk, mock_partition, GB};
+
+    fn partition(name: &str, mount_point: Option<&str>) -> PartitionInfo {
+        PartitionInfo {
+            total_size: 100 * GB,
+            mount_point: mount_point.map(str::to_string),
+            is_mounted: mount_point.is_some(),
+            ..mock_partition(name)
+        }
+    }
+
+    #[test]
+    fn test_status_is_worst_of_inputs() {
+        let disk = DiskInfo {
+            total_size: 300 * GB,
+            ..mock_disk("/dev/sda", vec![partition("sda1", Some("/")), partition("sda2", None), partition("sda3", Some("/data/"))])
+        };
+        let volume = |mount_point: &str, free_space: u64| VolumeUsage {
+            name: String::new(),
+            mount_point: mount_point.to_string(),
+            filesystem: "ext4".to_string(),
+            total_space: 100 * GB,
+            used_space: 100 * GB - free_space,
+            free_space,
+            is_removable: false,
+            mount_kind: MountKind::default(),
+        };
+        let volumes = vec![volume("/", 50 * GB), volume("/data", 5 * GB)];
+        let locked: HashSet<String> = ["/dev/sda2".to_string()].into();
+        let smart = SmartStatus {
+            health: HealthStatus::Good,
+            temperature: Some(41.0),
+            power_on_hours: None,
+            percent_used: Some(3),
+            data_written_bytes: None,
+        };
+
+        let summary = summarize_disk(&disk, &volumes, Some(smart), &locked);
+        assert_eq!(summary.partition_count, 3);
+        assert_eq!(summary.free_space, 55 * GB);
+        assert_eq!(summary.temperature, Some(41.0));
+        assert_eq!(summary.warnings.len(), 2); // Locked sda2, low space on /data
+        assert_eq!(summary.status, HealthStatus::Warning);
+
+        // No SMART data and nothing wrong: unknown rather than good
+        let summary = summarize_disk(&disk, &[volume("/", 50 * GB)], None, &HashSet::new());
+        assert!(summary.warnings.is_empty());
+        assert_eq!(summary.status, HealthStatus::Unknown);
+    }
+}
@@ -1,43 +1,24 @@
 // Partition information reading functionality
 
+use super::provider::{DiskProvider, PlatformDiskProvider};
 use super::types::*;
+use crate::mounts::mount_table;
 use anyhow::{anyhow, Result};
-
-#[cfg(target_os = "windows")]
-use super::platform::windows;
-
-#[cfg(target_os = "linux")]
-use super::platform::linux;
-
-#[cfg(target_os = "macos")]
-use super::platform::macos;
+use sysinfo::Disks;
 
 /// Get all disks available on the system
 pub fn get_all_disks() -> Result<Vec<DiskInfo>> {
-    #[cfg(target_os = "windows")]
-    {
-        windows::get_disks()
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        linux::get_disks()
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        macos::get_disks()
-    }
-
-    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
-    {
-        Err(anyhow!("Unsupported operating system"))
-    }
+    PlatformDiskProvider.get_disks()
 }
 
 /// Get a specific disk by its device path
 pub fn get_disk_by_path(path: &str) -> Result<DiskInfo> {
-    let disks = get_all_disks()?;
+    get_disk_by_path_from(&PlatformDiskProvider, path)
+}
+
+/// Get a specific disk by its device path from `provider`
+pub fn get_disk_by_path_from(provider: &dyn DiskProvider, path: &str) -> Result<DiskInfo> {
+    let disks = provider.get_disks()?;
 
     disks
         .into_iter()
@@ -53,7 +34,12 @@ pub fn get_partitions(disk_path: &str) -> Result<Vec<PartitionInfo>> {
 
 /// Get detailed information about a specific partition
 pub fn get_partition_info(partition_id: &str) -> Result<PartitionInfo> {
-    let disks = get_all_disks()?;
+    get_partition_info_from(&PlatformDiskProvider, partition_id)
+}
+
+/// Get detailed information about a specific partition from `provider`
+pub fn get_partition_info_from(provider: &dyn DiskProvider, partition_id: &str) -> Result<PartitionInfo> {
+    let disks = provider.get_disks()?;
 
     for disk in disks {
         if let Some(partition) = disk.partitions.into_iter().find(|p| p.id == partition_id) {
@@ -63,3 +49,64 @@ pub fn get_partition_info(partition_id: &str) -> Result<PartitionInfo> {
 
     Err(anyhow!("Partition not found: {}", partition_id))
 }
+
+/// Get usage for every mounted filesystem
+///
+/// This uses `sysinfo` only, so it works even when full disk/partition
+/// enumeration fails (unknown partition table, missing permissions).
+pub fn get_mounted_volumes() -> Vec<VolumeUsage> {
+    let disks = Disks::new_with_refreshed_list();
+    let table = mount_table();
+
+    disks
+        .iter()
+        .map(|disk| {
+            let total = disk.total_space();
+            let free = disk.available_space();
+            let mount_kind = table
+                .iter()
+                .rev()
+                .find(|m| m.mount_point == disk.mount_point())
+                .map(|m| m.kind)
+                .unwrap_or_default();
+
+            VolumeUsage {
+                name: disk.name().to_string_lossy().to_string(),
+                mount_point: disk.mount_point().to_string_lossy().to_string(),
+                filesystem: disk.file_system().to_string_lossy().to_string(),
+                total_space: total,
+                used_space: total.saturating_sub(free),
+                free_space: free,
+                is_removable: disk.is_removable(),
+                mount_kind,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::partition::provider::MockDiskProvider;
+    use crate::test_support::{mock_disk, mock_partition, GB};
+
+    fn provider() -> MockDiskProvider {
+        let partition = PartitionInfo {
+            label: Some("Data".to_string()),
+            total_size: 50 * GB,
+            used_space: Some(10 * GB),
+            ..mock_partition("sda1")
+        };
+        MockDiskProvider::new(vec![mock_disk("/dev/sda", vec![partition])])
+    }
+
+    #[test]
+    fn test_get_partition_info_from_mock() {
+        let provider = provider();
+
+        let partition = get_partition_info_from(&provider, "sda1").unwrap();
+        assert_eq!(partition.device_path, "/dev/sda1");
+        assert!(get_partition_info_from(&provider, "sdb1").is_err());
+        assert_eq!(get_disk_by_path_from(&provider, "/dev/sda").unwrap().partitions.len(), 1);
+    }
+}
@@ -0,0 +1,234 @@
+// Read-only filesystem inspection
+//
+// Reads filesystem metadata the enumeration doesn't cover (cluster/block
+// size, inode counts, creation date) using read-only tools:
+// `fsutil fsinfo ntfsinfo` (Windows), `dumpe2fs -h` / `ntfsinfo -m` (Linux)
+// and `diskutil info` (macOS). None of these modify the filesystem.
+//
+// ntfsinfo is never run with `-f`: it refuses volumes that are hibernated or
+// marked dirty, and forcing it would report metadata from an inconsistent
+// volume. The details then say why they're missing instead.
+
+#[cfg(not(target_os = "windows"))]
+use crate::partition::exec::CLocale;
+use crate::partition::types::*;
+use std::collections::HashMap;
+use std::process::Command;
+
+/// Filesystem metadata for the details panel
+///
+/// Fields are `None` when the filesystem or platform doesn't report them;
+/// `notes` explains what couldn't be read.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct PartitionDetails {
+    pub partition_id: String,
+
+    /// Allocation unit size in bytes (NTFS/FAT clusters, APFS/HFS+ allocation blocks)
+    pub cluster_size: Option<u64>,
+
+    /// ext block size in bytes
+    pub block_size: Option<u64>,
+
+    pub total_inodes: Option<u64>,
+    pub free_inodes: Option<u64>,
+
+    /// Creation date as reported by the tool
+    pub created: Option<String>,
+
+    /// Volume serial number or UUID
+    pub volume_id: Option<String>,
+
+    /// The tool the details were read with
+    pub source: Option<String>,
+
+    pub notes: Vec<String>,
+}
+
+/// Read filesystem metadata for a partition without modifying it
+pub fn inspect_partition(partition: &PartitionInfo) -> PartitionDetails {
+    let mut details = PartitionDetails {
+        partition_id: partition.id.clone(),
+        ..PartitionDetails::default()
+    };
+
+    match inspect_platform(partition) {
+        Some((tool, output)) => {
+            details.source = Some(tool.to_string());
+            apply_fields(&mut details, &parse_key_values(&output));
+        }
+        None if cfg!(target_os = "linux") && partition.filesystem == FilesystemType::NTFS => details.notes.push(
+            "ntfsinfo is not installed or refused the volume. It refuses NTFS volumes that are \
+             hibernated or marked dirty; boot Windows and shut it down fully (or run chkdsk) to read them."
+                .to_string(),
+        ),
+        None => details.notes.push(format!(
+            "No read-only inspection tool is available for {} on this system.",
+            partition.filesystem.display_name()
+        )),
+    }
+
+    if details.source.is_some() && details.cluster_size.is_none() && details.block_size.is_none() {
+        details.notes.push("The inspection tool didn't report an allocation size.".to_string());
+    }
+
+    details
+}
+
+/// Run the platform's inspection tool, returning its name and stdout
+#[cfg(target_os = "windows")]
+fn inspect_platform(partition: &PartitionInfo) -> Option<(&'static str, String)> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    // fsutil addresses volumes by drive letter
+    if partition.filesystem != FilesystemType::NTFS {
+        return None;
+    }
+    let drive = crate::partition::diskpart::drive_letter(partition.mount_point.as_ref()?)?;
+
+    let output = Command::new("fsutil")
+        .args(["fsinfo", "ntfsinfo", &format!("{}:", drive)])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| ("fsutil", String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[cfg(target_os = "linux")]
+fn inspect_platform(partition: &PartitionInfo) -> Option<(&'static str, String)> {
+    let (tool, args): (&'static str, &[&str]) = match partition.filesystem {
+        FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4 => ("dumpe2fs", &["-h"]),
+        FilesystemType::NTFS => ("ntfsinfo", &["-m"]),
+        _ => return None,
+    };
+
+    let output = Command::new(tool)
+        .c_locale()
+        .args(args)
+        .arg(&partition.device_path)
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| (tool, String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[cfg(target_os = "macos")]
+fn inspect_platform(partition: &PartitionInfo) -> Option<(&'static str, String)> {
+    let output = Command::new("diskutil")
+        .c_locale()
+        .arg("info")
+        .arg(&partition.device_path)
+        .output()
+        .ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| ("diskutil", String::from_utf8_lossy(&output.stdout).to_string()))
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn inspect_platform(_partition: &PartitionInfo) -> Option<(&'static str, String)> {
+    None
+}
+
+/// Parse "Key : Value" / "Key: Value" lines into a map with lowercase keys
+pub fn parse_key_values(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+            (!key.is_empty() && !value.is_empty()).then(|| (key, value.to_string()))
+        })
+        .collect()
+}
+
+/// Fill in details from the key/value output of any of the inspection tools
+fn apply_fields(details: &mut PartitionDetails, fields: &HashMap<String, String>) {
+    let first = |keys: &[&str]| keys.iter().find_map(|k| fields.get(*k)).cloned();
+    let number = |keys: &[&str]| first(keys).and_then(|v| parse_leading_number(&v));
+
+    details.cluster_size = number(&[
+        "bytes per cluster",     // fsutil
+        "cluster size",          // ntfsinfo
+        "allocation block size", // diskutil
+    ]);
+    details.block_size = number(&["block size"]); // dumpe2fs
+    details.total_inodes = number(&["inode count"]);
+    details.free_inodes = number(&["free inodes"]);
+    details.created = first(&["filesystem created"]);
+    details.volume_id = first(&[
+        "ntfs volume serial number",
+        "volume serial number",
+        "filesystem uuid",
+        "volume uuid",
+    ]);
+}
+
+/// Parse the number at the start of a value like "4096  (4 KB)" or "512 Bytes (exactly ...)"
+fn parse_leading_number(value: &str) -> Option<u64> {
+    let digits: String = value
+        .trim()
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == ',')
+        .filter(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details_from(output: &str) -> PartitionDetails {
+        let mut details = PartitionDetails::default();
+        apply_fields(&mut details, &parse_key_values(output));
+        details
+    }
+
+    #[test]
+    fn test_parse_dumpe2fs_header() {
+        let output = "\
+Filesystem volume name:   <none>
+Filesystem UUID:          0b6a3f5e-8f4c-4a8e-9d1a-2f7c1b2e3d4f
+Inode count:              6553600
+Block count:              26214400
+Free inodes:              6400123
+Block size:               4096
+Filesystem created:       Mon Jan  8 10:15:42 2024
+";
+        let details = details_from(output);
+
+        assert_eq!(details.block_size, Some(4096));
+        assert_eq!(details.total_inodes, Some(6553600));
+        assert_eq!(details.free_inodes, Some(6400123));
+        assert_eq!(details.created.as_deref(), Some("Mon Jan  8 10:15:42 2024"));
+        assert_eq!(details.volume_id.as_deref(), Some("0b6a3f5e-8f4c-4a8e-9d1a-2f7c1b2e3d4f"));
+        assert_eq!(details.cluster_size, None);
+    }
+
+    #[test]
+    fn test_parse_fsutil_ntfsinfo() {
+        let output = "\
+NTFS Volume Serial Number :        0x6c2a1b3e4f5d6a7b
+NTFS Version      :                3.1
+Number Sectors :                   0x000000003b9e7fff
+Bytes Per Sector  :                512
+Bytes Per Cluster :                4096  (4 KB)
+";
+        let details = details_from(output);
+
+        assert_eq!(details.cluster_size, Some(4096));
+        assert_eq!(details.volume_id.as_deref(), Some("0x6c2a1b3e4f5d6a7b"));
+        assert_eq!(details.total_inodes, None);
+    }
+}
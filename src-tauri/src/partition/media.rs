@@ -0,0 +1,242 @@
+// Storage media type (SSD vs HDD) detection
+//
+// Defrag recommendations, secure-wipe methods and scan heuristics all depend
+// on whether the backing disk is solid-state. A file path is mapped to its
+// device and asked the OS: seek penalty via IOCTL_STORAGE_QUERY_PROPERTY on
+// Windows, `queue/rotational` in sysfs on Linux, `diskutil info` on macOS.
+// Answers are cached per device for the session; the media doesn't change.
+
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaType {
+    Ssd,
+    Hdd,
+    Unknown,
+}
+
+impl MediaType {
+    /// None when the media type couldn't be determined
+    pub fn is_ssd(self) -> Option<bool> {
+        match self {
+            MediaType::Ssd => Some(true),
+            MediaType::Hdd => Some(false),
+            MediaType::Unknown => None,
+        }
+    }
+}
+
+lazy_static! {
+    static ref MEDIA_CACHE: Mutex<HashMap<String, MediaType>> = Mutex::new(HashMap::new());
+}
+
+/// Media type of the disk holding `path`
+pub fn media_type_for_path(path: &str) -> MediaType {
+    match platform::device_for_path(path) {
+        Some(device) => media_type_for_device(&device),
+        None => MediaType::Unknown,
+    }
+}
+
+/// Media type of a disk or volume device (e.g. "/dev/sda", "\\.\PhysicalDrive0")
+pub fn media_type_for_device(device: &str) -> MediaType {
+    if let Some(cached) = MEDIA_CACHE.lock().ok().and_then(|cache| cache.get(device).copied()) {
+        return cached;
+    }
+
+    let media = platform::query_media_type(device);
+
+    // Unknown may be transient (e.g. a disk that was still spinning up), so it isn't cached
+    if media != MediaType::Unknown {
+        if let Ok(mut cache) = MEDIA_CACHE.lock() {
+            cache.insert(device.to_string(), media);
+        }
+    }
+    media
+}
+
+/// Split a Linux `st_dev` into (major, minor), glibc encoding
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn dev_major_minor(dev: u64) -> (u64, u64) {
+    let major = ((dev >> 8) & 0xfff) | ((dev >> 32) & !0xfff);
+    let minor = (dev & 0xff) | ((dev >> 12) & !0xff);
+    (major, minor)
+}
+
+/// "Solid State: Yes" / "No" from `diskutil info`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_diskutil_solid_state(output: &str) -> MediaType {
+    let value = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Solid State:"))
+        .map(str::trim);
+
+    match value {
+        Some("Yes") => MediaType::Ssd,
+        Some("No") => MediaType::Hdd,
+        _ => MediaType::Unknown,
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{dev_major_minor, MediaType};
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+
+    /// The sysfs block directory of the device holding `path`
+    pub fn device_for_path(path: &str) -> Option<String> {
+        let dev = std::fs::metadata(path).ok()?.dev();
+        let (major, minor) = dev_major_minor(dev);
+        let sys = std::fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)).ok()?;
+
+        // Partitions inherit the queue of their parent disk
+        let disk = if sys.join("partition").exists() { sys.parent()?.to_path_buf() } else { sys };
+        Some(format!("/dev/{}", disk.file_name()?.to_string_lossy()))
+    }
+
+    pub fn query_media_type(device: &str) -> MediaType {
+        let Some(name) = device.rsplit('/').next() else {
+            return MediaType::Unknown;
+        };
+        let rotational = PathBuf::from("/sys/block").join(name).join("queue/rotational");
+
+        match std::fs::read_to_string(rotational).as_deref().map(str::trim) {
+            Ok("0") => MediaType::Ssd,
+            Ok("1") => MediaType::Hdd,
+            _ => MediaType::Unknown,
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{parse_diskutil_solid_state, MediaType};
+    use crate::partition::exec::CLocale;
+    use std::process::Command;
+
+    /// The /dev node `df` reports for `path`
+    pub fn device_for_path(path: &str) -> Option<String> {
+        let output = Command::new("df").c_locale().arg(path).output().ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let device = stdout.lines().nth(1)?.split_whitespace().next()?;
+        device.starts_with("/dev/").then(|| device.to_string())
+    }
+
+    pub fn query_media_type(device: &str) -> MediaType {
+        match Command::new("diskutil").c_locale().arg("info").arg(device).output() {
+            Ok(output) if output.status.success() => {
+                parse_diskutil_solid_state(&String::from_utf8_lossy(&output.stdout))
+            }
+            _ => MediaType::Unknown,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::MediaType;
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, GetVolumePathNameW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows::Win32::System::Ioctl::{
+        PropertyStandardQuery, StorageDeviceSeekPenaltyProperty, DEVICE_SEEK_PENALTY_DESCRIPTOR,
+        IOCTL_STORAGE_QUERY_PROPERTY, STORAGE_PROPERTY_QUERY,
+    };
+    use windows::Win32::System::IO::DeviceIoControl;
+
+    /// The volume device (e.g. "\\.\C:") holding `path`
+    pub fn device_for_path(path: &str) -> Option<String> {
+        let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+        let mut root = [0u16; 261];
+        unsafe { GetVolumePathNameW(PCWSTR(wide.as_ptr()), &mut root) }.ok()?;
+
+        let len = root.iter().position(|&c| c == 0).unwrap_or(root.len());
+        let root = String::from_utf16_lossy(&root[..len]);
+        let letter = crate::partition::diskpart::drive_letter(&root)?;
+        Some(format!(r"\\.\{}:", letter))
+    }
+
+    pub fn query_media_type(device: &str) -> MediaType {
+        let wide: Vec<u16> = device.encode_utf16().chain(std::iter::once(0)).collect();
+
+        // No access rights are needed for property queries, so this works unelevated
+        let Ok(handle) = (unsafe {
+            CreateFileW(
+                PCWSTR(wide.as_ptr()),
+                0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                None,
+            )
+        }) else {
+            return MediaType::Unknown;
+        };
+
+        let query = STORAGE_PROPERTY_QUERY {
+            PropertyId: StorageDeviceSeekPenaltyProperty,
+            QueryType: PropertyStandardQuery,
+            ..Default::default()
+        };
+        let mut descriptor = DEVICE_SEEK_PENALTY_DESCRIPTOR::default();
+        let mut returned = 0u32;
+        let result = unsafe {
+            DeviceIoControl(
+                handle,
+                IOCTL_STORAGE_QUERY_PROPERTY,
+                Some(&query as *const STORAGE_PROPERTY_QUERY as *const std::ffi::c_void),
+                std::mem::size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+                Some(&mut descriptor as *mut DEVICE_SEEK_PENALTY_DESCRIPTOR as *mut std::ffi::c_void),
+                std::mem::size_of::<DEVICE_SEEK_PENALTY_DESCRIPTOR>() as u32,
+                Some(&mut returned),
+                None,
+            )
+        };
+        let _ = unsafe { CloseHandle(handle) };
+
+        match result {
+            Ok(()) if descriptor.IncursSeekPenalty.as_bool() => MediaType::Hdd,
+            Ok(()) => MediaType::Ssd,
+            Err(_) => MediaType::Unknown,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+mod platform {
+    use super::MediaType;
+
+    pub fn device_for_path(_path: &str) -> Option<String> {
+        None
+    }
+
+    pub fn query_media_type(_device: &str) -> MediaType {
+        MediaType::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_major_minor() {
+        // 8:1 (/dev/sda1) and 259:3 (an NVMe partition, major above 255)
+        assert_eq!(dev_major_minor(0x801), (8, 1));
+        assert_eq!(dev_major_minor((259 << 8) | 3), (259, 3));
+    }
+
+    #[test]
+    fn test_parse_diskutil_solid_state() {
+        assert_eq!(parse_diskutil_solid_state("   Solid State:               Yes\n"), MediaType::Ssd);
+        assert_eq!(parse_diskutil_solid_state("   Solid State:               No\n"), MediaType::Hdd);
+        assert_eq!(parse_diskutil_solid_state("   Device Node: /dev/disk0\n"), MediaType::Unknown);
+    }
+}
@@ -5,6 +5,7 @@
 
 pub mod types;
 pub mod info;
+pub mod provider;
 pub mod platform;
 pub mod resize;
 pub mod move_partition;
@@ -12,13 +13,40 @@ pub mod reallocation_wizard;
 pub mod mount;
 pub mod delete;
 pub mod move_simple;
+pub mod create;
+pub mod split;
+pub mod diskpart;
+pub mod exec;
+pub mod error;
+pub mod safety;
+pub mod smart;
+pub mod health;
+pub mod inspect;
+pub mod fragmentation;
+pub mod fs_probe;
+pub mod media;
+pub mod power;
+pub mod storage_cmdlets;
+pub mod table_probe;
+pub mod transaction;
 
 // Re-export commonly used types
 pub use types::*;
 pub use info::*;
+pub use provider::*;
 pub use resize::*;
 pub use move_partition::*;
 pub use reallocation_wizard::*;
 pub use mount::*;
 pub use delete::*;
 pub use move_simple::*;
+pub use create::*;
+pub use split::*;
+pub use transaction::{PartialFailure, StepRecord, Transaction, Undo};
+pub use error::*;
+pub use safety::*;
+pub use power::{check_power, power_state};
+pub use smart::*;
+pub use health::{get_disk_health_summary, DiskHealthSummary, HealthWarning};
+pub use inspect::*;
+pub use fragmentation::*;
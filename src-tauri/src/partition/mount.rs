@@ -3,6 +3,7 @@
 // This module handles mounting and unmounting partitions safely
 
 use crate::partition::types::*;
+use crate::partition::exec::RunCommand;
 use anyhow::{anyhow, Result};
 use std::process::Command;
 
@@ -41,34 +42,27 @@ pub fn mount_partition(partition: &PartitionInfo) -> Result<()> {
 // Windows implementations
 #[cfg(target_os = "windows")]
 fn unmount_windows(partition: &PartitionInfo) -> Result<()> {
-    use std::fs;
-    use std::io::Write;
+    use crate::partition::diskpart::{drive_letter, volume_selector, ScriptFile};
 
-    let drive_letter = partition
+    let mount_point = partition
         .mount_point
-        .as_ref()
-        .and_then(|mp| mp.chars().next())
+        .as_deref()
         .ok_or_else(|| anyhow!("No drive letter found for partition"))?;
 
-    // Create diskpart script to remove drive letter (unmount)
-    let script_content = format!(
-        "select volume {}\nremove letter={}\n",
-        drive_letter, drive_letter
-    );
+    // Create diskpart script to remove the drive letter or folder mount (unmount)
+    let volume = volume_selector(mount_point);
+    let script_content = match drive_letter(mount_point) {
+        Some(letter) => format!("select volume {}\nremove letter={}\n", volume, letter),
+        None => format!("select volume {}\nremove mount={}\n", volume, volume),
+    };
 
-    let script_path = std::env::temp_dir().join("unmount_partition.txt");
-    let mut file = fs::File::create(&script_path)?;
-    file.write_all(script_content.as_bytes())?;
-    drop(file);
+    let script_file = ScriptFile::create(&script_content, "unmount")?;
 
     // Execute diskpart
     let output = Command::new("diskpart")
         .arg("/s")
-        .arg(&script_path)
-        .output()?;
-
-    // Clean up script file
-    let _ = fs::remove_file(&script_path);
+        .arg(script_file.path())
+        .run()?;
 
     if !output.status.success() {
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -80,12 +74,8 @@ fn unmount_windows(partition: &PartitionInfo) -> Result<()> {
         ));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.contains("successfully") || stdout.contains("removed") {
-        Ok(())
-    } else {
-        Err(anyhow!("Unmount may have failed. Output: {}", stdout))
-    }
+    // diskpart exits non-zero when a command fails; its messages are localized
+    Ok(())
 }
 
 #[cfg(target_os = "windows")]
@@ -107,7 +97,7 @@ fn unmount_macos(partition: &PartitionInfo) -> Result<()> {
     let output = Command::new("diskutil")
         .arg("unmount")
         .arg(&partition.device_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -122,7 +112,7 @@ fn mount_macos(partition: &PartitionInfo) -> Result<()> {
     let output = Command::new("diskutil")
         .arg("mount")
         .arg(&partition.device_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
@@ -140,7 +130,7 @@ fn unmount_linux(partition: &PartitionInfo) -> Result<()> {
         .as_ref()
         .ok_or_else(|| anyhow!("Partition is not mounted"))?;
 
-    let output = Command::new("umount").arg(mount_point).output()?;
+    let output = Command::new("umount").arg(mount_point).run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
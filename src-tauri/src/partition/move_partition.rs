@@ -158,6 +158,9 @@ pub fn validate_move(
         maximum_size: None,
         has_adjacent_space: false,
         adjacent_space: 0,
+        requires_unmount: false,
+        on_battery: false,
+        battery_percent: None,
     };
 
     // Check 1: Target offset must be within disk bounds
@@ -275,7 +278,7 @@ pub async fn move_partition(
 
     // Step 2: Delete old partition
     progress_callback(MoveProgress::deleting_partition("Deleting old partition..."));
-    delete_partition(partition).await?;
+    delete_partition(partition, disk).await?;
 
     // Step 3: Create new partition at target offset
     progress_callback(MoveProgress::creating_partition("Creating partition at new location..."));
@@ -341,6 +344,7 @@ async fn backup_partition_windows(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
 
     let mount_point = partition
         .mount_point
@@ -358,7 +362,7 @@ async fn backup_partition_windows(
         .arg("/R:3") // Retry 3 times on failed copies
         .arg("/W:5") // Wait 5 seconds between retries
         .arg("/MT:8") // Multi-threaded (8 threads)
-        .output()?;
+        .run()?;
 
     // Robocopy returns exit codes 0-7 for success, 8+ for errors
     let exit_code = output.status.code().unwrap_or(16);
@@ -382,6 +386,7 @@ async fn backup_partition_linux(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
 
     let mount_point = partition
         .mount_point
@@ -395,7 +400,7 @@ async fn backup_partition_linux(
         .arg("--progress")
         .arg(format!("{}/", mount_point))
         .arg(backup_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!(
@@ -416,6 +421,7 @@ async fn backup_partition_macos(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
 
     let mount_point = partition
         .mount_point
@@ -429,7 +435,7 @@ async fn backup_partition_macos(
         .arg("--progress")
         .arg(format!("{}/", mount_point))
         .arg(backup_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!("rsync backup failed: {}", String::from_utf8_lossy(&output.stderr)));
@@ -439,8 +445,9 @@ async fn backup_partition_macos(
     Ok(true)
 }
 
-/// Delete a partition from the disk
-async fn delete_partition(partition: &PartitionInfo) -> Result<()> {
+/// Delete a partition from `disk`
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+async fn delete_partition(partition: &PartitionInfo, disk: &DiskInfo) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
         delete_partition_windows(partition).await
@@ -448,7 +455,7 @@ async fn delete_partition(partition: &PartitionInfo) -> Result<()> {
 
     #[cfg(target_os = "linux")]
     {
-        delete_partition_linux(partition).await
+        delete_partition_linux(partition, disk).await
     }
 
     #[cfg(target_os = "macos")]
@@ -465,29 +472,28 @@ async fn delete_partition(partition: &PartitionInfo) -> Result<()> {
 /// Delete partition on Windows using diskpart
 #[cfg(target_os = "windows")]
 async fn delete_partition_windows(partition: &PartitionInfo) -> Result<()> {
+    use crate::partition::diskpart::ScriptFile;
     use std::os::windows::process::CommandExt;
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
 
-    let drive_letter = partition
+    let volume = partition
         .mount_point
-        .as_ref()
-        .and_then(|m| m.chars().next())
+        .as_deref()
+        .map(crate::partition::diskpart::volume_selector)
         .ok_or_else(|| anyhow!("No drive letter found for partition"))?;
 
-    let script = format!("select volume {}\ndelete partition\n", drive_letter);
+    let script = format!("select volume {}\ndelete partition\n", volume);
 
-    let script_path = std::env::temp_dir().join("diskpart_delete.txt");
-    std::fs::write(&script_path, script)?;
+    let script_file = ScriptFile::create(&script, "delete")?;
 
     const CREATE_NO_WINDOW: u32 = 0x08000000;
 
     let output = Command::new("diskpart")
         .arg("/s")
-        .arg(&script_path)
+        .arg(script_file.path())
         .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
-
-    let _ = std::fs::remove_file(&script_path);
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!(
@@ -499,31 +505,17 @@ async fn delete_partition_windows(partition: &PartitionInfo) -> Result<()> {
     Ok(())
 }
 
-/// Delete partition on Linux using parted
+/// Delete partition on Linux using parted, addressed by its disk and number
 #[cfg(target_os = "linux")]
-async fn delete_partition_linux(partition: &PartitionInfo) -> Result<()> {
+async fn delete_partition_linux(partition: &PartitionInfo, disk: &DiskInfo) -> Result<()> {
     use std::process::Command;
-
-    let device = &partition.device_path;
-
-    // Extract partition number
-    let part_num = device
-        .chars()
-        .rev()
-        .take_while(|c| c.is_numeric())
-        .collect::<String>()
-        .chars()
-        .rev()
-        .collect::<String>();
-
-    // Extract base device
-    let base_device = device.trim_end_matches(&part_num);
+    use crate::partition::exec::RunCommand;
 
     let output = Command::new("parted")
-        .arg(base_device)
+        .arg(&disk.device_path)
         .arg("rm")
-        .arg(&part_num)
-        .output()?;
+        .arg(partition.number.to_string())
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!(
@@ -539,13 +531,14 @@ async fn delete_partition_linux(partition: &PartitionInfo) -> Result<()> {
 #[cfg(target_os = "macos")]
 async fn delete_partition_macos(partition: &PartitionInfo) -> Result<()> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
 
     let output = Command::new("diskutil")
         .arg("eraseVolume")
         .arg("Free Space")
         .arg("Untitled")
         .arg(&partition.device_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!(
@@ -585,6 +578,8 @@ async fn create_partition_at_offset_windows(
     target_offset: u64,
 ) -> Result<PartitionInfo> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
+    use crate::partition::diskpart::ScriptFile;
     use std::os::windows::process::CommandExt;
 
     // Convert size to MB (diskpart expects MB)
@@ -602,16 +597,13 @@ async fn create_partition_at_offset_windows(
          disk.device_path.replace("\\\\.\\PhysicalDrive", "")
     };
 
-    // Try to preserve original drive letter
-    let letter_cmd = if let Some(ref mp) = original_partition.mount_point {
-        // mount_point is likely "E:" or "E:\"
-        if let Some(letter) = mp.chars().next() {
-             format!("assign letter={}", letter)
-        } else {
-             "assign".to_string()
-        }
-    } else {
-         "assign".to_string()
+    // Try to preserve original drive letter or folder mount
+    let letter_cmd = match original_partition.mount_point.as_deref() {
+        Some(mp) => match crate::partition::diskpart::drive_letter(mp) {
+            Some(letter) => format!("assign letter={}", letter),
+            None => format!("assign mount={}", crate::partition::diskpart::volume_selector(mp)),
+        },
+        None => "assign".to_string(),
     };
 
     // Construct diskpart script
@@ -624,18 +616,15 @@ async fn create_partition_at_offset_windows(
         letter_cmd
     );
 
-    let script_path = std::env::temp_dir().join("diskpart_create.txt");
-    std::fs::write(&script_path, script)?;
+    let script_file = ScriptFile::create(&script, "create")?;
 
     const CREATE_NO_WINDOW: u32 = 0x08000000;
 
     let output = Command::new("diskpart")
         .arg("/s")
-        .arg(&script_path)
+        .arg(script_file.path())
         .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
-        
-    let _ = std::fs::remove_file(&script_path);
+        .run()?;
 
     if !output.status.success() {
          return Err(anyhow!(
@@ -709,6 +698,7 @@ async fn restore_partition_windows(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
     
     // We need the mount point of the target partition
     // If the partition struct doesn't have it (freshly created), we have a problem.
@@ -728,7 +718,7 @@ async fn restore_partition_windows(
         .arg("/R:3")
         .arg("/W:5")
         .arg("/MT:8")
-        .output()?;
+        .run()?;
 
     let exit_code = output.status.code().unwrap_or(16);
     if exit_code >= 8 {
@@ -750,6 +740,7 @@ async fn restore_partition_linux(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
     
     let mount_point = partition
         .mount_point
@@ -763,7 +754,7 @@ async fn restore_partition_linux(
         .arg("--progress")
         .arg(format!("{}/", backup_path.display()))
         .arg(mount_point)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!("Rsync restore failed: {}", String::from_utf8_lossy(&output.stderr)));
@@ -780,6 +771,7 @@ async fn restore_partition_macos(
     progress_callback: &impl Fn(MoveProgress),
 ) -> Result<bool> {
     use std::process::Command;
+    use crate::partition::exec::RunCommand;
     
     let mount_point = partition
         .mount_point
@@ -793,7 +785,7 @@ async fn restore_partition_macos(
         .arg("--progress")
         .arg(format!("{}/", backup_path.display()))
         .arg(mount_point)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         return Err(anyhow!("rsync restore failed: {}", String::from_utf8_lossy(&output.stderr)));
@@ -802,18 +794,3 @@ async fn restore_partition_macos(
     progress_callback(MoveProgress::restoring_data(100.0, partition.total_size, partition.total_size));
     Ok(true)
 }
-
-/// Format bytes to human-readable string
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    if bytes == 0 {
-        return "0 B".to_string();
-    }
-
-    let base = 1024_f64;
-    let exp = (bytes as f64).log(base).floor() as usize;
-    let exp = exp.min(UNITS.len() - 1);
-    let value = bytes as f64 / base.powi(exp as i32);
-
-    format!("{:.2} {}", value, UNITS[exp])
-}
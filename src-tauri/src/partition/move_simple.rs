@@ -3,6 +3,8 @@
 
 use crate::partition::types::*;
 use crate::partition::delete::delete_partition;
+use crate::partition::transaction::PartialFailure;
+use crate::partition::exec::RunCommand;
 use anyhow::{anyhow, Result};
 use std::path::PathBuf;
 use std::process::Command;
@@ -22,6 +24,91 @@ pub struct MoveExecutionPlan {
     pub affected_partitions: Vec<String>,
 }
 
+/// Assumed copy throughput for move time estimates
+const ESTIMATED_COPY_BYTES_PER_SEC: u64 = 100 * 1024 * 1024;
+
+/// Recommended for moves the app leaves to the user
+pub const MANUAL_MOVE_TOOL: &str = "GParted Live";
+
+/// One partition move within `MoveInstructions`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MoveStep {
+    pub partition_id: String,
+    pub partition_label: Option<String>,
+    pub from_offset: u64,
+    pub to_offset: u64,
+    /// Data copied out and back in (used space, or the whole partition if unknown)
+    pub data_size: u64,
+    pub completed: bool,
+}
+
+/// Structured result of a set of partition moves, for the frontend to render
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MoveInstructions {
+    /// External tool to use instead, when the moves can't be done natively
+    pub tool_recommendation: Option<String>,
+    pub steps: Vec<MoveStep>,
+    pub estimated_minutes: u32,
+    /// Why the moves stopped; steps after the failed one are left pending
+    pub failure: Option<PartialFailure>,
+}
+
+impl MoveStep {
+    pub fn new(partition: &PartitionInfo, to_offset: u64) -> Self {
+        MoveStep {
+            partition_id: partition.id.clone(),
+            partition_label: partition.label.clone(),
+            from_offset: partition.start_offset,
+            to_offset,
+            data_size: partition.used_space.unwrap_or(partition.total_size),
+            completed: false,
+        }
+    }
+}
+
+impl MoveInstructions {
+    pub fn new(steps: Vec<MoveStep>, tool_recommendation: Option<String>) -> Self {
+        // Every byte is copied twice: out to the backup and back in
+        let bytes: u64 = steps.iter().map(|s| s.data_size.saturating_mul(2)).sum();
+        let minutes = bytes / ESTIMATED_COPY_BYTES_PER_SEC / 60;
+
+        MoveInstructions {
+            tool_recommendation,
+            estimated_minutes: (minutes as u32).max(1),
+            steps,
+            failure: None,
+        }
+    }
+
+    /// English summary, as previously returned by `execute_partition_moves`
+    pub fn to_plain_text(&self) -> String {
+        if !self.steps.is_empty() && self.steps.iter().all(|s| s.completed) {
+            return "All partition moves completed successfully!".to_string();
+        }
+
+        let mut text = String::new();
+        if let Some(failure) = &self.failure {
+            text.push_str(&format!("{}\n", failure));
+        }
+        if let Some(tool) = &self.tool_recommendation {
+            text.push_str(&format!("Recommended tool: {}\n", tool));
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            text.push_str(&format!(
+                "{}. {} {}: move from offset {} to {} ({} bytes of data)\n",
+                i + 1,
+                if step.completed { "[done]" } else { "[pending]" },
+                step.partition_label.as_deref().unwrap_or(&step.partition_id),
+                step.from_offset,
+                step.to_offset,
+                step.data_size
+            ));
+        }
+        text.push_str(&format!("Estimated time: about {} minute(s)", self.estimated_minutes));
+        text
+    }
+}
+
 /// Validate a partition move operation
 pub fn validate_move_operation(
     partition: &PartitionInfo,
@@ -92,7 +179,7 @@ pub fn backup_partition_data(partition: &PartitionInfo, backup_path: &PathBuf) -
         .arg("/W:5") // Wait 5 seconds between retries
         .arg("/MT:8") // Multi-threaded with 8 threads
         .arg("/NP") // No progress display
-        .output()?;
+        .run()?;
 
     // Robocopy returns exit codes 0-7 for success (various levels)
     let exit_code = output.status.code().unwrap_or(-1);
@@ -120,7 +207,7 @@ pub fn backup_partition_data(partition: &PartitionInfo, backup_path: &PathBuf) -
         .arg("--progress")
         .arg(format!("{}/", source)) // Trailing slash = copy contents
         .arg(backup_path)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -147,7 +234,7 @@ pub fn restore_partition_data(backup_path: &PathBuf, partition: &PartitionInfo)
         .arg("/W:5")
         .arg("/MT:8")
         .arg("/NP")
-        .output()?;
+        .run()?;
 
     let exit_code = output.status.code().unwrap_or(-1);
     if exit_code < 0 || exit_code > 7 {
@@ -170,7 +257,7 @@ pub fn restore_partition_data(backup_path: &PathBuf, partition: &PartitionInfo)
         .arg("--progress")
         .arg(format!("{}/", backup_path.display()))
         .arg(dest)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -182,11 +269,14 @@ pub fn restore_partition_data(backup_path: &PathBuf, partition: &PartitionInfo)
 
 /// Simple partition move: backup -> delete -> recreate -> restore
 /// This is safer than low-level sector manipulation
+///
+/// Not automated yet: returns the move as a pending step for the user to
+/// carry out with `MANUAL_MOVE_TOOL`.
 pub async fn execute_simple_move(
     partition: &PartitionInfo,
     new_offset: u64,
     disk_size: u64,
-) -> Result<()> {
+) -> Result<MoveInstructions> {
     // Validate the move
     let warnings = validate_move_operation(partition, new_offset, disk_size)?;
 
@@ -194,7 +284,6 @@ pub async fn execute_simple_move(
         eprintln!("Move warnings: {:?}", warnings);
     }
 
-    // For now, this is a stub that requires manual intervention
     // Full implementation would require:
     // 1. Create temporary backup location
     // 2. Backup partition data
@@ -203,12 +292,49 @@ pub async fn execute_simple_move(
     // 5. Format new partition
     // 6. Restore data
     // 7. Clean up backup
-
-    Err(anyhow!(
-        "Partition moving requires manual backup and restore. \
-         Please use this feature as a planning tool, then:\n\
-         1. Backup your data manually\n\
-         2. Use Windows Disk Management or other tools to move the partition\n\
-         3. Restore your data if needed"
+    Ok(MoveInstructions::new(
+        vec![MoveStep::new(partition, new_offset)],
+        Some(MANUAL_MOVE_TOOL.to_string()),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_move_instructions_plain_text() {
+        let step = |completed| MoveStep {
+            partition_id: "sda2".to_string(),
+            partition_label: Some("Data".to_string()),
+            from_offset: 1024,
+            to_offset: 2048,
+            data_size: 60 * 100 * 1024 * 1024,
+            completed,
+        };
+
+        let done = MoveInstructions::new(vec![step(true)], None);
+        assert_eq!(done.estimated_minutes, 2);
+        assert_eq!(done.to_plain_text(), "All partition moves completed successfully!");
+
+        let pending = MoveInstructions::new(vec![step(false)], Some("GParted Live".to_string()));
+        let text = pending.to_plain_text();
+        assert!(text.starts_with("Recommended tool: GParted Live\n1. [pending] Data"));
+        assert!(text.ends_with("about 2 minute(s)"));
+
+        let mut failed = MoveInstructions::new(vec![step(true), step(false)], Some("GParted Live".to_string()));
+        failed.failure = Some(PartialFailure {
+            operation: "Partition reorganization".to_string(),
+            failed_step: "move Data".to_string(),
+            reason: "rsync failed".to_string(),
+            completed: Vec::new(),
+            rolled_back: Vec::new(),
+            manual_steps: Vec::new(),
+            table_backups: Vec::new(),
+        });
+        let text = failed.to_plain_text();
+        assert!(text.starts_with("Partition reorganization failed at \"move Data\": rsync failed."));
+        assert!(text.contains("1. [done] Data"));
+        assert!(text.contains("2. [pending] Data"));
+    }
+}
@@ -1,7 +1,54 @@
 // Platform-specific implementations for partition operations
 
+/// Compute used space from a reported total and free size
+///
+/// Some Windows configurations (quotas, VSS) report more free space than the
+/// volume's total size. Treat that as unknown rather than wrapping around.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn used_space_from_totals(total: Option<u64>, free: Option<u64>) -> Option<u64> {
+    let (total, free) = (total?, free?);
+
+    if free > total {
+        log::warn!(
+            "Reported free space ({}) exceeds total size ({}); used space unknown",
+            free,
+            total
+        );
+        return None;
+    }
+
+    Some(total - free)
+}
+
+/// How long to wait for a mount point to answer before treating it as dead
+const MOUNT_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Check that a mount point answers a cheap metadata read
+///
+/// A drive letter or mount entry can outlive the device behind it (failing
+/// disk, disconnected USB). The read runs on a helper thread so a dead device
+/// can't hang enumeration; if it doesn't answer in time it's inaccessible.
+fn is_mount_accessible(mount_point: &str) -> bool {
+    let path = std::path::PathBuf::from(mount_point);
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    std::thread::spawn(move || {
+        let _ = tx.send(std::fs::metadata(&path).is_ok());
+    });
+
+    match rx.recv_timeout(MOUNT_PROBE_TIMEOUT) {
+        Ok(accessible) => accessible,
+        Err(_) => {
+            log::warn!("Mount point {} did not respond; marking inaccessible", mount_point);
+            false
+        }
+    }
+}
+
 #[cfg(target_os = "windows")]
 pub mod windows {
+    use super::super::smart;
+    use super::super::table_probe;
     use super::super::types::*;
     use anyhow::{anyhow, Result};
     use std::collections::HashMap;
@@ -19,7 +66,10 @@ pub mod windows {
             .raw_query("SELECT * FROM Win32_DiskDrive")
             .map_err(|e| anyhow!("Failed to query disks: {}", e))?;
 
+        let volumes = volume_mount_paths();
+        let predictions = smart::wmi_failure_predictions(com_con);
         let mut result = Vec::new();
+        let mut skipped = Vec::new();
 
         for (index, disk_data) in disks.iter().enumerate() {
             let device_id = get_string_property(disk_data, "DeviceID")
@@ -32,8 +82,18 @@ pub mod windows {
 
             let serial = get_string_property(disk_data, "SerialNumber");
 
+            let pnp_device_id = get_string_property(disk_data, "PNPDeviceID");
+
             // Get partitions for this disk
-            let partitions = get_partitions_for_disk(&wmi_con, &device_id, index as u32)?;
+            // A removable disk can vanish between the two queries; skip it and keep the rest
+            let partitions = match get_partitions_for_disk(&wmi_con, &device_id, index as u32, &volumes) {
+                Ok(partitions) => partitions,
+                Err(e) => {
+                    log::warn!("Skipping {} ({}): could not read its partitions: {}", device_id, model, e);
+                    skipped.push(device_id);
+                    continue;
+                }
+            };
 
             // Fix for stale WMI disk size: ensure total_size covers all partitions
             let max_partition_end = partitions.iter()
@@ -53,17 +113,23 @@ pub mod windows {
                 total_size: final_size,
                 table_type,
                 partitions,
+                largest_free_block: 0,
+                total_free: 0,
                 serial_number: serial,
-                status: DiskStatus {
-                    is_online: true,
-                    has_errors: false,
-                    smart_status: None, // TODO: Add SMART status
-                },
+                status: smart::disk_status(smart::read_windows_smart_status(
+                    &device_id,
+                    pnp_device_id.as_deref(),
+                    &predictions,
+                )),
             };
 
             result.push(disk_info);
         }
 
+        if !skipped.is_empty() {
+            log::warn!("Listed {} disk(s); skipped {}: {}", result.len(), skipped.len(), skipped.join(", "));
+        }
+
         Ok(result)
     }
 
@@ -72,6 +138,7 @@ pub mod windows {
         wmi_con: &WMIConnection,
         disk_device_id: &str,
         disk_index: u32,
+        volumes: &HashMap<(u32, u64), (String, Vec<String>)>,
     ) -> Result<Vec<PartitionInfo>> {
         // Query disk partitions
         let query = format!(
@@ -94,16 +161,45 @@ pub mod windows {
             let start_offset = get_u64_property(&partition_data, "StartingOffset").unwrap_or(0);
             let is_boot = get_bool_property(&partition_data, "BootPartition").unwrap_or(false);
             let is_primary = get_bool_property(&partition_data, "PrimaryPartition").unwrap_or(false);
+            let wmi_type = get_string_property(&partition_data, "Type").unwrap_or_default();
 
             // Get associated logical disk (drive letter)
-            let (drive_letter, filesystem, used_space, label) =
+            let (drive_letter, mut filesystem, mut used_space, mut label) =
                 get_logical_disk_info(wmi_con, &device_id)?;
 
+            let volume = volumes.get(&(disk_index, start_offset));
+
+            // Volumes mounted into an NTFS folder have no logical disk
+            let folder_mount = if drive_letter.is_none() {
+                volume
+                    .and_then(|(_, paths)| paths.first())
+                    .map(|path| path.trim_end_matches('\\').to_string())
+            } else {
+                None
+            };
+            if let Some(path) = &folder_mount {
+                (filesystem, used_space, label) = folder_volume_info(path);
+            }
+            let mount_point = drive_letter.clone().or(folder_mount);
+
+            // A mount point alone doesn't mean the volume is usable
+            let is_accessible = mount_point
+                .as_ref()
+                .map(|mp| super::is_mount_accessible(&format!("{}\\", mp)))
+                .unwrap_or(true);
+
             let mut flags = Vec::new();
             if is_boot {
                 flags.push(PartitionFlag::Boot);
             }
 
+            // e.g. "GPT: System" for the ESP, "GPT: Reserved" for the MSR
+            if wmi_type.contains("System") {
+                flags.push(PartitionFlag::System);
+            } else if wmi_type.contains("Reserved") {
+                flags.push(PartitionFlag::Reserved);
+            }
+
             let partition_type = if is_primary {
                 PartitionType::Primary
             } else {
@@ -120,9 +216,12 @@ pub mod windows {
                 used_space,
                 partition_type,
                 filesystem: parse_filesystem_type(&filesystem),
-                mount_point: drive_letter.clone(),
-                is_mounted: drive_letter.is_some(),
+                is_mounted: mount_point.is_some() && is_accessible,
+                mount_point,
+                is_accessible,
                 flags,
+                uuid: volume.map(|(guid, _)| guid.clone()),
+                is_system_volume: false,
             };
 
             result.push(partition_info);
@@ -154,11 +253,7 @@ pub mod windows {
             let free_space = get_u64_property(logical_disk, "FreeSpace");
             let volume_name = get_string_property(logical_disk, "VolumeName");
 
-            let used_space = if let (Some(total), Some(free)) = (size, free_space) {
-                Some(total - free)
-            } else {
-                None
-            };
+            let used_space = super::used_space_from_totals(size, free_space);
 
             Ok((drive_letter, filesystem, used_space, volume_name))
         } else {
@@ -166,10 +261,159 @@ pub mod windows {
         }
     }
 
-    /// Detect partition table type (MBR or GPT)
+    /// Each volume's GUID and every path it is mounted at, keyed by (disk number, starting offset)
+    ///
+    /// Win32_LogicalDisk only covers drive letters, so volumes mounted into an
+    /// NTFS folder are found here instead. Volumes spanning several disks are
+    /// left out.
+    fn volume_mount_paths() -> HashMap<(u32, u64), (String, Vec<String>)> {
+        use windows::Win32::Foundation::MAX_PATH;
+        use windows::Win32::Storage::FileSystem::{FindFirstVolumeW, FindNextVolumeW, FindVolumeClose};
+
+        let mut volumes = HashMap::new();
+        let mut name = [0u16; MAX_PATH as usize];
+
+        let Ok(find) = (unsafe { FindFirstVolumeW(&mut name) }) else {
+            return volumes;
+        };
+
+        loop {
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            let volume = String::from_utf16_lossy(&name[..len]);
+
+            if let Some(key) = volume_extent(&volume) {
+                // `\\?\Volume{GUID}\` -> GUID
+                let guid = volume
+                    .split(['{', '}'])
+                    .nth(1)
+                    .unwrap_or(&volume)
+                    .to_string();
+                volumes.insert(key, (guid, volume_path_names(&volume)));
+            }
+
+            if unsafe { FindNextVolumeW(find, &mut name) }.is_err() {
+                break;
+            }
+        }
+
+        let _ = unsafe { FindVolumeClose(find) };
+        volumes
+    }
+
+    /// Disk number and starting offset of a single-extent volume (`\\?\Volume{GUID}\`)
+    fn volume_extent(volume: &str) -> Option<(u32, u64)> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::CloseHandle;
+        use windows::Win32::Storage::FileSystem::{
+            CreateFileW, FILE_FLAGS_AND_ATTRIBUTES, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+        };
+        use windows::Win32::System::Ioctl::{IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS, VOLUME_DISK_EXTENTS};
+        use windows::Win32::System::IO::DeviceIoControl;
+
+        // The device is opened without the trailing backslash, for queries only
+        let device: Vec<u16> = volume.trim_end_matches('\\').encode_utf16().chain(std::iter::once(0)).collect();
+        let handle = unsafe {
+            CreateFileW(
+                PCWSTR(device.as_ptr()),
+                0,
+                FILE_SHARE_READ | FILE_SHARE_WRITE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                None,
+            )
+        }
+        .ok()?;
+
+        let mut extents = VOLUME_DISK_EXTENTS::default();
+        let mut returned = 0u32;
+        let result = unsafe {
+            DeviceIoControl(
+                handle,
+                IOCTL_VOLUME_GET_VOLUME_DISK_EXTENTS,
+                None,
+                0,
+                Some(&mut extents as *mut VOLUME_DISK_EXTENTS as *mut std::ffi::c_void),
+                std::mem::size_of::<VOLUME_DISK_EXTENTS>() as u32,
+                Some(&mut returned),
+                None,
+            )
+        };
+        let _ = unsafe { CloseHandle(handle) };
+
+        // Spanned volumes don't fit the single-extent buffer and fail here
+        result.ok()?;
+        let extent = extents.Extents[0];
+        Some((extent.DiskNumber, extent.StartingOffset as u64))
+    }
+
+    /// Drive letters and folder paths a volume is mounted at
+    fn volume_path_names(volume: &str) -> Vec<String> {
+        use windows::core::PCWSTR;
+        use windows::Win32::Storage::FileSystem::GetVolumePathNamesForVolumeNameW;
+
+        let wide: Vec<u16> = volume.encode_utf16().chain(std::iter::once(0)).collect();
+
+        // The first call reports the buffer length needed
+        let mut len = 0u32;
+        let _ = unsafe { GetVolumePathNamesForVolumeNameW(PCWSTR(wide.as_ptr()), None, &mut len) };
+
+        let mut buffer = vec![0u16; len.max(1) as usize];
+        if unsafe { GetVolumePathNamesForVolumeNameW(PCWSTR(wide.as_ptr()), Some(&mut buffer), &mut len) }.is_err() {
+            return Vec::new();
+        }
+
+        // Double-NUL-terminated list of paths
+        buffer
+            .split(|&c| c == 0)
+            .filter(|path| !path.is_empty())
+            .map(String::from_utf16_lossy)
+            .collect()
+    }
+
+    /// Filesystem, used space and label of a folder-mounted volume
+    fn folder_volume_info(path: &str) -> (String, Option<u64>, Option<String>) {
+        use windows::core::PCWSTR;
+        use windows::Win32::Foundation::MAX_PATH;
+        use windows::Win32::Storage::FileSystem::{GetDiskFreeSpaceExW, GetVolumeInformationW};
+
+        let root: Vec<u16> = format!("{}\\", path).encode_utf16().chain(std::iter::once(0)).collect();
+        let to_string = |buffer: &[u16]| {
+            let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+            String::from_utf16_lossy(&buffer[..len])
+        };
+
+        let mut label = [0u16; MAX_PATH as usize + 1];
+        let mut filesystem = [0u16; MAX_PATH as usize + 1];
+        let info = unsafe {
+            GetVolumeInformationW(PCWSTR(root.as_ptr()), Some(&mut label), None, None, None, Some(&mut filesystem))
+        };
+        let (filesystem, label) = match info {
+            Ok(()) => (to_string(&filesystem), Some(to_string(&label)).filter(|l| !l.is_empty())),
+            Err(_) => ("Unknown".to_string(), None),
+        };
+
+        let (mut total, mut free) = (0u64, 0u64);
+        let space = unsafe {
+            GetDiskFreeSpaceExW(PCWSTR(root.as_ptr()), None, Some(&mut total as *mut u64), Some(&mut free as *mut u64))
+        };
+        let used_space = space
+            .ok()
+            .and_then(|_| super::used_space_from_totals(Some(total), Some(free)));
+
+        (filesystem, used_space, label)
+    }
+
+    /// Partition table type from the disk's first sectors
+    ///
+    /// Reading the disk needs an elevated process; without one this falls
+    /// back to the partition `Type` string from WMI, which is less reliable.
     fn detect_partition_table_type(device_path: &str) -> PartitionTableType {
-        // Try to read the first sector to detect partition table type
-        // For now, use WMI query
+        match table_probe::read_partition_table_type(device_path) {
+            Ok(table_type) => return table_type,
+            Err(e) => log::debug!("Could not read the partition table of {}: {}", device_path, e),
+        }
+
         let com_con = match COMLibrary::new() {
             Ok(c) => c,
             Err(_) => return PartitionTableType::Unknown,
@@ -264,7 +508,9 @@ pub mod windows {
 
 #[cfg(target_os = "linux")]
 pub mod linux {
+    use super::super::smart;
     use super::super::types::*;
+    use super::super::exec::CLocale;
     use anyhow::{anyhow, Result};
     use std::process::Command;
 
@@ -273,7 +519,8 @@ pub mod linux {
 
         // Use lsblk to get block devices in JSON format
         let output = Command::new("lsblk")
-            .args(&["-b", "-J", "-o", "NAME,SIZE,TYPE,FSTYPE,MOUNTPOINT,LABEL,PTTYPE,MODEL"])
+            .c_locale()
+            .args(&["-b", "-J", "-o", "NAME,SIZE,TYPE,FSTYPE,MOUNTPOINT,LABEL,PTTYPE,PARTTYPE,PARTUUID,MODEL"])
             .output()?;
 
         if !output.status.success() {
@@ -290,8 +537,9 @@ pub mod linux {
                     // Only process disk devices (not partitions, loops, etc.)
                     if let Some(dev_type) = device["type"].as_str() {
                         if dev_type == "disk" {
-                            if let Ok(disk_info) = parse_disk_info(device) {
-                                result.push(disk_info);
+                            match parse_disk_info(device) {
+                                Ok(disk_info) => result.push(disk_info),
+                                Err(e) => log::warn!("Skipping {}: {}", device["name"], e),
                             }
                         }
                     }
@@ -324,6 +572,8 @@ pub mod linux {
             }
         }
 
+        let status = smart::disk_status(smart::read_smart_status(&device_path));
+
         Ok(DiskInfo {
             id: name.clone(),
             device_path,
@@ -331,12 +581,10 @@ pub mod linux {
             total_size,
             table_type,
             partitions,
+            largest_free_block: 0,
+            total_free: 0,
             serial_number: None,
-            status: DiskStatus {
-                is_online: true,
-                has_errors: false,
-                smart_status: None,
-            },
+            status,
         })
     }
 
@@ -346,24 +594,44 @@ pub mod linux {
         let total_size = partition["size"].as_u64().unwrap_or(0);
         let label = partition["label"].as_str().map(|s| s.to_string());
         let mount_point = partition["mountpoint"].as_str().map(|s| s.to_string());
-        let is_mounted = mount_point.is_some();
+
+        // Swap reports "[SWAP]" as its mount point, which isn't a path to probe
+        let is_accessible = mount_point
+            .as_deref()
+            .filter(|mp| mp.starts_with('/'))
+            .map(super::is_mount_accessible)
+            .unwrap_or(true);
+        let is_mounted = mount_point.is_some() && is_accessible;
 
         let filesystem = match partition["fstype"].as_str() {
             Some("ext2") => FilesystemType::Ext2,
             Some("ext3") => FilesystemType::Ext3,
             Some("ext4") => FilesystemType::Ext4,
+            Some("btrfs") => FilesystemType::Btrfs,
+            Some("xfs") => FilesystemType::XFS,
             Some("ntfs") => FilesystemType::NTFS,
             Some("vfat") => FilesystemType::FAT32,
             Some("exfat") => FilesystemType::ExFAT,
             Some("apfs") => FilesystemType::APFS,
             Some("hfsplus") | Some("hfs+") => FilesystemType::HFSPlus,
+            Some("swap") => FilesystemType::Swap,
             None | Some("") => FilesystemType::Unknown,
             _ => FilesystemType::Unknown,
         };
 
-        // Get used space if mounted
-        let used_space = if let Some(ref mp) = mount_point {
+        let part_type = partition["parttype"].as_str().unwrap_or("").to_lowercase();
+        let filesystem = if filesystem == FilesystemType::Unknown && is_swap_part_type(&part_type) {
+            FilesystemType::Swap
+        } else {
+            filesystem
+        };
+        let flags = part_type_flags(&part_type);
+
+        // Get used space from df if mounted, otherwise from the filesystem's own tools
+        let used_space = if let (Some(ref mp), true) = (&mount_point, is_accessible) {
             get_used_space(mp).ok()
+        } else if mount_point.is_none() {
+            crate::partition::fs_probe::probe_used_space(&device_path, &filesystem)
         } else {
             None
         };
@@ -380,12 +648,34 @@ pub mod linux {
             filesystem,
             mount_point,
             is_mounted,
-            flags: vec![],
+            is_accessible,
+            flags,
+            uuid: partition["partuuid"].as_str().map(|s| s.to_string()),
+            is_system_volume: false,
         })
     }
 
+    /// GPT type GUID / MBR type ID for Linux swap
+    fn is_swap_part_type(part_type: &str) -> bool {
+        part_type == "0657fd6d-a4ab-43c4-84e5-0933c84b4f4f" || part_type == "0x82"
+    }
+
+    /// Flags implied by a GPT type GUID / MBR type ID
+    fn part_type_flags(part_type: &str) -> Vec<PartitionFlag> {
+        match part_type {
+            // EFI System Partition
+            "c12a7328-f81f-11d2-ba4b-00a0c93ec93b" | "0xef" => vec![PartitionFlag::System],
+            // Microsoft Reserved, BIOS boot
+            "e3c9e316-0b5c-4db8-817d-f92df00215ae" | "21686148-6449-6e6f-744e-656564454649" => {
+                vec![PartitionFlag::Reserved]
+            }
+            _ => vec![],
+        }
+    }
+
     fn get_used_space(mount_point: &str) -> Result<u64> {
         let output = Command::new("df")
+            .c_locale()
             .args(&["-B1", mount_point])
             .output()?;
 
@@ -413,7 +703,9 @@ pub mod linux {
 
 #[cfg(target_os = "macos")]
 pub mod macos {
+    use super::super::smart;
     use super::super::types::*;
+    use super::super::exec::CLocale;
     use anyhow::{anyhow, Result};
     use std::process::Command;
 
@@ -422,6 +714,7 @@ pub mod macos {
 
         // Get list of all disks using diskutil
         let output = Command::new("diskutil")
+            .c_locale()
             .arg("list")
             .arg("-plist")
             .output()?;
@@ -435,6 +728,7 @@ pub mod macos {
         // Parse disk identifiers from output
         // diskutil list returns something like: /dev/disk0, /dev/disk1, etc.
         let disk_output = Command::new("diskutil")
+            .c_locale()
             .arg("list")
             .output()?;
 
@@ -466,9 +760,11 @@ pub mod macos {
         }
 
         // Get detailed info for each disk
+        // A disk ejected mid-enumeration is skipped rather than failing the whole list
         for disk_id in disk_ids {
-            if let Ok(disk_info) = get_disk_info(&disk_id) {
-                result.push(disk_info);
+            match get_disk_info(&disk_id) {
+                Ok(disk_info) => result.push(disk_info),
+                Err(e) => log::warn!("Skipping {}: {}", disk_id, e),
             }
         }
 
@@ -478,6 +774,7 @@ pub mod macos {
     fn get_disk_info(disk_id: &str) -> Result<DiskInfo> {
         // Get disk information using diskutil info
         let output = Command::new("diskutil")
+            .c_locale()
             .arg("info")
             .arg(disk_id)
             .output()?;
@@ -518,19 +815,20 @@ pub mod macos {
         // Get partitions for this disk
         let partitions = get_partitions_for_disk(disk_id)?;
 
+        let device_path = format!("/dev/{}", disk_id);
+        let status = smart::disk_status(smart::read_smart_status(&device_path));
+
         Ok(DiskInfo {
             id: disk_id.to_string(),
-            device_path: format!("/dev/{}", disk_id),
+            device_path,
             model,
             total_size,
             table_type,
             partitions,
+            largest_free_block: 0,
+            total_free: 0,
             serial_number: None,
-            status: DiskStatus {
-                is_online: true,
-                has_errors: false,
-                smart_status: None,
-            },
+            status,
         })
     }
 
@@ -539,6 +837,7 @@ pub mod macos {
 
         // List all volumes/partitions on this disk
         let output = Command::new("diskutil")
+            .c_locale()
             .arg("list")
             .arg(disk_id)
             .output()?;
@@ -571,6 +870,7 @@ pub mod macos {
 
     fn get_partition_info(partition_id: &str, number: u32) -> Result<PartitionInfo> {
         let output = Command::new("diskutil")
+            .c_locale()
             .arg("info")
             .arg(partition_id)
             .output()?;
@@ -587,6 +887,7 @@ pub mod macos {
         let mut filesystem = FilesystemType::Unknown;
         let mut mount_point: Option<String> = None;
         let mut is_mounted = false;
+        let mut uuid: Option<String> = None;
 
         for line in info_str.lines() {
             let line = line.trim();
@@ -622,6 +923,8 @@ pub mod macos {
                 }
             } else if line.starts_with("Mounted:") {
                 is_mounted = line.contains("Yes");
+            } else if let Some(value) = line.strip_prefix("Disk / Partition UUID:") {
+                uuid = Some(value.trim().to_string());
             }
         }
 
@@ -635,9 +938,32 @@ pub mod macos {
             used_space,
             partition_type: PartitionType::Normal,
             filesystem,
+            is_accessible: mount_point
+                .as_deref()
+                .map(super::is_mount_accessible)
+                .unwrap_or(true),
             mount_point,
             is_mounted,
             flags: vec![],
+            uuid,
+            is_system_volume: false,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_used_space_from_totals() {
+        assert_eq!(used_space_from_totals(Some(100), Some(40)), Some(60));
+        assert_eq!(used_space_from_totals(Some(100), None), None);
+    }
+
+    #[test]
+    fn test_used_space_free_exceeds_total() {
+        // Must not panic or wrap when free > total
+        assert_eq!(used_space_from_totals(Some(100), Some(150)), None);
+    }
+}
@@ -0,0 +1,205 @@
+// Power source check before long partition operations
+//
+// A resize that loses power half way through can leave the filesystem and
+// the partition table disagreeing, which corrupts data. Laptops report AC vs
+// battery through the OS: GetSystemPowerStatus on Windows, the
+// power_supply class in sysfs on Linux, `pmset -g batt` on macOS.
+
+use super::error::OperationError;
+use super::resize::validation::ValidationResult;
+use serde::{Deserialize, Serialize};
+
+/// Long operations refuse to start on battery below this charge
+pub const MIN_BATTERY_PERCENT: u8 = 50;
+
+/// Where the machine is drawing power from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct PowerState {
+    /// Running on battery (false on desktops and when unknown)
+    pub on_battery: bool,
+    /// Remaining charge, when the machine has a battery
+    pub battery_percent: Option<u8>,
+}
+
+impl PowerState {
+    /// On battery with too little charge to risk a long operation
+    pub fn is_low(&self) -> bool {
+        self.on_battery && self.battery_percent.map_or(true, |p| p < MIN_BATTERY_PERCENT)
+    }
+}
+
+/// Record the power state on a resize validation and warn about running on battery
+pub fn annotate_validation(result: &mut ValidationResult, power: &PowerState) {
+    result.on_battery = power.on_battery;
+    result.battery_percent = power.battery_percent;
+
+    if !power.on_battery {
+        return;
+    }
+    let charge = power
+        .battery_percent
+        .map(|p| format!("{}%", p))
+        .unwrap_or_else(|| "unknown charge".to_string());
+
+    if power.is_low() {
+        result.warnings.push(format!(
+            "The computer is on battery ({}). Resizing will not start below {}% unless forced: \
+             losing power mid-resize can corrupt the partition. Plug in the charger first.",
+            charge, MIN_BATTERY_PERCENT
+        ));
+    } else {
+        result.warnings.push(format!(
+            "The computer is on battery ({}). Plug in the charger before resizing; \
+             losing power mid-resize can corrupt the partition.",
+            charge
+        ));
+    }
+}
+
+/// Refuse to start a long operation on low battery unless `ignore_battery` is set
+pub fn check_power(power: &PowerState, ignore_battery: bool) -> Result<(), OperationError> {
+    if ignore_battery || !power.is_low() {
+        return Ok(());
+    }
+
+    Err(OperationError::LowBattery {
+        percent: power.battery_percent,
+        minimum: MIN_BATTERY_PERCENT,
+    })
+}
+
+/// Current power source; assumes mains power when it can't be determined
+pub fn power_state() -> PowerState {
+    platform::power_state()
+}
+
+/// Power state from a `/sys/class/power_supply`-style directory
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_power_supply(dir: &std::path::Path) -> PowerState {
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).map(|s| s.trim().to_string()).ok();
+    let mut mains_online = false;
+    let mut battery: Option<(Option<u8>, bool)> = None;
+
+    for entry in std::fs::read_dir(dir).into_iter().flatten().flatten() {
+        let supply = entry.path();
+        match read(supply.join("type")).as_deref() {
+            Some("Mains") | Some("USB") => mains_online |= read(supply.join("online")).as_deref() == Some("1"),
+            // Peripheral batteries (mice, headsets) have scope "Device"
+            Some("Battery") if read(supply.join("scope")).as_deref() != Some("Device") => {
+                let percent = read(supply.join("capacity")).and_then(|c| c.parse().ok());
+                let discharging = read(supply.join("status")).as_deref() == Some("Discharging");
+                battery = Some((percent, discharging));
+            }
+            _ => {}
+        }
+    }
+
+    match battery {
+        Some((battery_percent, discharging)) => PowerState {
+            on_battery: discharging && !mains_online,
+            battery_percent,
+        },
+        None => PowerState::default(),
+    }
+}
+
+/// Power state from `pmset -g batt`
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn parse_pmset(output: &str) -> PowerState {
+    let on_battery = output.contains("'Battery Power'");
+    let battery_percent = output
+        .lines()
+        .find(|line| line.contains("InternalBattery"))
+        .and_then(|line| line.split_whitespace().find(|w| w.contains('%')))
+        .and_then(|w| w.trim_end_matches(';').trim_end_matches('%').parse().ok());
+
+    PowerState { on_battery, battery_percent }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{parse_power_supply, PowerState};
+
+    pub fn power_state() -> PowerState {
+        parse_power_supply(std::path::Path::new("/sys/class/power_supply"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{parse_pmset, PowerState};
+    use std::process::Command;
+
+    pub fn power_state() -> PowerState {
+        match Command::new("pmset").args(["-g", "batt"]).output() {
+            Ok(output) if output.status.success() => parse_pmset(&String::from_utf8_lossy(&output.stdout)),
+            _ => PowerState::default(),
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::PowerState;
+    use windows::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    /// BatteryFlag bit for machines without a battery
+    const NO_SYSTEM_BATTERY: u8 = 128;
+
+    pub fn power_state() -> PowerState {
+        let mut status = SYSTEM_POWER_STATUS::default();
+        if unsafe { GetSystemPowerStatus(&mut status) }.is_err() || status.BatteryFlag & NO_SYSTEM_BATTERY != 0 {
+            return PowerState::default();
+        }
+
+        PowerState {
+            // 0 = offline, 1 = online, 255 = unknown
+            on_battery: status.ACLineStatus == 0,
+            battery_percent: (status.BatteryLifePercent <= 100).then_some(status.BatteryLifePercent),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::PowerState;
+
+    pub fn power_state() -> PowerState {
+        PowerState::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+    use std::fs;
+
+    #[test]
+    fn test_power_supply_and_pmset_parsing() {
+        let dir = TestDir::new("power-test");
+        let supply = |name: &str, files: &[(&str, &str)]| {
+            fs::create_dir_all(dir.join(name)).unwrap();
+            for (file, value) in files {
+                fs::write(dir.join(name).join(file), format!("{}\n", value)).unwrap();
+            }
+        };
+        supply("AC", &[("type", "Mains"), ("online", "0")]);
+        supply("BAT0", &[("type", "Battery"), ("capacity", "23"), ("status", "Discharging")]);
+        supply("hidpp_battery_0", &[("type", "Battery"), ("scope", "Device"), ("capacity", "90"), ("status", "Charging")]);
+
+        let power = parse_power_supply(&dir);
+        assert_eq!(power, PowerState { on_battery: true, battery_percent: Some(23) });
+        assert!(check_power(&power, false).is_err());
+        assert!(check_power(&power, true).is_ok());
+
+        supply("AC", &[("online", "1")]);
+        assert!(!parse_power_supply(&dir).on_battery);
+
+        let pmset = "Now drawing from 'Battery Power'\n -InternalBattery-0 (id=4653155)\t81%; discharging; 5:12 remaining present: true\n";
+        let power = parse_pmset(pmset);
+        assert_eq!(power, PowerState { on_battery: true, battery_percent: Some(81) });
+        assert!(check_power(&power, false).is_ok());
+        assert!(!parse_pmset("Now drawing from 'AC Power'\n -InternalBattery-0 (id=1)\t100%; charged;\n").on_battery);
+    }
+}
@@ -0,0 +1,77 @@
+// Disk enumeration backends
+//
+// Partition logic reads the disk layout through `DiskProvider` so it can run
+// against the real platform tools (WMI, lsblk, diskutil) or, in tests,
+// against injected fixtures on any OS.
+
+use super::types::*;
+use anyhow::Result;
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+use anyhow::anyhow;
+
+/// Source of disk and partition information
+pub trait DiskProvider: Send + Sync {
+    /// Enumerate all disks with their partitions
+    fn get_disks(&self) -> Result<Vec<DiskInfo>>;
+}
+
+/// Reads disks from the current platform
+pub struct PlatformDiskProvider;
+
+impl DiskProvider for PlatformDiskProvider {
+    fn get_disks(&self) -> Result<Vec<DiskInfo>> {
+        let mut disks = enumerate_platform_disks()?;
+        for disk in &mut disks {
+            disk.update_free_space();
+        }
+        super::safety::mark_system_volume(&mut disks);
+        Ok(disks)
+    }
+}
+
+fn enumerate_platform_disks() -> Result<Vec<DiskInfo>> {
+    #[cfg(target_os = "windows")]
+    {
+        super::platform::windows::get_disks()
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        super::platform::linux::get_disks()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        super::platform::macos::get_disks()
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        Err(anyhow!("Unsupported operating system"))
+    }
+}
+
+/// Returns a fixed set of disks, for exercising partition logic without real hardware
+#[cfg(test)]
+pub struct MockDiskProvider {
+    disks: Vec<DiskInfo>,
+}
+
+#[cfg(test)]
+impl MockDiskProvider {
+    pub fn new(disks: Vec<DiskInfo>) -> Self {
+        Self { disks }
+    }
+}
+
+#[cfg(test)]
+impl DiskProvider for MockDiskProvider {
+    fn get_disks(&self) -> Result<Vec<DiskInfo>> {
+        let mut disks = self.disks.clone();
+        for disk in &mut disks {
+            disk.update_free_space();
+        }
+        Ok(disks)
+    }
+}
@@ -9,23 +9,63 @@
 // 3. Delete E: entirely
 // 4. Expand C: into the freed space
 // 5. Optionally recreate E: at the end with remaining space
+//
+// ReallocationStrategy selects other outcomes, e.g. shrinking a partition
+// just to leave room for a new one the user sets up themselves.
 
 use crate::partition::types::*;
+use crate::partition::delete::delete_partition;
+use crate::partition::info::get_all_disks;
+use crate::partition::resize::expand::expand_partition_with_progress;
+use crate::partition::resize::progress::{ProgressSink, ResizeProgress};
+use crate::partition::resize::shrink::shrink_partition_with_progress;
+use crate::partition::resize::validation::{minimum_shrink_size, propose_shrink_target};
+use crate::partition::transaction::{StepRecord, Transaction, Undo};
 use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+
+/// What the reallocation should achieve
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub enum ReallocationStrategy {
+    /// Free space after the target and expand the target into it
+    #[default]
+    GrowTarget,
+    /// Shrink the partition next to the target and leave the freed space
+    /// unallocated; the target itself is untouched
+    ShrinkNeighborOnly,
+    /// Free a contiguous unallocated block after the target without growing it
+    ConsolidateFreeSpace,
+}
 
 /// Plan for reallocating space from one partition to another
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
 pub struct ReallocationPlan {
-    /// The partition that needs more space (e.g., C:)
+    /// The partition the plan is built around: grown for `GrowTarget`, left
+    /// as-is for `ShrinkNeighborOnly` (its neighbor shrinks) and `ConsolidateFreeSpace`
     pub target_partition_id: String,
 
+    /// The target's stable UUID, so it can be found again after renumbering
+    #[serde(default)]
+    pub target_partition_uuid: Option<String>,
+
+    /// The target's size when the plan was made (bytes)
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub target_current_size: u64,
+
+    pub strategy: ReallocationStrategy,
+
     /// The partition(s) that will be shrunk/deleted to free space
     pub source_partitions: Vec<SourcePartitionPlan>,
 
     /// Total space that will be freed (bytes)
+    #[ts(type = "number")]
     pub total_space_freed: u64,
 
     /// New size for target partition after reallocation (bytes)
+    #[ts(type = "number")]
     pub target_new_size: u64,
 
     /// Steps the user must follow
@@ -33,37 +73,158 @@ pub struct ReallocationPlan {
 
     /// Warnings about this operation
     pub warnings: Vec<String>,
+
+    /// Disk layout after the plan runs, in offset order
+    pub simulated_layout: Vec<LayoutSegment>,
+}
+
+/// A region of the disk in a simulated layout
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct LayoutSegment {
+    /// None for unallocated space
+    pub partition_id: Option<String>,
+    pub label: String,
+    #[ts(type = "number")]
+    pub start_offset: u64,
+    #[ts(type = "number")]
+    pub size: u64,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
 pub struct SourcePartitionPlan {
     pub partition_id: String,
+    #[serde(default)]
+    pub uuid: Option<String>,
     pub partition_label: String,
+    #[ts(type = "number")]
     pub current_size: u64,
+    #[ts(type = "number | null")]
     pub used_space: Option<u64>,
     pub action: SourcePartitionAction,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
 pub enum SourcePartitionAction {
-    /// Shrink partition to this size, then delete
-    ShrinkAndDelete { shrink_to: u64 },
     /// Delete partition entirely
     DeleteEntirely,
     /// Keep partition but shrink it
-    ShrinkOnly { new_size: u64 },
+    ShrinkOnly {
+        #[ts(type = "number")]
+        new_size: u64,
+    },
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
 pub struct ReallocationStep {
     pub step_number: usize,
     pub title: String,
     pub description: String,
     pub action_type: StepActionType,
     pub can_automate: bool,
+    #[serde(default)]
+    pub kind: StepKind,
+    /// The partition a delete, shrink or expand acts on
+    #[serde(default)]
+    pub partition_id: Option<String>,
+}
+
+/// What a step does, for estimating how long it takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub enum StepKind {
+    Backup,
+    Delete,
+    Shrink,
+    Expand,
+    #[default]
+    Create,
+}
+
+impl StepKind {
+    /// Rough relative duration: table edits are quick, filesystem resizes are slow
+    pub fn weight(self) -> f32 {
+        match self {
+            StepKind::Backup => 1.0,
+            StepKind::Delete => 1.0,
+            StepKind::Create => 2.0,
+            StepKind::Expand => 5.0,
+            StepKind::Shrink => 10.0,
+        }
+    }
+}
+
+/// Overall progress of a multi-step plan
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct PlanProgress {
+    /// 0-based index of the running step
+    pub step_index: usize,
+    pub step_count: usize,
+    pub step_title: String,
+    /// Progress of the running step (0-100)
+    pub step_percent: f32,
+    /// Progress of the whole plan (0-100)
+    pub overall_percent: f32,
+    pub message: String,
+}
+
+/// Maps each step's local 0-100 onto its slice of the plan's 0-100
+///
+/// Slices are proportional to the step weights, so the overall bar keeps
+/// moving forward across steps instead of restarting at 0.
+#[derive(Debug, Clone)]
+pub struct PlanProgressTracker {
+    titles: Vec<String>,
+    /// Overall percent at the start of each step, plus 100 at the end
+    boundaries: Vec<f32>,
+}
+
+impl PlanProgressTracker {
+    pub fn new(steps: Vec<(String, f32)>) -> Self {
+        let total: f32 = steps.iter().map(|(_, w)| w.max(0.0)).sum();
+        let mut boundaries = Vec::with_capacity(steps.len() + 1);
+        let mut acc = 0.0;
+        boundaries.push(0.0);
+        for (_, weight) in &steps {
+            acc += if total > 0.0 { weight.max(0.0) / total * 100.0 } else { 100.0 / steps.len() as f32 };
+            boundaries.push(acc.min(100.0));
+        }
+
+        PlanProgressTracker {
+            titles: steps.into_iter().map(|(title, _)| title).collect(),
+            boundaries,
+        }
+    }
+
+    pub fn for_plan(plan: &ReallocationPlan) -> Self {
+        Self::new(plan.steps.iter().map(|s| (s.title.clone(), s.kind.weight())).collect())
+    }
+
+    pub fn progress(&self, step_index: usize, step_percent: f32, message: impl Into<String>) -> PlanProgress {
+        let index = step_index.min(self.titles.len().saturating_sub(1));
+        let step_percent = step_percent.clamp(0.0, 100.0);
+        let (start, end) = match (self.boundaries.get(index), self.boundaries.get(index + 1)) {
+            (Some(&start), Some(&end)) => (start, end),
+            _ => (0.0, 100.0),
+        };
+
+        PlanProgress {
+            step_index: index,
+            step_count: self.titles.len(),
+            step_title: self.titles.get(index).cloned().unwrap_or_default(),
+            step_percent,
+            overall_percent: start + (end - start) * step_percent / 100.0,
+            message: message.into(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
 pub enum StepActionType {
     UserManual,       // User must do this manually
     AppAutomated,     // App can do this automatically
@@ -75,6 +236,7 @@ pub fn create_reallocation_plan(
     disk: &DiskInfo,
     target_partition_id: &str,
     desired_additional_space: u64,
+    strategy: ReallocationStrategy,
 ) -> Result<ReallocationPlan> {
     // Find the target partition (e.g., C:)
     let target_partition = disk
@@ -83,6 +245,35 @@ pub fn create_reallocation_plan(
         .find(|p| p.id == target_partition_id)
         .ok_or_else(|| anyhow!("Target partition not found"))?;
 
+    let mut plan = match strategy {
+        ReallocationStrategy::ShrinkNeighborOnly => {
+            plan_shrink_neighbor(disk, target_partition, desired_additional_space)?
+        }
+        ReallocationStrategy::GrowTarget | ReallocationStrategy::ConsolidateFreeSpace => {
+            plan_free_after_target(disk, target_partition, desired_additional_space, strategy)?
+        }
+    };
+
+    plan.simulated_layout = simulate_layout(disk, &plan);
+    Ok(plan)
+}
+
+/// Free space after the target by deleting the partitions that follow it,
+/// then either grow the target into it or leave it for a new partition
+fn plan_free_after_target(
+    disk: &DiskInfo,
+    target_partition: &PartitionInfo,
+    desired_additional_space: u64,
+    strategy: ReallocationStrategy,
+) -> Result<ReallocationPlan> {
+    let target_partition_id = target_partition.id.as_str();
+    let grow_target = strategy == ReallocationStrategy::GrowTarget;
+    let target_new_size = if grow_target {
+        target_partition.total_size + desired_additional_space
+    } else {
+        target_partition.total_size
+    };
+
     // Find partitions that are blocking expansion (between target and free space)
     let target_end = target_partition.start_offset + target_partition.total_size;
 
@@ -106,26 +297,36 @@ pub fn create_reallocation_plan(
         }
 
         // Simple case: just expand into unallocated space
+        let step = if grow_target {
+            ReallocationStep {
+                step_number: 1,
+                title: "Expand partition".to_string(),
+                description: format!(
+                    "Expand {} from {} to {}",
+                    target_partition.display_name(),
+                    format_bytes(target_partition.total_size),
+                    format_bytes(target_new_size)
+                ),
+                action_type: StepActionType::AppAutomated,
+                can_automate: true,
+                kind: StepKind::Expand,
+                partition_id: Some(target_partition.id.clone()),
+            }
+        } else {
+            create_partition_step(1, available_space)
+        };
+
         return Ok(ReallocationPlan {
             target_partition_id: target_partition_id.to_string(),
+            target_partition_uuid: target_partition.uuid.clone(),
+            target_current_size: target_partition.total_size,
+            strategy,
             source_partitions: vec![],
             total_space_freed: available_space,
-            target_new_size: target_partition.total_size + desired_additional_space,
-            steps: vec![
-                ReallocationStep {
-                    step_number: 1,
-                    title: "Expand partition".to_string(),
-                    description: format!(
-                        "Expand {} from {} to {}",
-                        target_partition.device_path,
-                        format_bytes(target_partition.total_size),
-                        format_bytes(target_partition.total_size + desired_additional_space)
-                    ),
-                    action_type: StepActionType::AppAutomated,
-                    can_automate: true,
-                },
-            ],
+            target_new_size,
+            steps: vec![step],
             warnings: vec![],
+            simulated_layout: vec![],
         });
     }
 
@@ -140,20 +341,38 @@ pub fn create_reallocation_plan(
             break;
         }
 
-        let has_data = partition.used_space.map(|used| used > 0).unwrap_or(false);
+        if partition.flags.contains(&PartitionFlag::Reserved) {
+            return Err(anyhow!(
+                "Partition {} is a reserved system partition (Microsoft Reserved or BIOS boot) and cannot be deleted to free space",
+                partition.display_name()
+            ));
+        }
 
-        if has_data {
+        if partition.filesystem == FilesystemType::Swap {
             warnings.push(format!(
-                "Partition {} ({}) contains {} of data. YOU MUST BACKUP THIS DATA before proceeding!",
-                partition.device_path,
-                partition.label.as_ref().unwrap_or(&"Unlabeled".to_string()),
-                format_bytes(partition.used_space.unwrap_or(0))
+                "Partition {} is a swap partition. Disable it and remove it from /etc/fstab before it is deleted.",
+                partition.display_name()
             ));
         }
 
+        // Unknown usage (unmounted and unprobeable) is treated as full
+        match partition.used_space {
+            Some(used) if used > 0 => warnings.push(format!(
+                "Partition {} contains {} of data. YOU MUST BACKUP THIS DATA before proceeding!",
+                partition.display_name(),
+                format_bytes(used)
+            )),
+            Some(_) => {}
+            None => warnings.push(format!(
+                "Partition {} may contain data; its used space could not be determined. YOU MUST BACKUP THIS DATA before proceeding!",
+                partition.display_name()
+            )),
+        }
+
         source_partitions.push(SourcePartitionPlan {
             partition_id: partition.id.clone(),
-            partition_label: partition.label.clone().unwrap_or_else(|| partition.device_path.clone()),
+            uuid: partition.uuid.clone(),
+            partition_label: partition.display_name(),
             current_size: partition.total_size,
             used_space: partition.used_space,
             action: SourcePartitionAction::DeleteEntirely,
@@ -190,6 +409,8 @@ pub fn create_reallocation_plan(
             ),
             action_type: StepActionType::UserManual,
             can_automate: false,
+            kind: StepKind::Backup,
+            partition_id: None,
         });
         step_num += 1;
     }
@@ -206,53 +427,541 @@ pub fn create_reallocation_plan(
             ),
             action_type: StepActionType::AppAssistedManual,
             can_automate: true,
+            kind: StepKind::Delete,
+            partition_id: Some(source.partition_id.clone()),
         });
         step_num += 1;
     }
 
-    // Expand target partition
-    steps.push(ReallocationStep {
-        step_number: step_num,
-        title: format!("Expand {}", target_partition.device_path),
-        description: format!(
-            "Expand {} from {} to {} (+{})",
-            target_partition.device_path,
-            format_bytes(target_partition.total_size),
-            format_bytes(target_partition.total_size + desired_additional_space),
-            format_bytes(desired_additional_space)
-        ),
-        action_type: StepActionType::AppAutomated,
-        can_automate: true,
-    });
+    if grow_target {
+        // Expand target partition
+        steps.push(ReallocationStep {
+            step_number: step_num,
+            title: format!("Expand {}", target_partition.display_name()),
+            description: format!(
+                "Expand {} from {} to {} (+{})",
+                target_partition.display_name(),
+                format_bytes(target_partition.total_size),
+                format_bytes(target_new_size),
+                format_bytes(desired_additional_space)
+            ),
+            action_type: StepActionType::AppAutomated,
+            can_automate: true,
+            kind: StepKind::Expand,
+            partition_id: Some(target_partition.id.clone()),
+        });
+    } else {
+        steps.push(create_partition_step(step_num, total_freed));
+    }
 
     Ok(ReallocationPlan {
         target_partition_id: target_partition_id.to_string(),
+        target_partition_uuid: target_partition.uuid.clone(),
+        target_current_size: target_partition.total_size,
+        strategy,
         source_partitions,
         total_space_freed: total_freed,
-        target_new_size: target_partition.total_size + desired_additional_space,
+        target_new_size,
         steps,
         warnings,
+        simulated_layout: vec![],
     })
 }
 
-/// Format bytes to human-readable string
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    if bytes == 0 {
-        return "0 B".to_string();
+/// Shrink the target's neighbor by the requested amount and leave the space unallocated
+///
+/// The neighbor is the partition right after the target, or right before it
+/// when the target is last on the disk. The target keeps its size.
+fn plan_shrink_neighbor(disk: &DiskInfo, target_partition: &PartitionInfo, space_to_free: u64) -> Result<ReallocationPlan> {
+    let mut partitions: Vec<&PartitionInfo> = disk.partitions.iter().collect();
+    partitions.sort_by_key(|p| p.start_offset);
+    let index = partitions
+        .iter()
+        .position(|p| p.id == target_partition.id)
+        .ok_or_else(|| anyhow!("Target partition not found"))?;
+    let neighbor = partitions
+        .get(index + 1)
+        .or_else(|| index.checked_sub(1).and_then(|i| partitions.get(i)))
+        .copied()
+        .ok_or_else(|| anyhow!("{} has no neighboring partition to shrink", target_partition.display_name()))?;
+
+    if neighbor.flags.contains(&PartitionFlag::Reserved) {
+        return Err(anyhow!(
+            "Partition {} is a reserved system partition (Microsoft Reserved or BIOS boot) and cannot be shrunk",
+            neighbor.display_name()
+        ));
+    }
+
+    if space_to_free >= neighbor.total_size {
+        return Err(anyhow!(
+            "Cannot free {} from {}: the partition is only {}",
+            format_bytes(space_to_free),
+            neighbor.display_name(),
+            format_bytes(neighbor.total_size)
+        ));
     }
 
-    let base = 1024_f64;
-    let exp = (bytes as f64).log(base).floor() as usize;
-    let exp = exp.min(UNITS.len() - 1);
-    let value = bytes as f64 / base.powi(exp as i32);
+    let proposal = propose_shrink_target(neighbor, space_to_free)?;
+    if !proposal.achievable {
+        return Err(anyhow!("{}", proposal.reason.unwrap_or_default()));
+    }
+    let new_size = proposal.target_size;
+    let space_freed = proposal.space_freed;
+
+    let label = neighbor.display_name();
+
+    let steps = vec![
+        ReallocationStep {
+            step_number: 1,
+            title: format!("Shrink {}", label),
+            description: format!(
+                "Shrink {} from {} to {} (frees {})",
+                label,
+                format_bytes(neighbor.total_size),
+                format_bytes(new_size),
+                format_bytes(space_freed)
+            ),
+            action_type: StepActionType::AppAutomated,
+            can_automate: true,
+            kind: StepKind::Shrink,
+            partition_id: Some(neighbor.id.clone()),
+        },
+        create_partition_step(2, space_freed),
+    ];
+
+    Ok(ReallocationPlan {
+        target_partition_id: target_partition.id.clone(),
+        target_partition_uuid: target_partition.uuid.clone(),
+        target_current_size: target_partition.total_size,
+        strategy: ReallocationStrategy::ShrinkNeighborOnly,
+        source_partitions: vec![SourcePartitionPlan {
+            partition_id: neighbor.id.clone(),
+            uuid: neighbor.uuid.clone(),
+            partition_label: label,
+            current_size: neighbor.total_size,
+            used_space: neighbor.used_space,
+            action: SourcePartitionAction::ShrinkOnly { new_size },
+        }],
+        total_space_freed: space_freed,
+        target_new_size: target_partition.total_size,
+        steps,
+        warnings: proposal.warnings,
+        simulated_layout: vec![],
+    })
+}
+
+/// Whether a plan still matches the disk it was made for
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct PlanValidation {
+    /// No blocking issues: the plan can run as-is
+    pub is_valid: bool,
+    pub issues: Vec<PlanIssue>,
+    /// Plan partition id -> current id, for partitions renumbered since planning
+    pub renumbered: HashMap<String, String>,
+}
 
-    format!("{:.2} {}", value, UNITS[exp])
+/// Something that changed on disk since a plan was made
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export)]
+pub struct PlanIssue {
+    pub partition_id: String,
+    /// The plan step this makes unsafe, if it maps to one
+    pub step_number: Option<usize>,
+    pub message: String,
+    /// The plan must be recreated before it runs
+    pub blocking: bool,
+}
+
+/// Re-check a plan against a fresh enumeration of the disks
+///
+/// Partitions are matched by UUID where the plan recorded one, so a
+/// renumbered partition is still recognised (and reported in `renumbered`).
+/// Any size change, a to-be-deleted partition gaining data, a shrink target
+/// the partition no longer fits in, or a new partition in the space the
+/// target grows into blocks the plan.
+pub fn validate_reallocation_plan(plan: &ReallocationPlan, disks: &[DiskInfo]) -> PlanValidation {
+    let mut validation = PlanValidation {
+        is_valid: true,
+        issues: Vec::new(),
+        renumbered: HashMap::new(),
+    };
+
+    let step_of = |kind: StepKind, partition_id: &str| {
+        plan.steps
+            .iter()
+            .find(|s| s.kind == kind && s.partition_id.as_deref() == Some(partition_id))
+            .map(|s| s.step_number)
+    };
+    let issue = |validation: &mut PlanValidation, partition_id: &str, step_number, message: String, blocking| {
+        validation.is_valid &= !blocking;
+        validation.issues.push(PlanIssue {
+            partition_id: partition_id.to_string(),
+            step_number,
+            message,
+            blocking,
+        });
+    };
+
+    for source in &plan.source_partitions {
+        let (kind, deletes) = match source.action {
+            SourcePartitionAction::ShrinkOnly { .. } => (StepKind::Shrink, false),
+            SourcePartitionAction::DeleteEntirely => (StepKind::Delete, true),
+        };
+        let step = step_of(kind, &source.partition_id);
+
+        let Some((_, current)) = find_partition(disks, &source.partition_id, source.uuid.as_deref()) else {
+            issue(&mut validation, &source.partition_id, step, format!("{} no longer exists.", source.partition_label), true);
+            continue;
+        };
+        if current.id != source.partition_id {
+            validation.renumbered.insert(source.partition_id.clone(), current.id.clone());
+        }
+
+        if current.total_size != source.current_size {
+            issue(
+                &mut validation,
+                &source.partition_id,
+                step,
+                format!(
+                    "{} was resized from {} to {} since the plan was made.",
+                    source.partition_label,
+                    format_bytes(source.current_size),
+                    format_bytes(current.total_size)
+                ),
+                true,
+            );
+            continue;
+        }
+
+        if deletes {
+            match (source.used_space, current.used_space) {
+                (Some(planned), Some(now)) if now > planned => issue(
+                    &mut validation,
+                    &source.partition_id,
+                    step,
+                    format!(
+                        "{} gained {} of data since the plan was made. Back it up again before deleting it.",
+                        source.partition_label,
+                        format_bytes(now - planned)
+                    ),
+                    true,
+                ),
+                (Some(_), None) => issue(
+                    &mut validation,
+                    &source.partition_id,
+                    step,
+                    format!(
+                        "{}'s used space can no longer be determined; make sure your backup is current.",
+                        source.partition_label
+                    ),
+                    false,
+                ),
+                _ => {}
+            }
+        }
+
+        if let SourcePartitionAction::ShrinkOnly { new_size } = source.action {
+            if let Some(minimum) = minimum_shrink_size(current).filter(|&m| m > new_size) {
+                issue(
+                    &mut validation,
+                    &source.partition_id,
+                    step,
+                    format!(
+                        "{} now needs at least {}, more than the planned size of {}.",
+                        source.partition_label,
+                        format_bytes(minimum),
+                        format_bytes(new_size)
+                    ),
+                    true,
+                );
+            }
+        }
+    }
+
+    let expand_step = step_of(StepKind::Expand, &plan.target_partition_id);
+    match find_partition(disks, &plan.target_partition_id, plan.target_partition_uuid.as_deref()) {
+        None => issue(
+            &mut validation,
+            &plan.target_partition_id,
+            expand_step,
+            "The target partition no longer exists.".to_string(),
+            true,
+        ),
+        Some((disk, target)) => {
+            if target.id != plan.target_partition_id {
+                validation.renumbered.insert(plan.target_partition_id.clone(), target.id.clone());
+            }
+
+            // Plans made before the size was recorded can't be checked for this
+            if plan.target_current_size != 0 && target.total_size != plan.target_current_size {
+                issue(
+                    &mut validation,
+                    &plan.target_partition_id,
+                    expand_step,
+                    format!(
+                        "The target partition was resized from {} to {} since the plan was made.",
+                        format_bytes(plan.target_current_size),
+                        format_bytes(target.total_size)
+                    ),
+                    true,
+                );
+            } else if plan.strategy == ReallocationStrategy::GrowTarget {
+                // Nothing outside the plan may sit where the target grows into
+                let grow_start = target.start_offset + target.total_size;
+                let grow_end = target.start_offset + plan.target_new_size;
+                let planned = |p: &PartitionInfo| {
+                    plan.source_partitions
+                        .iter()
+                        .any(|s| is_same_partition(p, &s.partition_id, s.uuid.as_deref()))
+                };
+
+                if let Some(blocker) = disk.partitions.iter().find(|p| {
+                    p.id != target.id && !planned(p) && p.start_offset < grow_end && p.start_offset + p.total_size > grow_start
+                }) {
+                    issue(
+                        &mut validation,
+                        &blocker.id,
+                        expand_step,
+                        format!(
+                            "{} now occupies the space {} would expand into.",
+                            blocker.display_name(),
+                            target.display_name()
+                        ),
+                        true,
+                    );
+                }
+            }
+        }
+    }
+
+    validation
+}
+
+/// Run a plan's automated steps as one transaction
+///
+/// The plan should have passed `validate_reallocation_plan` just before.
+/// Partitions are looked up again (by UUID where the plan has one) before
+/// every step, since a delete can renumber the partitions after it, and each
+/// disk's partition table is saved before its first change. When a step
+/// fails, completed shrinks are grown back; deletes and expands can't be
+/// undone automatically, so the error is a `PartialFailure` saying what is
+/// still in effect and what is left to do by hand.
+pub async fn execute_reallocation_plan(
+    plan: &ReallocationPlan,
+    on_plan: &(dyn Fn(PlanProgress) + Sync),
+    on_resize: ProgressSink<'_>,
+) -> Result<()> {
+    let tracker = PlanProgressTracker::for_plan(plan);
+    let mut transaction = Transaction::new("Space reallocation");
+
+    for (index, step) in plan.steps.iter().enumerate() {
+        on_plan(tracker.progress(index, 0.0, step.title.clone()));
+        if !step.can_automate {
+            continue;
+        }
+
+        let report = |progress: ResizeProgress| {
+            on_plan(tracker.progress(index, progress.percent, progress.message.clone()));
+            on_resize(progress);
+        };
+        if let Err(e) = run_step(plan, step, &mut transaction, &report).await {
+            let failure = transaction.rollback(&step.title, e, None).await;
+            return Err(failure.into());
+        }
+        on_plan(tracker.progress(index, 100.0, format!("{} done", step.title)));
+    }
+
+    Ok(())
+}
+
+/// Run one automated step and record how to undo it
+async fn run_step(
+    plan: &ReallocationPlan,
+    step: &ReallocationStep,
+    transaction: &mut Transaction,
+    on_progress: ProgressSink<'_>,
+) -> Result<()> {
+    let disks = get_all_disks()?;
+
+    match step.kind {
+        StepKind::Delete | StepKind::Shrink => {
+            let shrinks = step.kind == StepKind::Shrink;
+            let source = plan
+                .source_partitions
+                .iter()
+                .find(|s| {
+                    step.partition_id.as_deref() == Some(s.partition_id.as_str())
+                        && matches!(s.action, SourcePartitionAction::ShrinkOnly { .. }) == shrinks
+                })
+                .ok_or_else(|| anyhow!("\"{}\" doesn't match any partition in the plan", step.title))?;
+            let (disk, partition) = find_partition(&disks, &source.partition_id, source.uuid.as_deref())
+                .ok_or_else(|| anyhow!("{} no longer exists", source.partition_label))?;
+            transaction.snapshot(disk)?;
+
+            if let SourcePartitionAction::ShrinkOnly { new_size } = source.action {
+                shrink_partition_with_progress(partition, disk, new_size, on_progress).await?;
+                transaction.record(
+                    StepRecord {
+                        description: format!("shrink {}", source.partition_label),
+                        before: format_bytes(partition.total_size),
+                        after: format_bytes(new_size),
+                    },
+                    Undo::Regrow {
+                        disk_path: disk.device_path.clone(),
+                        start_offset: partition.start_offset,
+                        original_size: partition.total_size,
+                    },
+                );
+            } else {
+                on_progress(ResizeProgress::updating_partition_table(format!(
+                    "Deleting {}...",
+                    source.partition_label
+                )));
+                delete_partition(partition, disk)?;
+                transaction.record(
+                    StepRecord {
+                        description: format!("delete {}", source.partition_label),
+                        before: format!("{} at offset {}", format_bytes(partition.total_size), partition.start_offset),
+                        after: "unallocated".to_string(),
+                    },
+                    Undo::Manual(format!(
+                        "recreate {} at offset {} with {} bytes; its data stays intact until something else is written there",
+                        source.partition_label, partition.start_offset, partition.total_size
+                    )),
+                );
+            }
+        }
+        StepKind::Expand => {
+            let (disk, target) = find_partition(&disks, &plan.target_partition_id, plan.target_partition_uuid.as_deref())
+                .ok_or_else(|| anyhow!("The target partition no longer exists"))?;
+            transaction.snapshot(disk)?;
+
+            expand_partition_with_progress(target, disk, plan.target_new_size, on_progress).await?;
+            transaction.record(
+                StepRecord {
+                    description: format!("expand {}", target.display_name()),
+                    before: format_bytes(target.total_size),
+                    after: format_bytes(plan.target_new_size),
+                },
+                Undo::Manual(format!(
+                    "shrink {} back to {} bytes if you need the space elsewhere",
+                    target.display_name(),
+                    target.total_size
+                )),
+            );
+        }
+        // Never automated: the user backs up and creates partitions themselves
+        StepKind::Backup | StepKind::Create => {}
+    }
+
+    Ok(())
+}
+
+/// Match by UUID when one was recorded, otherwise by id
+fn is_same_partition(partition: &PartitionInfo, id: &str, uuid: Option<&str>) -> bool {
+    match uuid {
+        Some(uuid) => partition.uuid.as_deref() == Some(uuid),
+        None => partition.id == id,
+    }
+}
+
+fn find_partition<'a>(
+    disks: &'a [DiskInfo],
+    id: &str,
+    uuid: Option<&str>,
+) -> Option<(&'a DiskInfo, &'a PartitionInfo)> {
+    disks.iter().find_map(|disk| {
+        disk.partitions
+            .iter()
+            .find(|p| is_same_partition(p, id, uuid))
+            .map(|p| (disk, p))
+    })
+}
+
+/// Final step for strategies that leave the freed space for the user
+fn create_partition_step(step_number: usize, free_space: u64) -> ReallocationStep {
+    ReallocationStep {
+        step_number,
+        title: "Create a new partition".to_string(),
+        description: format!(
+            "{} of unallocated space is now available. Create and format a new partition in it, or leave it unallocated for later.",
+            format_bytes(free_space)
+        ),
+        action_type: StepActionType::UserManual,
+        can_automate: false,
+        kind: StepKind::Create,
+        partition_id: None,
+    }
+}
+
+/// Work out the disk layout after the plan's deletes/shrinks/expands
+fn simulate_layout(disk: &DiskInfo, plan: &ReallocationPlan) -> Vec<LayoutSegment> {
+    let mut layout: Vec<LayoutSegment> = disk
+        .partitions
+        .iter()
+        .filter_map(|partition| {
+            let source = plan.source_partitions.iter().find(|s| s.partition_id == partition.id);
+
+            let size = match source.map(|s| &s.action) {
+                Some(SourcePartitionAction::DeleteEntirely) => return None,
+                Some(SourcePartitionAction::ShrinkOnly { new_size }) => *new_size,
+                None if partition.id == plan.target_partition_id => plan.target_new_size,
+                None => partition.total_size,
+            };
+
+            Some(LayoutSegment {
+                partition_id: Some(partition.id.clone()),
+                label: partition.label.clone().unwrap_or_else(|| partition.device_path.clone()),
+                start_offset: partition.start_offset,
+                size,
+            })
+        })
+        .collect();
+
+    let free = free_regions(disk.total_size, layout.iter().map(|s| (s.start_offset, s.size)));
+    layout.extend(free.into_iter().map(|(start_offset, size)| LayoutSegment {
+        partition_id: None,
+        label: "Unallocated".to_string(),
+        start_offset,
+        size,
+    }));
+    layout.sort_by_key(|s| s.start_offset);
+    layout
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{mock_disk, mock_partition, GB};
+
+    /// [C: 50GB FULL] [E: 20GB, 1GB used] [30GB unallocated]
+    fn c_and_e_disk() -> DiskInfo {
+        let c = PartitionInfo {
+            device_path: "C:".to_string(),
+            label: Some("System".to_string()),
+            total_size: 50 * GB,
+            used_space: Some(50 * GB), // FULL
+            filesystem: FilesystemType::NTFS,
+            mount_point: Some("C:".to_string()),
+            is_mounted: true,
+            flags: vec![PartitionFlag::Boot, PartitionFlag::System],
+            ..mock_partition("part-c")
+        };
+        let e = PartitionInfo {
+            number: 2,
+            device_path: "E:".to_string(),
+            label: Some("Data".to_string()),
+            start_offset: 50 * GB + 1024 * 1024,
+            total_size: 20 * GB,
+            used_space: Some(GB),
+            filesystem: FilesystemType::NTFS,
+            mount_point: Some("E:".to_string()),
+            is_mounted: true,
+            ..mock_partition("part-e")
+        };
+        mock_disk("\\\\.\\PhysicalDrive0", vec![c, e])
+    }
 
     #[test]
     fn test_simple_reallocation_plan() {
@@ -260,55 +969,134 @@ mod tests {
         // [C: 50GB FULL] [E: 20GB empty] [F: 30GB]
         // Want to give C: 15GB more space
 
-        let disk = DiskInfo {
-            id: "disk-0".to_string(),
-            device_path: "\\\\.\\PhysicalDrive0".to_string(),
-            model: "Test Disk".to_string(),
-            total_size: 100 * 1024 * 1024 * 1024, // 100GB
-            table_type: PartitionTableType::GPT,
-            partitions: vec![
-                PartitionInfo {
-                    id: "part-c".to_string(),
-                    number: 1,
-                    device_path: "C:".to_string(),
-                    label: Some("System".to_string()),
-                    start_offset: 1024 * 1024,
-                    total_size: 50 * 1024 * 1024 * 1024, // 50GB
-                    used_space: Some(50 * 1024 * 1024 * 1024), // FULL
-                    partition_type: PartitionType::Primary,
-                    filesystem: FilesystemType::NTFS,
-                    mount_point: Some("C:".to_string()),
-                    is_mounted: true,
-                    flags: vec![PartitionFlag::Boot, PartitionFlag::System],
-                },
-                PartitionInfo {
-                    id: "part-e".to_string(),
-                    number: 2,
-                    device_path: "E:".to_string(),
-                    label: Some("Data".to_string()),
-                    start_offset: 50 * 1024 * 1024 * 1024 + 1024 * 1024,
-                    total_size: 20 * 1024 * 1024 * 1024, // 20GB
-                    used_space: Some(1 * 1024 * 1024 * 1024), // 1GB used
-                    partition_type: PartitionType::Primary,
-                    filesystem: FilesystemType::NTFS,
-                    mount_point: Some("E:".to_string()),
-                    is_mounted: true,
-                    flags: vec![],
-                },
-            ],
-            serial_number: None,
-            status: DiskStatus {
-                is_online: true,
-                has_errors: false,
-                smart_status: None,
-            },
-        };
+        let disk = c_and_e_disk();
 
-        let plan = create_reallocation_plan(&disk, "part-c", 15 * 1024 * 1024 * 1024).unwrap();
+        let plan = create_reallocation_plan(&disk, "part-c", 15 * GB, ReallocationStrategy::GrowTarget).unwrap();
 
         assert_eq!(plan.source_partitions.len(), 1);
         assert_eq!(plan.source_partitions[0].partition_id, "part-e");
         assert!(plan.warnings.len() > 0); // Should warn about data on E:
         assert!(plan.steps.len() >= 3); // Backup warning + delete + expand
+
+        // Partitions are named the same way in steps and warnings
+        assert_eq!(plan.source_partitions[0].partition_label, "Data (E:) — 20.00 GB NTFS");
+        assert!(plan.warnings[0].starts_with("Partition Data (E:) — 20.00 GB NTFS contains 1.00 GB"));
+        assert!(plan.steps.iter().any(|s| s.title == "Delete partition Data (E:) — 20.00 GB NTFS"));
+        assert!(plan.steps.iter().any(|s| s.title == "Expand System (C:) — 50.00 GB NTFS"));
+    }
+
+    #[test]
+    fn test_validate_plan_detects_changes_since_planning() {
+        let mut disk = c_and_e_disk();
+        disk.partitions[0].uuid = Some("c-uuid".to_string());
+        disk.partitions[1].uuid = Some("e-uuid".to_string());
+        let plan = create_reallocation_plan(&disk, "part-c", 15 * GB, ReallocationStrategy::GrowTarget).unwrap();
+
+        let validation = validate_reallocation_plan(&plan, &[disk.clone()]);
+        assert!(validation.is_valid);
+        assert!(validation.issues.is_empty());
+
+        // Renumbered partitions are still found by UUID
+        disk.partitions[1].id = "part-e-renumbered".to_string();
+        let validation = validate_reallocation_plan(&plan, &[disk.clone()]);
+        assert!(validation.is_valid);
+        assert_eq!(validation.renumbered.get("part-e").map(String::as_str), Some("part-e-renumbered"));
+
+        // E: gained data after the user backed it up
+        disk.partitions[1].used_space = Some(3 * GB);
+        let validation = validate_reallocation_plan(&plan, &[disk.clone()]);
+        assert!(!validation.is_valid);
+        let issue = &validation.issues[0];
+        assert!(issue.blocking);
+        assert!(issue.message.contains("gained"));
+        let delete_step = plan.steps.iter().find(|s| s.kind == StepKind::Delete).unwrap();
+        assert_eq!(issue.step_number, Some(delete_step.step_number));
+
+        disk.partitions.remove(1);
+        let validation = validate_reallocation_plan(&plan, &[disk]);
+        assert!(!validation.is_valid);
+        assert!(validation.issues[0].message.contains("no longer exists"));
+    }
+
+    #[test]
+    fn test_reserved_partition_blocks_plan() {
+        // [C: 50GB] [MSR 16MB] - the reserved partition must not be deleted
+        let partition = |id: &str, start_offset: u64, total_size: u64, flags: Vec<PartitionFlag>| PartitionInfo {
+            device_path: id.to_string(),
+            start_offset,
+            total_size,
+            filesystem: FilesystemType::Unknown,
+            flags,
+            ..mock_partition(id)
+        };
+
+        let disk = mock_disk(
+            "\\\\.\\PhysicalDrive0",
+            vec![
+                partition("part-c", 0, 50 * GB, vec![]),
+                partition("part-msr", 50 * GB, 16 * 1024 * 1024, vec![PartitionFlag::Reserved]),
+            ],
+        );
+
+        assert!(create_reallocation_plan(&disk, "part-c", GB, ReallocationStrategy::GrowTarget).is_err());
+    }
+
+    #[test]
+    fn test_shrink_neighbor_only_plan() {
+        let disk = c_and_e_disk();
+
+        // C: is the target; E:, the partition after it, is shrunk
+        let plan = create_reallocation_plan(&disk, "part-c", 10 * GB, ReallocationStrategy::ShrinkNeighborOnly).unwrap();
+
+        assert_eq!(plan.target_new_size, 50 * GB);
+        assert_eq!(plan.source_partitions[0].partition_id, "part-e");
+        assert!(matches!(plan.source_partitions[0].action, SourcePartitionAction::ShrinkOnly { new_size } if new_size == 10 * GB));
+        assert!(!plan.steps.iter().any(|s| s.kind == StepKind::Expand));
+
+        // C: untouched, E: shrunk, everything after it unallocated
+        let free: Vec<_> = plan.simulated_layout.iter().filter(|s| s.partition_id.is_none()).collect();
+        assert_eq!(free.len(), 1);
+        assert_eq!(free[0].start_offset, 50 * GB + 1024 * 1024 + 10 * GB);
+        assert_eq!(free[0].size, 40 * GB - 1024 * 1024);
+
+        // E: is last, so its neighbor C: would shrink, but C: is full
+        assert!(create_reallocation_plan(&disk, "part-e", 10 * GB, ReallocationStrategy::ShrinkNeighborOnly).is_err());
+    }
+
+    #[test]
+    fn test_consolidate_free_space_plan() {
+        let disk = c_and_e_disk();
+
+        let plan = create_reallocation_plan(&disk, "part-c", 15 * GB, ReallocationStrategy::ConsolidateFreeSpace).unwrap();
+
+        assert_eq!(plan.target_new_size, 50 * GB);
+        assert_eq!(plan.source_partitions[0].partition_id, "part-e");
+        assert!(!plan.steps.iter().any(|s| s.kind == StepKind::Expand));
+
+        let ids: Vec<_> = plan.simulated_layout.iter().map(|s| s.partition_id.as_deref()).collect();
+        assert_eq!(ids, vec![Some("part-c"), None]);
+        assert_eq!(plan.simulated_layout[1].size, 50 * GB - 1024 * 1024);
+    }
+
+    #[test]
+    fn test_plan_progress_is_weighted_and_continuous() {
+        let tracker = PlanProgressTracker::new(vec![
+            ("Delete".to_string(), StepKind::Delete.weight()),
+            ("Shrink".to_string(), StepKind::Shrink.weight()),
+        ]);
+
+        let start = tracker.progress(0, 0.0, "");
+        let end_of_delete = tracker.progress(0, 100.0, "");
+        let start_of_shrink = tracker.progress(1, 0.0, "");
+        let halfway_shrink = tracker.progress(1, 50.0, "");
+        let done = tracker.progress(1, 100.0, "");
+
+        assert_eq!(start.overall_percent, 0.0);
+        assert!((end_of_delete.overall_percent - 100.0 / 11.0).abs() < 0.01);
+        assert_eq!(end_of_delete.overall_percent, start_of_shrink.overall_percent);
+        assert!((halfway_shrink.overall_percent - (100.0 / 11.0 + 50.0 * 10.0 / 11.0)).abs() < 0.01);
+        assert!((done.overall_percent - 100.0).abs() < 0.01);
+        assert_eq!(halfway_shrink.step_title, "Shrink");
+        assert_eq!(halfway_shrink.step_count, 2);
     }
 }
@@ -0,0 +1,150 @@
+// Cancelling an in-flight resize
+//
+// A resize can be stopped while it is validating or running its read-only
+// filesystem check (`e2fsck -n`, `ntfsresize --no-action`): nothing has been
+// written yet, so killing the check changes nothing. Before the first tool
+// that writes to the filesystem or partition table runs (including the
+// repairing `e2fsck -y`), the operation calls `commit` and from then on
+// `cancel_resize` refuses, since killing a repair, resize2fs or parted
+// midway can leave the partition unusable. Jobs are keyed by partition id,
+// like the partition locks.
+
+use crate::partition::exec::{ChildSlot, RunCommand};
+use crate::partition::resize::ResizeError;
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use std::process::{Command, Output};
+use std::sync::{Arc, Mutex};
+
+type Result<T> = std::result::Result<T, ResizeError>;
+
+lazy_static! {
+    /// Resizes that are running, by partition id
+    static ref JOBS: Mutex<HashMap<String, Arc<JobState>>> = Mutex::new(HashMap::new());
+}
+
+#[derive(Debug, Default)]
+struct JobState {
+    flags: Mutex<JobFlags>,
+    check: ChildSlot, // Filesystem check being run, if any
+}
+
+#[derive(Debug, Default)]
+struct JobFlags {
+    cancelled: bool,
+    committed: bool,
+}
+
+/// Registers a resize as cancellable until dropped
+pub struct ResizeJob(String);
+
+impl ResizeJob {
+    pub fn register(partition_id: &str) -> Self {
+        let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.insert(partition_id.to_string(), Arc::default());
+        ResizeJob(partition_id.to_string())
+    }
+}
+
+impl Drop for ResizeJob {
+    fn drop(&mut self) {
+        let mut jobs = JOBS.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.remove(&self.0);
+    }
+}
+
+fn job(partition_id: &str) -> Option<Arc<JobState>> {
+    JOBS.lock().unwrap_or_else(|e| e.into_inner()).get(partition_id).cloned()
+}
+
+impl JobState {
+    fn flags(&self) -> std::sync::MutexGuard<'_, JobFlags> {
+        self.flags.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Ask the resize running on `partition_id` to stop
+///
+/// Kills the filesystem check if one is running; the operation itself fails
+/// with `ResizeError::Cancelled` at its next step.
+pub fn cancel_resize(partition_id: &str) -> Result<()> {
+    let Some(job) = job(partition_id) else {
+        return Err(ResizeError::NotCancellable(format!("No resize is running on {}", partition_id)));
+    };
+
+    {
+        let mut flags = job.flags();
+        if flags.committed {
+            return Err(ResizeError::NotCancellable(format!(
+                "The resize of {} can no longer be cancelled: the filesystem or partition table is being changed, and stopping now could leave it unusable",
+                partition_id
+            )));
+        }
+        flags.cancelled = true;
+    }
+    job.check.kill();
+    Ok(())
+}
+
+/// Fail with `Cancelled` if the resize on `partition_id` has been cancelled
+pub fn checkpoint(partition_id: &str) -> Result<()> {
+    match job(partition_id) {
+        Some(job) if job.flags().cancelled => Err(ResizeError::Cancelled),
+        _ => Ok(()),
+    }
+}
+
+/// Pass the point of no return: after this the resize can't be cancelled
+///
+/// Fails with `Cancelled` instead when a cancel got in first.
+pub fn commit(partition_id: &str) -> Result<()> {
+    if let Some(job) = job(partition_id) {
+        let mut flags = job.flags();
+        if flags.cancelled {
+            return Err(ResizeError::Cancelled);
+        }
+        flags.committed = true;
+    }
+    Ok(())
+}
+
+/// Run a read-only filesystem check that `cancel_resize` can kill
+///
+/// Only for checks that don't write: a killed repair could leave the
+/// filesystem inconsistent. Without a registered job (split, undo) the check
+/// simply runs.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub(crate) fn run_check(partition_id: &str, command: &mut Command) -> Result<Output> {
+    let Some(job) = job(partition_id) else {
+        return Ok(command.run()?);
+    };
+
+    checkpoint(partition_id)?;
+    let output = command.run_killable(&job.check)?;
+    // A killed check exits unsuccessfully; report the cancel, not a check failure
+    checkpoint(partition_id)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_only_before_commit() {
+        let id = format!("test-{}", uuid::Uuid::new_v4());
+        assert!(matches!(cancel_resize(&id), Err(ResizeError::NotCancellable(_))));
+
+        {
+            let _job = ResizeJob::register(&id);
+            checkpoint(&id).unwrap();
+            commit(&id).unwrap();
+            assert!(matches!(cancel_resize(&id), Err(ResizeError::NotCancellable(_))));
+        }
+
+        let _job = ResizeJob::register(&id);
+        cancel_resize(&id).unwrap();
+        assert!(matches!(checkpoint(&id), Err(ResizeError::Cancelled)));
+        assert!(matches!(commit(&id), Err(ResizeError::Cancelled)));
+    }
+}
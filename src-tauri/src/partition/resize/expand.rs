@@ -1,22 +1,79 @@
 // Partition expansion functionality
 
 use crate::partition::types::*;
-use anyhow::{anyhow, Result};
+use crate::partition::resize::validation::{check_expand_capacity, is_expand_noop};
+use crate::partition::exec::RunCommand;
+use crate::partition::resize::ResizeError;
+use crate::partition::resize::cancel::commit;
+use crate::partition::resize::progress::{ProgressSink, ResizeProgress};
+#[cfg(not(target_os = "windows"))]
+use crate::partition::resize::progress::{run_with_progress, ToolProgress};
 use std::process::Command;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+type Result<T> = std::result::Result<T, ResizeError>;
 
 /// Expand a partition to the specified size
 pub async fn expand_partition(
     partition: &PartitionInfo,
+    disk: &DiskInfo,
+    target_size: u64,
+) -> Result<()> {
+    expand_partition_with_progress(partition, disk, target_size, &|_| {}).await
+}
+
+/// Expand a partition, reporting each step and the filesystem tool's progress
+pub async fn expand_partition_with_progress(
+    partition: &PartitionInfo,
+    disk: &DiskInfo,
     target_size: u64,
+    on_progress: ProgressSink<'_>,
 ) -> Result<()> {
-    // Step 1: Expand the partition table entry
-    expand_partition_table(partition, target_size).await?;
+    // Nothing to do - don't hand diskpart/parted a zero or negative growth
+    if is_expand_noop(partition, target_size) {
+        log::info!(
+            "Expand of {} skipped: target size is not larger than current size",
+            partition.device_path
+        );
+        return Ok(());
+    }
 
-    // Step 2: Expand the filesystem
-    expand_filesystem(partition, target_size).await?;
+    // Don't hand the platform tools an impossible size if the caller miscalculated
+    check_expand_capacity(partition, disk, target_size)?;
+
+    // Refuse before touching the table if the filesystem step would fail and
+    // leave the partition bigger than its filesystem
+    if !matches!(
+        partition.filesystem,
+        FilesystemType::RAW
+            | FilesystemType::NTFS
+            | FilesystemType::Ext2
+            | FilesystemType::Ext3
+            | FilesystemType::Ext4
+            | FilesystemType::APFS
+            | FilesystemType::HFSPlus
+    ) {
+        return Err(ResizeError::UnsupportedFilesystem(partition.filesystem.display_name()));
+    }
+
+    // Step 1: Expand the partition table entry. The tools report no progress
+    commit(&partition.id)?;
+    on_progress(ResizeProgress::updating_partition_table(format!(
+        "Extending the partition {} to {}...",
+        partition.device_path,
+        format_bytes(target_size)
+    )));
+    expand_partition_table(partition, disk, target_size).await?;
+
+    // Step 2: Expand the filesystem. An unformatted partition has none; the
+    // user creates one afterwards
+    if partition.filesystem == FilesystemType::RAW {
+        log::info!(
+            "{} has no filesystem; extended the partition only",
+            partition.device_path
+        );
+        return Ok(());
+    }
+    expand_filesystem(partition, target_size, on_progress).await?;
 
     Ok(())
 }
@@ -24,20 +81,23 @@ pub async fn expand_partition(
 /// Expand the partition table entry
 async fn expand_partition_table(
     partition: &PartitionInfo,
+    disk: &DiskInfo,
     target_size: u64,
 ) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
+        let _ = disk;
         expand_partition_table_windows(partition, target_size).await
     }
 
     #[cfg(target_os = "linux")]
     {
-        expand_partition_table_linux(partition, target_size).await
+        resize_partition_table_linux(partition, disk, target_size)
     }
 
     #[cfg(target_os = "macos")]
     {
+        let _ = disk;
         // macOS uses diskutil resizeVolume which handles both partition and filesystem
         // So we don't need separate partition table expansion
         Ok(())
@@ -45,136 +105,116 @@ async fn expand_partition_table(
 
     #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
     {
-        Err(anyhow!("Partition table expansion not yet implemented for this platform"))
+        Err(ResizeError::UnsupportedPlatform("Partition table expansion not yet implemented for this platform"))
     }
 }
 
-/// Expand partition table on Windows using diskpart
+/// Expand partition table on Windows using the Storage cmdlets, or diskpart without them
 #[cfg(target_os = "windows")]
 async fn expand_partition_table_windows(
     partition: &PartitionInfo,
     target_size: u64,
 ) -> Result<()> {
-    // Extract drive letter
-    let drive_letter = partition.mount_point.as_ref()
-        .and_then(|m| m.chars().next())
-        .ok_or_else(|| anyhow!("No drive letter found for partition"))?;
+    if crate::capabilities::use_storage_cmdlets() {
+        return crate::partition::storage_cmdlets::resize_partition(partition, target_size)
+            .map_err(|e| ResizeError::tool("Resize-Partition", e.to_string()));
+    }
+
+    // Drive letter or folder mount path
+    let volume = partition.mount_point.as_deref()
+        .map(crate::partition::diskpart::volume_selector)
+        .ok_or(ResizeError::MissingDriveLetter)?;
 
     // Calculate size increase in MB (diskpart extend uses size increase, not absolute size)
     let current_size = partition.total_size;
     let size_increase_mb = (target_size.saturating_sub(current_size)) / (1024 * 1024);
 
     if size_increase_mb == 0 {
-        return Err(anyhow!("Target size must be larger than current size"));
+        return Err(ResizeError::InvalidTarget("Target size must be larger than current size".to_string()));
     }
 
     // Create diskpart script
     let script = format!(
         "select volume {}\nextend size={}\n",
-        drive_letter,
+        volume,
         size_increase_mb
     );
 
-    // Write script to temp file
-    let script_path = std::env::temp_dir().join("diskpart_expand.txt");
-    std::fs::write(&script_path, &script)?;
-
-    // Execute diskpart
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-    let output = Command::new("diskpart")
-        .arg("/s")
-        .arg(&script_path)
-        .creation_flags(CREATE_NO_WINDOW)
-        .output()?;
-
-    // Clean up temp file
-    let _ = std::fs::remove_file(&script_path);
-
-    // Capture both stdout and stderr for better error reporting
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-
-    if !output.status.success() || stdout.contains("failed") || stdout.contains("error") {
-        let error_msg = if !stderr.is_empty() {
-            stderr.to_string()
-        } else if !stdout.is_empty() {
-            stdout.to_string()
-        } else {
-            "Unknown diskpart error".to_string()
-        };
-
-        return Err(anyhow!(
-            "Diskpart failed: {}\n\nScript used:\n{}\n\nFull output:\n{}",
-            error_msg.trim(),
-            script,
-            stdout
-        ));
-    }
+    // Run diskpart; failures name the script step that failed (e.g. "extend failed: ...")
+    crate::partition::diskpart::run_script(&script, "expand")
+        .map_err(|e| ResizeError::tool("Diskpart", format!("{}\n\nScript used:\n{}", e, script)))?;
 
     Ok(())
 }
 
-/// Expand partition table on Linux using parted
+/// Move the partition's end so it is `target_size` bytes long
+///
+/// Uses libparted when built with it and installed, otherwise the parted CLI.
+/// The disk comes from `disk` rather than the partition's device name, which
+/// doesn't end in the bare number for nvme0n1p2, mmcblk0p1 or loop0p1.
 #[cfg(target_os = "linux")]
-async fn expand_partition_table_linux(
-    partition: &PartitionInfo,
-    target_size: u64,
-) -> Result<()> {
-    let device = &partition.device_path;
-    let size_mb = target_size / (1024 * 1024);
-
-    // Use parted to resize the partition
-    // Format: parted /dev/sda resizepart 1 100%
-    // or: parted /dev/sda resizepart 1 500MB
-
-    // Extract partition number from device path (e.g., /dev/sda1 -> 1)
-    let part_num = device
-        .chars()
-        .rev()
-        .take_while(|c| c.is_numeric())
-        .collect::<String>()
-        .chars()
-        .rev()
-        .collect::<String>();
-
-    // Extract base device (e.g., /dev/sda1 -> /dev/sda)
-    let base_device = device.trim_end_matches(&part_num);
+pub(crate) fn resize_partition_table_linux(partition: &PartitionInfo, disk: &DiskInfo, target_size: u64) -> Result<()> {
+    #[cfg(all(feature = "libparted", not(feature = "dry-run-exec")))]
+    if resize_table_with_libparted(&disk.device_path, partition.number, target_size)? {
+        return Ok(());
+    }
 
+    // resizepart takes the new end, not the size; bytes keep it exact.
+    // Script mode answers parted's "shrinking can cause data loss" prompt
+    let end = partition.start_offset + target_size - 1;
     let output = Command::new("parted")
-        .arg(base_device)
+        .arg("-s")
+        .arg(&disk.device_path)
+        .arg("unit")
+        .arg("B")
         .arg("resizepart")
-        .arg(&part_num)
-        .arg(format!("{}MB", size_mb))
-        .output()?;
+        .arg(partition.number.to_string())
+        .arg(format!("{}B", end))
+        .run()?;
 
     if !output.status.success() {
-        return Err(anyhow!(
-            "parted failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
+        return Err(ResizeError::tool("parted", String::from_utf8_lossy(&output.stderr)));
     }
 
     Ok(())
 }
 
+/// Resize the partition table entry through libparted
+///
+/// Returns false when libparted isn't installed, so the caller falls back to the CLI.
+#[cfg(all(target_os = "linux", feature = "libparted", not(feature = "dry-run-exec")))]
+pub(crate) fn resize_table_with_libparted(base_device: &str, number: u32, target_size: u64) -> Result<bool> {
+    use crate::partition::resize::libparted::{self, LibpartedError};
+
+    match libparted::resize_partition(base_device, number, target_size) {
+        Ok(()) => Ok(true),
+        Err(LibpartedError::Unavailable) => {
+            log::info!("libparted is not installed; falling back to the parted CLI");
+            Ok(false)
+        }
+        Err(e) => Err(ResizeError::tool("libparted", e.to_string())),
+    }
+}
+
 /// Expand the filesystem to fill the partition
 async fn expand_filesystem(
     partition: &PartitionInfo,
     target_size: u64,
+    on_progress: ProgressSink<'_>,
 ) -> Result<()> {
     match partition.filesystem {
-        FilesystemType::NTFS => expand_ntfs(partition, target_size).await,
+        FilesystemType::NTFS => expand_ntfs(partition, target_size, on_progress).await,
         FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4 => {
-            expand_ext4(partition, target_size).await
+            expand_ext4(partition, target_size, on_progress).await
         }
         FilesystemType::APFS | FilesystemType::HFSPlus => {
+            on_progress(
+                ResizeProgress::expanding_filesystem(0.0, format!("Expanding {}...", partition.device_path))
+                    .indeterminate(),
+            );
             expand_apfs_hfs(partition, target_size).await
         }
-        _ => Err(anyhow!(
-            "Filesystem expansion not supported for {}",
-            partition.filesystem.display_name()
-        )),
+        _ => Err(ResizeError::UnsupportedFilesystem(partition.filesystem.display_name())),
     }
 }
 
@@ -182,11 +222,13 @@ async fn expand_filesystem(
 async fn expand_ntfs(
     partition: &PartitionInfo,
     _target_size: u64,
+    on_progress: ProgressSink<'_>,
 ) -> Result<()> {
     #[cfg(target_os = "windows")]
     {
         // On Windows, NTFS expansion happens automatically with diskpart extend
         // No additional action needed
+        let _ = (partition, on_progress);
         Ok(())
     }
 
@@ -199,26 +241,22 @@ async fn expand_ntfs(
             .arg("--force")
             .arg("--no-action")  // Dry run first
             .arg(device)
-            .output()?;
+            .run()?;
 
         if !output.status.success() {
-            return Err(anyhow!(
-                "NTFS dry-run failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
+            return Err(ResizeError::tool("NTFS dry-run", String::from_utf8_lossy(&output.stderr)));
         }
 
         // Actual resize
-        let output = Command::new("ntfsresize")
-            .arg("--force")
-            .arg(device)
-            .output()?;
+        let output = run_with_progress(
+            Command::new("ntfsresize").arg("--force").arg(device),
+            ToolProgress::ntfsresize(),
+            on_progress,
+            |percent| ResizeProgress::expanding_filesystem(percent, format!("Expanding the filesystem on {}...", device)),
+        )?;
 
         if !output.status.success() {
-            return Err(anyhow!(
-                "NTFS resize failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
+            return Err(ResizeError::tool("NTFS resize", String::from_utf8_lossy(&output.stderr)));
         }
 
         Ok(())
@@ -229,21 +267,23 @@ async fn expand_ntfs(
 async fn expand_ext4(
     partition: &PartitionInfo,
     _target_size: u64,
+    on_progress: ProgressSink<'_>,
 ) -> Result<()> {
     #[cfg(target_os = "linux")]
     {
         let device = &partition.device_path;
 
-        // resize2fs can expand online (while mounted) or offline
-        let output = Command::new("resize2fs")
-            .arg(device)
-            .output()?;
+        // resize2fs can expand online (while mounted) or offline; -p only
+        // prints progress offline, online growth stays indeterminate
+        let output = run_with_progress(
+            Command::new("resize2fs").arg("-p").arg(device),
+            ToolProgress::resize2fs(),
+            on_progress,
+            |percent| ResizeProgress::expanding_filesystem(percent, format!("Expanding the filesystem on {}...", device)),
+        )?;
 
         if !output.status.success() {
-            return Err(anyhow!(
-                "resize2fs failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
+            return Err(ResizeError::tool("resize2fs", String::from_utf8_lossy(&output.stderr)));
         }
 
         Ok(())
@@ -251,7 +291,8 @@ async fn expand_ext4(
 
     #[cfg(not(target_os = "linux"))]
     {
-        Err(anyhow!("ext4 resize is only supported on Linux"))
+        let _ = on_progress;
+        Err(ResizeError::UnsupportedPlatform("ext4 resize is only supported on Linux"))
     }
 }
 
@@ -274,13 +315,10 @@ async fn expand_apfs_hfs(
             .arg("resizeVolume")
             .arg(device)
             .arg(&size_arg)
-            .output()?;
+            .run()?;
 
         if !output.status.success() {
-            return Err(anyhow!(
-                "diskutil resizeVolume failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ));
+            return Err(ResizeError::tool("diskutil resizeVolume", String::from_utf8_lossy(&output.stderr)));
         }
 
         Ok(())
@@ -288,6 +326,42 @@ async fn expand_apfs_hfs(
 
     #[cfg(not(target_os = "macos"))]
     {
-        Err(anyhow!("APFS/HFS+ resize is only supported on macOS"))
+        Err(ResizeError::UnsupportedPlatform("APFS/HFS+ resize is only supported on macOS"))
+    }
+}
+
+#[cfg(all(test, feature = "dry-run-exec", target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::partition::exec::dry_run;
+    use crate::test_support::{mock_disk, mock_partition, GB};
+
+    #[test]
+    fn test_expand_runs_parted_then_resize2fs() {
+        // Partition numbers follow a "p" on NVMe and MMC devices
+        for (disk_path, device_path, number) in [
+            ("/dev/sda", "/dev/sda1", 1),
+            ("/dev/nvme0n1", "/dev/nvme0n1p2", 2),
+            ("/dev/mmcblk0", "/dev/mmcblk0p1", 1),
+        ] {
+            let partition = PartitionInfo {
+                number,
+                total_size: 50 * GB,
+                used_space: Some(10 * GB),
+                ..mock_partition(device_path.trim_start_matches("/dev/"))
+            };
+            let disk = mock_disk(disk_path, vec![partition.clone()]);
+
+            let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+            runtime.block_on(expand_partition(&partition, &disk, 60 * GB)).unwrap();
+
+            let log = dry_run::take_log();
+            let number = number.to_string();
+            assert_eq!(log.len(), 2);
+            // The new end, counted from the partition's start
+            let end = format!("{}B", 1024 * 1024 + 60 * GB - 1);
+            assert_eq!(log[0], vec!["parted", "-s", disk_path, "unit", "B", "resizepart", number.as_str(), end.as_str()]);
+            assert_eq!(log[1], vec!["resize2fs", "-p", device_path]);
+        }
     }
 }
@@ -0,0 +1,230 @@
+// libparted backend for Linux partition table changes
+//
+// Enabled with the `libparted` feature. The library is loaded at runtime with
+// dlopen, so the build needs no parted headers and machines without
+// libparted fall back to the `parted` CLI (`LibpartedError::Unavailable`).
+// Errors come from libparted's exception handler instead of scraped stderr.
+//
+// Only the partition table step goes through here: filesystem resizing stays
+// with resize2fs/ntfsresize, since neither libext2fs nor libntfs-3g exposes
+// a resize API.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_longlong, c_void};
+use std::sync::{Mutex, OnceLock};
+
+type PedSector = c_longlong;
+
+// Leading fields of libparted's public structs; only these are read
+
+#[repr(C)]
+#[allow(dead_code)]
+struct PedDevice {
+    next: *mut PedDevice,
+    model: *mut c_char,
+    path: *mut c_char,
+    device_type: c_int,
+    sector_size: c_longlong,
+    phys_sector_size: c_longlong,
+    length: PedSector,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct PedGeometry {
+    dev: *mut PedDevice,
+    start: PedSector,
+    length: PedSector,
+    end: PedSector,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct PedPartition {
+    prev: *mut PedPartition,
+    next: *mut PedPartition,
+    disk: *mut c_void,
+    geom: PedGeometry,
+    num: c_int,
+}
+
+#[repr(C)]
+struct PedException {
+    message: *mut c_char,
+    exception_type: c_int,
+    options: c_int,
+}
+
+// PedExceptionType
+const PED_EXCEPTION_WARNING: c_int = 2;
+
+// PedExceptionOption
+const PED_EXCEPTION_UNHANDLED: c_int = 0;
+const PED_EXCEPTION_YES: c_int = 2;
+const PED_EXCEPTION_OK: c_int = 8;
+const PED_EXCEPTION_IGNORE: c_int = 32;
+const PED_EXCEPTION_CANCEL: c_int = 64;
+
+type ExceptionHandler = extern "C" fn(*mut PedException) -> c_int;
+
+/// Function pointers resolved from libparted
+struct Library {
+    device_get: unsafe extern "C" fn(*const c_char) -> *mut PedDevice,
+    disk_new: unsafe extern "C" fn(*mut PedDevice) -> *mut c_void,
+    disk_destroy: unsafe extern "C" fn(*mut c_void),
+    disk_get_partition: unsafe extern "C" fn(*const c_void, c_int) -> *mut PedPartition,
+    disk_set_partition_geom:
+        unsafe extern "C" fn(*mut c_void, *mut PedPartition, *const c_void, PedSector, PedSector) -> c_int,
+    disk_commit: unsafe extern "C" fn(*mut c_void) -> c_int,
+    constraint_any: unsafe extern "C" fn(*const PedDevice) -> *mut c_void,
+    constraint_destroy: unsafe extern "C" fn(*mut c_void),
+    exception_set_handler: unsafe extern "C" fn(Option<ExceptionHandler>),
+}
+
+// libparted keeps global state (device list, exception handler)
+static LIBRARY: OnceLock<Option<Mutex<Library>>> = OnceLock::new();
+
+thread_local! {
+    static EXCEPTIONS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Debug)]
+pub enum LibpartedError {
+    /// libparted isn't installed; use the CLI instead
+    Unavailable,
+    /// libparted refused or failed the change
+    Failed(String),
+}
+
+impl std::fmt::Display for LibpartedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LibpartedError::Unavailable => write!(f, "libparted is not available"),
+            LibpartedError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+fn library() -> Option<&'static Mutex<Library>> {
+    LIBRARY.get_or_init(|| load_library().map(Mutex::new)).as_ref()
+}
+
+fn load_library() -> Option<Library> {
+    let handle = ["libparted.so.2", "libparted.so"].iter().find_map(|name| {
+        let name = CString::new(*name).ok()?;
+        // SAFETY: dlopen with a valid C string; the handle is kept for the process lifetime
+        let handle = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_NOW) };
+        (!handle.is_null()).then_some(handle)
+    })?;
+
+    macro_rules! symbol {
+        ($name:literal) => {{
+            let name = CString::new($name).ok()?;
+            // SAFETY: the symbol is looked up in the handle opened above
+            let ptr = unsafe { libc::dlsym(handle, name.as_ptr()) };
+            if ptr.is_null() {
+                log::warn!("libparted is missing {}", $name);
+                return None;
+            }
+            // SAFETY: the declared signature matches libparted's public API
+            unsafe { std::mem::transmute::<*mut c_void, _>(ptr) }
+        }};
+    }
+
+    Some(Library {
+        device_get: symbol!("ped_device_get"),
+        disk_new: symbol!("ped_disk_new"),
+        disk_destroy: symbol!("ped_disk_destroy"),
+        disk_get_partition: symbol!("ped_disk_get_partition"),
+        disk_set_partition_geom: symbol!("ped_disk_set_partition_geom"),
+        disk_commit: symbol!("ped_disk_commit"),
+        constraint_any: symbol!("ped_constraint_any"),
+        constraint_destroy: symbol!("ped_constraint_destroy"),
+        exception_set_handler: symbol!("ped_exception_set_handler"),
+    })
+}
+
+/// Record libparted's messages; proceed past warnings, cancel on errors
+extern "C" fn exception_handler(exception: *mut PedException) -> c_int {
+    // SAFETY: libparted passes a valid exception for the duration of the call
+    let exception = unsafe { &*exception };
+    let message = if exception.message.is_null() {
+        "unknown libparted error".to_string()
+    } else {
+        // SAFETY: message is a NUL-terminated string owned by libparted
+        unsafe { CStr::from_ptr(exception.message) }.to_string_lossy().to_string()
+    };
+    EXCEPTIONS.with(|e| e.borrow_mut().push(message));
+
+    if exception.exception_type > PED_EXCEPTION_WARNING {
+        return PED_EXCEPTION_CANCEL;
+    }
+
+    [PED_EXCEPTION_IGNORE, PED_EXCEPTION_YES, PED_EXCEPTION_OK]
+        .into_iter()
+        .find(|option| exception.options & option != 0)
+        .unwrap_or(PED_EXCEPTION_UNHANDLED)
+}
+
+fn failure(fallback: &str) -> LibpartedError {
+    let messages = EXCEPTIONS.with(|e| std::mem::take(&mut *e.borrow_mut()));
+    if messages.is_empty() {
+        LibpartedError::Failed(fallback.to_string())
+    } else {
+        LibpartedError::Failed(messages.join("; "))
+    }
+}
+
+/// Resize partition `number` on `disk_path` to `new_size` bytes, keeping its start
+pub fn resize_partition(disk_path: &str, number: u32, new_size: u64) -> Result<(), LibpartedError> {
+    let lib = library().ok_or(LibpartedError::Unavailable)?;
+    let lib = lib.lock().map_err(|_| LibpartedError::Failed("libparted state is poisoned".to_string()))?;
+
+    let path = CString::new(disk_path).map_err(|_| LibpartedError::Failed("Invalid device path".to_string()))?;
+    EXCEPTIONS.with(|e| e.borrow_mut().clear());
+
+    // SAFETY: every pointer below comes from libparted and is checked for null
+    // before use; objects are destroyed in reverse order of creation.
+    unsafe {
+        (lib.exception_set_handler)(Some(exception_handler));
+
+        let device = (lib.device_get)(path.as_ptr());
+        if device.is_null() {
+            return Err(failure("Could not open device"));
+        }
+
+        // Devices are cached by libparted and aren't destroyed here
+        let disk = (lib.disk_new)(device);
+        if disk.is_null() {
+            return Err(failure("Could not read partition table"));
+        }
+
+        let result = (|| {
+            let partition = (lib.disk_get_partition)(disk, number as c_int);
+            if partition.is_null() {
+                return Err(failure(&format!("Partition {} not found", number)));
+            }
+
+            let sector_size = (*device).sector_size.max(1) as u64;
+            let start = (*partition).geom.start;
+            let end = start + (new_size / sector_size) as PedSector - 1;
+
+            let constraint = (lib.constraint_any)(device);
+            let ok = (lib.disk_set_partition_geom)(disk, partition, constraint, start, end);
+            (lib.constraint_destroy)(constraint);
+            if ok == 0 {
+                return Err(failure("libparted rejected the new partition geometry"));
+            }
+
+            if (lib.disk_commit)(disk) == 0 {
+                return Err(failure("Could not write the partition table"));
+            }
+            Ok(())
+        })();
+
+        (lib.disk_destroy)(disk);
+        (lib.exception_set_handler)(None);
+        result
+    }
+}
@@ -1,13 +1,75 @@
 // Partition resize functionality
 
 pub mod validation;
+pub mod cancel;
 pub mod expand;
 pub mod progress;
 pub mod shrink;
+pub mod unmovable;
+#[cfg(all(feature = "libparted", target_os = "linux"))]
+pub mod libparted;
 
 pub use validation::*;
+pub use cancel::*;
 pub use expand::*;
 pub use progress::*;
 pub use shrink::*;
-pub use expand::*;
-pub use progress::*;
+
+use crate::partition::types::format_bytes;
+use thiserror::Error;
+
+/// Why a resize (expand/shrink) failed or was rejected
+#[derive(Debug, Error)]
+pub enum ResizeError {
+    /// Target would run into the next partition or past the end of the disk
+    #[error("Target size ({}) exceeds available contiguous space (max {})", format_bytes(*requested), format_bytes(*maximum))]
+    ExceedsCapacity { requested: u64, maximum: u64 },
+
+    /// Target size doesn't make sense for the requested direction
+    #[error("{0}")]
+    InvalidTarget(String),
+
+    /// The filesystem can't be resized by this tool
+    #[error("Resizing {0} filesystems is not supported")]
+    UnsupportedFilesystem(&'static str),
+
+    /// The operation isn't available on this OS
+    #[error("{0}")]
+    UnsupportedPlatform(&'static str),
+
+    /// The partition has no drive letter to address it by
+    #[error("No drive letter found for partition")]
+    MissingDriveLetter,
+
+    /// The partition is mounted when it must not be, or vice versa
+    #[error("{0}")]
+    MountState(&'static str),
+
+    /// An external tool reported failure
+    #[error("{tool} failed: {message}")]
+    ToolFailed { tool: String, message: String },
+
+    /// The user cancelled the resize before anything was written
+    #[error("Resize cancelled; the partition was not changed")]
+    Cancelled,
+
+    /// A cancel request came too late, or for a partition with no resize running
+    #[error("{0}")]
+    NotCancellable(String),
+
+    /// An external tool couldn't be run
+    #[error("Failed to run resize tool: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl ResizeError {
+    pub(crate) fn tool(tool: &str, message: impl Into<String>) -> Self {
+        ResizeError::ToolFailed {
+            tool: tool.to_string(),
+            message: message.into(),
+        }
+    }
+}
@@ -1,9 +1,22 @@
 // Progress tracking for resize operations
+//
+// resize2fs (with -p) and ntfsresize print their own progress while they
+// work; `ToolProgress` turns that output into a percentage. Tools that print
+// nothing parseable (diskpart, diskutil, online resize2fs) leave the update
+// marked indeterminate, so the UI shows activity instead of a stuck bar.
 
+use crate::partition::exec::RunCommand;
 use serde::{Deserialize, Serialize};
+use std::io;
+use std::process::{Command, Output};
+use ts_rs::TS;
+
+/// Receives progress updates while a resize runs
+pub type ProgressSink<'a> = &'a (dyn Fn(ResizeProgress) + Sync);
 
 /// Progress update for a resize operation
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct ResizeProgress {
     /// Current operation phase
     pub phase: ResizePhase,
@@ -16,10 +29,15 @@ pub struct ResizeProgress {
 
     /// Whether the operation can be cancelled at this point
     pub can_cancel: bool,
+
+    /// The tool reports no progress, so `percent` only marks the phase's start
+    #[serde(default)]
+    pub indeterminate: bool,
 }
 
 /// Phases of a resize operation
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[ts(export)]
 pub enum ResizePhase {
     /// Validating resize parameters
     Validating,
@@ -59,9 +77,16 @@ impl ResizeProgress {
             percent,
             message,
             can_cancel,
+            indeterminate: false,
         }
     }
 
+    /// Mark the update as having no measurable progress within its phase
+    pub fn indeterminate(mut self) -> Self {
+        self.indeterminate = true;
+        self
+    }
+
     /// Create a validation progress update
     pub fn validating(message: impl Into<String>) -> Self {
         Self::new(ResizePhase::Validating, 5.0, message.into())
@@ -107,3 +132,154 @@ impl ResizeProgress {
         Self::new(ResizePhase::Error, 0.0, message.into())
     }
 }
+
+/// resize2fs numbers its passes 1 to 5; a run skips the ones it doesn't need
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const RESIZE2FS_PASSES: f32 = 5.0;
+
+/// Width of resize2fs's progress bar in 'X' marks
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const RESIZE2FS_BAR_WIDTH: u32 = 40;
+
+/// ntfsresize stages that print a percentage, in order
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+const NTFSRESIZE_STAGES: [&str; 2] = ["Checking filesystem consistency", "Relocating needed data"];
+
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+#[derive(Debug, Clone, Copy)]
+enum ResizeTool {
+    Resize2fs,
+    Ntfsresize,
+}
+
+/// Completion of a resize tool, parsed from its output as it arrives
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+#[derive(Debug)]
+pub struct ToolProgress {
+    tool: ResizeTool,
+    line: String,     // Output since the last \r or \n
+    stage: usize,     // resize2fs pass or ntfsresize stage
+    marks: u32,       // resize2fs 'X' marks in the current pass
+    percent: f32,
+}
+
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+impl ToolProgress {
+    /// resize2fs run with -p: "Begin pass N" then a bar of 40 'X' marks per pass
+    pub fn resize2fs() -> Self {
+        Self::new(ResizeTool::Resize2fs)
+    }
+
+    /// ntfsresize: " 12.34 percent completed" lines, redrawn with \r
+    pub fn ntfsresize() -> Self {
+        Self::new(ResizeTool::Ntfsresize)
+    }
+
+    fn new(tool: ResizeTool) -> Self {
+        Self { tool, line: String::new(), stage: 0, marks: 0, percent: 0.0 }
+    }
+
+    /// Feed a chunk of output; returns the overall percentage (0-100) when it moved
+    ///
+    /// The result never goes backwards, even when a tool restarts its bar.
+    pub fn feed(&mut self, chunk: &str) -> Option<f32> {
+        let before = self.percent;
+        for c in chunk.chars() {
+            match c {
+                '\r' | '\n' => {
+                    self.end_line();
+                    self.line.clear();
+                }
+                'X' if matches!(self.tool, ResizeTool::Resize2fs) && self.stage > 0 => {
+                    self.marks = (self.marks + 1).min(RESIZE2FS_BAR_WIDTH);
+                    let pass = (self.stage - 1) as f32 + self.marks as f32 / RESIZE2FS_BAR_WIDTH as f32;
+                    self.advance(pass * 100.0 / RESIZE2FS_PASSES);
+                }
+                _ => self.line.push(c),
+            }
+        }
+        (self.percent > before).then_some(self.percent)
+    }
+
+    fn end_line(&mut self) {
+        let line = self.line.trim();
+        match self.tool {
+            ResizeTool::Resize2fs => {
+                if let Some(pass) = line
+                    .strip_prefix("Begin pass ")
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .and_then(|n| n.parse::<usize>().ok())
+                {
+                    self.stage = pass.clamp(1, RESIZE2FS_PASSES as usize);
+                    self.marks = 0;
+                }
+            }
+            ResizeTool::Ntfsresize => {
+                if let Some(stage) = NTFSRESIZE_STAGES.iter().position(|s| line.starts_with(s)) {
+                    self.stage = stage + 1;
+                } else if let Some(value) = line
+                    .strip_suffix("percent completed")
+                    .and_then(|n| n.trim().parse::<f32>().ok())
+                {
+                    // Output without a stage header is taken as a single stage
+                    let (index, count) = match self.stage {
+                        0 => (0.0, 1.0),
+                        stage => ((stage - 1) as f32, NTFSRESIZE_STAGES.len() as f32),
+                    };
+                    self.advance((index * 100.0 + value.clamp(0.0, 100.0)) / count);
+                }
+            }
+        }
+    }
+
+    fn advance(&mut self, percent: f32) {
+        self.percent = self.percent.max(percent.min(100.0));
+    }
+}
+
+/// Run a resize tool, passing `update` its parsed completion as it changes
+///
+/// An indeterminate `update(0.0)` goes out first and stays in effect when the
+/// tool prints nothing `parser` understands.
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub(crate) fn run_with_progress(
+    command: &mut Command,
+    mut parser: ToolProgress,
+    on_progress: ProgressSink,
+    update: impl Fn(f32) -> ResizeProgress,
+) -> io::Result<Output> {
+    on_progress(update(0.0).indeterminate());
+    command.run_streaming(&mut |chunk| {
+        if let Some(percent) = parser.feed(chunk) {
+            on_progress(update(percent));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_progress_parses_output() {
+        // Two resize2fs passes, the bar arriving in pieces
+        let mut progress = ToolProgress::resize2fs();
+        assert_eq!(progress.feed("resize2fs 1.47.0 (5-Feb-2023)\nResizing the filesystem on /dev/sda1\n"), None);
+        assert_eq!(progress.feed("Begin pass 2 (max = 3281)\nRelocating blocks             "), None);
+        assert_eq!(progress.feed(&"X".repeat(20)), Some(30.0));
+        progress.feed(&format!("{}\n", "X".repeat(20)));
+        progress.feed("Begin pass 3 (max = 160)\nScanning inode table          ");
+        assert_eq!(progress.feed(&"X".repeat(40)), Some(60.0));
+
+        // ntfsresize redraws its percentage with \r; the check is the first half
+        let mut progress = ToolProgress::ntfsresize();
+        assert_eq!(progress.feed("Checking filesystem consistency ...\n  0.00 percent completed\r"), None);
+        assert_eq!(progress.feed(" 50.00 percent completed\r100.00 perc"), Some(25.0));
+        assert_eq!(progress.feed("ent completed\n"), Some(50.0));
+        progress.feed("Relocating needed data ...\n");
+        assert_eq!(progress.feed("  0.00 percent completed\r 40.00 percent completed\r"), Some(70.0));
+
+        // Nothing recognisable leaves the caller's indeterminate update in place
+        assert_eq!(ToolProgress::ntfsresize().feed("ntfsresize v2022.10.3\nNothing to do.\n"), None);
+    }
+}
@@ -4,159 +4,257 @@
 // Shrinking is more complex than expansion as it requires filesystem checks and data movement.
 
 use crate::partition::types::*;
-use anyhow::{anyhow, Result};
+use crate::partition::resize::validation::is_shrink_noop;
+use crate::partition::exec::RunCommand;
+use crate::partition::resize::ResizeError;
+use crate::partition::resize::progress::{ProgressSink, ResizeProgress};
+#[cfg(target_os = "linux")]
+use crate::partition::resize::progress::{run_with_progress, ToolProgress};
+#[cfg(target_os = "linux")]
+use crate::partition::resize::cancel::run_check;
+use crate::partition::resize::cancel::commit;
 use std::process::Command;
 
+type Result<T> = std::result::Result<T, ResizeError>;
+
+/// Shrink a partition on `disk` to the specified size
+pub async fn shrink_partition(partition: &PartitionInfo, disk: &DiskInfo, target_size: u64) -> Result<()> {
+    shrink_partition_with_progress(partition, disk, target_size, &|_| {}).await
+}
+
+/// Shrink a partition, reporting each step and the filesystem tool's progress
+pub async fn shrink_partition_with_progress(
+    partition: &PartitionInfo,
+    disk: &DiskInfo,
+    target_size: u64,
+    on_progress: ProgressSink<'_>,
+) -> Result<()> {
+    // Nothing to do - target is not smaller than the current size
+    if is_shrink_noop(partition, target_size) {
+        log::info!(
+            "Shrink of {} skipped: target size is not smaller than current size",
+            partition.device_path
+        );
+        return Ok(());
+    }
+
+    shrink_platform(partition, disk, target_size, on_progress).await
+}
+
+// The Windows and macOS tools address the volume, so only Linux needs the disk
 #[cfg(target_os = "windows")]
-pub async fn shrink_partition(partition: &PartitionInfo, target_size: u64) -> Result<()> {
-    shrink_windows(partition, target_size).await
+async fn shrink_platform(partition: &PartitionInfo, _disk: &DiskInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
+    shrink_windows(partition, target_size, on_progress).await
 }
 
 #[cfg(target_os = "macos")]
-pub async fn shrink_partition(partition: &PartitionInfo, target_size: u64) -> Result<()> {
-    shrink_macos(partition, target_size).await
+async fn shrink_platform(partition: &PartitionInfo, _disk: &DiskInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
+    shrink_macos(partition, target_size, on_progress).await
 }
 
 #[cfg(target_os = "linux")]
-pub async fn shrink_partition(partition: &PartitionInfo, target_size: u64) -> Result<()> {
-    shrink_linux(partition, target_size).await
+async fn shrink_platform(partition: &PartitionInfo, disk: &DiskInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
+    shrink_linux(partition, disk, target_size, on_progress).await
 }
 
 /// Windows NTFS shrink implementation
+///
+/// Resize-Partition and diskpart's `shrink` both shrink the filesystem first
+/// and then the partition, as one step. The Storage cmdlets can shrink
+/// unmounted partitions; diskpart needs a volume to select. Neither reports
+/// progress, so the update stays indeterminate.
 #[cfg(target_os = "windows")]
-async fn shrink_windows(partition: &PartitionInfo, target_size: u64) -> Result<()> {
-    use std::fs;
-    use std::io::Write;
+async fn shrink_windows(partition: &PartitionInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
+    commit(&partition.id)?;
+    on_progress(
+        ResizeProgress::resizing_filesystem(0.0, format!("Shrinking {}...", partition.device_path)).indeterminate(),
+    );
+
+    if crate::capabilities::use_storage_cmdlets() {
+        return crate::partition::storage_cmdlets::resize_partition(partition, target_size)
+            .map_err(|e| ResizeError::tool("Resize-Partition", e.to_string()));
+    }
 
     // Convert bytes to MB for diskpart
     let shrink_amount_mb = (partition.total_size - target_size) / (1024 * 1024);
 
     // Create diskpart script
-    // If partition is mounted (has drive letter), use volume selection
+    // If partition is mounted (drive letter or folder), use volume selection
     // If unmounted, we need to use disk and partition number
     let script_content = if let Some(mount_point) = &partition.mount_point {
-        // Extract drive letter from mount point (e.g., "C:" -> "C")
-        let drive_letter = mount_point.chars().next()
-            .ok_or_else(|| anyhow!("Invalid mount point format"))?;
         format!(
             "select volume {}\nShrink desired={}\n",
-            drive_letter,
+            crate::partition::diskpart::volume_selector(mount_point),
             shrink_amount_mb
         )
     } else {
         // For unmounted partitions, we need disk number and partition number
         // Parse device_path to get these (e.g., "\\.\PHYSICALDRIVE0" and partition number)
         // Note: This is a simplified approach - may need refinement
-        return Err(anyhow!(
-            "Cannot shrink unmounted partition on Windows. Please mount the partition first or use Disk Management."
+        return Err(ResizeError::MountState(
+            "Cannot shrink an unmounted partition with diskpart. Assign a drive letter first, or use Disk Management."
         ));
     };
 
-    let script_path = std::env::temp_dir().join("shrink_partition.txt");
-    let mut file = fs::File::create(&script_path)?;
-    file.write_all(script_content.as_bytes())?;
-    drop(file);
-
-    // Execute diskpart
-    let output = Command::new("diskpart")
-        .arg("/s")
-        .arg(&script_path)
-        .output()?;
-
-    // Clean up script file
-    let _ = fs::remove_file(&script_path);
+    // Run diskpart; failures name the script step that failed (e.g. "shrink failed: ...")
+    crate::partition::diskpart::run_script(&script_content, "shrink")
+        .map_err(|e| ResizeError::tool("Diskpart shrink", e.to_string()))?;
 
-    if !output.status.success() {
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!(
-            "Diskpart shrink failed.\nStdout: {}\nStderr: {}",
-            stdout,
-            stderr
-        ));
-    }
-
-    // Verify the operation
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.contains("successfully") || stdout.contains("completed") {
-        Ok(())
-    } else {
-        Err(anyhow!("Shrink operation may have failed. Output: {}", stdout))
-    }
+    Ok(())
 }
 
 /// macOS APFS shrink implementation
 #[cfg(target_os = "macos")]
-async fn shrink_macos(partition: &PartitionInfo, target_size: u64) -> Result<()> {
+async fn shrink_macos(partition: &PartitionInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
     // APFS volumes can be resized online
     // diskutil resizeVolume /dev/diskXsY size
     
     // Convert bytes to human-readable format for diskutil
     let size_str = format_size_for_diskutil(target_size);
 
+    commit(&partition.id)?;
+    on_progress(
+        ResizeProgress::resizing_filesystem(0.0, format!("Shrinking {}...", partition.device_path)).indeterminate(),
+    );
+
     let output = Command::new("diskutil")
         .arg("resizeVolume")
         .arg(&partition.device_path)
         .arg(&size_str)
-        .output()?;
+        .run()?;
 
     if !output.status.success() {
         let error = String::from_utf8_lossy(&output.stderr);
-        return Err(anyhow!("diskutil resize failed: {}", error));
+        return Err(ResizeError::tool("diskutil resize", error));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    if stdout.contains("Finished") || stdout.contains("successfully") {
-        Ok(())
-    } else {
-        Err(anyhow!("Resize operation may have failed. Output: {}", stdout))
-    }
+    Ok(())
 }
 
-/// Linux ext4 shrink implementation
+/// Linux shrink: filesystem first, then the partition table entry
+///
+/// The reverse of expand. The partition must never end inside its
+/// filesystem, so if the table step fails the partition is left larger than
+/// the filesystem, which is safe and can be retried.
 #[cfg(target_os = "linux")]
-async fn shrink_linux(partition: &PartitionInfo, target_size: u64) -> Result<()> {
-    // For ext4, we need to:
-    // 1. Ensure partition is unmounted
-    // 2. Run e2fsck to check filesystem
-    // 3. Resize filesystem with resize2fs
-    // 4. Update partition table (not implemented yet - requires libparted)
-
-    // Check if mounted
+async fn shrink_linux(partition: &PartitionInfo, disk: &DiskInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
     if partition.is_mounted {
-        return Err(anyhow!("Partition must be unmounted before shrinking"));
+        return Err(ResizeError::MountState("Partition must be unmounted before shrinking"));
     }
 
-    // Step 1: Force filesystem check
-    let fsck_output = Command::new("e2fsck")
-        .arg("-f")
-        .arg("-y")
-        .arg(&partition.device_path)
-        .output()?;
-
-    if !fsck_output.status.success() {
-        let error = String::from_utf8_lossy(&fsck_output.stderr);
-        return Err(anyhow!("Filesystem check failed: {}", error));
+    // Refuse before touching anything if the filesystem can't be shrunk
+    if !matches!(
+        partition.filesystem,
+        FilesystemType::RAW | FilesystemType::NTFS | FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4
+    ) {
+        return Err(ResizeError::UnsupportedFilesystem(partition.filesystem.display_name()));
     }
 
-    // Step 2: Resize filesystem
-    // Convert bytes to 4K blocks (ext4 default block size)
-    let target_blocks = target_size / 4096;
-    
-    let resize_output = Command::new("resize2fs")
-        .arg(&partition.device_path)
-        .arg(format!("{}s", target_blocks)) // 's' suffix means 512-byte sectors
-        .output()?;
+    shrink_filesystem_linux(partition, target_size, on_progress)?;
 
-    if !resize_output.status.success() {
-        let error = String::from_utf8_lossy(&resize_output.stderr);
-        return Err(anyhow!("resize2fs failed: {}", error));
-    }
+    on_progress(ResizeProgress::updating_partition_table(format!(
+        "Updating the partition table for {}...",
+        partition.device_path
+    )));
+    super::expand::resize_partition_table_linux(partition, disk, target_size)
+}
+
+/// Shrink the filesystem to at most `target_size` bytes
+#[cfg(target_os = "linux")]
+fn shrink_filesystem_linux(partition: &PartitionInfo, target_size: u64, on_progress: ProgressSink<'_>) -> Result<()> {
+    let device = &partition.device_path;
+    let resizing = |percent| ResizeProgress::resizing_filesystem(percent, format!("Shrinking the filesystem on {}...", device));
+
+    match partition.filesystem {
+        FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4 => {
+            // Read-only pass first (-n): it can be cancelled, since killing it changes nothing
+            on_progress(ResizeProgress::checking_filesystem(format!("Checking {}...", device)).indeterminate());
+            let check_output = run_check(&partition.id, Command::new("e2fsck").arg("-f").arg("-n").arg(device))?;
+
+            // Exit status 4 means errors were found and left alone; the repair pass fixes them
+            if !matches!(check_output.status.code(), Some(0) | Some(1) | Some(4)) {
+                let error = String::from_utf8_lossy(&check_output.stderr);
+                return Err(ResizeError::tool("Filesystem check", error));
+            }
+
+            commit(&partition.id)?;
+
+            // resize2fs refuses to shrink a filesystem that hasn't just been
+            // checked, and only a repairing check counts. This one writes, so
+            // it runs after the point of no return
+            on_progress(
+                ResizeProgress::resizing_filesystem(0.0, format!("Repairing and preparing {}...", device)).indeterminate(),
+            );
+            let fsck_output = Command::new("e2fsck")
+                .arg("-f")
+                .arg("-y")
+                .arg(device)
+                .run()?;
+
+            // Exit status 1 means errors were found and corrected
+            if !matches!(fsck_output.status.code(), Some(0) | Some(1)) {
+                let error = String::from_utf8_lossy(&fsck_output.stderr);
+                return Err(ResizeError::tool("Filesystem repair", error));
+            }
+
+            // 'K' is KiB; rounding down keeps the filesystem within the new partition.
+            // -p prints a progress bar per pass
+            let resize_output = run_with_progress(
+                Command::new("resize2fs")
+                    .arg("-p")
+                    .arg(device)
+                    .arg(format!("{}K", target_size / 1024)),
+                ToolProgress::resize2fs(),
+                on_progress,
+                resizing,
+            )?;
 
-    // Step 3: Update partition table
-    // TODO: This requires libparted or parted command
-    // For now, we'll just resize the filesystem and leave partition table as-is
-    // The partition will show as larger than the filesystem, which is safe
+            if !resize_output.status.success() {
+                let error = String::from_utf8_lossy(&resize_output.stderr);
+                return Err(ResizeError::tool("resize2fs", error));
+            }
+        }
+        FilesystemType::NTFS => {
+            // Size in bytes; ntfsresize rounds it down to whole clusters
+            let size = target_size.to_string();
+
+            // Dry run first: fails if the data doesn't fit
+            on_progress(ResizeProgress::checking_filesystem(format!("Checking {}...", device)).indeterminate());
+            let output = run_check(
+                &partition.id,
+                Command::new("ntfsresize")
+                    .arg("--force")
+                    .arg("--no-action")
+                    .arg("--size")
+                    .arg(&size)
+                    .arg(device),
+            )?;
+
+            if !output.status.success() {
+                return Err(ResizeError::tool("NTFS dry-run", String::from_utf8_lossy(&output.stderr)));
+            }
+
+            commit(&partition.id)?;
+
+            let output = run_with_progress(
+                Command::new("ntfsresize")
+                    .arg("--force")
+                    .arg("--size")
+                    .arg(&size)
+                    .arg(device),
+                ToolProgress::ntfsresize(),
+                on_progress,
+                resizing,
+            )?;
+
+            if !output.status.success() {
+                return Err(ResizeError::tool("NTFS resize", String::from_utf8_lossy(&output.stderr)));
+            }
+        }
+        // An unformatted partition has no filesystem to shrink
+        FilesystemType::RAW => commit(&partition.id)?,
+        _ => return Err(ResizeError::UnsupportedFilesystem(partition.filesystem.display_name())),
+    }
 
     Ok(())
 }
@@ -180,6 +278,39 @@ fn format_size_for_diskutil(bytes: u64) -> String {
 mod tests {
     use super::*;
 
+    #[cfg(all(feature = "dry-run-exec", target_os = "linux"))]
+    #[test]
+    fn test_shrink_resizes_filesystem_before_table() {
+        use crate::partition::exec::dry_run;
+        use crate::test_support::{mock_disk, mock_partition, GB};
+
+        // NVMe, where the partition number follows a "p"
+        let partition = |filesystem| PartitionInfo {
+            number: 2,
+            total_size: 50 * GB,
+            used_space: Some(10 * GB),
+            filesystem,
+            ..mock_partition("nvme0n1p2")
+        };
+        let disk = mock_disk("/dev/nvme0n1", vec![partition(FilesystemType::Ext4)]);
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let end = format!("{}B", 1024 * 1024 + 20 * GB - 1);
+
+        runtime.block_on(shrink_partition(&partition(FilesystemType::Ext4), &disk, 20 * GB)).unwrap();
+        let log = dry_run::take_log();
+        assert_eq!(log.len(), 4);
+        assert_eq!(log[0], vec!["e2fsck", "-f", "-n", "/dev/nvme0n1p2"]);
+        assert_eq!(log[1], vec!["e2fsck", "-f", "-y", "/dev/nvme0n1p2"]);
+        assert_eq!(log[2], vec!["resize2fs", "-p", "/dev/nvme0n1p2", "20971520K"]);
+        assert_eq!(log[3], vec!["parted", "-s", "/dev/nvme0n1", "unit", "B", "resizepart", "2", end.as_str()]);
+
+        runtime.block_on(shrink_partition(&partition(FilesystemType::NTFS), &disk, 20 * GB)).unwrap();
+        let log = dry_run::take_log();
+        assert_eq!(log.len(), 3);
+        assert_eq!(log[1], vec!["ntfsresize", "--force", "--size", "21474836480", "/dev/nvme0n1p2"]);
+        assert_eq!(log[2][0], "parted");
+    }
+
     #[cfg(target_os = "macos")]
     #[test]
     fn test_format_size_for_diskutil() {
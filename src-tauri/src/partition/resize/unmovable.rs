@@ -0,0 +1,259 @@
+// Unmovable NTFS files that limit how far a volume can shrink
+//
+// diskpart can only shrink an NTFS volume down to its last unmovable cluster.
+// The usual culprits are the pagefile, hibernation file, swapfile and the
+// MFT, which sit wherever Windows first put them - often near the end of the
+// volume. Their cluster runs are read with FSCTL_GET_RETRIEVAL_POINTERS (the
+// MFT's from FSCTL_GET_NTFS_VOLUME_DATA) so a shrink past them can be flagged
+// before diskpart fails with a generic error.
+
+use crate::partition::types::*;
+
+/// What kind of system file is pinning clusters in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum UnmovableKind {
+    Pagefile,
+    Hibernation,
+    Swapfile,
+    Mft,
+}
+
+impl UnmovableKind {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            UnmovableKind::Pagefile => "the page file (pagefile.sys)",
+            UnmovableKind::Hibernation => "the hibernation file (hiberfil.sys)",
+            UnmovableKind::Swapfile => "the swap file (swapfile.sys)",
+            UnmovableKind::Mft => "the Master File Table ($MFT)",
+        }
+    }
+
+    /// How to get the file out of the way temporarily
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            UnmovableKind::Pagefile | UnmovableKind::Swapfile => {
+                "Set virtual memory to \"No paging file\" for this drive (System Properties > Advanced > Performance), reboot, shrink, then re-enable it."
+            }
+            UnmovableKind::Hibernation => {
+                "Run `powercfg /h off` as administrator, shrink, then `powercfg /h on` to restore hibernation."
+            }
+            UnmovableKind::Mft => {
+                "The MFT can't be moved by Windows tools; shrink to a size above this boundary or use an offline partition tool."
+            }
+        }
+    }
+}
+
+/// A system file and the byte offset (from the volume start) just past its last cluster
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct UnmovableFile {
+    pub kind: UnmovableKind,
+    pub end_offset: u64,
+}
+
+/// Warnings for each unmovable file extending past `target_size`
+pub fn unmovable_warnings(files: &[UnmovableFile], target_size: u64) -> Vec<String> {
+    files
+        .iter()
+        .filter(|f| f.end_offset > target_size)
+        .map(|f| {
+            format!(
+                "Shrinking to {} is likely to fail: {} occupies space up to {} from the start of the volume. {}",
+                format_bytes(target_size),
+                f.kind.display_name(),
+                format_bytes(f.end_offset),
+                f.kind.remediation()
+            )
+        })
+        .collect()
+}
+
+/// Unmovable files on a mounted NTFS volume; empty when they can't be read
+#[cfg(target_os = "windows")]
+pub fn find_unmovable_files(partition: &PartitionInfo) -> Vec<UnmovableFile> {
+    let Some(root) = partition.mount_point.as_deref() else {
+        return Vec::new();
+    };
+    if partition.filesystem != FilesystemType::NTFS {
+        return Vec::new();
+    }
+    let root = format!("{}\\", root.trim_end_matches('\\'));
+
+    let Some(cluster_size) = windows_impl::cluster_size(&root) else {
+        return Vec::new();
+    };
+
+    let mut files: Vec<UnmovableFile> = [
+        ("pagefile.sys", UnmovableKind::Pagefile),
+        ("hiberfil.sys", UnmovableKind::Hibernation),
+        ("swapfile.sys", UnmovableKind::Swapfile),
+    ]
+    .iter()
+    .filter_map(|(name, kind)| {
+        let last_cluster = windows_impl::last_cluster(&format!("{}{}", root, name))?;
+        Some(UnmovableFile { kind: *kind, end_offset: last_cluster * cluster_size })
+    })
+    .collect();
+
+    // Reading volume data needs elevation; without it the MFT is simply not reported
+    if let Some(letter) = crate::partition::diskpart::drive_letter(&root) {
+        if let Some(end_offset) = windows_impl::mft_end(letter) {
+            files.push(UnmovableFile { kind: UnmovableKind::Mft, end_offset });
+        }
+    }
+
+    files
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn find_unmovable_files(_partition: &PartitionInfo) -> Vec<UnmovableFile> {
+    Vec::new()
+}
+
+#[cfg(target_os = "windows")]
+mod windows_impl {
+    use windows::core::PCWSTR;
+    use windows::Win32::Foundation::{CloseHandle, ERROR_MORE_DATA, HANDLE};
+    use windows::Win32::Storage::FileSystem::{
+        CreateFileW, GetDiskFreeSpaceW, FILE_FLAGS_AND_ATTRIBUTES, FILE_READ_ATTRIBUTES, FILE_SHARE_DELETE,
+        FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+    };
+    use windows::Win32::System::Ioctl::{
+        FSCTL_GET_NTFS_VOLUME_DATA, FSCTL_GET_RETRIEVAL_POINTERS, NTFS_VOLUME_DATA_BUFFER,
+        RETRIEVAL_POINTERS_BUFFER, STARTING_VCN_INPUT_BUFFER,
+    };
+    use windows::Win32::System::IO::DeviceIoControl;
+
+    fn open(path: &str, access: u32) -> Option<HANDLE> {
+        let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            CreateFileW(
+                PCWSTR(wide.as_ptr()),
+                access,
+                FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+                None,
+                OPEN_EXISTING,
+                FILE_FLAGS_AND_ATTRIBUTES(0),
+                None,
+            )
+        }
+        .ok()
+    }
+
+    pub fn cluster_size(root: &str) -> Option<u64> {
+        let wide: Vec<u16> = root.encode_utf16().chain(std::iter::once(0)).collect();
+        let (mut sectors_per_cluster, mut bytes_per_sector) = (0u32, 0u32);
+        unsafe {
+            GetDiskFreeSpaceW(
+                PCWSTR(wide.as_ptr()),
+                Some(&mut sectors_per_cluster as *mut u32),
+                Some(&mut bytes_per_sector as *mut u32),
+                None,
+                None,
+            )
+        }
+        .ok()?;
+        Some(sectors_per_cluster as u64 * bytes_per_sector as u64)
+    }
+
+    /// One past the highest logical cluster the file occupies
+    pub fn last_cluster(path: &str) -> Option<u64> {
+        // Attribute access is enough for the query and works while the file is in use
+        let handle = open(path, FILE_READ_ATTRIBUTES.0)?;
+
+        // RETRIEVAL_POINTERS_BUFFER is followed by a variable number of extents
+        const EXTENTS_PER_CALL: usize = 512;
+        let mut buffer = vec![0u64; 4 + EXTENTS_PER_CALL * 2];
+        let mut input = STARTING_VCN_INPUT_BUFFER::default();
+        let mut last = None;
+
+        loop {
+            let mut returned = 0u32;
+            let result = unsafe {
+                DeviceIoControl(
+                    handle,
+                    FSCTL_GET_RETRIEVAL_POINTERS,
+                    Some(&input as *const STARTING_VCN_INPUT_BUFFER as *const std::ffi::c_void),
+                    std::mem::size_of::<STARTING_VCN_INPUT_BUFFER>() as u32,
+                    Some(buffer.as_mut_ptr() as *mut std::ffi::c_void),
+                    (buffer.len() * 8) as u32,
+                    Some(&mut returned),
+                    None,
+                )
+            };
+            let more = matches!(&result, Err(e) if e.code() == ERROR_MORE_DATA.to_hresult());
+            if result.is_err() && !more {
+                break;
+            }
+
+            let pointers = unsafe { &*(buffer.as_ptr() as *const RETRIEVAL_POINTERS_BUFFER) };
+            let extents = unsafe {
+                std::slice::from_raw_parts(pointers.Extents.as_ptr(), pointers.ExtentCount as usize)
+            };
+
+            let mut vcn = pointers.StartingVcn;
+            for extent in extents {
+                // An LCN of -1 is a sparse/unallocated run
+                if extent.Lcn >= 0 {
+                    let end = (extent.Lcn + (extent.NextVcn - vcn)) as u64;
+                    last = Some(last.map_or(end, |l: u64| l.max(end)));
+                }
+                vcn = extent.NextVcn;
+            }
+
+            if !more || extents.is_empty() {
+                break;
+            }
+            input.StartingVcn = vcn;
+        }
+
+        let _ = unsafe { CloseHandle(handle) };
+        last
+    }
+
+    /// Byte offset just past the MFT's valid data, from the volume's NTFS data
+    pub fn mft_end(letter: char) -> Option<u64> {
+        let handle = open(&format!(r"\\.\{}:", letter), 0)?;
+
+        let mut data = NTFS_VOLUME_DATA_BUFFER::default();
+        let mut returned = 0u32;
+        let result = unsafe {
+            DeviceIoControl(
+                handle,
+                FSCTL_GET_NTFS_VOLUME_DATA,
+                None,
+                0,
+                Some(&mut data as *mut NTFS_VOLUME_DATA_BUFFER as *mut std::ffi::c_void),
+                std::mem::size_of::<NTFS_VOLUME_DATA_BUFFER>() as u32,
+                Some(&mut returned),
+                None,
+            )
+        };
+        let _ = unsafe { CloseHandle(handle) };
+        result.ok()?;
+
+        let start = data.MftStartLcn as u64 * data.BytesPerCluster as u64;
+        Some(start + data.MftValidDataLength as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unmovable_warnings_only_past_boundary() {
+        let gb = 1024 * 1024 * 1024;
+        let files = vec![
+            UnmovableFile { kind: UnmovableKind::Pagefile, end_offset: 90 * gb },
+            UnmovableFile { kind: UnmovableKind::Mft, end_offset: 10 * gb },
+        ];
+
+        let warnings = unmovable_warnings(&files, 60 * gb);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("pagefile.sys"));
+        assert!(warnings[0].contains("No paging file"));
+
+        assert!(unmovable_warnings(&files, 95 * gb).is_empty());
+    }
+}
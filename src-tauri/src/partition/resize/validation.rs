@@ -1,7 +1,9 @@
 // Validation logic for resize operations
 
 use crate::partition::types::*;
-use anyhow::{anyhow, Result};
+use crate::partition::resize::ResizeError;
+
+type Result<T> = std::result::Result<T, ResizeError>;
 
 /// Result of a resize validation check
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -29,6 +31,18 @@ pub struct ValidationResult {
 
     /// Amount of adjacent unallocated space (bytes)
     pub adjacent_space: u64,
+
+    /// The partition must be unmounted before this resize can run
+    #[serde(default)]
+    pub requires_unmount: bool,
+
+    /// The computer is running on battery
+    #[serde(default)]
+    pub on_battery: bool,
+
+    /// Remaining battery charge, when the computer has a battery
+    #[serde(default)]
+    pub battery_percent: Option<u8>,
 }
 
 /// Validate a partition expansion request
@@ -46,10 +60,22 @@ pub fn validate_expand(
         maximum_size: None,
         has_adjacent_space: false,
         adjacent_space: 0,
+        requires_unmount: false,
+        on_battery: false,
+        battery_percent: None,
     };
 
     // Check 1: Target size must be larger than current size
-    if target_size <= partition.total_size {
+    if target_size == partition.total_size {
+        result.is_valid = false;
+        result.errors.push(format!(
+            "Partition is already {}. Nothing to expand.",
+            format_bytes(partition.total_size)
+        ));
+        return Ok(result);
+    }
+
+    if target_size < partition.total_size {
         result.is_valid = false;
         result.errors.push(format!(
             "Target size ({}) must be larger than current size ({})",
@@ -60,43 +86,41 @@ pub fn validate_expand(
     }
 
     // Check 2: Calculate available space after this partition
-    let partition_end = partition.start_offset + partition.total_size;
-    let next_partition = find_next_partition(disk, partition);
-
-    let available_space = if let Some(next) = next_partition {
-        // Space between this partition and the next one
-        next.start_offset.saturating_sub(partition_end)
-    } else {
-        // Space between this partition and end of disk
-        disk.total_size.saturating_sub(partition_end)
-    };
+    let available_space = adjacent_free_space(partition, disk);
 
     result.adjacent_space = available_space;
     result.has_adjacent_space = available_space > 0;
 
+    // Calculate maximum safe size
+    let maximum_size = partition.total_size + available_space;
+    result.maximum_size = Some(maximum_size);
+
     // Check 3: Verify there's enough adjacent space
-    let size_increase = target_size - partition.total_size;
-    if size_increase > available_space {
+    if let Err(e) = check_expand_capacity(partition, disk, target_size) {
         result.is_valid = false;
-        result.errors.push(format!(
-            "Not enough adjacent space. Requested increase: {}, Available: {}",
-            format_bytes(size_increase),
-            format_bytes(available_space)
-        ));
+        result.errors.push(e.to_string());
     }
 
-    // Calculate maximum safe size
-    result.maximum_size = Some(partition.total_size + available_space);
-
-    // Check 4: Ensure partition is not mounted (for safety)
-    if partition.is_mounted {
+    // Check 4: Ensure partition is not mounted (for safety). ext grows online
+    if partition.is_mounted && !is_ext(partition.filesystem) {
         result.warnings.push(
             "Partition is currently mounted. Expansion may require unmounting or system restart.".to_string()
         );
     }
 
-    // Check 5: Filesystem support check
-    if !partition.filesystem.supports_resize() {
+    // Check 5: Filesystem support check. An unformatted partition has no
+    // filesystem step, so only its table entry is extended
+    if partition.filesystem == FilesystemType::RAW && cfg!(not(target_os = "macos")) {
+        result.warnings.push(
+            "This partition has no filesystem. Only the partition will be extended; \
+             create a filesystem on it afterwards to use the space.".to_string()
+        );
+    } else if partition.filesystem == FilesystemType::Unknown {
+        result.is_valid = false;
+        result.errors.push(
+            "The filesystem on this partition could not be identified, so it can't be resized safely.".to_string()
+        );
+    } else if !partition.filesystem.supports_resize() {
         result.is_valid = false;
         result.errors.push(format!(
             "Filesystem type '{}' does not support resize operations",
@@ -121,10 +145,22 @@ pub fn validate_shrink(
         maximum_size: Some(partition.total_size),
         has_adjacent_space: false,
         adjacent_space: 0,
+        requires_unmount: false,
+        on_battery: false,
+        battery_percent: None,
     };
 
     // Check 1: Target size must be smaller than current size
-    if target_size >= partition.total_size {
+    if target_size == partition.total_size {
+        result.is_valid = false;
+        result.errors.push(format!(
+            "Partition is already {}. Nothing to shrink.",
+            format_bytes(partition.total_size)
+        ));
+        return Ok(result);
+    }
+
+    if target_size > partition.total_size {
         result.is_valid = false;
         result.errors.push(format!(
             "Target size ({}) must be smaller than current size ({})",
@@ -134,10 +170,20 @@ pub fn validate_shrink(
         return Ok(result);
     }
 
-    // Check 2: Ensure target size is larger than used space
-    if let Some(used_space) = partition.used_space {
-        // Add 20% buffer for safety
-        let min_safe_size = (used_space as f64 * 1.2) as u64;
+    // Check 2: A partition without a recognised filesystem has no used-space
+    // figure and nothing to shrink into; shrinking its table entry could cut off data
+    if matches!(partition.filesystem, FilesystemType::RAW | FilesystemType::Unknown) {
+        result.is_valid = false;
+        result.errors.push(format!(
+            "Cannot shrink {}: it has no recognised filesystem ({}).",
+            partition.display_name(),
+            partition.filesystem.display_name()
+        ));
+        return Ok(result);
+    }
+
+    // Check 3: Ensure target size is larger than used space
+    if let (Some(used_space), Some(min_safe_size)) = (partition.used_space, minimum_shrink_size(partition)) {
         result.minimum_size = Some(min_safe_size);
 
         if target_size < min_safe_size {
@@ -160,7 +206,7 @@ pub fn validate_shrink(
         );
     }
 
-    // Check 3: Filesystem support check
+    // Check 4: Filesystem support check
     // Note: On Windows, diskpart can shrink mounted NTFS volumes
     // On Linux/macOS, we may need to unmount first (handled in shrink operation)
     if !partition.filesystem.supports_resize() {
@@ -171,22 +217,44 @@ pub fn validate_shrink(
         ));
     }
 
-    // Check 4: Mounted partition warnings (Windows can shrink mounted volumes)
-    #[cfg(not(target_os = "windows"))]
-    if partition.is_mounted {
+    // Check 5: Mounted partitions. resize2fs refuses to shrink a mounted ext
+    // filesystem (it can only grow online), so reject that up front
+    if partition.is_mounted && is_ext(partition.filesystem) {
+        result.is_valid = false;
+        result.requires_unmount = true;
+
+        if partition.mount_point.as_deref() == Some("/") {
+            result.errors.push(
+                "ext filesystems cannot be shrunk while mounted, and this is the root filesystem. \
+                 Boot from a live USB or recovery environment to shrink it.".to_string()
+            );
+        } else {
+            result.errors.push(format!(
+                "ext filesystems cannot be shrunk while mounted. Unmount {} and try again.",
+                partition.mount_point.as_deref().unwrap_or(&partition.device_path)
+            ));
+        }
+    } else if cfg!(not(target_os = "windows")) && partition.is_mounted {
+        // Windows can shrink mounted volumes
         result.warnings.push(
             "This partition is mounted. You may need to unmount it before shrinking on this OS.".to_string()
         );
     }
 
-    // Check 5: Boot partition warning
+    // Check 6: Unmovable NTFS files (pagefile, hibernation, MFT) past the new end
+    if result.is_valid {
+        let unmovable = super::unmovable::find_unmovable_files(partition);
+        result.warnings.extend(super::unmovable::unmovable_warnings(&unmovable, target_size));
+    }
+
+    // Check 7: Boot partition warning
     if partition.flags.contains(&PartitionFlag::Boot) {
         result.warnings.push(
             "WARNING: This is a boot partition. Shrinking it may make the system unbootable!".to_string()
         );
     }
 
-    // Check 6: System partition warning
+    // Check 8: System partition warning
     if partition.flags.contains(&PartitionFlag::System) {
         result.warnings.push(
             "WARNING: This is a system partition. Shrinking it requires extreme caution!".to_string()
@@ -196,6 +264,136 @@ pub fn validate_shrink(
     Ok(result)
 }
 
+/// Smallest size a partition can safely be shrunk to: used space plus a 20% buffer
+pub fn minimum_shrink_size(partition: &PartitionInfo) -> Option<u64> {
+    partition.used_space.map(|used| (used as f64 * 1.2) as u64)
+}
+
+/// A concrete, aligned shrink target for freeing space from a partition
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ShrinkProposal {
+    pub partition_id: String,
+
+    /// Space the caller asked to free (bytes)
+    pub free_needed: u64,
+
+    /// Proposed new partition size, aligned to PARTITION_ALIGNMENT.
+    /// When the request isn't achievable this is the smallest safe size instead.
+    pub target_size: u64,
+
+    /// Space freed by shrinking to `target_size` (bytes)
+    pub space_freed: u64,
+
+    /// Smallest safe size for the filesystem, if used space is known
+    pub minimum_size: Option<u64>,
+
+    /// Whether `free_needed` can be freed from this partition
+    pub achievable: bool,
+
+    /// Why the request can't be met, when `achievable` is false
+    pub reason: Option<String>,
+
+    pub warnings: Vec<String>,
+}
+
+/// Work out how far to shrink a partition to free `free_needed` bytes
+///
+/// The target is aligned down so at least `free_needed` is freed, and is
+/// never below the filesystem's minimum safe size (rounded up to alignment).
+pub fn propose_shrink_target(partition: &PartitionInfo, free_needed: u64) -> Result<ShrinkProposal> {
+    let mut proposal = ShrinkProposal {
+        partition_id: partition.id.clone(),
+        free_needed,
+        target_size: partition.total_size,
+        space_freed: 0,
+        minimum_size: None,
+        achievable: false,
+        reason: None,
+        warnings: Vec::new(),
+    };
+
+    let Some(minimum) = minimum_shrink_size(partition) else {
+        proposal.reason = Some("Cannot determine used space, so no safe shrink size can be proposed.".to_string());
+        return Ok(proposal);
+    };
+    let minimum = minimum.div_ceil(PARTITION_ALIGNMENT) * PARTITION_ALIGNMENT;
+    proposal.minimum_size = Some(minimum);
+
+    if minimum >= partition.total_size {
+        proposal.reason = Some(format!(
+            "{} is too full to shrink (minimum safe size {}).",
+            partition.display_name(),
+            format_bytes(minimum)
+        ));
+        return Ok(proposal);
+    }
+
+    let desired = partition.total_size.saturating_sub(free_needed) / PARTITION_ALIGNMENT * PARTITION_ALIGNMENT;
+    let target_size = desired.max(minimum);
+    proposal.target_size = target_size;
+    proposal.space_freed = partition.total_size - target_size;
+
+    if desired < minimum {
+        proposal.reason = Some(format!(
+            "Only {} can be freed from {} without going below its minimum safe size of {}.",
+            format_bytes(proposal.space_freed),
+            partition.display_name(),
+            format_bytes(minimum)
+        ));
+        return Ok(proposal);
+    }
+
+    let validation = validate_shrink(partition, target_size)?;
+    proposal.warnings = validation.warnings;
+    if validation.is_valid {
+        proposal.achievable = true;
+    } else {
+        proposal.reason = Some(validation.errors.join(", "));
+    }
+
+    Ok(proposal)
+}
+
+/// Contiguous unallocated space directly after a partition
+/// (up to the next partition, or to the end of the disk)
+pub fn adjacent_free_space(partition: &PartitionInfo, disk: &DiskInfo) -> u64 {
+    let partition_end = partition.start_offset + partition.total_size;
+
+    match find_next_partition(disk, partition) {
+        Some(next) => next.start_offset.saturating_sub(partition_end),
+        None => disk.total_size.saturating_sub(partition_end),
+    }
+}
+
+/// Reject an expand target that would run into the next partition or past the end of the disk
+pub fn check_expand_capacity(partition: &PartitionInfo, disk: &DiskInfo, target_size: u64) -> Result<()> {
+    let maximum_size = partition.total_size + adjacent_free_space(partition, disk);
+
+    if target_size > maximum_size {
+        return Err(ResizeError::ExceedsCapacity {
+            requested: target_size,
+            maximum: maximum_size,
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether an expand request would not grow the partition at all
+pub fn is_expand_noop(partition: &PartitionInfo, target_size: u64) -> bool {
+    target_size <= partition.total_size
+}
+
+/// Whether a shrink request would not reduce the partition at all
+pub fn is_shrink_noop(partition: &PartitionInfo, target_size: u64) -> bool {
+    target_size >= partition.total_size
+}
+
+/// ext2/3/4 can grow while mounted but only shrink offline
+fn is_ext(filesystem: FilesystemType) -> bool {
+    matches!(filesystem, FilesystemType::Ext2 | FilesystemType::Ext3 | FilesystemType::Ext4)
+}
+
 /// Find the next partition after the given one on the same disk
 fn find_next_partition<'a>(disk: &'a DiskInfo, current: &PartitionInfo) -> Option<&'a PartitionInfo> {
     let current_end = current.start_offset + current.total_size;
@@ -206,86 +404,181 @@ fn find_next_partition<'a>(disk: &'a DiskInfo, current: &PartitionInfo) -> Optio
         .min_by_key(|p| p.start_offset)
 }
 
-/// Format bytes to human-readable string
-fn format_bytes(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    if bytes == 0 {
-        return "0 B".to_string();
-    }
-
-    let base = 1024_f64;
-    let exp = (bytes as f64).log(base).floor() as usize;
-    let exp = exp.min(UNITS.len() - 1);
-    let value = bytes as f64 / base.powi(exp as i32);
-
-    format!("{:.2} {}", value, UNITS[exp])
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{mock_disk, mock_partition, GB};
 
     #[test]
     fn test_validate_expand_basic() {
+        let (partition, disk) = expand_fixture();
+
+        let target_size = 150 * GB;
+        let result = validate_expand(&partition, &disk, target_size).unwrap();
+
+        assert!(result.is_valid);
+        assert!(result.has_adjacent_space);
+        assert!(result.adjacent_space > 0);
+    }
+
+    /// C: at 100GB with 50GB used, alone on a 500GB disk
+    fn expand_fixture() -> (PartitionInfo, DiskInfo) {
         let partition = PartitionInfo {
-            id: "test-1".to_string(),
-            number: 1,
             device_path: "C:".to_string(),
-            label: None,
-            start_offset: 1024 * 1024,
-            total_size: 100 * 1024 * 1024 * 1024, // 100GB
-            used_space: Some(50 * 1024 * 1024 * 1024), // 50GB
-            partition_type: PartitionType::Primary,
+            total_size: 100 * GB,
+            used_space: Some(50 * GB),
             filesystem: FilesystemType::NTFS,
             mount_point: Some("C:".to_string()),
             is_mounted: true,
-            flags: vec![],
+            ..mock_partition("test-1")
         };
-
         let disk = DiskInfo {
-            id: "disk-0".to_string(),
-            device_path: "\\\\.\\PhysicalDrive0".to_string(),
-            model: "Test Disk".to_string(),
-            total_size: 500 * 1024 * 1024 * 1024, // 500GB
-            table_type: PartitionTableType::GPT,
-            partitions: vec![partition.clone()],
-            serial_number: None,
-            status: DiskStatus {
-                is_online: true,
-                has_errors: false,
-                smart_status: None,
-            },
+            total_size: 500 * GB,
+            ..mock_disk("\\\\.\\PhysicalDrive0", vec![partition.clone()])
         };
 
-        let target_size = 150 * 1024 * 1024 * 1024; // 150GB
+        (partition, disk)
+    }
+
+    #[test]
+    fn test_validate_expand_equal_size_is_noop() {
+        let (partition, disk) = expand_fixture();
+
+        let result = validate_expand(&partition, &disk, partition.total_size).unwrap();
+
+        assert!(!result.is_valid);
+        assert!(result.errors[0].contains("Nothing to expand"));
+        assert!(is_expand_noop(&partition, partition.total_size));
+    }
+
+    #[test]
+    fn test_validate_expand_smaller_than_current() {
+        let (partition, disk) = expand_fixture();
+        let target_size = 80 * GB;
+
         let result = validate_expand(&partition, &disk, target_size).unwrap();
 
-        assert!(result.is_valid);
-        assert!(result.has_adjacent_space);
-        assert!(result.adjacent_space > 0);
+        assert!(!result.is_valid);
+        assert!(result.errors[0].contains("must be larger"));
+        assert!(is_expand_noop(&partition, target_size));
+    }
+
+    #[test]
+    fn test_validate_expand_overruns_next_partition() {
+        let (partition, mut disk) = expand_fixture();
+
+        // Next partition starts 20GB after C: ends
+        let mut next = partition.clone();
+        next.id = "test-2".to_string();
+        next.start_offset = partition.start_offset + partition.total_size + 20 * GB;
+        disk.partitions.push(next);
+
+        let target_size = 130 * GB; // +30GB
+        let result = validate_expand(&partition, &disk, target_size).unwrap();
+
+        assert!(!result.is_valid);
+        assert!(result.errors[0].contains("exceeds available contiguous space"));
+        assert_eq!(result.maximum_size, Some(120 * GB));
+        assert!(matches!(
+            check_expand_capacity(&partition, &disk, target_size),
+            Err(ResizeError::ExceedsCapacity { maximum, .. }) if maximum == 120 * GB
+        ));
+        assert!(check_expand_capacity(&partition, &disk, 120 * GB).is_ok());
     }
 
     #[test]
     fn test_validate_shrink_below_used_space() {
-        let partition = PartitionInfo {
-            id: "test-1".to_string(),
-            number: 1,
-            device_path: "C:".to_string(),
-            label: None,
-            start_offset: 1024 * 1024,
-            total_size: 100 * 1024 * 1024 * 1024, // 100GB
-            used_space: Some(80 * 1024 * 1024 * 1024), // 80GB used
-            partition_type: PartitionType::Primary,
-            filesystem: FilesystemType::NTFS,
-            mount_point: Some("C:".to_string()),
-            is_mounted: false,
-            flags: vec![],
-        };
+        let (mut partition, _) = expand_fixture();
+        partition.used_space = Some(80 * GB);
+        partition.is_mounted = false;
 
-        let target_size = 70 * 1024 * 1024 * 1024; // 70GB (less than used)
+        let target_size = 70 * GB; // Less than used
         let result = validate_shrink(&partition, target_size).unwrap();
 
         assert!(!result.is_valid);
         assert!(!result.errors.is_empty());
     }
+
+    #[test]
+    fn test_validate_shrink_unmounted_ext4() {
+        let partition = PartitionInfo {
+            total_size: 50 * GB,
+            used_space: Some(10 * GB),
+            ..mock_partition("sda1")
+        };
+
+        assert!(!validate_shrink(&partition, 5 * GB).unwrap().is_valid);
+        assert!(validate_shrink(&partition, 30 * GB).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_validate_shrink_rejects_mounted_ext() {
+        let (mut partition, mut disk) = expand_fixture();
+        partition.device_path = "/dev/sda2".to_string();
+        partition.filesystem = FilesystemType::Ext4;
+        partition.mount_point = Some("/".to_string());
+        partition.is_mounted = true;
+        disk.partitions = vec![partition.clone()];
+
+        let result = validate_shrink(&partition, 90 * GB).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.requires_unmount);
+        assert!(result.errors.iter().any(|e| e.contains("live USB")));
+
+        // Growing a mounted ext filesystem works online
+        let result = validate_expand(&partition, &disk, 110 * GB).unwrap();
+        assert!(result.is_valid);
+        assert!(!result.requires_unmount);
+
+        partition.is_mounted = false;
+        let result = validate_shrink(&partition, 90 * GB).unwrap();
+        assert!(result.is_valid);
+    }
+
+    #[test]
+    fn test_resize_raw_partition() {
+        let (mut partition, mut disk) = expand_fixture();
+        partition.filesystem = FilesystemType::RAW;
+        partition.used_space = None;
+        partition.is_mounted = false;
+        disk.partitions = vec![partition.clone()];
+
+        let result = validate_shrink(&partition, 90 * GB).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.errors[0].contains("no recognised filesystem"));
+
+        // Expanding only touches the partition table, with a note to format it
+        let result = validate_expand(&partition, &disk, 110 * GB).unwrap();
+        if cfg!(target_os = "macos") {
+            assert!(!result.is_valid);
+        } else {
+            assert!(result.is_valid);
+            assert!(result.warnings.iter().any(|w| w.contains("no filesystem")));
+        }
+
+        partition.filesystem = FilesystemType::Unknown;
+        assert!(!validate_expand(&partition, &disk, 110 * GB).unwrap().is_valid);
+        assert!(!validate_shrink(&partition, 90 * GB).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_propose_shrink_target_aligns_and_respects_minimum() {
+        let (mut partition, _) = expand_fixture();
+        partition.total_size = 20 * GB;
+        partition.used_space = Some(GB);
+        partition.is_mounted = false;
+
+        // An unaligned request rounds the target down, freeing slightly more
+        let proposal = propose_shrink_target(&partition, 5 * GB + 1).unwrap();
+        assert!(proposal.achievable);
+        assert_eq!(proposal.target_size % PARTITION_ALIGNMENT, 0);
+        assert_eq!(proposal.target_size, 15 * GB - PARTITION_ALIGNMENT);
+        assert!(proposal.space_freed >= 5 * GB + 1);
+
+        // Asking for too much proposes the minimum safe size instead
+        let proposal = propose_shrink_target(&partition, 19 * GB).unwrap();
+        assert!(!proposal.achievable);
+        assert_eq!(proposal.target_size, proposal.minimum_size.unwrap());
+        assert!(proposal.target_size >= (1.2 * GB as f64) as u64);
+    }
 }
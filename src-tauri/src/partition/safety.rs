@@ -0,0 +1,173 @@
+// Safety guards for risky partition operations
+
+use crate::partition::error::OperationError;
+use crate::partition::types::*;
+use std::path::{Path, PathBuf};
+
+/// Whether `path` lives under the mount point `mount`
+fn path_is_under(path: &Path, mount: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let path = path.to_string_lossy().to_lowercase();
+        let mount = mount.trim_end_matches('\\').to_lowercase();
+        path == mount || path.starts_with(&format!("{}\\", mount))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        path.starts_with(mount)
+    }
+}
+
+/// Find the partition a path lives on (the one with the longest matching mount point)
+pub fn partition_for_path<'a>(disks: &'a [DiskInfo], path: &Path) -> Option<&'a PartitionInfo> {
+    disks
+        .iter()
+        .flat_map(|d| d.partitions.iter())
+        .filter(|p| {
+            p.mount_point
+                .as_deref()
+                .map(|mp| path_is_under(path, mp))
+                .unwrap_or(false)
+        })
+        .max_by_key(|p| p.mount_point.as_ref().map(|mp| mp.len()).unwrap_or(0))
+}
+
+/// Paths the running app depends on, with a short description of each
+fn self_paths() -> Vec<(&'static str, PathBuf)> {
+    let mut paths = Vec::new();
+
+    if let Ok(exe) = std::env::current_exe() {
+        paths.push(("the running application", exe));
+    }
+    paths.push(("the system temp directory", std::env::temp_dir()));
+
+    paths
+}
+
+/// Where the running OS lives: `/`, or the Windows directory
+fn system_root() -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        std::env::var_os("SystemRoot")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(r"C:\Windows"))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        PathBuf::from("/")
+    }
+}
+
+/// Flag the partition holding the running OS
+pub fn mark_system_volume(disks: &mut [DiskInfo]) {
+    let Some(system_id) = partition_for_path(disks, &system_root()).map(|p| p.id.clone()) else {
+        return;
+    };
+
+    for partition in disks.iter_mut().flat_map(|d| d.partitions.iter_mut()) {
+        partition.is_system_volume = partition.id == system_id;
+    }
+}
+
+/// Refuse to `operation` ("delete", "shrink", "unmount") the running system volume unless forced
+pub fn check_system_volume(partition: &PartitionInfo, operation: &str, force: bool) -> Result<(), OperationError> {
+    if !partition.is_system_volume || force {
+        return Ok(());
+    }
+
+    Err(OperationError::SystemVolume {
+        partition: partition.device_path.clone(),
+        operation: operation.to_string(),
+    })
+}
+
+/// Fail fast on volumes whose mount point doesn't respond instead of hanging on a dead device
+pub fn check_accessible(partition: &PartitionInfo) -> Result<(), OperationError> {
+    if partition.is_accessible {
+        return Ok(());
+    }
+
+    Err(OperationError::Inaccessible {
+        partition: partition.device_path.clone(),
+        mount_point: partition.mount_point.clone().unwrap_or_default(),
+    })
+}
+
+/// Refuse to modify the partition hosting the app or its temp dir unless overridden
+pub fn check_self_disk(
+    disks: &[DiskInfo],
+    partition: &PartitionInfo,
+    allow_self_disk: bool,
+) -> Result<(), OperationError> {
+    if allow_self_disk {
+        return Ok(());
+    }
+
+    for (what, path) in self_paths() {
+        if let Some(host) = partition_for_path(disks, &path) {
+            if host.id == partition.id {
+                return Err(OperationError::SelfDisk {
+                    partition: partition.device_path.clone(),
+                    what: what.to_string(),
+                    path: path.to_string_lossy().to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+    use crate::test_support::{mock_disk, mock_partition};
+
+    fn partition(id: &str, mount_point: &str) -> PartitionInfo {
+        PartitionInfo {
+            mount_point: Some(mount_point.to_string()),
+            is_mounted: true,
+            ..mock_partition(id)
+        }
+    }
+
+    fn disks() -> Vec<DiskInfo> {
+        vec![mock_disk(
+            "/dev/sda",
+            vec![
+                partition("sda1", "/"),
+                partition("sda2", "/home"),
+                partition("sda3", "/homework"),
+            ],
+        )]
+    }
+
+    #[test]
+    fn test_partition_for_path_prefers_longest_mount() {
+        let disks = disks();
+
+        assert_eq!(partition_for_path(&disks, Path::new("/home/user/app")).unwrap().id, "sda2");
+        assert_eq!(partition_for_path(&disks, Path::new("/homework/x")).unwrap().id, "sda3");
+        assert_eq!(partition_for_path(&disks, Path::new("/usr/bin/app")).unwrap().id, "sda1");
+    }
+
+    #[test]
+    fn test_system_volume_is_flagged_and_refused() {
+        let mut disks = disks();
+        mark_system_volume(&mut disks);
+
+        let root = &disks[0].partitions[0];
+        let home = &disks[0].partitions[1];
+        assert!(root.is_system_volume);
+        assert!(!home.is_system_volume);
+
+        assert!(matches!(
+            check_system_volume(root, "delete", false),
+            Err(OperationError::SystemVolume { .. })
+        ));
+        assert!(check_system_volume(root, "delete", true).is_ok());
+        assert!(check_system_volume(home, "delete", false).is_ok());
+    }
+}
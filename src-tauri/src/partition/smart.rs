@@ -0,0 +1,309 @@
+// SMART data from smartctl
+//
+// Parses `smartctl --json -a` output into `SmartStatus`, covering both NVMe
+// (health information log) and SATA (attribute table) drives. On Windows
+// without smartctl, the storage driver's failure prediction (WMI) gives at
+// least a pass/fail verdict.
+//
+// Readings are cached for a few minutes: disk enumeration runs for nearly
+// every partition command just to look a partition up, and a smartctl run
+// per disk each time would be slow. Drives in standby are skipped rather
+// than spun up.
+
+use crate::partition::types::*;
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a drive's SMART reading is reused before smartctl runs again
+const SMART_CACHE_TTL: Duration = Duration::from_secs(300);
+
+lazy_static! {
+    /// Last reading per device path, with when it was taken
+    static ref SMART_CACHE: Mutex<HashMap<String, (Instant, Option<SmartStatus>)>> = Mutex::new(HashMap::new());
+}
+
+/// NVMe "Data Units Written" are reported in thousands of 512-byte units
+const NVME_DATA_UNIT_BYTES: u64 = 512 * 1000;
+
+/// SATA attributes whose normalized value is the remaining SSD life in percent
+const SATA_WEAR_ATTRIBUTES: &[u64] = &[
+    177, // Wear_Leveling_Count (Samsung)
+    231, // SSD_Life_Left
+    233, // Media_Wearout_Indicator (Intel)
+];
+
+/// SATA attribute holding the raw count of LBAs written
+const SATA_TOTAL_LBAS_WRITTEN: u64 = 241;
+
+/// smartctl exit status bits for never reaching the drive (bad arguments, device open failed)
+const SMARTCTL_NO_DEVICE_BITS: u64 = 0b11;
+
+/// Device name smartctl understands; on Windows it names physical drives "/dev/pdN"
+fn smartctl_device(device_path: &str) -> String {
+    match device_path.strip_prefix("\\\\.\\PhysicalDrive") {
+        Some(index) => format!("/dev/pd{}", index),
+        None => device_path.to_string(),
+    }
+}
+
+/// Read SMART data for a disk with smartctl, if it's installed and the disk reports any
+///
+/// Served from the cache when the last reading is under `SMART_CACHE_TTL` old.
+pub fn read_smart_status(device_path: &str) -> Option<SmartStatus> {
+    cached_reading(device_path, Instant::now(), || query_smartctl(device_path))
+}
+
+/// The cached reading for `key` if still fresh at `now`, otherwise `fetch`'s (then cached)
+fn cached_reading(key: &str, now: Instant, fetch: impl FnOnce() -> Option<SmartStatus>) -> Option<SmartStatus> {
+    {
+        let cache = SMART_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((taken, status)) = cache.get(key) {
+            if now.saturating_duration_since(*taken) < SMART_CACHE_TTL {
+                return status.clone();
+            }
+        }
+    }
+
+    // Not under the lock: smartctl can take seconds per drive
+    let status = fetch();
+    SMART_CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key.to_string(), (now, status.clone()));
+    status
+}
+
+fn query_smartctl(device_path: &str) -> Option<SmartStatus> {
+    // -n standby leaves sleeping drives alone; smartctl then exits without reading them
+    let output = Command::new("smartctl")
+        .args(["--json", "-a", "-n", "standby", &smartctl_device(device_path)])
+        .output()
+        .ok()?;
+
+    // smartctl uses its exit status as a bitmask of findings, so parse regardless
+    let json: Value = serde_json::from_slice(&output.stdout).ok()?;
+    device_was_read(&json).then(|| parse_smartctl_json(&json))
+}
+
+/// Whether smartctl got as far as the drive; without root it usually can't open it,
+/// and a drive in standby is skipped
+fn device_was_read(json: &Value) -> bool {
+    json["smartctl"]["exit_status"]
+        .as_u64()
+        .map_or(true, |status| status & SMARTCTL_NO_DEVICE_BITS == 0)
+}
+
+/// Status of an online disk with its SMART data; a failing verdict counts as an error
+pub fn disk_status(smart_status: Option<SmartStatus>) -> DiskStatus {
+    DiskStatus {
+        is_online: true,
+        has_errors: smart_status.as_ref().is_some_and(|s| s.health == HealthStatus::Critical),
+        smart_status,
+    }
+}
+
+/// Storage driver failure predictions, as (upper-cased instance name, status)
+///
+/// MSStorageDriver_FailurePredictStatus needs an elevated process; without
+/// one, or on drives that don't support it, nothing is returned. The instance
+/// name is the disk's PNP device ID with an instance suffix, see
+/// `failure_prediction_for`.
+#[cfg(target_os = "windows")]
+pub fn wmi_failure_predictions(com: wmi::COMLibrary) -> Vec<(String, SmartStatus)> {
+    lazy_static! {
+        static ref PREDICTIONS: Mutex<Option<(Instant, Vec<(String, SmartStatus)>)>> = Mutex::new(None);
+    }
+
+    if let Some((taken, predictions)) = &*PREDICTIONS.lock().unwrap_or_else(|e| e.into_inner()) {
+        if taken.elapsed() < SMART_CACHE_TTL {
+            return predictions.clone();
+        }
+    }
+    let predictions = query_wmi_failure_predictions(com);
+    *PREDICTIONS.lock().unwrap_or_else(|e| e.into_inner()) = Some((Instant::now(), predictions.clone()));
+    predictions
+}
+
+#[cfg(target_os = "windows")]
+fn query_wmi_failure_predictions(com: wmi::COMLibrary) -> Vec<(String, SmartStatus)> {
+    use wmi::{Variant, WMIConnection};
+
+    let Ok(con) = WMIConnection::with_namespace_path("ROOT\\WMI", com) else {
+        return Vec::new();
+    };
+    let rows: Vec<HashMap<String, Variant>> = con
+        .raw_query("SELECT InstanceName, PredictFailure FROM MSStorageDriver_FailurePredictStatus")
+        .unwrap_or_default();
+
+    rows.iter()
+        .filter_map(|row| {
+            let Some(Variant::String(instance)) = row.get("InstanceName") else { return None };
+            let Some(Variant::Bool(predict_failure)) = row.get("PredictFailure") else { return None };
+            let status = SmartStatus {
+                health: if *predict_failure { HealthStatus::Critical } else { HealthStatus::Good },
+                temperature: None,
+                power_on_hours: None,
+                percent_used: None,
+                data_written_bytes: None,
+            };
+            Some((instance.to_uppercase(), status))
+        })
+        .collect()
+}
+
+/// The prediction whose instance name is `pnp_device_id` plus an instance suffix ("_0")
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn failure_prediction_for(predictions: &[(String, SmartStatus)], pnp_device_id: &str) -> Option<SmartStatus> {
+    let prefix = format!("{}_", pnp_device_id.to_uppercase());
+    predictions
+        .iter()
+        .find(|(instance, _)| instance.starts_with(&prefix))
+        .map(|(_, status)| status.clone())
+}
+
+/// SMART data for a Windows disk: smartctl when installed, otherwise the driver's failure prediction
+#[cfg(target_os = "windows")]
+pub fn read_windows_smart_status(
+    device_path: &str,
+    pnp_device_id: Option<&str>,
+    predictions: &[(String, SmartStatus)],
+) -> Option<SmartStatus> {
+    read_smart_status(device_path)
+        .or_else(|| pnp_device_id.and_then(|id| failure_prediction_for(predictions, id)))
+}
+
+/// Build a `SmartStatus` from `smartctl --json -a` output
+pub fn parse_smartctl_json(json: &Value) -> SmartStatus {
+    let health = match json["smart_status"]["passed"].as_bool() {
+        Some(true) => HealthStatus::Good,
+        Some(false) => HealthStatus::Critical,
+        None => HealthStatus::Unknown,
+    };
+
+    let nvme = &json["nvme_smart_health_information_log"];
+
+    let percent_used = nvme["percentage_used"]
+        .as_u64()
+        .or_else(|| sata_wear_percent_used(json))
+        .map(|p| p.min(u8::MAX as u64) as u8);
+
+    let data_written_bytes = nvme["data_units_written"]
+        .as_u64()
+        .map(|units| units.saturating_mul(NVME_DATA_UNIT_BYTES))
+        .or_else(|| {
+            let lbas = sata_attribute(json, SATA_TOTAL_LBAS_WRITTEN)?["raw"]["value"].as_u64()?;
+            let sector_size = json["logical_block_size"].as_u64().unwrap_or(512);
+            Some(lbas.saturating_mul(sector_size))
+        });
+
+    SmartStatus {
+        health,
+        temperature: json["temperature"]["current"].as_f64().map(|t| t as f32),
+        power_on_hours: json["power_on_time"]["hours"].as_u64(),
+        percent_used,
+        data_written_bytes,
+    }
+}
+
+fn sata_attribute(json: &Value, id: u64) -> Option<&Value> {
+    json["ata_smart_attributes"]["table"]
+        .as_array()?
+        .iter()
+        .find(|attr| attr["id"].as_u64() == Some(id))
+}
+
+/// Wear used from the first SATA life-remaining attribute present
+fn sata_wear_percent_used(json: &Value) -> Option<u64> {
+    SATA_WEAR_ATTRIBUTES.iter().find_map(|id| {
+        let remaining = sata_attribute(json, *id)?["value"].as_u64()?;
+        Some(100u64.saturating_sub(remaining.min(100)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvme_wear() {
+        let json = serde_json::json!({
+            "smart_status": { "passed": true },
+            "temperature": { "current": 38 },
+            "power_on_time": { "hours": 1200 },
+            "nvme_smart_health_information_log": {
+                "percentage_used": 12,
+                "data_units_written": 1000
+            }
+        });
+
+        let status = parse_smartctl_json(&json);
+
+        assert_eq!(status.health, HealthStatus::Good);
+        assert_eq!(status.percent_used, Some(12));
+        assert_eq!(status.data_written_bytes, Some(512_000_000));
+        assert_eq!(status.power_on_hours, Some(1200));
+    }
+
+    #[test]
+    fn test_unreadable_device_and_wmi_match() {
+        assert!(!device_was_read(&serde_json::json!({ "smartctl": { "exit_status": 2 } })));
+        assert!(device_was_read(&serde_json::json!({ "smartctl": { "exit_status": 4 } })));
+
+        let failing = SmartStatus {
+            health: HealthStatus::Critical,
+            temperature: None,
+            power_on_hours: None,
+            percent_used: None,
+            data_written_bytes: None,
+        };
+        let predictions = vec![("SCSI\\DISK&VEN_NVME&PROD_X\\5&1A2B&0&000000_0".to_string(), failing)];
+        let found = failure_prediction_for(&predictions, "scsi\\disk&ven_nvme&prod_x\\5&1a2b&0&000000");
+        assert_eq!(found.map(|s| s.health), Some(HealthStatus::Critical));
+        assert!(failure_prediction_for(&predictions, "SCSI\\DISK&VEN_NVME&PROD_X\\5&1A2B&0&00000").is_none());
+        assert!(disk_status(predictions.first().map(|(_, s)| s.clone())).has_errors);
+    }
+
+    #[test]
+    fn test_parse_sata_wear() {
+        let json = serde_json::json!({
+            "smart_status": { "passed": true },
+            "logical_block_size": 512,
+            "ata_smart_attributes": { "table": [
+                { "id": 177, "name": "Wear_Leveling_Count", "value": 95, "raw": { "value": 40 } },
+                { "id": 241, "name": "Total_LBAs_Written", "value": 99, "raw": { "value": 2048 } }
+            ] }
+        });
+
+        let status = parse_smartctl_json(&json);
+
+        assert_eq!(status.percent_used, Some(5));
+        assert_eq!(status.data_written_bytes, Some(2048 * 512));
+    }
+
+    #[test]
+    fn test_reading_is_cached_until_ttl() {
+        let key = format!("/dev/test-{}", uuid::Uuid::new_v4());
+        let start = Instant::now();
+        let reading = |health| {
+            move || Some(SmartStatus {
+                health,
+                temperature: None,
+                power_on_hours: None,
+                percent_used: None,
+                data_written_bytes: None,
+            })
+        };
+
+        assert_eq!(cached_reading(&key, start, reading(HealthStatus::Good)).unwrap().health, HealthStatus::Good);
+        // Fresh: the new reading isn't taken
+        let later = start + SMART_CACHE_TTL / 2;
+        assert_eq!(cached_reading(&key, later, reading(HealthStatus::Critical)).unwrap().health, HealthStatus::Good);
+        // Expired: read again
+        let expired = start + SMART_CACHE_TTL;
+        assert_eq!(cached_reading(&key, expired, reading(HealthStatus::Critical)).unwrap().health, HealthStatus::Critical);
+    }
+}
@@ -0,0 +1,184 @@
+// Split partition workflow
+//
+// Carves a new partition out of an existing one in a single guided action:
+// 1. Validate the shrink of the source partition
+// 2. Shrink the source (frees space at its end)
+// 3. Create and format a new partition in the freed gap
+//
+// If step 3 fails, a partition it left half-created is deleted and the source
+// is grown back, so the disk ends up as it started.
+
+use crate::partition::create::{align_up, create_partition};
+use crate::partition::resize::progress::ResizeProgress;
+use crate::partition::resize::shrink::shrink_partition;
+use crate::partition::resize::validation::validate_shrink;
+use crate::partition::transaction::{StepRecord, Transaction, Undo};
+use crate::partition::types::*;
+use anyhow::{anyhow, Result};
+
+/// Layout computed for a split before anything is executed
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SplitLayout {
+    /// New size of the source partition after shrinking
+    pub source_new_size: u64,
+
+    /// Offset of the new partition
+    pub new_partition_offset: u64,
+
+    /// Size of the new partition
+    pub new_partition_size: u64,
+}
+
+/// Compute where the new partition will go when `partition` is split
+pub fn plan_split(partition: &PartitionInfo, new_partition_size: u64) -> Result<SplitLayout> {
+    if partition.flags.contains(&PartitionFlag::Boot) || partition.flags.contains(&PartitionFlag::System) {
+        return Err(anyhow!(
+            "Cannot split a boot or system partition - this could make the system unbootable!"
+        ));
+    }
+
+    if new_partition_size == 0 || new_partition_size >= partition.total_size {
+        return Err(anyhow!(
+            "New partition size must be greater than zero and smaller than the source partition"
+        ));
+    }
+
+    // Shrink to an aligned size so the new partition starts on a boundary
+    let source_new_size =
+        (partition.total_size - new_partition_size) / PARTITION_ALIGNMENT * PARTITION_ALIGNMENT;
+    let partition_end = partition.start_offset + partition.total_size;
+    let new_partition_offset = align_up(partition.start_offset + source_new_size);
+
+    if new_partition_offset >= partition_end {
+        return Err(anyhow!("Not enough space left after alignment to create a partition"));
+    }
+
+    Ok(SplitLayout {
+        source_new_size,
+        new_partition_offset,
+        new_partition_size: new_partition_size.min(partition_end - new_partition_offset),
+    })
+}
+
+/// Split a partition: shrink it, then create a new partition in the freed space
+///
+/// If the create step fails after the shrink succeeded, the shrink is rolled
+/// back. When that isn't possible the error is a `PartialFailure` saying
+/// exactly where the operation stopped and what is left to do.
+pub async fn split_partition(
+    partition: &PartitionInfo,
+    disk: &DiskInfo,
+    new_partition_size: u64,
+    new_filesystem: FilesystemType,
+    new_label: Option<&str>,
+    progress_callback: impl Fn(ResizeProgress),
+) -> Result<()> {
+    progress_callback(ResizeProgress::validating("Validating split..."));
+
+    let layout = plan_split(partition, new_partition_size)?;
+
+    let validation = validate_shrink(partition, layout.source_new_size)?;
+    if !validation.is_valid {
+        return Err(anyhow!(
+            "Split validation failed: {}",
+            validation.errors.join(", ")
+        ));
+    }
+
+    let mut transaction = Transaction::new(format!("Split of {}", partition.device_path));
+    transaction
+        .snapshot(disk)
+        .map_err(|e| anyhow!("Couldn't back up the partition table of {}: {}. No changes were made.", disk.device_path, e))?;
+
+    // Step 1: Shrink the source partition
+    progress_callback(ResizeProgress::resizing_filesystem(
+        0.0,
+        format!("Shrinking {} to make room...", partition.device_path),
+    ));
+
+    shrink_partition(partition, disk, layout.source_new_size)
+        .await
+        .map_err(|e| anyhow!("Split failed while shrinking {}: {}. No changes were made.", partition.device_path, e))?;
+
+    transaction.record(
+        StepRecord {
+            description: format!("shrink {}", partition.device_path),
+            before: format!("{} bytes", partition.total_size),
+            after: format!("{} bytes", layout.source_new_size),
+        },
+        Undo::Regrow {
+            disk_path: disk.device_path.clone(),
+            start_offset: partition.start_offset,
+            original_size: partition.total_size,
+        },
+    );
+
+    // Step 2: Create the new partition in the freed gap
+    progress_callback(ResizeProgress::updating_partition_table(
+        "Creating new partition in freed space...",
+    ));
+
+    if let Err(e) = create_partition(
+        disk,
+        layout.new_partition_offset,
+        layout.new_partition_size,
+        new_filesystem,
+        new_label,
+    ) {
+        progress_callback(ResizeProgress::updating_partition_table(format!(
+            "Creating the new partition failed; restoring {}...",
+            partition.device_path
+        )));
+
+        // The partition may exist in the table without a filesystem
+        let half_created = Undo::RemoveCreated {
+            disk_path: disk.device_path.clone(),
+            start_offset: layout.new_partition_offset,
+        };
+        let failure = transaction
+            .rollback("create the new partition", e, Some(half_created))
+            .await;
+
+        progress_callback(ResizeProgress::error(failure.to_string()));
+        return Err(failure.into());
+    }
+
+    progress_callback(ResizeProgress::complete("Partition split successfully!"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{mock_partition, GB};
+
+    fn partition(flags: Vec<PartitionFlag>) -> PartitionInfo {
+        PartitionInfo {
+            number: 2,
+            device_path: "D:".to_string(),
+            label: Some("Data".to_string()),
+            start_offset: 100 * GB,
+            total_size: 200 * GB,
+            used_space: Some(50 * GB),
+            filesystem: FilesystemType::NTFS,
+            mount_point: Some("D:".to_string()),
+            is_mounted: true,
+            flags,
+            ..mock_partition("part-d")
+        }
+    }
+
+    #[test]
+    fn test_plan_split_places_new_partition_at_end() {
+        let layout = plan_split(&partition(vec![]), 50 * GB).unwrap();
+
+        assert_eq!(layout.source_new_size, 150 * GB);
+        assert_eq!(layout.new_partition_offset, 250 * GB);
+        assert_eq!(layout.new_partition_size, 50 * GB);
+    }
+
+    #[test]
+    fn test_plan_split_rejects_boot_partition() {
+        assert!(plan_split(&partition(vec![PartitionFlag::Boot]), 50 * GB).is_err());
+    }
+}
@@ -0,0 +1,194 @@
+// Windows Storage module (PowerShell) backend
+//
+// The Storage cmdlets (Get-Partition, Resize-Partition, Remove-Partition,
+// Windows 8+) address partitions by disk number and offset instead of drive
+// letter, so unmounted partitions can be resized and deleted, and they fail
+// with a stable error id rather than localized text to scrape. Resize-Partition
+// also resizes the NTFS filesystem along with the partition.
+//
+// Used in preference to diskpart when the capability probe finds the module;
+// diskpart stays the fallback on systems without it.
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use crate::partition::types::*;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+
+/// Where a partition lives, in the terms the Storage cmdlets use
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartitionRef {
+    pub disk_number: u32,
+    pub offset: u64,
+}
+
+impl PartitionRef {
+    /// From a Windows partition id ("partition-<disk>-<n>") and its start offset
+    pub fn of(partition: &PartitionInfo) -> Option<Self> {
+        let disk_number = partition
+            .id
+            .strip_prefix("partition-")?
+            .split('-')
+            .next()?
+            .parse()
+            .ok()?;
+        Some(Self {
+            disk_number,
+            offset: partition.start_offset,
+        })
+    }
+
+    /// Pipeline that yields exactly this partition, failing if it's gone
+    fn select(&self) -> String {
+        format!(
+            "$p = Get-Partition -DiskNumber {} | Where-Object Offset -eq {}; \
+             if (-not $p) {{ throw 'No partition at offset {} on disk {}' }}; $p",
+            self.disk_number, self.offset, self.offset, self.disk_number
+        )
+    }
+}
+
+/// Error record written to stderr by the `run` wrapper
+#[derive(Debug, Deserialize)]
+struct CmdletError {
+    message: String,
+    id: String, // FullyQualifiedErrorId, e.g. "StorageWMI 40002,Resize-Partition"
+}
+
+/// Wrap `script` so any error is written to stderr as one JSON object and the exit status is 1
+fn wrap(script: &str) -> String {
+    format!(
+        "$ErrorActionPreference = 'Stop'; $ProgressPreference = 'SilentlyContinue'; \
+         try {{ {} }} catch {{ \
+         [Console]::Error.WriteLine((@{{ message = $_.Exception.Message; id = $_.FullyQualifiedErrorId }} | ConvertTo-Json -Compress)); \
+         exit 1 }}",
+        script
+    )
+}
+
+/// Turn the wrapper's stderr into a readable error
+fn parse_error(stderr: &str) -> String {
+    stderr
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<CmdletError>(line.trim()).ok())
+        .map(|e| format!("{} ({})", e.message.trim(), e.id))
+        .unwrap_or_else(|| stderr.trim().to_string())
+}
+
+/// Smallest and largest sizes Resize-Partition accepts for a partition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct SupportedSize {
+    #[serde(rename = "SizeMin")]
+    pub min: u64,
+    #[serde(rename = "SizeMax")]
+    pub max: u64,
+}
+
+impl SupportedSize {
+    /// Fail with the allowed range unless `size` is inside it
+    fn check(&self, size: u64) -> Result<()> {
+        if size < self.min || size > self.max {
+            return Err(anyhow!(
+                "Windows can only resize this partition to between {} and {} bytes, not {}",
+                self.min,
+                self.max,
+                size
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn run(script: &str) -> Result<String> {
+    use crate::partition::exec::RunCommand;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &wrap(script)])
+        .creation_flags(CREATE_NO_WINDOW)
+        .run()?;
+
+    if !output.status.success() {
+        return Err(anyhow!("{}", parse_error(&String::from_utf8_lossy(&output.stderr))));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn run(_script: &str) -> Result<String> {
+    Err(anyhow!("The Storage PowerShell module is only available on Windows"))
+}
+
+/// Whether the Storage module is installed (Windows 8 / Server 2012 and later)
+pub fn probe() -> bool {
+    cfg!(target_os = "windows")
+        && run("if (-not (Get-Command Resize-Partition -ErrorAction SilentlyContinue)) { throw 'missing' }").is_ok()
+}
+
+fn partition_ref(partition: &PartitionInfo) -> Result<PartitionRef> {
+    PartitionRef::of(partition).ok_or_else(|| anyhow!("Can't locate {} by disk number", partition.device_path))
+}
+
+/// Size limits for resizing `partition`, as reported by Get-PartitionSupportedSize
+pub fn supported_size(partition: &PartitionInfo) -> Result<SupportedSize> {
+    let target = partition_ref(partition)?;
+    let json = run(&format!(
+        "{} | Get-PartitionSupportedSize | Select-Object SizeMin, SizeMax | ConvertTo-Json -Compress",
+        target.select()
+    ))?;
+    serde_json::from_str(json.trim()).map_err(|e| anyhow!("Unexpected Get-PartitionSupportedSize output: {}", e))
+}
+
+/// Grow or shrink `partition` (and its filesystem) to `size` bytes
+///
+/// Checks `size` against Get-PartitionSupportedSize first, so an impossible
+/// size is refused with the allowed range instead of a StorageWMI error id.
+pub fn resize_partition(partition: &PartitionInfo, size: u64) -> Result<()> {
+    supported_size(partition)?.check(size)?;
+    let target = partition_ref(partition)?;
+    run(&format!("{} | Resize-Partition -Size {}", target.select(), size))?;
+    Ok(())
+}
+
+/// Delete `partition`; works whether or not it has a drive letter
+pub fn remove_partition(partition: &PartitionInfo) -> Result<()> {
+    let target = partition_ref(partition)?;
+    run(&format!("{} | Remove-Partition -Confirm:$false", target.select()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::mock_partition;
+
+    #[test]
+    fn test_partition_ref_and_error_parsing() {
+        let partition = PartitionInfo {
+            number: 3,
+            device_path: "\\\\?\\Volume{abc}\\".to_string(),
+            start_offset: 16_777_216,
+            total_size: 1 << 30,
+            filesystem: FilesystemType::NTFS,
+            ..mock_partition("partition-2-3")
+        };
+        let target = PartitionRef::of(&partition).unwrap();
+        assert_eq!(target, PartitionRef { disk_number: 2, offset: 16_777_216 });
+        assert!(target.select().starts_with("$p = Get-Partition -DiskNumber 2 | Where-Object Offset -eq 16777216"));
+        assert!(PartitionRef::of(&PartitionInfo { id: "sda1".to_string(), ..partition }).is_none());
+
+        let stderr = "{\"message\":\"Size Not Supported\",\"id\":\"StorageWMI 4097,Resize-Partition\"}\n";
+        assert_eq!(parse_error(stderr), "Size Not Supported (StorageWMI 4097,Resize-Partition)");
+        assert_eq!(parse_error("plain text\n"), "plain text");
+
+        let size: SupportedSize = serde_json::from_str("{\"SizeMin\":1048576,\"SizeMax\":2147483648}").unwrap();
+        assert_eq!(size, SupportedSize { min: 1_048_576, max: 2_147_483_648 });
+        assert!(size.check(1 << 30).is_ok());
+        assert!(size.check(1024).unwrap_err().to_string().contains("between 1048576 and 2147483648 bytes"));
+        assert!(size.check(4 << 30).is_err());
+    }
+}
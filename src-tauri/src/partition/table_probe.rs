@@ -0,0 +1,108 @@
+// Partition table detection from the disk's first sectors
+//
+// LBA0 holds an MBR ending in the 0x55AA boot signature; on a GPT disk it is
+// a protective MBR (one entry of type 0xEE) and LBA1 starts with the
+// "EFI PART" header. LBA1 is at byte 512 or 4096 depending on the logical
+// sector size, so both offsets are checked. Opening a physical disk needs
+// administrator/root rights.
+
+use super::types::PartitionTableType;
+use std::fs::File;
+use std::io::Read;
+
+/// Bytes read from the start of the disk: LBA0 and LBA1 at either sector size
+const PROBE_BYTES: usize = 8192;
+
+const MBR_SIGNATURE_OFFSET: usize = 510;
+const MBR_ENTRIES_OFFSET: usize = 446;
+const MBR_ENTRY_SIZE: usize = 16;
+const GPT_PROTECTIVE_TYPE: u8 = 0xEE;
+const GPT_SIGNATURE: &[u8; 8] = b"EFI PART";
+
+/// Possible byte offsets of LBA1 (512- and 4096-byte logical sectors)
+const LBA1_OFFSETS: [usize; 2] = [512, 4096];
+
+/// Partition table type from the first bytes of a disk
+///
+/// A boot sector without a valid partition table (a "superfloppy" formatted
+/// without partitions) also ends in 0x55AA; it is told apart by the entries'
+/// boot flags, which are only ever 0x00 or 0x80 in a real MBR.
+pub fn detect_table_type(head: &[u8]) -> PartitionTableType {
+    let has_gpt_header = LBA1_OFFSETS
+        .iter()
+        .any(|&offset| head.get(offset..offset + GPT_SIGNATURE.len()) == Some(GPT_SIGNATURE.as_slice()));
+    if has_gpt_header {
+        return PartitionTableType::GPT;
+    }
+
+    if head.get(MBR_SIGNATURE_OFFSET..MBR_SIGNATURE_OFFSET + 2) != Some([0x55, 0xAA].as_slice()) {
+        return PartitionTableType::Unknown;
+    }
+    let entries: Vec<&[u8]> = (0..4)
+        .map(|i| &head[MBR_ENTRIES_OFFSET + i * MBR_ENTRY_SIZE..MBR_ENTRIES_OFFSET + (i + 1) * MBR_ENTRY_SIZE])
+        .collect();
+    if !entries.iter().all(|entry| entry[0] == 0x00 || entry[0] == 0x80) {
+        return PartitionTableType::Unknown;
+    }
+
+    // A protective entry without a readable header is still a (damaged) GPT disk
+    if entries.iter().any(|entry| entry[4] == GPT_PROTECTIVE_TYPE) {
+        PartitionTableType::GPT
+    } else {
+        PartitionTableType::MBR
+    }
+}
+
+/// Read the start of `device_path` (e.g. `\\.\PhysicalDrive0`, `/dev/sda`) and detect its table type
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn read_partition_table_type(device_path: &str) -> std::io::Result<PartitionTableType> {
+    crate::capabilities::require_raw_disk_access("Partition table detection")
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::PermissionDenied, e))?;
+
+    let mut head = vec![0u8; PROBE_BYTES];
+    let mut file = File::open(device_path)?;
+    // Raw disk reads must be whole sectors; PROBE_BYTES is a multiple of both sizes
+    let mut filled = 0;
+    while filled < head.len() {
+        match file.read(&mut head[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+    head.truncate(filled);
+    Ok(detect_table_type(&head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mbr_with_type(partition_type: u8) -> Vec<u8> {
+        let mut head = vec![0u8; PROBE_BYTES];
+        head[MBR_ENTRIES_OFFSET + 4] = partition_type;
+        head[MBR_SIGNATURE_OFFSET] = 0x55;
+        head[MBR_SIGNATURE_OFFSET + 1] = 0xAA;
+        head
+    }
+
+    #[test]
+    fn test_detect_table_type() {
+        assert_eq!(detect_table_type(&mbr_with_type(0x07)), PartitionTableType::MBR);
+
+        // GPT header at LBA1 for 512- and 4096-byte sectors
+        for offset in LBA1_OFFSETS {
+            let mut head = mbr_with_type(GPT_PROTECTIVE_TYPE);
+            head[offset..offset + 8].copy_from_slice(GPT_SIGNATURE);
+            assert_eq!(detect_table_type(&head), PartitionTableType::GPT);
+        }
+        assert_eq!(detect_table_type(&mbr_with_type(GPT_PROTECTIVE_TYPE)), PartitionTableType::GPT);
+
+        // Boot sector of an unpartitioned volume: jump code where the boot flags would be
+        let mut superfloppy = mbr_with_type(0x07);
+        superfloppy[MBR_ENTRIES_OFFSET] = 0xEB;
+        assert_eq!(detect_table_type(&superfloppy), PartitionTableType::Unknown);
+
+        assert_eq!(detect_table_type(&[0u8; PROBE_BYTES]), PartitionTableType::Unknown);
+        assert_eq!(detect_table_type(&[]), PartitionTableType::Unknown);
+    }
+}
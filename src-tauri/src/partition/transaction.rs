@@ -0,0 +1,342 @@
+// Bookkeeping for compound partition operations
+//
+// A split (shrink, then create) or a reallocation plan (several moves) runs
+// as a sequence of sub-steps. If one fails partway, the disk is left in a
+// state that matches neither the start nor the goal. The transaction records
+// every completed step with its before/after layout and how to undo it; on
+// failure it undoes what it safely can, newest first, and otherwise reports a
+// `PartialFailure` listing what happened and what is left to do by hand.
+//
+// Undo is limited to changes that touch no user data: growing a partition
+// back into space it just gave up, and deleting a partition created by the
+// same operation. Anything else (e.g. a completed move) is reported, not undone.
+//
+// Before the first step changes a disk, its partition table is saved to the
+// app's data directory, so a layout that can't be rolled back automatically
+// can still be restored from what it was before the operation started.
+
+use crate::partition::delete::delete_partition;
+#[cfg(target_os = "linux")]
+use crate::partition::exec::RunCommand;
+use crate::partition::info::get_disk_by_path;
+use crate::partition::resize::expand::expand_partition;
+use crate::partition::types::*;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// A completed sub-step, as shown to the user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub description: String,
+    pub before: String, // Layout before the step
+    pub after: String, // Layout after the step
+}
+
+/// How to reverse a completed sub-step
+#[derive(Debug, Clone)]
+pub enum Undo {
+    /// Grow the partition starting at `start_offset` back to `original_size`
+    Regrow {
+        disk_path: String,
+        start_offset: u64,
+        original_size: u64,
+    },
+    /// Delete the partition this operation created at `start_offset`
+    RemoveCreated { disk_path: String, start_offset: u64 },
+    /// Can't be reversed automatically; the text says what to do instead
+    Manual(String),
+}
+
+/// A compound operation stopped partway and could not be fully rolled back
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
+#[error(
+    "{operation} failed at \"{failed_step}\": {reason}. {}",
+    summary(.completed, .rolled_back, .manual_steps, .table_backups)
+)]
+pub struct PartialFailure {
+    pub operation: String,
+    pub failed_step: String,
+    pub reason: String,
+    /// Steps that completed and are still in effect
+    pub completed: Vec<StepRecord>,
+    /// Steps that completed and were undone
+    pub rolled_back: Vec<String>,
+    /// What the user has to do to get to a consistent layout
+    pub manual_steps: Vec<String>,
+    /// Files holding the partition tables as they were before the first step
+    pub table_backups: Vec<String>,
+}
+
+fn summary(completed: &[StepRecord], rolled_back: &[String], manual_steps: &[String], table_backups: &[String]) -> String {
+    let mut parts = Vec::new();
+    if !rolled_back.is_empty() {
+        parts.push(format!("Rolled back: {}.", rolled_back.join("; ")));
+    }
+    if completed.is_empty() {
+        parts.push("The disk is back to its original layout.".to_string());
+    } else {
+        let steps: Vec<String> = completed
+            .iter()
+            .map(|s| format!("{} ({} -> {})", s.description, s.before, s.after))
+            .collect();
+        parts.push(format!("Still in effect: {}.", steps.join("; ")));
+    }
+    if !manual_steps.is_empty() {
+        parts.push(format!("To finish by hand: {}.", manual_steps.join("; ")));
+    }
+    if !completed.is_empty() && !table_backups.is_empty() {
+        parts.push(format!("The partition table from before the operation is saved in {}.", table_backups.join(", ")));
+    }
+    parts.join(" ")
+}
+
+impl PartialFailure {
+    /// True when every completed step was undone
+    pub fn fully_rolled_back(&self) -> bool {
+        self.completed.is_empty()
+    }
+}
+
+/// Completed steps of one compound operation
+pub struct Transaction {
+    operation: String,
+    steps: Vec<(StepRecord, Undo)>,
+    /// Disk path and backup file of every table saved by `snapshot`
+    table_backups: Vec<(String, PathBuf)>,
+}
+
+impl Transaction {
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self {
+            operation: operation.into(),
+            steps: Vec::new(),
+            table_backups: Vec::new(),
+        }
+    }
+
+    /// Save `disk`'s partition table, once per disk, before a step changes it
+    pub fn snapshot(&mut self, disk: &DiskInfo) -> Result<()> {
+        if self.table_backups.iter().any(|(path, _)| *path == disk.device_path) {
+            return Ok(());
+        }
+        let backup = save_table(disk)?;
+        log::info!("Saved the partition table of {} to {}", disk.device_path, backup.display());
+        self.table_backups.push((disk.device_path.clone(), backup));
+        Ok(())
+    }
+
+    /// Record a step that completed
+    pub fn record(&mut self, step: StepRecord, undo: Undo) {
+        self.steps.push((step, undo));
+    }
+
+    /// Roll back after `failed_step` failed with `error`, newest step first
+    ///
+    /// Stops at the first step that can't be undone, since undoing an older
+    /// step is only safe once everything after it is gone. `cleanup` undoes
+    /// leftovers of the failed step itself (e.g. a created but unformatted
+    /// partition) before any completed step.
+    pub async fn rollback(mut self, failed_step: &str, error: anyhow::Error, cleanup: Option<Undo>) -> PartialFailure {
+        let mut rolled_back = Vec::new();
+        let mut manual_steps = Vec::new();
+
+        if let Some(undo) = cleanup {
+            if let Err(e) = apply(&undo).await {
+                manual_steps.push(failed_undo(&undo, &e));
+                manual_steps.extend(self.steps.iter().rev().map(|(_, undo)| describe(undo)));
+                return self.into_failure(failed_step, error, rolled_back, manual_steps);
+            }
+            rolled_back.push(format!("removed the partial result of \"{}\"", failed_step));
+        }
+
+        while let Some((step, undo)) = self.steps.pop() {
+            match apply(&undo).await {
+                Ok(()) => rolled_back.push(step.description),
+                Err(e) => {
+                    manual_steps.push(failed_undo(&undo, &e));
+                    self.steps.push((step, undo));
+                    manual_steps.extend(self.steps.iter().rev().skip(1).map(|(_, undo)| describe(undo)));
+                    break;
+                }
+            }
+        }
+
+        self.into_failure(failed_step, error, rolled_back, manual_steps)
+    }
+
+    fn into_failure(
+        self,
+        failed_step: &str,
+        error: anyhow::Error,
+        rolled_back: Vec<String>,
+        manual_steps: Vec<String>,
+    ) -> PartialFailure {
+        PartialFailure {
+            operation: self.operation,
+            failed_step: failed_step.to_string(),
+            reason: error.to_string(),
+            completed: self.steps.into_iter().map(|(step, _)| step).collect(),
+            rolled_back,
+            manual_steps,
+            table_backups: self
+                .table_backups
+                .into_iter()
+                .map(|(_, backup)| backup.display().to_string())
+                .collect(),
+        }
+    }
+}
+
+/// What the user has to do when `undo` can't run automatically
+fn describe(undo: &Undo) -> String {
+    match undo {
+        Undo::Regrow { disk_path, start_offset, original_size } => format!(
+            "extend the partition at offset {} on {} back to {} bytes",
+            start_offset, disk_path, original_size
+        ),
+        Undo::RemoveCreated { disk_path, start_offset } => format!(
+            "delete the new partition at offset {} on {}",
+            start_offset, disk_path
+        ),
+        Undo::Manual(text) => text.clone(),
+    }
+}
+
+/// Manual instruction for an undo that didn't run, with the reason when it was attempted
+fn failed_undo(undo: &Undo, error: &anyhow::Error) -> String {
+    match undo {
+        Undo::Manual(text) => text.clone(),
+        _ => format!("{} (automatic rollback failed: {})", describe(undo), error),
+    }
+}
+
+/// Where partition tables are saved before compound operations
+fn table_backup_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("com.ittoolkit.app")
+        .join("partition-tables")
+}
+
+/// Write `disk`'s current layout to the backup directory, returning the file
+///
+/// On Linux this is `sfdisk --dump` output, which `sfdisk <disk> < file`
+/// restores exactly. Elsewhere, or when sfdisk fails, the layout is saved as
+/// JSON: offsets and sizes are enough to recreate the partitions by hand.
+fn save_table(disk: &DiskInfo) -> Result<PathBuf> {
+    let dir = table_backup_dir();
+    std::fs::create_dir_all(&dir)?;
+    let name: String = disk
+        .id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stem = dir.join(format!("{}-{}", name, chrono::Local::now().format("%Y%m%d-%H%M%S")));
+
+    #[cfg(target_os = "linux")]
+    {
+        let output = std::process::Command::new("sfdisk")
+            .arg("--dump")
+            .arg(&disk.device_path)
+            .run()?;
+        if output.status.success() {
+            let backup = stem.with_extension("sfdisk");
+            std::fs::write(&backup, &output.stdout)?;
+            return Ok(backup);
+        }
+        log::warn!(
+            "sfdisk --dump {} failed ({}); saving the layout as JSON instead",
+            disk.device_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let backup = stem.with_extension("json");
+    std::fs::write(&backup, serde_json::to_vec_pretty(disk)?)?;
+    Ok(backup)
+}
+
+/// The partition on a freshly read `disk_path` that starts at `start_offset`
+///
+/// Ids and numbers can change after the table is rewritten; the start offset doesn't.
+fn partition_at(disk_path: &str, start_offset: u64) -> Result<(DiskInfo, PartitionInfo)> {
+    let disk = get_disk_by_path(disk_path)?;
+    let partition = disk
+        .partitions
+        .iter()
+        .find(|p| p.start_offset.abs_diff(start_offset) < PARTITION_ALIGNMENT)
+        .cloned()
+        .ok_or_else(|| anyhow!("no partition at offset {} on {}", start_offset, disk_path))?;
+    Ok((disk, partition))
+}
+
+async fn apply(undo: &Undo) -> Result<()> {
+    match undo {
+        Undo::Regrow { disk_path, start_offset, original_size } => {
+            let (disk, partition) = partition_at(disk_path, *start_offset)?;
+            expand_partition(&partition, &disk, *original_size).await
+        }
+        Undo::RemoveCreated { disk_path, start_offset } => {
+            // Nothing to remove when the create never reached the partition table
+            match partition_at(disk_path, *start_offset) {
+                Ok((disk, partition)) => delete_partition(&partition, &disk),
+                Err(_) => Ok(()),
+            }
+        }
+        Undo::Manual(text) => Err(anyhow!("{}", text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(description: &str) -> StepRecord {
+        StepRecord {
+            description: description.to_string(),
+            before: "before".to_string(),
+            after: "after".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rollback_stops_at_manual_step() {
+        let mut tx = Transaction::new("Reallocation plan");
+        tx.record(step("move A"), Undo::Manual("move A back to offset 0".to_string()));
+        tx.record(step("move B"), Undo::Manual("move B back to offset 10".to_string()));
+
+        let failure = tx.rollback("move C", anyhow!("disk busy"), None).await;
+
+        assert!(!failure.fully_rolled_back());
+        assert!(failure.rolled_back.is_empty());
+        assert_eq!(failure.completed.len(), 2);
+        assert_eq!(failure.manual_steps.len(), 2);
+        assert_eq!(failure.manual_steps[0], "move B back to offset 10");
+        assert_eq!(failure.manual_steps[1], "move A back to offset 0");
+
+        let message = failure.to_string();
+        assert!(message.contains("failed at \"move C\": disk busy"));
+        assert!(message.contains("Still in effect: move A"));
+    }
+
+    #[test]
+    fn test_partial_failure_serializes_its_fields() {
+        let failure = PartialFailure {
+            operation: "Split of /dev/sda2".to_string(),
+            failed_step: "create the new partition".to_string(),
+            reason: "parted failed".to_string(),
+            completed: vec![step("shrink /dev/sda2")],
+            rolled_back: vec![],
+            manual_steps: vec!["delete the new partition".to_string()],
+            table_backups: vec!["/backups/sda.sfdisk".to_string()],
+        };
+
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["failed_step"], "create the new partition");
+        assert_eq!(json["completed"][0]["description"], "shrink /dev/sda2");
+        assert_eq!(json["table_backups"][0], "/backups/sda.sfdisk");
+        assert!(failure.to_string().contains("saved in /backups/sda.sfdisk"));
+    }
+}
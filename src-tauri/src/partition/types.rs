@@ -1,9 +1,15 @@
 // Type definitions for partition management
 
+use crate::mounts::MountKind;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Partition boundaries are aligned to 1 MiB, matching diskpart/parted defaults
+pub const PARTITION_ALIGNMENT: u64 = 1024 * 1024;
 
 /// Represents a physical disk
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct DiskInfo {
     /// Unique identifier for the disk
     pub id: String,
@@ -15,6 +21,7 @@ pub struct DiskInfo {
     pub model: String,
 
     /// Total size in bytes
+    #[ts(type = "number")]
     pub total_size: u64,
 
     /// Partition table type
@@ -28,10 +35,24 @@ pub struct DiskInfo {
 
     /// Health status
     pub status: DiskStatus,
+
+    /// Largest contiguous unallocated region (bytes)
+    ///
+    /// This and `total_free` are filled in from the partition layout by the
+    /// disk provider; platform code leaves them at 0.
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub largest_free_block: u64,
+
+    /// Total unallocated space across all gaps (bytes)
+    #[serde(default)]
+    #[ts(type = "number")]
+    pub total_free: u64,
 }
 
 /// Represents a partition on a disk
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct PartitionInfo {
     /// Unique identifier for the partition
     pub id: String,
@@ -46,12 +67,15 @@ pub struct PartitionInfo {
     pub label: Option<String>,
 
     /// Start offset in bytes
+    #[ts(type = "number")]
     pub start_offset: u64,
 
     /// Total size in bytes
+    #[ts(type = "number")]
     pub total_size: u64,
 
     /// Used space in bytes (if available)
+    #[ts(type = "number | null")]
     pub used_space: Option<u64>,
 
     /// Partition type
@@ -66,12 +90,29 @@ pub struct PartitionInfo {
     /// Whether the partition is mounted
     pub is_mounted: bool,
 
+    /// Whether the mount point responds to a metadata read
+    /// (false for lettered/mounted volumes on failing or offline devices)
+    pub is_accessible: bool,
+
     /// Partition flags
     pub flags: Vec<PartitionFlag>,
+
+    /// Identifier that follows a partition across renumbering: the GPT
+    /// partition GUID (PARTUUID) on Linux/macOS, the volume GUID on Windows.
+    /// On MBR disks the PARTUUID is `<disk-signature>-<NN>`, which changes
+    /// with the partition number
+    #[serde(default)]
+    pub uuid: Option<String>,
+
+    /// Holds the running OS (`/`, or the volume with the Windows directory).
+    /// Deleting, shrinking or unmounting it is refused without `force`
+    #[serde(default)]
+    pub is_system_volume: bool,
 }
 
 /// Type of partition table
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub enum PartitionTableType {
     /// Master Boot Record (legacy, max 2TB)
     MBR,
@@ -84,7 +125,8 @@ pub enum PartitionTableType {
 }
 
 /// Type of partition
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub enum PartitionType {
     /// Primary partition (MBR)
     Primary,
@@ -103,7 +145,8 @@ pub enum PartitionType {
 }
 
 /// Filesystem type
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub enum FilesystemType {
     /// NTFS (Windows)
     NTFS,
@@ -117,6 +160,12 @@ pub enum FilesystemType {
     /// ext4 filesystem (Linux)
     Ext4,
 
+    /// Btrfs filesystem (Linux)
+    Btrfs,
+
+    /// XFS filesystem (Linux)
+    XFS,
+
     /// FAT32 filesystem
     FAT32,
 
@@ -129,6 +178,9 @@ pub enum FilesystemType {
     /// HFS+ (older macOS)
     HFSPlus,
 
+    /// Swap / paging space (no mountable filesystem)
+    Swap,
+
     /// Unformatted/RAW
     RAW,
 
@@ -137,7 +189,8 @@ pub enum FilesystemType {
 }
 
 /// Partition flags
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub enum PartitionFlag {
     /// Boot/Active partition
     Boot,
@@ -150,10 +203,14 @@ pub enum PartitionFlag {
 
     /// Read-only
     ReadOnly,
+
+    /// Reserved partition without a filesystem (Microsoft Reserved, BIOS boot)
+    Reserved,
 }
 
 /// Disk health status
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct DiskStatus {
     /// Whether the disk is online and accessible
     pub is_online: bool,
@@ -166,7 +223,8 @@ pub struct DiskStatus {
 }
 
 /// SMART status information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub struct SmartStatus {
     /// Overall health assessment
     pub health: HealthStatus,
@@ -175,11 +233,20 @@ pub struct SmartStatus {
     pub temperature: Option<f32>,
 
     /// Power-on hours (if available)
+    #[ts(type = "number | null")]
     pub power_on_hours: Option<u64>,
+
+    /// SSD wear: percentage of rated write endurance used (may exceed 100)
+    pub percent_used: Option<u8>,
+
+    /// Total bytes written over the drive's lifetime (if available)
+    #[ts(type = "number | null")]
+    pub data_written_bytes: Option<u64>,
 }
 
 /// Health status
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
 pub enum HealthStatus {
     /// Healthy, no issues
     Good,
@@ -194,6 +261,87 @@ pub enum HealthStatus {
     Unknown,
 }
 
+impl DiskInfo {
+    /// Unallocated regions as (offset, size), in offset order
+    ///
+    /// Gaps no larger than the partition alignment are slack between
+    /// aligned partitions rather than usable space, so they're left out.
+    pub fn free_regions(&self) -> Vec<(u64, u64)> {
+        free_regions(self.total_size, self.partitions.iter().map(|p| (p.start_offset, p.total_size)))
+    }
+
+    /// Fill in `largest_free_block` and `total_free` from the partition layout
+    pub fn update_free_space(&mut self) {
+        let regions = self.free_regions();
+        self.largest_free_block = regions.iter().map(|(_, size)| *size).max().unwrap_or(0);
+        self.total_free = regions.iter().map(|(_, size)| *size).sum();
+    }
+}
+
+/// Unallocated regions as (offset, size) on a disk of `total_size` bytes
+/// holding partitions at the given (offset, size) spans, in offset order
+///
+/// Shared by `DiskInfo::free_regions` and layouts simulated from a plan.
+pub fn free_regions(total_size: u64, spans: impl IntoIterator<Item = (u64, u64)>) -> Vec<(u64, u64)> {
+    let mut spans: Vec<(u64, u64)> = spans.into_iter().collect();
+    spans.sort_unstable();
+
+    let mut regions = Vec::new();
+    let mut cursor = 0u64;
+
+    for (offset, size) in spans {
+        if offset > cursor + PARTITION_ALIGNMENT {
+            regions.push((cursor, offset - cursor));
+        }
+        cursor = cursor.max(offset + size);
+    }
+
+    if total_size > cursor + PARTITION_ALIGNMENT {
+        regions.push((cursor, total_size - cursor));
+    }
+
+    regions
+}
+
+impl PartitionInfo {
+    /// Name to show users, e.g. "Data (E:) — 20.00 GB NTFS"
+    ///
+    /// Uses the label and drive letter or mount point when known, and the
+    /// device path only when neither is.
+    pub fn display_name(&self) -> String {
+        let label = self.label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+        let mount = self
+            .mount_point
+            .as_deref()
+            .map(|m| if m.len() > 1 { m.trim_end_matches(['\\', '/']) } else { m })
+            .filter(|m| !m.is_empty());
+
+        let name = match (label, mount) {
+            (Some(label), Some(mount)) => format!("{} ({})", label, mount),
+            (Some(label), None) => label.to_string(),
+            (None, Some(mount)) => mount.to_string(),
+            (None, None) => self.device_path.clone(),
+        };
+
+        format!("{} — {} {}", name, format_bytes(self.total_size), self.filesystem.display_name())
+    }
+}
+
+/// Format bytes to human-readable string, e.g. "20.00 GB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    if bytes == 0 {
+        return "0 B".to_string();
+    }
+
+    let base = 1024_f64;
+    let exp = (bytes as f64).log(base).floor() as usize;
+    let exp = exp.min(UNITS.len() - 1);
+    let value = bytes as f64 / base.powi(exp as i32);
+
+    format!("{:.2} {}", value, UNITS[exp])
+}
+
 impl FilesystemType {
     /// Get a human-readable name for the filesystem
     pub fn display_name(&self) -> &'static str {
@@ -202,10 +350,13 @@ impl FilesystemType {
             FilesystemType::Ext2 => "ext2",
             FilesystemType::Ext3 => "ext3",
             FilesystemType::Ext4 => "ext4",
+            FilesystemType::Btrfs => "Btrfs",
+            FilesystemType::XFS => "XFS",
             FilesystemType::FAT32 => "FAT32",
             FilesystemType::ExFAT => "exFAT",
             FilesystemType::APFS => "APFS",
             FilesystemType::HFSPlus => "HFS+",
+            FilesystemType::Swap => "Swap",
             FilesystemType::RAW => "Unformatted",
             FilesystemType::Unknown => "Unknown",
         }
@@ -227,3 +378,57 @@ impl PartitionTableType {
         }
     }
 }
+
+/// Usage of a mounted filesystem, independent of partition table parsing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeUsage {
+    /// Volume name as reported by the OS (may be empty)
+    pub name: String,
+
+    /// Mount point (e.g., "/" on Linux, "C:\" on Windows)
+    pub mount_point: String,
+
+    /// Filesystem name as reported by the OS (e.g., "ntfs", "ext4", "apfs")
+    pub filesystem: String,
+
+    /// Total size in bytes
+    pub total_space: u64,
+
+    /// Used space in bytes
+    pub used_space: u64,
+
+    /// Free space available to the current user in bytes
+    pub free_space: u64,
+
+    /// Whether the volume is on removable media
+    pub is_removable: bool,
+
+    /// Bind, overlay and loop mounts repeat data held by another volume (Linux only)
+    #[serde(default)]
+    pub mount_kind: MountKind,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_space_from_partition_gaps() {
+        use crate::test_support::{mock_disk, mock_partition, GB};
+
+        let partition = |start_offset: u64, total_size: u64| PartitionInfo {
+            start_offset,
+            total_size,
+            ..mock_partition(&format!("p-{}", start_offset))
+        };
+
+        // [1MB slack] [10GB] [5GB free] [20GB] [65GB free]
+        let mut disk = mock_disk("/dev/sda", vec![partition(15 * GB, 20 * GB), partition(1024 * 1024, 10 * GB - 1024 * 1024)]);
+
+        disk.update_free_space();
+
+        assert_eq!(disk.free_regions(), vec![(10 * GB, 5 * GB), (35 * GB, 65 * GB)]);
+        assert_eq!(disk.largest_free_block, 65 * GB);
+        assert_eq!(disk.total_free, 70 * GB);
+    }
+}
@@ -1,8 +1,150 @@
 // Tauri commands for partition management
 
-use crate::partition::{self, DiskInfo, PartitionInfo, ValidationResult, ResizeProgress, ReallocationPlan};
+use crate::partition::{self, OperationError, DiskInfo, FilesystemType, FragmentationReport, PartitionDetails, PartitionInfo, ValidationResult, ResizeProgress, ReallocationPlan, ReallocationStrategy, ResizeError, ShrinkProposal, VolumeUsage};
+use crate::confirmation::{self, DestructiveOp};
+use crate::error::ToolkitError;
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 use tauri::{command, AppHandle, Emitter};
 
+lazy_static! {
+    /// Disks with a mutating operation in progress, keyed by device path
+    static ref BUSY_DISKS: Mutex<HashMap<String, Busy>> = Mutex::new(HashMap::new());
+}
+
+/// What a disk in `BUSY_DISKS` is busy with
+enum Busy {
+    /// Its partition table is being changed
+    Table,
+    /// These partitions are being changed; the table is left alone
+    Partitions(HashSet<String>),
+}
+
+/// Marks a disk, or one partition on it, busy until dropped, so mutating
+/// commands can't race on it
+///
+/// Anything that changes the partition table (resize, split, delete, moves)
+/// locks the whole disk: it may shift or renumber every other partition on
+/// it. Mount and unmount lock only their partition. Read-only commands don't
+/// take the lock.
+struct OperationLock {
+    disk: String,
+    partition: Option<String>,
+}
+
+impl OperationLock {
+    /// Lock a whole disk, for operations that change its partition table
+    fn disk(disk: &str) -> Result<Self, OperationError> {
+        let mut busy = BUSY_DISKS.lock().unwrap_or_else(|e| e.into_inner());
+        match busy.get(disk) {
+            Some(Busy::Table) => {
+                return Err(OperationError::DiskBusy { disk: disk.to_string() });
+            }
+            Some(Busy::Partitions(partitions)) => {
+                let partition = partitions.iter().next().cloned().unwrap_or_default();
+                return Err(OperationError::Busy { partition });
+            }
+            None => {}
+        }
+        busy.insert(disk.to_string(), Busy::Table);
+        Ok(OperationLock { disk: disk.to_string(), partition: None })
+    }
+
+    /// Lock one partition on `disk`, for operations that leave the table alone
+    fn partition(disk: &str, partition_id: &str) -> Result<Self, OperationError> {
+        let mut busy = BUSY_DISKS.lock().unwrap_or_else(|e| e.into_inner());
+        let entry = busy
+            .entry(disk.to_string())
+            .or_insert_with(|| Busy::Partitions(HashSet::new()));
+        match entry {
+            Busy::Table => Err(OperationError::DiskBusy { disk: disk.to_string() }),
+            Busy::Partitions(partitions) => {
+                if !partitions.insert(partition_id.to_string()) {
+                    return Err(OperationError::Busy {
+                        partition: partition_id.to_string(),
+                    });
+                }
+                Ok(OperationLock {
+                    disk: disk.to_string(),
+                    partition: Some(partition_id.to_string()),
+                })
+            }
+        }
+    }
+
+    /// Lock every disk holding one of `partition_ids`
+    fn disks_of<'a>(
+        disks: &[DiskInfo],
+        partition_ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<Vec<Self>, ToolkitError> {
+        let mut devices: Vec<&str> = Vec::new();
+        for id in partition_ids {
+            let disk = disk_of(disks, id)?;
+            if !devices.contains(&disk.device_path.as_str()) {
+                devices.push(&disk.device_path);
+            }
+        }
+        Ok(devices
+            .into_iter()
+            .map(OperationLock::disk)
+            .collect::<Result<Vec<_>, _>>()?)
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        let mut busy = BUSY_DISKS.lock().unwrap_or_else(|e| e.into_inner());
+        let now_idle = match (busy.get_mut(&self.disk), &self.partition) {
+            (Some(Busy::Partitions(partitions)), Some(partition)) => {
+                partitions.remove(partition);
+                partitions.is_empty()
+            }
+            _ => true,
+        };
+        if now_idle {
+            busy.remove(&self.disk);
+        }
+    }
+}
+
+/// The disk holding `partition_id`
+fn disk_of<'a>(disks: &'a [DiskInfo], partition_id: &str) -> Result<&'a DiskInfo, String> {
+    disks
+        .iter()
+        .find(|d| d.partitions.iter().any(|p| p.id == partition_id))
+        .ok_or_else(|| "Disk not found for partition".to_string())
+}
+
+/// Emit an Error progress update when a resize stopped because it was cancelled
+fn report_cancelled<T>(app: &AppHandle, result: Result<T, ResizeError>) -> Result<T, ResizeError> {
+    if let Err(ResizeError::Cancelled) = &result {
+        let _ = app.emit("resize-progress", ResizeProgress::error(
+            "Resize cancelled before any changes were made; the partition is unchanged."
+        ));
+    }
+    result
+}
+
+/// Drop scan results a mutation of `partition` made stale and re-read its disk
+///
+/// Disk enumeration isn't cached, so the re-read is what picks up the new
+/// layout. Cached scans under the mount point (and the ancestors whose totals
+/// include it) are dropped so the next scan sees the new size.
+///
+/// The mutation has already been applied by the time this runs, so a failed
+/// re-read must not turn it into an error: it is logged and the pre-operation
+/// `disk` is returned instead, leaving the frontend to refresh on its own.
+fn refresh_after_mutation(partition: &PartitionInfo, disk: &DiskInfo) -> DiskInfo {
+    if let Some(mount_point) = &partition.mount_point {
+        crate::commands::invalidate_cached(&[std::path::PathBuf::from(mount_point)]);
+    }
+    partition::get_disk_by_path(&disk.device_path).unwrap_or_else(|e| {
+        log::warn!("Failed to re-read {} after changing {}: {}", disk.device_path, partition.device_path, e);
+        disk.clone()
+    })
+}
+
 /// Get all disks available on the system
 #[command]
 pub async fn get_disks() -> Result<Vec<DiskInfo>, String> {
@@ -15,30 +157,79 @@ pub async fn get_partitions(disk_path: String) -> Result<Vec<PartitionInfo>, Str
     partition::get_partitions(&disk_path).map_err(|e| e.to_string())
 }
 
+/// Get usage for all mounted filesystems
+/// Lightweight fallback that works even when `get_disks` fails
+#[command]
+pub async fn get_mounted_volumes() -> Result<Vec<VolumeUsage>, String> {
+    Ok(partition::get_mounted_volumes())
+}
+
+/// Health overview per disk: SMART verdict, temperature, space and warnings,
+/// with an overall status that is the worst of them
+/// Runs on a blocking thread: smartctl and WMI queries can take seconds per disk
+#[command]
+pub async fn get_disk_health_summary() -> Result<Vec<partition::DiskHealthSummary>, String> {
+    tauri::async_runtime::spawn_blocking(|| partition::get_disk_health_summary().map_err(|e| e.to_string()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 /// Get detailed information about a specific partition
 #[command]
 pub async fn get_partition_info(partition_id: String) -> Result<PartitionInfo, String> {
     partition::get_partition_info(&partition_id).map_err(|e| e.to_string())
 }
 
+/// Read filesystem metadata (cluster size, inodes, creation date) for the details panel
+/// Read-only: only runs inspection tools that never modify the filesystem
+#[command]
+pub async fn inspect_partition(partition_id: String) -> Result<PartitionDetails, String> {
+    let partition = partition::get_partition_info(&partition_id).map_err(|e| e.to_string())?;
+    Ok(partition::inspect_partition(&partition))
+}
+
+/// Analyze fragmentation (analysis only; nothing is defragmented)
+/// SSDs are skipped with a note
+/// Runs on a blocking thread: `defrag /A` and `e4defrag -c` can take minutes
+#[command]
+pub async fn analyze_fragmentation(partition_id: String) -> Result<FragmentationReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+        let (disk, partition) = disks
+            .iter()
+            .find_map(|d| d.partitions.iter().find(|p| p.id == partition_id).map(|p| (d, p)))
+            .ok_or_else(|| "Partition not found".to_string())?;
+
+        Ok(partition::analyze_fragmentation(disk, partition))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Whether the disk holding `path` is an SSD or HDD (cached per device)
+#[command]
+pub async fn get_media_type(path: String) -> partition::media::MediaType {
+    partition::media::media_type_for_path(&path)
+}
+
 /// Validate a partition expand request
 #[command]
 pub async fn validate_expand_partition(
     partition_id: String,
     target_size: u64,
-) -> Result<ValidationResult, String> {
-    let partition = partition::get_partition_info(&partition_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<ValidationResult, ToolkitError> {
+    let partition = partition::get_partition_info(&partition_id)?;
 
     // Find the disk containing this partition
-    let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+    let disks = partition::get_all_disks()?;
     let disk = disks
         .iter()
         .find(|d| d.partitions.iter().any(|p| p.id == partition_id))
-        .ok_or_else(|| "Disk not found for partition".to_string())?;
+        .ok_or("Disk not found for partition")?;
 
-    partition::validation::validate_expand(&partition, disk, target_size)
-        .map_err(|e| e.to_string())
+    let mut result = partition::validation::validate_expand(&partition, disk, target_size)?;
+    partition::power::annotate_validation(&mut result, &partition::power_state());
+    Ok(result)
 }
 
 /// Validate a partition shrink request
@@ -46,12 +237,24 @@ pub async fn validate_expand_partition(
 pub async fn validate_shrink_partition(
     partition_id: String,
     target_size: u64,
-) -> Result<ValidationResult, String> {
-    let partition = partition::get_partition_info(&partition_id)
-        .map_err(|e| e.to_string())?;
+) -> Result<ValidationResult, ToolkitError> {
+    let partition = partition::get_partition_info(&partition_id)?;
 
-    partition::validation::validate_shrink(&partition, target_size)
-        .map_err(|e| e.to_string())
+    let mut result = partition::validation::validate_shrink(&partition, target_size)?;
+    partition::power::annotate_validation(&mut result, &partition::power_state());
+    Ok(result)
+}
+
+/// Propose an aligned shrink size that frees `free_needed` bytes, or the
+/// closest safe size when that much can't be freed
+#[command]
+pub async fn propose_shrink_target(
+    partition_id: String,
+    free_needed: u64,
+) -> Result<ShrinkProposal, ToolkitError> {
+    let partition = partition::get_partition_info(&partition_id)?;
+
+    Ok(partition::validation::propose_shrink_target(&partition, free_needed)?)
 }
 
 /// Expand a partition to the specified size
@@ -60,29 +263,40 @@ pub async fn expand_partition(
     app: AppHandle,
     partition_id: String,
     target_size: u64,
-) -> Result<(), String> {
+    ignore_battery: Option<bool>,
+) -> Result<DiskInfo, ToolkitError> {
+    let disks = partition::get_all_disks()?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::disk(&disk.device_path)?;
+    let _job = partition::ResizeJob::register(&partition_id);
+
     // Emit progress: Validating
     let _ = app.emit("resize-progress", ResizeProgress::validating("Starting validation..."));
 
     // Get partition info
-    let partition = partition::get_partition_info(&partition_id)
-        .map_err(|e| e.to_string())?;
+    let partition = partition::get_partition_info(&partition_id)?;
+    partition::check_accessible(&partition)?;
+    partition::check_power(&partition::power_state(), ignore_battery.unwrap_or(false))?;
+
+    if partition::is_expand_noop(&partition, target_size) {
+        let _ = app.emit("resize-progress", ResizeProgress::complete(
+            "Partition is already at or above the requested size. Nothing to do."
+        ));
+        return Ok(disk.clone());
+    }
 
-    // Emit progress: Expanding
-    let _ = app.emit("resize-progress", ResizeProgress::expanding_filesystem(
-        0.0,
-        format!("Expanding partition {} to {}...", partition.device_path, format_size(target_size))
-    ));
-
-    // Perform expansion
-    partition::expand::expand_partition(&partition, target_size)
-        .await
-        .map_err(|e| e.to_string())?;
+    // Perform expansion; it reports its phases in the order its tools run
+    let on_progress = |progress: ResizeProgress| {
+        let _ = app.emit("resize-progress", progress);
+    };
+    report_cancelled(&app, partition::expand::expand_partition_with_progress(&partition, disk, target_size, &on_progress)
+        .await)?;
+    let disk = refresh_after_mutation(&partition, disk);
 
     // Emit progress: Complete
     let _ = app.emit("resize-progress", ResizeProgress::complete("Partition expanded successfully!"));
 
-    Ok(())
+    Ok(disk)
 }
 
 /// Shrink a partition to the specified size
@@ -91,34 +305,111 @@ pub async fn shrink_partition(
     app: AppHandle,
     partition_id: String,
     target_size: u64,
-) -> Result<(), String> {
+    allow_self_disk: Option<bool>,
+    force: Option<bool>,
+    ignore_battery: Option<bool>,
+) -> Result<DiskInfo, ToolkitError> {
+    let disks = partition::get_all_disks()?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::disk(&disk.device_path)?;
+    let _job = partition::ResizeJob::register(&partition_id);
+
     // Emit progress: Validating
     let _ = app.emit("resize-progress", ResizeProgress::validating("Starting validation..."));
 
     // Get partition info
-    let partition = partition::get_partition_info(&partition_id)
-        .map_err(|e| e.to_string())?;
+    let partition = partition::get_partition_info(&partition_id)?;
+    partition::check_accessible(&partition)?;
+    partition::check_power(&partition::power_state(), ignore_battery.unwrap_or(false))?;
+
+    partition::check_system_volume(&partition, "shrink", force.unwrap_or(false))?;
+    partition::check_self_disk(&disks, &partition, allow_self_disk.unwrap_or(false))?;
+
+    if partition::is_shrink_noop(&partition, target_size) {
+        let _ = app.emit("resize-progress", ResizeProgress::complete(
+            "Partition is already at or below the requested size. Nothing to do."
+        ));
+        return Ok(disk.clone());
+    }
 
-    // Emit progress: Checking filesystem
-    let _ = app.emit("resize-progress", ResizeProgress::checking_filesystem(
-        "Checking filesystem integrity..."
-    ));
+    // A cancel during validation takes effect here
+    report_cancelled(&app, partition::checkpoint(&partition_id))?;
 
-    // Emit progress: Shrinking
-    let _ = app.emit("resize-progress", ResizeProgress::resizing_filesystem(
-        0.0,
-        format!("Shrinking partition {} to {}...", partition.device_path, format_size(target_size))
+    // Emit progress: Checking filesystem. The shrink reports the later
+    // phases itself, in the order its tools run
+    let _ = app.emit("resize-progress", ResizeProgress::checking_filesystem(
+        format!("Checking {} before shrinking it to {}...", partition.device_path, partition::format_bytes(target_size))
     ));
 
-    // Perform shrink
-    partition::shrink::shrink_partition(&partition, target_size)
-        .await
-        .map_err(|e| e.to_string())?;
+    // Perform shrink, forwarding the tools' own progress
+    let on_progress = |progress: ResizeProgress| {
+        let _ = app.emit("resize-progress", progress);
+    };
+    report_cancelled(&app, partition::shrink::shrink_partition_with_progress(&partition, disk, target_size, &on_progress)
+        .await)?;
+    let disk = refresh_after_mutation(&partition, disk);
 
     // Emit progress: Complete
     let _ = app.emit("resize-progress", ResizeProgress::complete("Partition shrunk successfully!"));
 
-    Ok(())
+    Ok(disk)
+}
+
+/// Cancel the expand or shrink running on a partition
+///
+/// Only allowed while it is validating or checking the filesystem; once the
+/// filesystem or partition table is being changed the request is refused.
+/// The resize itself reports the cancellation as an Error progress update.
+#[command]
+pub async fn cancel_resize(partition_id: String) -> Result<(), ToolkitError> {
+    Ok(partition::cancel_resize(&partition_id)?)
+}
+
+/// List the filesystems new partitions can be formatted with on this machine
+#[command]
+pub async fn supported_create_filesystems() -> Result<Vec<FilesystemType>, String> {
+    Ok(partition::supported_create_filesystems())
+}
+
+/// Split a partition: shrink it and create a new partition in the freed space
+/// Returns the re-read disk, like the other partition mutations
+#[command]
+pub async fn split_partition(
+    app: AppHandle,
+    partition_id: String,
+    new_partition_size: u64,
+    new_filesystem: FilesystemType,
+    new_label: Option<String>,
+    allow_self_disk: Option<bool>,
+    force: Option<bool>,
+    ignore_battery: Option<bool>,
+) -> Result<DiskInfo, ToolkitError> {
+    let disks = partition::get_all_disks()?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::disk(&disk.device_path)?;
+
+    let partition = partition::get_partition_info(&partition_id)?;
+    partition::check_accessible(&partition)?;
+    partition::check_power(&partition::power_state(), ignore_battery.unwrap_or(false))?;
+    partition::check_system_volume(&partition, "shrink", force.unwrap_or(false))?;
+
+    partition::check_self_disk(&disks, &partition, allow_self_disk.unwrap_or(false))?;
+
+    // A failure after the shrink comes back as a structured PartialFailure
+    let app_handle = app.clone();
+    partition::split::split_partition(
+        &partition,
+        disk,
+        new_partition_size,
+        new_filesystem,
+        new_label.as_deref(),
+        move |progress| {
+            let _ = app_handle.emit("resize-progress", progress);
+        },
+    )
+    .await?;
+
+    Ok(refresh_after_mutation(&partition, disk))
 }
 
 /// Create a space reallocation plan
@@ -127,6 +418,7 @@ pub async fn shrink_partition(
 pub async fn create_space_reallocation_plan(
     target_partition_id: String,
     desired_additional_space: u64,
+    strategy: Option<ReallocationStrategy>,
 ) -> Result<ReallocationPlan, String> {
     // Get all disks
     let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
@@ -142,15 +434,118 @@ pub async fn create_space_reallocation_plan(
         disk,
         &target_partition_id,
         desired_additional_space,
+        strategy.unwrap_or_default(),
     )
     .map_err(|e| e.to_string())
 }
 
+/// Re-check a reallocation plan against the disks as they are now
+/// The wizard runs this right before executing a plan and refuses to start if it fails
+#[command]
+pub async fn validate_reallocation_plan(plan: ReallocationPlan) -> Result<partition::PlanValidation, String> {
+    let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+    Ok(partition::reallocation_wizard::validate_reallocation_plan(&plan, &disks))
+}
+
+/// Run a reallocation plan's automated steps as one transaction
+///
+/// Every partition the plan deletes needs a confirmation token from
+/// `prepare_destructive`, keyed in `confirmations` by its current id. On
+/// failure the completed steps are rolled back where safe and the error
+/// carries a PartialFailure. Returns the target's disk as re-read afterwards.
+#[command]
+pub async fn execute_reallocation_plan(
+    app: AppHandle,
+    plan: ReallocationPlan,
+    confirmations: HashMap<String, String>,
+    allow_self_disk: Option<bool>,
+    force: Option<bool>,
+    ignore_battery: Option<bool>,
+) -> Result<DiskInfo, ToolkitError> {
+    // The disk may have changed since the plan was made; never run a stale plan
+    let disks = partition::get_all_disks()?;
+    let validation = partition::reallocation_wizard::validate_reallocation_plan(&plan, &disks);
+    if !validation.is_valid {
+        let blocking: Vec<&str> = validation
+            .issues
+            .iter()
+            .filter(|issue| issue.blocking)
+            .map(|issue| issue.message.as_str())
+            .collect();
+        return Err(format!("The disk changed since this plan was made. Create a new plan. {}", blocking.join(" ")).into());
+    }
+
+    // Partitions may have been renumbered; check them and lock their disks by their current ids
+    let current = |id: &str| validation.renumbered.get(id).cloned().unwrap_or_else(|| id.to_string());
+    let find = |id: &str| {
+        disks
+            .iter()
+            .flat_map(|d| d.partitions.iter())
+            .find(|p| p.id == id)
+            .ok_or_else(|| format!("Partition {} not found", id))
+    };
+
+    let target = find(&current(&plan.target_partition_id))?;
+    let mut involved = vec![(target, if plan.strategy == ReallocationStrategy::GrowTarget { Some("expand") } else { None })];
+    for source in &plan.source_partitions {
+        let action = match source.action {
+            partition::SourcePartitionAction::ShrinkOnly { .. } => "shrink",
+            partition::SourcePartitionAction::DeleteEntirely => "delete",
+        };
+        involved.push((find(&current(&source.partition_id))?, Some(action)));
+    }
+
+    let _locks = OperationLock::disks_of(&disks, involved.iter().map(|(p, _)| p.id.as_str()))?;
+    for (partition, action) in &involved {
+        let Some(action) = *action else { continue };
+
+        partition::check_accessible(partition)?;
+        partition::check_system_volume(partition, action, force.unwrap_or(false))?;
+        partition::check_self_disk(&disks, partition, allow_self_disk.unwrap_or(false))?;
+        if action == "delete" {
+            confirmation::consume(
+                confirmations.get(&partition.id).map(String::as_str),
+                DestructiveOp::DeletePartition,
+                &partition.id,
+            )?;
+        }
+    }
+    partition::check_power(&partition::power_state(), ignore_battery.unwrap_or(false))?;
+
+    let target_disk = disk_of(&disks, &target.id)?;
+
+    let on_plan = |progress: partition::PlanProgress| {
+        let _ = app.emit("plan-progress", progress);
+    };
+    let on_resize = |progress: ResizeProgress| {
+        let _ = app.emit("resize-progress", progress);
+    };
+    partition::reallocation_wizard::execute_reallocation_plan(&plan, &on_plan, &on_resize).await?;
+
+    let disk = refresh_after_mutation(target, target_disk);
+    let _ = app.emit("resize-progress", ResizeProgress::complete("Space reallocated successfully!"));
+    Ok(disk)
+}
+
+/// Remove diskpart scripts left in the temp directory by crashed runs
+/// Also runs once at startup
+#[command]
+pub fn cleanup_stale_scripts() -> usize {
+    partition::diskpart::sweep_stale_scripts()
+}
+
 /// Unmount a partition
 #[command]
-pub async fn unmount_partition(partition_id: String) -> Result<(), String> {
+pub async fn unmount_partition(partition_id: String, force: Option<bool>) -> Result<(), String> {
+    let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::partition(&disk.device_path, &partition_id).map_err(|e| e.to_string())?;
+
     let partition = partition::get_partition_info(&partition_id)
         .map_err(|e| e.to_string())?;
+    partition::check_accessible(&partition).map_err(|e| e.to_string())?;
+    partition::check_system_volume(&partition, "unmount", force.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
 
     partition::unmount_partition(&partition)
         .map_err(|e| e.to_string())
@@ -159,6 +554,10 @@ pub async fn unmount_partition(partition_id: String) -> Result<(), String> {
 /// Mount a partition
 #[command]
 pub async fn mount_partition(partition_id: String) -> Result<(), String> {
+    let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::partition(&disk.device_path, &partition_id).map_err(|e| e.to_string())?;
+
     let partition = partition::get_partition_info(&partition_id)
         .map_err(|e| e.to_string())?;
 
@@ -179,12 +578,31 @@ pub async fn validate_delete_partition(partition_id: String) -> Result<Vec<Strin
 /// Delete a partition
 /// WARNING: This destroys all data on the partition!
 #[command]
-pub async fn delete_partition(partition_id: String) -> Result<(), String> {
+pub async fn delete_partition(
+    partition_id: String,
+    allow_self_disk: Option<bool>,
+    force: Option<bool>,
+    confirmation: Option<String>,
+) -> Result<DiskInfo, String> {
+    let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
+    let disk = disk_of(&disks, &partition_id)?;
+    let _lock = OperationLock::disk(&disk.device_path).map_err(|e| e.to_string())?;
+
     let partition = partition::get_partition_info(&partition_id)
         .map_err(|e| e.to_string())?;
+    partition::check_accessible(&partition).map_err(|e| e.to_string())?;
+    partition::check_system_volume(&partition, "delete", force.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
+    partition::check_self_disk(&disks, &partition, allow_self_disk.unwrap_or(false))
+        .map_err(|e| e.to_string())?;
 
-    partition::delete_partition(&partition)
-        .map_err(|e| e.to_string())
+    // Consumed last, so a refused or busy delete leaves the token usable
+    confirmation::consume(confirmation.as_deref(), DestructiveOp::DeletePartition, &partition_id)?;
+
+    partition::delete_partition(&partition, disk)
+        .map_err(|e| e.to_string())?;
+
+    Ok(refresh_after_mutation(&partition, disk))
 }
 
 /// Execute partition reorganization (move partitions)
@@ -193,34 +611,62 @@ pub async fn delete_partition(partition_id: String) -> Result<(), String> {
 pub async fn execute_partition_moves(
     app: AppHandle,
     move_operations: Vec<partition::MoveOperation>,
-) -> Result<String, String> {
-    // Get all disks once to find partitions
-    // Note: We might need to refresh this inside the loop if disk structure changes significantly,
-    // but for simple moves it might be okay. However, strictly speaking, after a delete/create, 
-    // the old PartitionInfo objects are stale.
-    // A better approach is to re-fetch disk info based on ID before each move.
-    
-    let total_ops = move_operations.len();
-    
+) -> Result<partition::MoveInstructions, ToolkitError> {
+    // Hold every disk in the plan until all moves are done
+    let disks = partition::get_all_disks()?;
+    let _locks = OperationLock::disks_of(&disks, move_operations.iter().map(|op| op.partition_id.as_str()))?;
+
+    // Every move is listed up front, so a failure can report the ones never started
+    let mut steps = move_operations
+        .iter()
+        .map(|op| {
+            disks
+                .iter()
+                .flat_map(|d| d.partitions.iter())
+                .find(|p| p.id == op.partition_id)
+                .map(|p| partition::MoveStep::new(p, op.to_offset))
+                .ok_or_else(|| format!("Partition {} not found", op.partition_id))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut transaction = partition::Transaction::new("Partition reorganization");
+
+    // Each move's share of the overall bar is proportional to the data it copies
+    let tracker = partition::PlanProgressTracker::new(
+        steps
+            .iter()
+            .map(|step| (format!("Move partition {}", step.partition_id), step.data_size.max(1) as f32))
+            .collect(),
+    );
+
     for (i, op) in move_operations.iter().enumerate() {
-        // Fetch fresh disk info
-        let disks = partition::get_all_disks().map_err(|e| e.to_string())?;
-        
-        // Find the disk and partition
-        let mut target_disk: Option<DiskInfo> = None;
-        let mut target_partition: Option<PartitionInfo> = None;
-        
-        for disk in disks {
-            if let Some(p) = disk.partitions.iter().find(|p| p.id == op.partition_id) {
-                target_partition = Some(p.clone());
-                target_disk = Some(disk.clone());
-                break;
+        // Fetch fresh disk info; earlier moves change the layout
+        let found = partition::get_all_disks().and_then(|disks| {
+            disks
+                .into_iter()
+                .find_map(|disk| {
+                    let p = disk.partitions.iter().find(|p| p.id == op.partition_id)?.clone();
+                    Some((p, disk))
+                })
+                .ok_or_else(|| anyhow::anyhow!("Partition {} not found", op.partition_id))
+        });
+        let (partition, disk) = match found {
+            Ok(found) => found,
+            Err(e) => {
+                let failure = transaction
+                    .rollback(&format!("find partition {}", op.partition_id), e, None)
+                    .await;
+                return Ok(moves_stopped(steps, failure));
             }
+        };
+
+        // Save each disk's table before its first move, so the original layout stays recoverable
+        if let Err(e) = transaction.snapshot(&disk) {
+            let failure = transaction
+                .rollback(&format!("back up the partition table of {}", disk.device_path), e, None)
+                .await;
+            return Ok(moves_stopped(steps, failure));
         }
         
-        let partition = target_partition.ok_or_else(|| format!("Partition {} not found", op.partition_id))?;
-        let disk = target_disk.ok_or_else(|| "Disk not found".to_string())?;
-        
         // Configure move options
         let options = partition::move_partition::MovePartitionOptions {
             target_offset: op.to_offset,
@@ -232,13 +678,12 @@ pub async fn execute_partition_moves(
         let app_handle = app.clone();
         let partition_id = partition.id.clone();
         let current_op_index = i;
+        let tracker = tracker.clone();
         
         let progress_callback = move |progress: partition::move_partition::MoveProgress| {
-            // Calculate global progress
-            // Each op is 1/total_ops of the total work
-            // Current op progress is progress.percent
-            let op_weight = 100.0 / total_ops as f32;
-            let global_percent = (current_op_index as f32 * op_weight) + (progress.percent * op_weight / 100.0);
+            let plan_progress = tracker.progress(current_op_index, progress.percent, progress.message.clone());
+            let global_percent = plan_progress.overall_percent;
+            let _ = app_handle.emit("plan-progress", plan_progress);
             
             // Emit event to frontend
             // We might need a new event type or reuse 'resize-progress'
@@ -257,29 +702,79 @@ pub async fn execute_partition_moves(
                 percent: global_percent,
                 message: format!("Partition {}: {}", partition_id, progress.message),
                 can_cancel: false,
+                indeterminate: false,
             });
         };
         
         // Execute move
-        partition::move_partition::move_partition(&partition, &disk, options, progress_callback)
-            .await
-            .map_err(|e| e.to_string())?;
+        if let Err(e) = partition::move_partition::move_partition(&partition, &disk, options, progress_callback).await {
+            // Moving data back is as risky as the move itself, so completed moves are reported, not undone
+            let failure = transaction
+                .rollback(&format!("move {}", partition.display_name()), e, None)
+                .await;
+            return Ok(moves_stopped(steps, failure));
+        }
+
+        transaction.record(
+            partition::StepRecord {
+                description: format!("move {}", partition.display_name()),
+                before: format!("offset {}", partition.start_offset),
+                after: format!("offset {}", op.to_offset),
+            },
+            partition::Undo::Manual(format!(
+                "move {} back to offset {} if the plan is abandoned",
+                partition.display_name(),
+                partition.start_offset
+            )),
+        );
+        steps[i].completed = true;
     }
 
-    Ok("All partition moves completed successfully!".to_string())
+    // Moves run natively, so there's no external tool to recommend
+    Ok(partition::MoveInstructions::new(steps, None))
+}
+
+/// Instructions for a move sequence that stopped partway; the moves left pending
+/// are handed to the user along with the failure
+fn moves_stopped(steps: Vec<partition::MoveStep>, failure: partition::PartialFailure) -> partition::MoveInstructions {
+    let mut instructions =
+        partition::MoveInstructions::new(steps, Some(partition::MANUAL_MOVE_TOOL.to_string()));
+    instructions.failure = Some(failure);
+    log::warn!("{}", instructions.to_plain_text());
+    instructions
 }
 
-/// Format bytes to human-readable size
-fn format_size(bytes: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    if bytes == 0 {
-        return "0 B".to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_lock_rejects_second_operation_until_released() {
+        let disk = format!("test-disk-{}", uuid::Uuid::new_v4());
+
+        let lock = OperationLock::partition(&disk, "p1").unwrap();
+        assert!(matches!(OperationLock::partition(&disk, "p1"), Err(OperationError::Busy { .. })));
+        // Another partition on the same disk is independent
+        let other = OperationLock::partition(&disk, "p2").unwrap();
+
+        drop(lock);
+        assert!(OperationLock::partition(&disk, "p1").is_ok());
+        drop(other);
     }
 
-    let base = 1024_f64;
-    let exp = (bytes as f64).log(base).floor() as usize;
-    let exp = exp.min(UNITS.len() - 1);
-    let value = bytes as f64 / base.powi(exp as i32);
+    #[test]
+    fn test_disk_lock_excludes_every_partition_on_the_disk() {
+        let disk = format!("test-disk-{}", uuid::Uuid::new_v4());
+
+        let partition = OperationLock::partition(&disk, "p1").unwrap();
+        assert!(matches!(OperationLock::disk(&disk), Err(OperationError::Busy { .. })));
+        drop(partition);
 
-    format!("{:.2} {}", value, UNITS[exp])
+        let table = OperationLock::disk(&disk).unwrap();
+        assert!(matches!(OperationLock::disk(&disk), Err(OperationError::DiskBusy { .. })));
+        assert!(matches!(OperationLock::partition(&disk, "p2"), Err(OperationError::DiskBusy { .. })));
+
+        drop(table);
+        assert!(OperationLock::partition(&disk, "p2").is_ok());
+    }
 }
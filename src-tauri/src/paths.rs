@@ -0,0 +1,85 @@
+// Windows extended-length paths
+//
+// Win32 file APIs reject paths longer than MAX_PATH (260 chars) unless they
+// carry the `\\?\` prefix (`\\?\UNC\server\share\...` for network paths).
+// Deep node_modules or build caches exceed that routinely, so the scanner and
+// cleaner hand the prefixed form to filesystem calls and strip it again
+// before paths are shown or compared. On other platforms both are no-ops.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+const VERBATIM_PREFIX: &str = r"\\?\";
+const VERBATIM_UNC_PREFIX: &str = r"\\?\UNC\";
+
+/// Form of `path` to pass to filesystem calls
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    if !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+    match path.to_str().and_then(to_extended) {
+        Some(extended) => Cow::Owned(extended.into()),
+        None => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// `path` without the extended-length prefix, as the user would type it
+#[cfg(windows)]
+pub fn display_path(path: &Path) -> Cow<'_, Path> {
+    match path.to_str().and_then(from_extended) {
+        Some(plain) => Cow::Owned(plain.into()),
+        None => Cow::Borrowed(path),
+    }
+}
+
+#[cfg(not(windows))]
+pub fn display_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
+/// Prefix an absolute path; None if it's already in verbatim or device form
+#[cfg_attr(not(windows), allow(dead_code))]
+fn to_extended(path: &str) -> Option<String> {
+    if path.starts_with(VERBATIM_PREFIX) || path.starts_with(r"\\.\") {
+        return None;
+    }
+
+    // Verbatim paths skip normalization, so separators must already be backslashes
+    let path = path.replace('/', "\\");
+    match path.strip_prefix(r"\\") {
+        Some(unc) => Some(format!("{}{}", VERBATIM_UNC_PREFIX, unc)),
+        None => Some(format!("{}{}", VERBATIM_PREFIX, path)),
+    }
+}
+
+/// Undo `to_extended`; None if there's no prefix to strip
+#[cfg_attr(not(windows), allow(dead_code))]
+fn from_extended(path: &str) -> Option<String> {
+    if let Some(unc) = path.strip_prefix(VERBATIM_UNC_PREFIX) {
+        return Some(format!(r"\\{}", unc));
+    }
+    path.strip_prefix(VERBATIM_PREFIX).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_length_roundtrip() {
+        assert_eq!(to_extended(r"C:\Users\me").as_deref(), Some(r"\\?\C:\Users\me"));
+        assert_eq!(to_extended("C:/Users/me").as_deref(), Some(r"\\?\C:\Users\me"));
+        assert_eq!(to_extended(r"\\server\share\dir").as_deref(), Some(r"\\?\UNC\server\share\dir"));
+        assert_eq!(to_extended(r"\\?\C:\already"), None);
+
+        assert_eq!(from_extended(r"\\?\C:\Users\me").as_deref(), Some(r"C:\Users\me"));
+        assert_eq!(from_extended(r"\\?\UNC\server\share\dir").as_deref(), Some(r"\\server\share\dir"));
+        assert_eq!(from_extended(r"C:\plain"), None);
+    }
+}
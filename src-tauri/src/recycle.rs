@@ -0,0 +1,116 @@
+// Trash / Recycle Bin listing and restore
+//
+// Items moved to the trash by cleaning can be brought back from inside the
+// app. Listing and restoring go through the `trash` crate, which supports this
+// on Windows and freedesktop (Linux/BSD) trashes. The macOS Trash can't be
+// enumerated through a public API, so there both report `Unsupported`.
+
+use crate::error::ToolkitError;
+use serde::{Deserialize, Serialize};
+use tauri::command;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashedItem {
+    /// Opaque identifier passed back to `restore_trashed`
+    pub id: String,
+    pub name: String,
+    pub original_path: String,
+    /// Unix timestamp (seconds) of the deletion
+    pub deleted_at: i64,
+    /// Size in bytes; None for folders, where the trash only reports an entry count
+    pub size: Option<u64>,
+    pub is_dir: bool,
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+))]
+mod platform {
+    use super::TrashedItem;
+    use crate::error::ToolkitError;
+    use trash::os_limited::{self, TrashItemSize};
+
+    pub fn list_trashed() -> Result<Vec<TrashedItem>, ToolkitError> {
+        let items = os_limited::list().map_err(|e| format!("Failed to list trash: {}", e))?;
+
+        let mut trashed: Vec<TrashedItem> = items
+            .iter()
+            .map(|item| {
+                let size = os_limited::metadata(item).ok().map(|m| m.size);
+                TrashedItem {
+                    id: item.id.to_string_lossy().to_string(),
+                    name: item.name.to_string_lossy().to_string(),
+                    original_path: item.original_path().to_string_lossy().to_string(),
+                    deleted_at: item.time_deleted,
+                    size: match size {
+                        Some(TrashItemSize::Bytes(bytes)) => Some(bytes),
+                        _ => None,
+                    },
+                    is_dir: matches!(size, Some(TrashItemSize::Entries(_))),
+                }
+            })
+            .collect();
+
+        // Most recently deleted first
+        trashed.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+        Ok(trashed)
+    }
+
+    pub fn restore_trashed(ids: &[String]) -> Result<usize, ToolkitError> {
+        let items = os_limited::list().map_err(|e| format!("Failed to list trash: {}", e))?;
+        let selected: Vec<_> = items
+            .into_iter()
+            .filter(|item| ids.iter().any(|id| item.id.to_string_lossy() == id.as_str()))
+            .collect();
+
+        if selected.len() < ids.len() {
+            return Err(format!(
+                "{} of the selected items are no longer in the trash",
+                ids.len() - selected.len()
+            )
+            .into());
+        }
+
+        let count = selected.len();
+        os_limited::restore_all(selected).map_err(|e| match e {
+            trash::Error::RestoreCollision { path, .. } => {
+                format!("Cannot restore: {} already exists", path.display())
+            }
+            other => format!("Failed to restore from trash: {}", other),
+        })?;
+
+        Ok(count)
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))
+)))]
+mod platform {
+    use super::TrashedItem;
+    use crate::error::ToolkitError;
+
+    pub fn list_trashed() -> Result<Vec<TrashedItem>, ToolkitError> {
+        Err(ToolkitError::Unsupported("Listing the trash".to_string()))
+    }
+
+    pub fn restore_trashed(_ids: &[String]) -> Result<usize, ToolkitError> {
+        Err(ToolkitError::Unsupported("Restoring from the trash".to_string()))
+    }
+}
+
+/// Items currently in the trash, most recently deleted first
+#[command]
+pub fn list_trashed() -> Result<Vec<TrashedItem>, ToolkitError> {
+    platform::list_trashed()
+}
+
+/// Restore items (by `TrashedItem::id`) to their original locations
+///
+/// Returns how many were restored. Nothing is restored if any id is stale.
+#[command]
+pub fn restore_trashed(ids: Vec<String>) -> Result<usize, ToolkitError> {
+    platform::restore_trashed(&ids)
+}
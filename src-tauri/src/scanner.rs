@@ -1,18 +1,596 @@
 // use jwalk::WalkDir;
 use serde::{Deserialize, Serialize};
+use ts_rs::TS;
 use std::time::SystemTime;
 use rayon::prelude::*;
-use std::sync::{Arc, atomic::{AtomicBool, AtomicU64, Ordering}};
+use std::path::{Path, PathBuf};
+use crate::paths::{display_path, long_path};
+use crate::partition::media::MediaType;
+use crate::mounts::{mount_for_path, mount_table};
+#[cfg(target_os = "linux")]
+use crate::mounts::MountKind;
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering}};
+use std::collections::{BTreeMap, HashMap};
+use lazy_static::lazy_static;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileNode {
     pub name: String,
     pub path: String,
-    pub size: u64,
+    pub size: u64, // Recursive total of all files below this node, per the scan's SizeBasis (directory metadata itself isn't counted)
+    #[serde(default)]
+    pub own_size: u64, // Files directly inside this directory only, excluding subdirectories; equals `size` for files
+    pub logical_size: u64, // Apparent size (sum of file lengths)
+    pub allocated_size: u64, // Space actually allocated on disk
     pub is_dir: bool,
     pub children: Option<Vec<FileNode>>,
     pub last_modified: u64,
     pub file_count: u64,
+    #[serde(default)]
+    pub skipped_paths: Vec<String>, // System directories left out of this scan (root node only)
+    #[serde(default)]
+    pub size_unknown: bool, // Size wasn't available without scanning (reported as 0)
+    #[serde(default)]
+    pub is_cloud_placeholder: bool, // Dehydrated OneDrive/iCloud file; content isn't stored locally
+    #[serde(default)]
+    pub is_estimate: bool, // On-disk size approximated from cluster size (`cluster_estimate` scans)
+    #[serde(default)]
+    pub summarized: bool, // Children dropped to stay within the scan's node/memory limits; sizes are still complete
+}
+
+/// Which size is rolled up into `FileNode.size`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SizeBasis {
+    /// Apparent file length (what `ls -l` shows)
+    Logical,
+    /// Allocated blocks on disk (sparse/compressed files count at their real cost)
+    #[default]
+    OnDisk,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct ScanOptions {
+    pub size_basis: SizeBasis,
+    pub skip_system_dirs: bool, // Leave out pseudo-filesystems and OS directories
+    pub skip_paths: Vec<String>, // Extra directories to leave out
+    pub cluster_estimate: bool, // Round lengths up to the cluster size instead of querying each file's allocation (NTFS only; still walks every file)
+    pub concurrency: Option<usize>, // Worker threads; None picks by media type (few for HDDs, many for SSDs)
+    pub io_limit_bytes_per_sec: Option<u64>, // Pace metadata reads so a background scan leaves I/O for the user
+    pub stay_on_filesystem: bool, // Don't descend into other filesystems mounted below the scan root
+    pub max_nodes: Option<usize>, // Nodes kept in the tree before deeper branches are summarized (None = no limit)
+    pub max_tree_bytes: Option<u64>, // Estimated tree memory before deeper branches are summarized (None = no limit)
+    pub max_depth: usize, // Levels of children listed below the root (1 = immediate children only); deeper levels are only summed
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            size_basis: SizeBasis::default(),
+            skip_system_dirs: true,
+            skip_paths: Vec::new(),
+            cluster_estimate: false,
+            concurrency: None,
+            io_limit_bytes_per_sec: None,
+            stay_on_filesystem: false,
+            max_nodes: Some(DEFAULT_MAX_NODES),
+            max_tree_bytes: Some(DEFAULT_MAX_TREE_BYTES),
+            max_depth: DEFAULT_MAX_DEPTH,
+        }
+    }
+}
+
+/// Default `max_nodes`: enough for any folder a user browses, far short of a whole volume
+pub const DEFAULT_MAX_NODES: usize = 1_000_000;
+
+/// Default `max_tree_bytes`
+pub const DEFAULT_MAX_TREE_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Default `max_depth`: the root's children and theirs, so opening a child is served from the cache
+pub const DEFAULT_MAX_DEPTH: usize = 2;
+
+/// Room left in one scan's tree
+///
+/// Sizes are always rolled up in full; only the per-entry nodes count
+/// against the budget, so a branch that doesn't fit keeps its totals and
+/// drops its children. Nodes are admitted once the walk is done, in a fixed
+/// order (see `fit_subtrees`), so which branches are summarized doesn't
+/// depend on which thread finished first.
+struct NodeBudget {
+    max_nodes: usize,
+    max_bytes: u64,
+    nodes: AtomicUsize,
+    bytes: AtomicU64,
+}
+
+impl NodeBudget {
+    fn new(options: &ScanOptions) -> Self {
+        NodeBudget {
+            max_nodes: options.max_nodes.unwrap_or(usize::MAX),
+            max_bytes: options.max_tree_bytes.unwrap_or(u64::MAX),
+            nodes: AtomicUsize::new(0),
+            bytes: AtomicU64::new(0),
+        }
+    }
+
+    /// Approximate memory of a node, not counting its children
+    fn cost(node: &FileNode) -> u64 {
+        (std::mem::size_of::<FileNode>() + node.name.len() + node.path.len()) as u64
+    }
+
+    /// Claim room for `nodes`, or claim nothing and return false when they don't fit
+    fn reserve(&self, nodes: &[FileNode]) -> bool {
+        let count = nodes.len();
+        let bytes: u64 = nodes.iter().map(Self::cost).sum();
+        let prev_nodes = self.nodes.fetch_add(count, Ordering::Relaxed);
+        let prev_bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        if prev_nodes.saturating_add(count) > self.max_nodes || prev_bytes.saturating_add(bytes) > self.max_bytes {
+            self.nodes.fetch_sub(count, Ordering::Relaxed);
+            self.bytes.fetch_sub(bytes, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    /// Claim `bytes` of tree memory for data kept alongside the nodes
+    fn reserve_bytes(&self, bytes: u64) -> bool {
+        let prev = self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        if prev.saturating_add(bytes) > self.max_bytes {
+            self.bytes.fetch_sub(bytes, Ordering::Relaxed);
+            return false;
+        }
+        true
+    }
+
+    /// Count `nodes` that are kept regardless of the limits
+    fn charge(&self, nodes: &[FileNode]) {
+        self.nodes.fetch_add(nodes.len(), Ordering::Relaxed);
+        self.bytes.fetch_add(nodes.iter().map(Self::cost).sum(), Ordering::Relaxed);
+    }
+
+    /// Keep the largest of `nodes` (sorted by `largest_first`) that fit; true if any were dropped
+    fn keep_fitting(&self, nodes: &mut Vec<FileNode>) -> bool {
+        if self.reserve(nodes) {
+            return false;
+        }
+        let fitting = nodes.iter().take_while(|n| self.reserve(std::slice::from_ref(*n))).count();
+        nodes.truncate(fitting);
+        true
+    }
+
+    /// Admit the child lists below `dirs` level by level, largest directory
+    /// first (ties by name); a list that doesn't fit is dropped whole and its
+    /// directory marked summarized
+    fn fit_subtrees(&self, dirs: Vec<&mut FileNode>) {
+        let mut level = dirs;
+        while !level.is_empty() {
+            level.sort_by(|a, b| largest_first(a, b));
+            let mut next = Vec::new();
+            for dir in level {
+                if dir.children.as_ref().is_some_and(|c| !self.reserve(c)) {
+                    dir.children = None;
+                    dir.summarized = true;
+                    continue;
+                }
+                next.extend(dir.children.iter_mut().flatten().filter(|c| c.is_dir));
+            }
+            level = next;
+        }
+    }
+}
+
+/// Order nodes by size, descending, then by name, so equal sizes always
+/// come out the same way
+fn largest_first(a: &FileNode, b: &FileNode) -> std::cmp::Ordering {
+    b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name))
+}
+
+/// Worker threads for a scan when `ScanOptions::concurrency` isn't set
+///
+/// Parallel walks make HDD heads seek between directories and end up slower
+/// than walking sequentially; SSDs keep up with one thread per core.
+fn default_concurrency(media: MediaType) -> usize {
+    match media {
+        MediaType::Hdd => 2,
+        MediaType::Ssd => std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4),
+        MediaType::Unknown => 4,
+    }
+}
+
+lazy_static! {
+    /// Scan thread pools by thread count, built on first use and shared by later scans
+    static ref SCAN_POOLS: Mutex<HashMap<usize, Arc<rayon::ThreadPool>>> = Mutex::new(HashMap::new());
+}
+
+/// The shared pool with `threads` workers, built the first time that size is asked for
+fn scan_pool(threads: usize) -> Result<Arc<rayon::ThreadPool>, String> {
+    let mut pools = SCAN_POOLS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(pool) = pools.get(&threads) {
+        return Ok(pool.clone());
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .thread_name(|i| format!("scan-{}", i))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let pool = Arc::new(pool);
+    pools.insert(threads, pool.clone());
+    Ok(pool)
+}
+
+/// Nominal I/O charged per directory entry: a scan reads inode/MFT records,
+/// not file contents, and those come in blocks of about this size
+const ENTRY_IO_COST: u64 = 4096;
+
+/// Paces a scan to stay under `io_limit_bytes_per_sec`, shared by all its threads
+struct IoThrottle {
+    limit: Option<u64>,
+    started: std::time::Instant,
+    consumed: AtomicU64,
+}
+
+impl IoThrottle {
+    fn new(limit: Option<u64>) -> Self {
+        IoThrottle {
+            limit: limit.filter(|&l| l > 0),
+            started: std::time::Instant::now(),
+            consumed: AtomicU64::new(0),
+        }
+    }
+
+    /// Account for `entries` reads, sleeping if the scan is ahead of its budget
+    fn charge(&self, entries: u64) {
+        let Some(limit) = self.limit else { return };
+
+        let cost = entries * ENTRY_IO_COST;
+        let consumed = self.consumed.fetch_add(cost, Ordering::Relaxed) + cost;
+        let due = std::time::Duration::from_secs_f64(consumed as f64 / limit as f64);
+        let elapsed = self.started.elapsed();
+        if due > elapsed {
+            std::thread::sleep(due - elapsed);
+        }
+    }
+}
+
+/// Directories excluded from a scan, recording which ones were actually hit
+struct SkipList {
+    paths: Vec<PathBuf>,
+    skipped: Mutex<Vec<String>>,
+}
+
+impl SkipList {
+    fn new(root: &Path, options: &ScanOptions) -> Self {
+        let mut paths: Vec<PathBuf> = options.skip_paths.iter().map(PathBuf::from).collect();
+        if options.skip_system_dirs {
+            paths.extend(system_skip_dirs());
+        }
+        if options.stay_on_filesystem {
+            paths.extend(other_filesystems(root));
+        }
+
+        SkipList {
+            paths,
+            skipped: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Whether `path` should be left out; records it if so
+    fn should_skip(&self, path: &Path) -> bool {
+        let path = display_path(path);
+        if !self.paths.iter().any(|p| p == path.as_ref()) {
+            return false;
+        }
+
+        if let Ok(mut skipped) = self.skipped.lock() {
+            skipped.push(path.to_string_lossy().to_string());
+        }
+        true
+    }
+
+    fn into_skipped(self) -> Vec<String> {
+        let mut skipped = self.skipped.into_inner().unwrap_or_default();
+        skipped.sort();
+        skipped.dedup();
+        skipped
+    }
+}
+
+/// Mount points below `root` that belong to a different filesystem than `root`
+fn other_filesystems(root: &Path) -> Vec<PathBuf> {
+    let table = mount_table();
+    let Some(own) = mount_for_path(&table, root).map(|m| m.mount_point.clone()) else {
+        return Vec::new();
+    };
+
+    table
+        .into_iter()
+        .map(|m| m.mount_point)
+        .filter(|mp| *mp != own && mp.starts_with(root))
+        .collect()
+}
+
+/// Filesystem types that don't hold real files (tmpfs does: /tmp and /dev/shm can fill RAM)
+#[cfg(target_os = "linux")]
+const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "proc", "sysfs", "devtmpfs", "devpts", "cgroup", "cgroup2", "securityfs",
+    "debugfs", "tracefs", "pstore", "bpf", "mqueue", "hugetlbfs", "configfs", "fusectl",
+    "autofs", "binfmt_misc", "efivarfs", "rpc_pipefs", "nsfs",
+];
+
+/// Mount points of pseudo-filesystems, plus bind, overlay and loop mounts
+/// whose contents are already counted where the data really lives
+#[cfg(target_os = "linux")]
+fn system_skip_dirs() -> Vec<PathBuf> {
+    let table = mount_table();
+    if table.is_empty() {
+        return ["/proc", "/sys", "/dev", "/run"].iter().map(PathBuf::from).collect();
+    }
+
+    table
+        .into_iter()
+        .filter(|m| m.kind != MountKind::Normal || PSEUDO_FILESYSTEMS.contains(&m.fs_type.as_str()))
+        .map(|m| m.mount_point)
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn system_skip_dirs() -> Vec<PathBuf> {
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| "C:\\Windows".to_string());
+    let system_drive = std::env::var("SystemDrive").unwrap_or_else(|_| "C:".to_string());
+
+    vec![
+        PathBuf::from(system_root),
+        PathBuf::from(format!("{}\\System Volume Information", system_drive)),
+    ]
+}
+
+#[cfg(target_os = "macos")]
+fn system_skip_dirs() -> Vec<PathBuf> {
+    // /System/Volumes holds firmlinked copies of the data volume, so
+    // descending into it from / would count user data twice
+    ["/dev", "/System/Volumes", "/private/var/vm"].iter().map(PathBuf::from).collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn system_skip_dirs() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Logical and allocated size accumulated together
+#[derive(Debug, Clone, Copy, Default)]
+struct Sizes {
+    logical: u64,
+    allocated: u64,
+}
+
+impl Sizes {
+    /// Placeholders keep their full logical size but take no space on disk,
+    /// so the OnDisk basis doesn't offer them as reclaimable space
+    ///
+    /// With a `cluster` size the allocation is estimated by rounding the length
+    /// up to whole clusters, skipping the per-file query.
+    fn of(path: &std::path::Path, meta: &std::fs::Metadata, cluster: Option<u64>) -> Self {
+        let allocated = if is_cloud_placeholder(meta) {
+            0
+        } else if let Some(cluster) = cluster {
+            meta.len().div_ceil(cluster) * cluster
+        } else {
+            allocated_size(path, meta)
+        };
+
+        Sizes {
+            logical: meta.len(),
+            allocated,
+        }
+    }
+
+    fn add(&mut self, other: Sizes) {
+        self.logical += other.logical;
+        self.allocated += other.allocated;
+    }
+
+    fn basis(&self, basis: SizeBasis) -> u64 {
+        match basis {
+            SizeBasis::Logical => self.logical,
+            SizeBasis::OnDisk => self.allocated,
+        }
+    }
+}
+
+/// Bytes actually allocated for a file (st_blocks on Unix)
+#[cfg(unix)]
+fn allocated_size(_path: &std::path::Path, meta: &std::fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.blocks() * 512
+}
+
+/// Bytes actually allocated for a file (compressed/sparse aware)
+#[cfg(windows)]
+fn allocated_size(path: &std::path::Path, meta: &std::fs::Metadata) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::GetCompressedFileSizeW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut high: u32 = 0;
+    let low = unsafe { GetCompressedFileSizeW(PCWSTR(wide.as_ptr()), Some(&mut high as *mut u32)) };
+
+    // INVALID_FILE_SIZE with no high part means the call failed
+    if low == u32::MAX && high == 0 {
+        return meta.len();
+    }
+
+    ((high as u64) << 32) | low as u64
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &std::path::Path, meta: &std::fs::Metadata) -> u64 {
+    meta.len()
+}
+
+/// Cluster size of the NTFS volume holding `path`, for a `cluster_estimate` scan
+///
+/// The estimate is a heuristic, not an MFT or USN journal query (USN records
+/// carry no sizes, so they can't replace the walk): the tree is still walked, but each file's length (which NTFS keeps in the directory
+/// index, so listing returns it) is rounded up to whole clusters instead of
+/// asking the filesystem for the file's allocation. That saves one open per
+/// file; compressed and sparse files are overcounted and small files stored
+/// inside their MFT record are counted as a full cluster. None on other
+/// filesystems, where the scan falls back to exact per-file sizes.
+#[cfg(windows)]
+fn ntfs_cluster_size(path: &std::path::Path) -> Option<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows::core::PCWSTR;
+    use windows::Win32::Storage::FileSystem::{GetDiskFreeSpaceW, GetVolumeInformationW, GetVolumePathNameW};
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let mut root = [0u16; 261];
+    unsafe { GetVolumePathNameW(PCWSTR(wide.as_ptr()), &mut root) }.ok()?;
+
+    let mut fs_name = [0u16; 32];
+    unsafe {
+        GetVolumeInformationW(PCWSTR(root.as_ptr()), None, None, None, None, Some(&mut fs_name))
+    }
+    .ok()?;
+    let len = fs_name.iter().position(|&c| c == 0).unwrap_or(fs_name.len());
+    if String::from_utf16_lossy(&fs_name[..len]) != "NTFS" {
+        return None;
+    }
+
+    let (mut sectors_per_cluster, mut bytes_per_sector) = (0u32, 0u32);
+    unsafe {
+        GetDiskFreeSpaceW(
+            PCWSTR(root.as_ptr()),
+            Some(&mut sectors_per_cluster as *mut u32),
+            Some(&mut bytes_per_sector as *mut u32),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+
+    let cluster = sectors_per_cluster as u64 * bytes_per_sector as u64;
+    (cluster > 0).then_some(cluster)
+}
+
+#[cfg(not(windows))]
+fn ntfs_cluster_size(_path: &std::path::Path) -> Option<u64> {
+    None
+}
+
+/// Cloud file whose content lives remotely (OneDrive Files On-Demand)
+#[cfg(windows)]
+fn is_cloud_placeholder(meta: &std::fs::Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+
+    const FILE_ATTRIBUTE_OFFLINE: u32 = 0x1000;
+    const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x40000;
+    const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x400000;
+
+    meta.file_attributes()
+        & (FILE_ATTRIBUTE_OFFLINE | FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS)
+        != 0
+}
+
+/// Cloud file whose content lives remotely (iCloud "dataless" files)
+#[cfg(target_os = "macos")]
+fn is_cloud_placeholder(meta: &std::fs::Metadata) -> bool {
+    use std::os::macos::fs::MetadataExt;
+
+    const SF_DATALESS: u32 = 0x40000000;
+
+    meta.st_flags() & SF_DATALESS != 0
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn is_cloud_placeholder(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// Modification time (ns since the epoch) of every directory a scan walked, by display path
+///
+/// A directory's mtime changes when entries are added, removed or renamed in
+/// it, so an unchanged stamp for every directory in a subtree means its file
+/// list is the same. Files rewritten in place don't touch their directory's
+/// mtime; a full rescan is still the exact option.
+pub type DirStamps = BTreeMap<String, u64>;
+
+fn mtime_nanos(meta: &std::fs::Metadata) -> Option<u64> {
+    let since_epoch = meta.modified().ok()?.duration_since(SystemTime::UNIX_EPOCH).ok()?;
+    Some(since_epoch.as_nanos() as u64)
+}
+
+/// Directory stamps collected by an incremental scan
+///
+/// Stamps count against the scan's `max_tree_bytes` like nodes do. When they
+/// stop fitting, all of them are dropped: a partial set would let a changed
+/// directory whose stamp was never taken pass as unchanged.
+struct StampRecorder<'a> {
+    budget: &'a NodeBudget,
+    stamps: Mutex<Option<DirStamps>>, // None when not recording, or after running out of room
+}
+
+impl<'a> StampRecorder<'a> {
+    fn new(budget: &'a NodeBudget, enabled: bool) -> Self {
+        StampRecorder {
+            budget,
+            stamps: Mutex::new(enabled.then(DirStamps::new)),
+        }
+    }
+
+    /// Approximate memory of one stamp
+    fn cost(path: &str) -> u64 {
+        (std::mem::size_of::<(String, u64)>() + path.len()) as u64
+    }
+
+    fn record(&self, path: &Path, meta: &std::fs::Metadata) {
+        let Some(mtime) = mtime_nanos(meta) else { return };
+        self.extend([(display_path(path).to_string_lossy().to_string(), mtime)]);
+    }
+
+    fn extend(&self, new: impl IntoIterator<Item = (String, u64)>) {
+        let Ok(mut guard) = self.stamps.lock() else { return };
+        let Some(stamps) = guard.as_mut() else { return };
+        for (path, mtime) in new {
+            if !self.budget.reserve_bytes(Self::cost(&path)) {
+                *guard = None;
+                return;
+            }
+            stamps.insert(path, mtime);
+        }
+    }
+
+    /// The stamps, or none if recording was off or ran out of room
+    fn into_stamps(self) -> DirStamps {
+        self.stamps.into_inner().ok().flatten().unwrap_or_default()
+    }
+}
+
+/// `previous`'s child `dir` and its stamps, if no directory in that subtree changed since they were taken
+///
+/// `previous` must have been scanned with the same `max_depth`.
+fn unchanged_child(previous: &FileNode, stamps: &DirStamps, dir: &str, max_depth: usize) -> Option<(FileNode, DirStamps)> {
+    // A child without its own children listed (below the lookahead level) can't stand in for a scanned one
+    let node = previous
+        .children
+        .as_ref()?
+        .iter()
+        .find(|c| c.is_dir && c.path == dir && (max_depth <= 1 || c.children.is_some() || c.summarized))?;
+    let subtree: DirStamps = stamps
+        .range(dir.to_string()..)
+        .take_while(|(p, _)| p.starts_with(dir))
+        .filter(|(p, _)| Path::new(p.as_str()).starts_with(dir))
+        .map(|(p, m)| (p.clone(), *m))
+        .collect();
+
+    // No stamps means the directory wasn't walked last time (or is new)
+    let unchanged = !subtree.is_empty()
+        && subtree.iter().all(|(p, m)| {
+            std::fs::metadata(long_path(Path::new(p))).ok().and_then(|meta| mtime_nanos(&meta)) == Some(*m)
+        });
+    unchanged.then(|| (node.clone(), subtree))
 }
 
 pub struct ScanStats {
@@ -23,13 +601,69 @@ pub struct ScanStats {
 
 pub fn scan_directory(
     path: &str,
+    options: &ScanOptions,
     stats: Option<Arc<ScanStats>>,
     cancel: Option<Arc<AtomicBool>>
 ) -> Result<FileNode, String> {
+    scan_with_pool(path, options, None, false, stats, cancel).map(|(node, _)| node)
+}
+
+/// Scan `path` and also return the directory stamps for a later incremental rescan
+///
+/// With `previous` (an earlier scan of the same path with the same options,
+/// and its stamps), subdirectories of the root whose whole subtree is
+/// unchanged are taken from it instead of being walked again. The root's own
+/// files are always re-read. Stamps that don't fit in `max_tree_bytes` next
+/// to the tree are dropped, and the next rescan walks everything.
+pub fn scan_directory_incremental(
+    path: &str,
+    options: &ScanOptions,
+    previous: Option<(&FileNode, &DirStamps)>,
+    stats: Option<Arc<ScanStats>>,
+    cancel: Option<Arc<AtomicBool>>
+) -> Result<(FileNode, DirStamps), String> {
+    scan_with_pool(path, options, previous, true, stats, cancel)
+}
+
+fn scan_with_pool(
+    path: &str,
+    options: &ScanOptions,
+    previous: Option<(&FileNode, &DirStamps)>,
+    record_stamps: bool,
+    stats: Option<Arc<ScanStats>>,
+    cancel: Option<Arc<AtomicBool>>
+) -> Result<(FileNode, DirStamps), String> {
+    let threads = options
+        .concurrency
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| default_concurrency(crate::partition::media::media_type_for_path(path)));
+    let pool = scan_pool(threads)?;
+
+    // Parallel iterators inside the scan run on this pool
+    pool.install(|| scan_directory_on_pool(path, options, previous, record_stamps, stats, cancel))
+}
+
+fn scan_directory_on_pool(
+    path: &str,
+    options: &ScanOptions,
+    previous: Option<(&FileNode, &DirStamps)>,
+    record_stamps: bool,
+    stats: Option<Arc<ScanStats>>,
+    cancel: Option<Arc<AtomicBool>>
+) -> Result<(FileNode, DirStamps), String> {
+    let basis = options.size_basis;
+    let skip = SkipList::new(Path::new(path), options);
+    let throttle = IoThrottle::new(options.io_limit_bytes_per_sec);
+    let budget = NodeBudget::new(options);
+    let max_depth = options.max_depth.max(1);
+    let stamps = StampRecorder::new(&budget, record_stamps);
     let root_path = std::path::Path::new(path);
-    if !root_path.exists() {
+    let long_root = long_path(root_path);
+    if !long_root.exists() {
         return Err("Directory does not exist".to_string());
     }
+    let cluster = if options.cluster_estimate { ntfs_cluster_size(root_path) } else { None };
+    let is_estimate = cluster.is_some();
 
     if let Some(c) = &cancel {
         if c.load(Ordering::Relaxed) {
@@ -38,7 +672,7 @@ pub fn scan_directory(
     }
 
     // 1. List immediate children of the requested path
-    let read_dir = std::fs::read_dir(path).map_err(|e| e.to_string())?;
+    let read_dir = std::fs::read_dir(&long_root).map_err(|e| e.to_string())?;
     let entries: Vec<_> = read_dir.filter_map(|e| e.ok()).collect();
     
     // Partition
@@ -49,9 +683,13 @@ pub fn scan_directory(
         if let Some(c) = &cancel {
             if c.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
         }
+        throttle.charge(1);
 
         if let Ok(metadata) = entry.metadata() {
             if metadata.is_dir() {
+                if skip.should_skip(&entry.path()) {
+                    continue;
+                }
                 dirs.push(entry);
             } else {
                 files.push((entry, metadata));
@@ -59,21 +697,53 @@ pub fn scan_directory(
         }
     }
     
-    let mut total_size = 0;
+    let mut total = Sizes::default();
+    let mut own = Sizes::default();
     let mut file_count = 0;
     
     // Files in root
-    for (_entry, meta) in &files {
-        let size = meta.len();
-        total_size += size;
+    let files: Vec<_> = files.into_iter().map(|(entry, meta)| {
+        let sizes = Sizes::of(&entry.path(), &meta, cluster);
+        (entry, meta, sizes)
+    }).collect();
+
+    for (_entry, _meta, sizes) in &files {
+        total.add(*sizes);
+        own.add(*sizes);
         file_count += 1;
         
         if let Some(s) = &stats {
             s.scanned_files.fetch_add(1, Ordering::Relaxed);
-            s.total_size.fetch_add(size, Ordering::Relaxed);
+            s.total_size.fetch_add(sizes.basis(basis), Ordering::Relaxed);
         }
     }
     
+    // Convert files in root to FileNodes
+    let mut file_nodes: Vec<FileNode> = files.iter().map(|(entry, meta, sizes)| {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path_str = display_path(&entry.path()).to_string_lossy().to_string();
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)
+            .duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+
+        FileNode {
+            name,
+            path: path_str,
+            size: sizes.basis(basis),
+            own_size: sizes.basis(basis),
+            logical_size: sizes.logical,
+            allocated_size: sizes.allocated,
+            is_dir: false,
+            children: None,
+            last_modified: modified,
+            file_count: 1,
+            skipped_paths: Vec::new(),
+            size_unknown: false,
+            is_cloud_placeholder: is_cloud_placeholder(meta),
+            is_estimate,
+            summarized: false,
+        }
+    }).collect();
+
     // 2. Process subdirectories in parallel (Lookahead scan)
     // We want to return a node for each directory that INCLUDES its own children list
     // This allows the caller to cache these nodes effectively.
@@ -83,81 +753,178 @@ pub fn scan_directory(
         }
 
         let path = entry.path();
-        let path_str = path.to_string_lossy().to_string();
+        let path_str = display_path(&path).to_string_lossy().to_string();
         let name = entry.file_name().to_string_lossy().to_string();
         
         let metadata = entry.metadata().unwrap();
         let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)
             .duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
 
+        if let Some((node, subtree)) = previous.and_then(|(prev, old)| unchanged_child(prev, old, &path_str, max_depth)) {
+            if let Some(s) = &stats {
+                s.scanned_files.fetch_add(node.file_count, Ordering::Relaxed);
+                s.total_size.fetch_add(node.size, Ordering::Relaxed);
+            }
+            stamps.extend(subtree);
+            return Ok(node);
+        }
+        stamps.record(&path, &metadata);
+
         // LOOKAHEAD: Scan the children of this subdirectory 
         // to populate its `children` field and calculate exact size.
-        let (size, count, children) = scan_subdir_details(&path, stats.clone(), cancel.clone())?;
+        let (sizes, own, count, children) = if max_depth > 1 {
+            let (sizes, own, count, children) = scan_subdir_details(&path, max_depth - 1, basis, cluster, &skip, &throttle, &stamps, stats.clone(), cancel.clone())?;
+            (sizes, own, count, Some(children))
+        } else {
+            let (sizes, own, count) = get_deep_stats(&path, basis, cluster, &skip, &throttle, &stamps, stats.clone(), cancel.clone())?;
+            (sizes, own, count, None)
+        };
 
         Ok(FileNode {
             name,
             path: path_str,
-            size,
+            size: sizes.basis(basis),
+            own_size: own.basis(basis),
+            logical_size: sizes.logical,
+            allocated_size: sizes.allocated,
             is_dir: true,
-            children: Some(children), // We now populate this!
+            summarized: false,
+            children, // Populated unless max_depth is 1; the budget may drop them below
             last_modified: modified,
             file_count: count,
+            skipped_paths: Vec::new(),
+            size_unknown: false,
+            is_cloud_placeholder: false,
+            is_estimate,
         })
     }).collect();
     
-    let dir_results = dir_results_res?;
+    let mut dir_results = dir_results_res?;
+
+    // Files in the scanned folder itself come first; deeper branches get what's left
+    file_nodes.sort_by(largest_first);
+    let files_summarized = budget.keep_fitting(&mut file_nodes);
+    budget.charge(&dir_results);
+    budget.fit_subtrees(dir_results.iter_mut().collect());
     
     // Aggregate totals
     for dir in &dir_results {
-        total_size += dir.size;
+        total.add(Sizes { logical: dir.logical_size, allocated: dir.allocated_size });
         file_count += dir.file_count;
     }
 
-    // Convert files in root to FileNodes
-    let mut file_nodes: Vec<FileNode> = files.iter().map(|(entry, meta)| {
-        let name = entry.file_name().to_string_lossy().to_string();
-        let path_str = entry.path().to_string_lossy().to_string();
-        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)
-            .duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
-
-        FileNode {
-            name,
-            path: path_str,
-            size: meta.len(),
-            is_dir: false,
-            children: None,
-            last_modified: modified,
-            file_count: 1,
-        }
-    }).collect();
-    
     // Combine dirs and files
     let mut children_nodes = dir_results;
     children_nodes.append(&mut file_nodes);
     
-    // Sort by size descending
-    children_nodes.sort_by(|a, b| b.size.cmp(&a.size));
+    children_nodes.sort_by(largest_first);
     
-    Ok(FileNode {
+    let node = FileNode {
         name: root_path.file_name().unwrap_or_default().to_string_lossy().to_string(),
         path: path.to_string(), // Keep original path string for consistency
-        size: total_size,
+        size: total.basis(basis),
+        own_size: own.basis(basis),
+        logical_size: total.logical,
+        allocated_size: total.allocated,
         is_dir: true,
         children: Some(children_nodes),
         last_modified: 0,
         file_count,
-    })
+        skipped_paths: skip.into_skipped(),
+        size_unknown: false,
+        is_cloud_placeholder: false,
+        is_estimate,
+        summarized: files_summarized,
+    };
+    Ok((node, stamps.into_stamps()))
+}
+
+/// Combine separately scanned directories under one synthetic root
+///
+/// When one directory lies inside another, its subtree is counted only under
+/// the more specific (nested) directory and subtracted from the enclosing one.
+pub fn combine_roots(mut roots: Vec<FileNode>) -> FileNode {
+    let originals = roots.clone();
+    for nested in &originals {
+        let nested_path = Path::new(&nested.path);
+        // The closest enclosing root gets the subtraction; it already contains any outer ones
+        let enclosing = roots
+            .iter_mut()
+            .filter(|r| r.path != nested.path && nested_path.starts_with(&r.path))
+            .max_by_key(|r| Path::new(&r.path).components().count());
+        if let Some(enclosing) = enclosing {
+            exclude_subtree(enclosing, nested);
+        }
+    }
+
+    let mut combined = FileNode {
+        name: "Selected folders".to_string(),
+        path: String::new(),
+        size: 0,
+        own_size: 0,
+        logical_size: 0,
+        allocated_size: 0,
+        is_dir: true,
+        children: None,
+        last_modified: 0,
+        file_count: 0,
+        skipped_paths: Vec::new(),
+        size_unknown: false,
+        is_cloud_placeholder: false,
+        is_estimate: roots.iter().any(|r| r.is_estimate),
+        summarized: false,
+    };
+    for root in &roots {
+        combined.size += root.size;
+        combined.logical_size += root.logical_size;
+        combined.allocated_size += root.allocated_size;
+        combined.file_count += root.file_count;
+        combined.skipped_paths.extend(root.skipped_paths.iter().cloned());
+    }
+    combined.skipped_paths.sort();
+    combined.skipped_paths.dedup();
+
+    roots.sort_by(|a, b| b.size.cmp(&a.size));
+    combined.children = Some(roots);
+    combined
+}
+
+/// Remove `nested`'s totals from `node` and every scanned level down to it
+fn exclude_subtree(node: &mut FileNode, nested: &FileNode) {
+    node.size = node.size.saturating_sub(nested.size);
+    node.logical_size = node.logical_size.saturating_sub(nested.logical_size);
+    node.allocated_size = node.allocated_size.saturating_sub(nested.allocated_size);
+    node.file_count = node.file_count.saturating_sub(nested.file_count);
+
+    let target = Path::new(&nested.path);
+    if let Some(children) = &mut node.children {
+        if let Some(index) = children.iter().position(|c| Path::new(&c.path) == target) {
+            children.remove(index);
+        } else if let Some(child) = children.iter_mut().find(|c| c.is_dir && target.starts_with(&c.path)) {
+            exclude_subtree(child, nested);
+        }
+    }
 }
 
 // Scans a subdirectory: Lists ITS children, and calculates their sizes (deep)
+// `levels` of children are listed (at least one); below that sizes are only summed
+// The node budget is applied afterwards, by `NodeBudget::fit_subtrees`
+#[allow(clippy::too_many_arguments)]
 fn scan_subdir_details(
     path: &std::path::Path, 
+    levels: usize,
+    basis: SizeBasis,
+    cluster: Option<u64>,
+    skip: &SkipList,
+    throttle: &IoThrottle,
+    stamps: &StampRecorder,
     stats: Option<Arc<ScanStats>>, 
     cancel: Option<Arc<AtomicBool>>
-) -> Result<(u64, u64, Vec<FileNode>), String> {
+) -> Result<(Sizes, Sizes, u64, Vec<FileNode>), String> {
     // List children of this subdirectory
     
-    let mut total_size = 0;
+    let mut total = Sizes::default();
+    let mut own = Sizes::default();
     let mut total_count = 0;
     let mut children_nodes = Vec::new();
 
@@ -165,7 +932,7 @@ fn scan_subdir_details(
         let entries: Vec<_> = read_dir.filter_map(|e| e.ok()).collect();
         
         // Split into files/dirs
-        let mut sub_files_size = 0;
+        let mut sub_files_size = Sizes::default();
         let mut sub_files_count = 0;
         let mut sub_dirs = Vec::new();
         
@@ -173,24 +940,29 @@ fn scan_subdir_details(
             if let Some(c) = &cancel {
                  if c.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
             }
+            throttle.charge(1);
 
              if let Ok(meta) = entry.metadata() {
                 if meta.is_dir() {
-                    sub_dirs.push(entry);
+                    if !skip.should_skip(&entry.path()) {
+                        stamps.record(&entry.path(), &meta);
+                        sub_dirs.push(entry);
+                    }
                 } else {
-                    let s = meta.len();
-                    sub_files_size += s;
+                    let s = Sizes::of(&entry.path(), &meta, cluster);
+                    sub_files_size.add(s);
                     sub_files_count += 1;
                     
                     if let Some(st) = &stats {
                         st.scanned_files.fetch_add(1, Ordering::Relaxed);
-                        st.total_size.fetch_add(s, Ordering::Relaxed);
+                        st.total_size.fetch_add(s.basis(basis), Ordering::Relaxed);
                     }
                 }
              }
         }
         
-        total_size += sub_files_size;
+        total.add(sub_files_size);
+        own = sub_files_size;
         total_count += sub_files_count;
         
         // Process these subdirectories (Deep scan for size)
@@ -201,10 +973,16 @@ fn scan_subdir_details(
              
              let p = entry.path();
              let name = entry.file_name().to_string_lossy().to_string();
-             let p_str = p.to_string_lossy().to_string();
+             let p_str = display_path(&p).to_string_lossy().to_string();
              
-             // Get stats using walkdir (Deep scan)
-             let (s, c) = get_deep_stats(&p, stats.clone(), cancel.clone())?;
+             let (s, own, c, children) = if levels > 1 {
+                 let (s, own, c, children) = scan_subdir_details(&p, levels - 1, basis, cluster, skip, throttle, stamps, stats.clone(), cancel.clone())?;
+                 (s, own, c, Some(children))
+             } else {
+                 // Get stats using walkdir (Deep scan)
+                 let (s, own, c) = get_deep_stats(&p, basis, cluster, skip, throttle, stamps, stats.clone(), cancel.clone())?;
+                 (s, own, c, None)
+             };
              
              let m = entry.metadata().ok().and_then(|m| m.modified().ok())
                 .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
@@ -213,54 +991,84 @@ fn scan_subdir_details(
              Ok(FileNode {
                  name,
                  path: p_str,
-                 size: s,
+                 size: s.basis(basis),
+                 own_size: own.basis(basis),
+                 logical_size: s.logical,
+                 allocated_size: s.allocated,
                  is_dir: true,
-                 children: None, // We stop lookahead at 1 level deep to avoid recursion explosion
+                 children, // None at the last listed level
                  last_modified: m,
                  file_count: c,
+                 skipped_paths: Vec::new(),
+                 size_unknown: false,
+                 is_cloud_placeholder: false,
+                 is_estimate: cluster.is_some(),
+                 summarized: false,
              })
         }).collect();
 
         let sub_dir_nodes = sub_dir_nodes_res?;
         
         for node in &sub_dir_nodes {
-            total_size += node.size;
+            total.add(Sizes { logical: node.logical_size, allocated: node.allocated_size });
             total_count += node.file_count;
         }
         
         children_nodes = sub_dir_nodes;
-        children_nodes.sort_by(|a, b| b.size.cmp(&a.size));
+        children_nodes.sort_by(largest_first);
     }
-    
-    Ok((total_size, total_count, children_nodes))
+
+    Ok((total, own, total_count, children_nodes))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn get_deep_stats(
     path: &std::path::Path, 
+    basis: SizeBasis,
+    cluster: Option<u64>,
+    skip: &SkipList,
+    throttle: &IoThrottle,
+    stamps: &StampRecorder,
     stats: Option<Arc<ScanStats>>, 
     cancel: Option<Arc<AtomicBool>>
-) -> Result<(u64, u64), String> {
-    let mut size = 0;
+) -> Result<(Sizes, Sizes, u64), String> {
+    let mut size = Sizes::default();
+    let mut own = Sizes::default();
     let mut count = 0;
     
     // Using simple walkdir; we should periodically check cancel
-    for (idx, entry) in walkdir::WalkDir::new(path).min_depth(1).into_iter().enumerate() {
+    let walker = walkdir::WalkDir::new(path)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !(e.file_type().is_dir() && skip.should_skip(e.path())));
+
+    for (idx, entry) in walker.enumerate() {
         if idx % 100 == 0 {
              if let Some(c) = &cancel {
                  if c.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
              }
         }
+        throttle.charge(1);
 
         match entry {
             Ok(entry) => {
-                if entry.file_type().is_file() {
-                    let s = entry.metadata().map(|m| m.len()).unwrap_or(0);
-                    size += s;
+                if entry.file_type().is_dir() {
+                    if let Ok(meta) = entry.metadata() {
+                        stamps.record(entry.path(), &meta);
+                    }
+                } else if entry.file_type().is_file() {
+                    let s = entry.metadata()
+                        .map(|m| Sizes::of(entry.path(), &m, cluster))
+                        .unwrap_or_default();
+                    size.add(s);
+                    if entry.depth() == 1 {
+                        own.add(s);
+                    }
                     count += 1;
 
                     if let Some(st) = &stats {
                         st.scanned_files.fetch_add(1, Ordering::Relaxed);
-                        st.total_size.fetch_add(s, Ordering::Relaxed);
+                        st.total_size.fetch_add(s.basis(basis), Ordering::Relaxed);
                     }
                 }
             }
@@ -273,5 +1081,625 @@ fn get_deep_stats(
         }
     }
     
-    Ok((size, count))
+    Ok((size, own, count))
+}
+
+/// Rough prediction of how long a full scan will take
+///
+/// Extrapolated from a sample, so it can be off by several times.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ScanEstimate {
+    pub path: String,
+    pub estimated_files: u64,
+    pub estimated_seconds: u64,
+    pub media_type: MediaType,
+    /// Top-level directories walked to extrapolate from
+    pub sampled_dirs: usize,
+}
+
+/// Subdirectories walked by `estimate_scan`
+const ESTIMATE_SAMPLE_DIRS: usize = 8;
+
+/// Entries walked per sampled directory before extrapolating
+const ESTIMATE_SAMPLE_ENTRIES: u64 = 5_000;
+
+/// Total time `estimate_scan` may spend probing
+const ESTIMATE_BUDGET: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Quick shallow probe that extrapolates a full scan's file count and duration
+///
+/// Lists the top level, walks a handful of evenly spaced subdirectories with a
+/// cap, and scales their average to the rest. The per-file cost depends on the
+/// media: seeks make HDDs roughly ten times slower than SSDs here.
+pub fn estimate_scan(path: &str, options: &ScanOptions) -> Result<ScanEstimate, String> {
+    let started = std::time::Instant::now();
+    let skip = SkipList::new(Path::new(path), options);
+    let root = long_path(Path::new(path));
+
+    let mut top_files = 0u64;
+    let mut dirs = Vec::new();
+    for entry in std::fs::read_dir(&root).map_err(|e| e.to_string())?.flatten() {
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => {
+                if !skip.should_skip(&entry.path()) {
+                    dirs.push(entry.path());
+                }
+            }
+            Ok(_) => top_files += 1,
+            Err(_) => {}
+        }
+    }
+
+    let step = (dirs.len() / ESTIMATE_SAMPLE_DIRS).max(1);
+    let mut sampled = 0usize;
+    let mut sampled_entries = 0u64;
+    for dir in dirs.iter().step_by(step).take(ESTIMATE_SAMPLE_DIRS) {
+        if started.elapsed() >= ESTIMATE_BUDGET {
+            break;
+        }
+
+        let mut count = 0u64;
+        let mut capped = false;
+        for entry in walkdir::WalkDir::new(dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_entry(|e| !(e.file_type().is_dir() && skip.should_skip(e.path())))
+            .flatten()
+        {
+            if entry.file_type().is_file() {
+                count += 1;
+            }
+            if count >= ESTIMATE_SAMPLE_ENTRIES || started.elapsed() >= ESTIMATE_BUDGET {
+                capped = true;
+                break;
+            }
+        }
+
+        // A capped walk only saw part of the tree; assume it's several times larger
+        sampled_entries += if capped { count * 4 } else { count };
+        sampled += 1;
+    }
+
+    let per_dir = if sampled > 0 { sampled_entries / sampled as u64 } else { 0 };
+    let estimated_files = top_files + per_dir * dirs.len() as u64;
+
+    let media_type = crate::partition::media::media_type_for_path(path);
+    let micros_per_file: u64 = match media_type {
+        MediaType::Ssd => 20,
+        MediaType::Hdd => 200,
+        MediaType::Unknown => 60,
+    };
+
+    Ok(ScanEstimate {
+        path: path.to_string(),
+        estimated_files,
+        estimated_seconds: (estimated_files * micros_per_file).div_ceil(1_000_000),
+        media_type,
+        sampled_dirs: sampled,
+    })
+}
+
+/// Candidate kept in `find_recent_large_files`' heap; ordered by size first
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct RecentFile {
+    size: u64,
+    path: PathBuf,
+    modified: u64,
+    logical: u64,
+    allocated: u64,
+    placeholder: bool,
+}
+
+/// Largest files under `path` modified within the last `since_hours`
+///
+/// Keeps only the top `limit` candidates in a min-heap while walking, so memory
+/// stays bounded on huge trees. Unreadable directories are skipped.
+pub fn find_recent_large_files(
+    path: &str,
+    since_hours: u64,
+    limit: usize,
+    options: &ScanOptions,
+    cancel: Option<Arc<AtomicBool>>,
+) -> Result<Vec<FileNode>, String> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let root = long_path(Path::new(path));
+    if !root.exists() {
+        return Err("Directory does not exist".to_string());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let cutoff = now.saturating_sub(since_hours.saturating_mul(3600));
+
+    let basis = options.size_basis;
+    let skip = SkipList::new(Path::new(path), options);
+    let mut heap: BinaryHeap<Reverse<RecentFile>> = BinaryHeap::new();
+
+    let walker = walkdir::WalkDir::new(&root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !(e.file_type().is_dir() && skip.should_skip(e.path())));
+
+    for (idx, entry) in walker.enumerate() {
+        if idx % 100 == 0 {
+            if let Some(c) = &cancel {
+                if c.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
+            }
+        }
+
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else { continue };
+
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH)
+            .duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+        if modified < cutoff {
+            continue;
+        }
+
+        let sizes = Sizes::of(entry.path(), &meta, None);
+        let size = sizes.basis(basis);
+        if heap.len() >= limit {
+            match heap.peek() {
+                Some(Reverse(smallest)) if size > smallest.size => { heap.pop(); }
+                _ => continue,
+            }
+        }
+        heap.push(Reverse(RecentFile {
+            size,
+            placeholder: is_cloud_placeholder(&meta),
+            path: entry.into_path(),
+            modified,
+            logical: sizes.logical,
+            allocated: sizes.allocated,
+        }));
+    }
+
+    let mut files: Vec<FileNode> = heap.into_iter().map(|Reverse(RecentFile { size, path, modified, logical, allocated, placeholder })| {
+        FileNode {
+            name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+            path: display_path(&path).to_string_lossy().to_string(),
+            size,
+            own_size: size,
+            logical_size: logical,
+            allocated_size: allocated,
+            is_dir: false,
+            children: None,
+            last_modified: modified,
+            file_count: 1,
+            skipped_paths: Vec::new(),
+            size_unknown: false,
+            is_cloud_placeholder: placeholder,
+            is_estimate: false,
+            summarized: false,
+        }
+    }).collect();
+
+    files.sort_by(|a, b| b.size.cmp(&a.size));
+    Ok(files)
+}
+
+/// Files with identical contents, found by `find_duplicates`
+#[derive(Debug, Serialize, Deserialize, Clone, TS)]
+#[ts(export)]
+pub struct DuplicateGroup {
+    #[ts(type = "number")]
+    pub size: u64, // Size of each file in the group
+    pub paths: Vec<String>,
+    #[ts(type = "number")]
+    pub reclaimable: u64, // Freed by keeping one copy and deleting the rest
+}
+
+/// Bytes hashed per read, so memory stays flat however large the file
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// Bytes compared before hashing whole files; most same-size files already differ here
+const HASH_PREFIX: u64 = 16 * 1024;
+
+/// BLAKE3 hash of the first `limit` bytes of a file (all of it when None), read in chunks
+fn hash_file(path: &Path, limit: Option<u64>) -> std::io::Result<blake3::Hash> {
+    use std::io::Read;
+
+    let file = std::fs::File::open(long_path(path))?;
+    let mut reader = file.take(limit.unwrap_or(u64::MAX));
+    let mut hasher = blake3::Hasher::new();
+    let mut buffer = vec![0u8; HASH_CHUNK];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize())
+}
+
+/// Split each group of paths by the hash of their first `limit` bytes, keeping groups of two or more
+///
+/// Files that can't be read drop out rather than failing the search.
+fn split_by_hash(
+    groups: Vec<(u64, Vec<PathBuf>)>,
+    limit: Option<u64>,
+    cancel: Option<&AtomicBool>,
+) -> Vec<(u64, Vec<PathBuf>)> {
+    groups
+        .into_par_iter()
+        .flat_map_iter(|(size, paths)| {
+            let mut by_hash: BTreeMap<[u8; 32], Vec<PathBuf>> = BTreeMap::new();
+            for path in paths {
+                if cancel.is_some_and(|c| c.load(Ordering::Relaxed)) {
+                    break;
+                }
+                if let Ok(hash) = hash_file(&path, limit) {
+                    by_hash.entry(*hash.as_bytes()).or_default().push(path);
+                }
+            }
+            by_hash
+                .into_values()
+                .filter(|paths| paths.len() > 1)
+                .map(move |paths| (size, paths))
+        })
+        .collect()
+}
+
+/// Groups of files under `path` with identical contents, most reclaimable space first
+///
+/// Files are grouped by size while walking; only sizes shared by several files
+/// are read, first their opening bytes and then, for those still matching,
+/// their whole contents. Empty files, cloud placeholders (reading one would
+/// download it) and extra hard links to the same file are left out.
+pub fn find_duplicates(
+    path: &str,
+    options: &ScanOptions,
+    cancel: Option<Arc<AtomicBool>>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let root = long_path(Path::new(path));
+    if !root.exists() {
+        return Err("Directory does not exist".to_string());
+    }
+
+    let skip = SkipList::new(Path::new(path), options);
+    let walker = walkdir::WalkDir::new(&root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|e| !(e.file_type().is_dir() && skip.should_skip(e.path())));
+
+    let mut by_size: BTreeMap<u64, Vec<PathBuf>> = BTreeMap::new();
+    #[cfg(unix)]
+    let mut seen_inodes = std::collections::HashSet::new();
+
+    for (idx, entry) in walker.enumerate() {
+        if idx % 100 == 0 {
+            if let Some(c) = &cancel {
+                if c.load(Ordering::Relaxed) { return Err("Cancelled".to_string()); }
+            }
+        }
+
+        let Ok(entry) = entry else { continue };
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(meta) = entry.metadata() else { continue };
+        if meta.len() == 0 || is_cloud_placeholder(&meta) {
+            continue;
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if !seen_inodes.insert((meta.dev(), meta.ino())) {
+                continue;
+            }
+        }
+
+        by_size.entry(meta.len()).or_default().push(entry.into_path());
+    }
+
+    let candidates: Vec<(u64, Vec<PathBuf>)> = by_size.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    let candidates = split_by_hash(candidates, Some(HASH_PREFIX), cancel.as_deref());
+
+    // Files no longer than the prefix were hashed in full already
+    let (whole, partial): (Vec<_>, Vec<_>) = candidates.into_iter().partition(|(size, _)| *size <= HASH_PREFIX);
+    let mut groups = whole;
+    groups.extend(split_by_hash(partial, None, cancel.as_deref()));
+
+    if cancel.as_ref().is_some_and(|c| c.load(Ordering::Relaxed)) {
+        return Err("Cancelled".to_string());
+    }
+
+    let mut groups: Vec<DuplicateGroup> = groups
+        .into_iter()
+        .map(|(size, paths)| {
+            let mut paths: Vec<String> = paths.iter().map(|p| display_path(p).to_string_lossy().to_string()).collect();
+            paths.sort();
+            DuplicateGroup {
+                size,
+                reclaimable: size * (paths.len() as u64 - 1),
+                paths,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.reclaimable.cmp(&a.reclaimable).then_with(|| a.paths.cmp(&b.paths)));
+    Ok(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestDir;
+
+    #[test]
+    fn test_skip_paths_are_excluded_and_reported() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("keep")).unwrap();
+        std::fs::create_dir_all(root.join("skip")).unwrap();
+        std::fs::write(root.join("keep").join("a.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(root.join("skip").join("b.bin"), vec![0u8; 10]).unwrap();
+
+        let skipped = root.join("skip").to_string_lossy().to_string();
+        let options = ScanOptions {
+            size_basis: SizeBasis::Logical,
+            skip_paths: vec![skipped.clone()],
+            ..ScanOptions::default()
+        };
+
+        let node = scan_directory(&root.to_string_lossy(), &options, None, None).unwrap();
+
+        assert_eq!(node.logical_size, 10);
+        assert_eq!(node.file_count, 1);
+        assert_eq!(node.skipped_paths, vec![skipped]);
+    }
+
+    #[test]
+    fn test_find_duplicates_confirms_contents() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("nested")).unwrap();
+        let big = |fill: u8, last: u8| {
+            let mut data = vec![fill; HASH_PREFIX as usize * 2];
+            *data.last_mut().unwrap() = last;
+            data
+        };
+        std::fs::write(root.join("a.bin"), big(1, 0)).unwrap();
+        std::fs::write(root.join("nested").join("a copy.bin"), big(1, 0)).unwrap();
+        std::fs::write(root.join("same-prefix.bin"), big(1, 9)).unwrap(); // Differs after the prefix
+        std::fs::write(root.join("x.txt"), b"hello").unwrap();
+        std::fs::write(root.join("y.txt"), b"hello").unwrap();
+        std::fs::write(root.join("z.txt"), b"world").unwrap();
+        std::fs::write(root.join("empty1"), b"").unwrap();
+        std::fs::write(root.join("empty2"), b"").unwrap();
+
+        let groups = find_duplicates(&root.to_string_lossy(), &ScanOptions::default(), None).unwrap();
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].size, HASH_PREFIX * 2);
+        assert_eq!(groups[0].reclaimable, HASH_PREFIX * 2);
+        assert!(groups[0].paths.iter().any(|p| p.ends_with("a copy.bin")));
+        assert!(!groups[0].paths.iter().any(|p| p.ends_with("same-prefix.bin")));
+        assert_eq!(groups[1].paths.len(), 2);
+        assert!(groups[1].paths[0].ends_with("x.txt"));
+    }
+
+    #[test]
+    fn test_own_size_excludes_subdirectories() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("sub").join("deep")).unwrap();
+        std::fs::write(root.join("top.bin"), vec![0u8; 5]).unwrap();
+        std::fs::write(root.join("sub").join("a.bin"), vec![0u8; 7]).unwrap();
+        std::fs::write(root.join("sub").join("deep").join("b.bin"), vec![0u8; 11]).unwrap();
+
+        let options = ScanOptions { size_basis: SizeBasis::Logical, ..ScanOptions::default() };
+        let node = scan_directory(&root.to_string_lossy(), &options, None, None).unwrap();
+
+        assert_eq!(node.size, 23);
+        assert_eq!(node.own_size, 5);
+
+        let sub = node.children.as_ref().unwrap().iter().find(|c| c.name == "sub").unwrap();
+        assert_eq!(sub.size, 18);
+        assert_eq!(sub.own_size, 7);
+
+        let deep = sub.children.as_ref().unwrap().iter().find(|c| c.name == "deep").unwrap();
+        assert_eq!(deep.own_size, 11);
+    }
+
+    #[test]
+    fn test_node_limit_summarizes_branches_but_keeps_sizes() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("sub").join("x")).unwrap();
+        std::fs::create_dir_all(root.join("sub").join("y")).unwrap();
+        for (name, size) in [("a.bin", 1), ("b.bin", 2), ("c.bin", 3)] {
+            std::fs::write(root.join(name), vec![0u8; size]).unwrap();
+        }
+        std::fs::write(root.join("sub").join("x").join("d.bin"), vec![0u8; 20]).unwrap();
+
+        let options = ScanOptions { size_basis: SizeBasis::Logical, max_nodes: Some(2), ..ScanOptions::default() };
+        let node = scan_directory(&root.to_string_lossy(), &options, None, None).unwrap();
+
+        assert_eq!(node.size, 26);
+        assert!(node.summarized);
+        let children = node.children.as_ref().unwrap();
+        let names: Vec<&str> = children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["sub", "c.bin", "b.bin"]);
+
+        let sub = &children[0];
+        assert!(sub.summarized);
+        assert!(sub.children.is_none());
+        assert_eq!(sub.size, 20);
+    }
+
+    #[test]
+    fn test_node_limit_keeps_the_same_branches_every_time() {
+        let root = TestDir::new("scan-test");
+        for i in 0..10 {
+            std::fs::create_dir_all(root.join(format!("d{}", i)).join("x")).unwrap();
+        }
+
+        // The ten directories, plus room for three of their one-entry child lists
+        let options = ScanOptions { size_basis: SizeBasis::Logical, max_nodes: Some(13), ..ScanOptions::default() };
+        for _ in 0..5 {
+            let node = scan_directory(&root.to_string_lossy(), &options, None, None).unwrap();
+            let listed: Vec<&str> = node
+                .children
+                .as_ref()
+                .unwrap()
+                .iter()
+                .filter(|c| c.children.is_some())
+                .map(|c| c.name.as_str())
+                .collect();
+            // Equal sizes are ordered by name
+            assert_eq!(listed, vec!["d0", "d1", "d2"]);
+        }
+    }
+
+    #[test]
+    fn test_max_depth_limits_listed_levels() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("a").join("b").join("c")).unwrap();
+        std::fs::write(root.join("a").join("b").join("c").join("deep.bin"), vec![0u8; 7]).unwrap();
+
+        let path = root.to_string_lossy().to_string();
+        let scan = |max_depth| {
+            let options = ScanOptions { size_basis: SizeBasis::Logical, max_depth, ..ScanOptions::default() };
+            scan_directory(&path, &options, None, None).unwrap()
+        };
+
+        // Sizes are complete at every depth; only the listed levels change
+        let shallow = scan(1);
+        let a = &shallow.children.as_ref().unwrap()[0];
+        assert_eq!(a.size, 7);
+        assert!(a.children.is_none());
+        assert!(!a.summarized);
+
+        let deep = scan(3);
+        let a = &deep.children.as_ref().unwrap()[0];
+        let b = &a.children.as_ref().unwrap()[0];
+        let c = &b.children.as_ref().unwrap()[0];
+        assert_eq!(c.size, 7);
+        assert!(c.children.is_none());
+    }
+
+    #[test]
+    fn test_incremental_rescan_rewalks_only_changed_subtrees() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("same").join("inner")).unwrap();
+        std::fs::create_dir_all(root.join("changed").join("inner")).unwrap();
+        std::fs::write(root.join("same").join("inner").join("a.bin"), vec![0u8; 10]).unwrap();
+        std::fs::write(root.join("changed").join("inner").join("b.bin"), vec![0u8; 20]).unwrap();
+
+        let path = root.to_string_lossy().to_string();
+        let options = ScanOptions { size_basis: SizeBasis::Logical, ..ScanOptions::default() };
+        let (mut previous, stamps) = scan_directory_incremental(&path, &options, None, None, None).unwrap();
+        assert_eq!(previous.size, 30);
+
+        // Mark the cached copy of "same" so reuse is visible
+        for child in previous.children.as_mut().unwrap().iter_mut() {
+            child.size_unknown = true;
+        }
+        std::fs::write(root.join("changed").join("inner").join("c.bin"), vec![0u8; 5]).unwrap();
+
+        let (node, new_stamps) = scan_directory_incremental(&path, &options, Some((&previous, &stamps)), None, None).unwrap();
+        let child = |name: &str| node.children.as_ref().unwrap().iter().find(|c| c.name == name).unwrap().clone();
+
+        assert!(child("same").size_unknown);
+        assert!(!child("changed").size_unknown);
+        assert_eq!(child("changed").size, 25);
+        assert_eq!(node.size, 35);
+        assert_eq!(new_stamps.len(), stamps.len());
+    }
+
+    #[test]
+    fn test_stamps_count_against_the_tree_budget() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("a").join("inner")).unwrap();
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        let path = root.to_string_lossy().to_string();
+
+        let (_, stamps) = scan_directory_incremental(&path, &ScanOptions::default(), None, None, None).unwrap();
+        assert_eq!(stamps.len(), 3);
+
+        // Stamps that don't all fit are dropped together, never kept partially
+        let tight = ScanOptions { max_tree_bytes: Some(1), ..ScanOptions::default() };
+        let (_, stamps) = scan_directory_incremental(&path, &tight, None, None, None).unwrap();
+        assert!(stamps.is_empty());
+    }
+
+    #[test]
+    fn test_combine_roots_counts_nested_directory_once() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("docs").join("sub")).unwrap();
+        std::fs::write(root.join("top.bin"), vec![0u8; 5]).unwrap();
+        std::fs::write(root.join("docs").join("a.bin"), vec![0u8; 7]).unwrap();
+        std::fs::write(root.join("docs").join("sub").join("b.bin"), vec![0u8; 11]).unwrap();
+
+        let options = ScanOptions { size_basis: SizeBasis::Logical, ..ScanOptions::default() };
+        let outer = scan_directory(&root.to_string_lossy(), &options, None, None).unwrap();
+        let inner = scan_directory(&root.join("docs").join("sub").to_string_lossy(), &options, None, None).unwrap();
+
+        let combined = combine_roots(vec![outer, inner]);
+        assert_eq!(combined.size, 23);
+        assert_eq!(combined.file_count, 3);
+
+        let children = combined.children.unwrap();
+        let outer = children.iter().find(|c| c.path == root.to_string_lossy()).unwrap();
+        assert_eq!(outer.size, 12);
+
+        let docs = outer.children.as_ref().unwrap().iter().find(|c| c.name == "docs").unwrap();
+        assert_eq!(docs.size, 7);
+        assert!(docs.children.as_ref().unwrap().iter().all(|c| c.name != "sub"));
+    }
+
+    #[test]
+    fn test_find_recent_large_files_keeps_largest_recent() {
+        let root = TestDir::new("scan-test");
+        std::fs::create_dir_all(root.join("logs")).unwrap();
+        std::fs::write(root.join("small.bin"), vec![0u8; 5]).unwrap();
+        std::fs::write(root.join("logs").join("big.log"), vec![0u8; 50]).unwrap();
+        std::fs::write(root.join("logs").join("mid.log"), vec![0u8; 20]).unwrap();
+
+        let options = ScanOptions { size_basis: SizeBasis::Logical, ..ScanOptions::default() };
+        let files = find_recent_large_files(&root.to_string_lossy(), 1, 2, &options, None).unwrap();
+
+        let names: Vec<_> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["big.log", "mid.log"]);
+    }
+
+    #[test]
+    fn test_io_throttle_paces_reads() {
+        // 20 entries per second: 2 entries take about 100ms
+        let throttle = IoThrottle::new(Some(ENTRY_IO_COST * 20));
+        throttle.charge(2);
+        assert!(throttle.started.elapsed() >= std::time::Duration::from_millis(90));
+
+        let unlimited = IoThrottle::new(None);
+        unlimited.charge(1_000_000);
+        assert!(unlimited.started.elapsed() < std::time::Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_estimate_scan_extrapolates_from_samples() {
+        let root = TestDir::new("scan-test");
+        for dir in ["a", "b"] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+            for i in 0..3 {
+                std::fs::write(root.join(dir).join(format!("{}.txt", i)), b"x").unwrap();
+            }
+        }
+        std::fs::write(root.join("top.txt"), b"x").unwrap();
+
+        let estimate = estimate_scan(&root.to_string_lossy(), &ScanOptions::default()).unwrap();
+        assert_eq!(estimate.estimated_files, 7);
+        assert_eq!(estimate.sampled_dirs, 2);
+    }
+
+    #[test]
+    fn test_scans_reuse_the_pool_for_their_thread_count() {
+        let first = scan_pool(3).unwrap();
+        assert!(Arc::ptr_eq(&first, &scan_pool(3).unwrap()));
+        assert_eq!(first.current_num_threads(), 3);
+        assert!(!Arc::ptr_eq(&first, &scan_pool(5).unwrap()));
+    }
 }
@@ -0,0 +1,273 @@
+// Windows Volume Shadow Copies (System Restore points)
+//
+// Shadow storage lives in the hidden "System Volume Information" folder and
+// can grow to tens of gigabytes without any file cleaning reaching it. It is
+// measured with `vssadmin list shadowstorage` and reclaimed with
+// `vssadmin delete shadows /oldest` or by lowering the storage maximum. Both
+// need an elevated process. Items carry `shadow://<volume>` paths.
+//
+// vssadmin output is localized; on non-English systems the parse finds
+// nothing and the category is simply not shown.
+
+use crate::capabilities;
+use crate::cleaner::{JunkCategory, AgeBasis, JunkItem, SafetyLevel};
+use crate::partition::OperationError;
+
+/// Path prefix of junk items that remove shadow copies
+pub const SHADOW_SCHEME: &str = "shadow://";
+
+/// One "Shadow Copy Storage association" block
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ShadowStorage {
+    volume: String, // Drive the shadow copies are for, e.g. "C:"
+    used: u64,
+    allocated: u64,
+    maximum: Option<u64>, // None when UNBOUNDED
+}
+
+/// Parse a vssadmin size ("2.35 GB (1%)", "512 bytes", "UNBOUNDED (100%)")
+fn parse_size(text: &str) -> Option<u64> {
+    let mut words = text.split_whitespace();
+    let number: f64 = words.next()?.replace(',', "").parse().ok()?;
+    let multiplier: f64 = match words.next()? {
+        "bytes" | "B" => 1.0,
+        "KB" => 1024.0,
+        "MB" => 1024.0 * 1024.0,
+        "GB" => 1024.0 * 1024.0 * 1024.0,
+        "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+
+    Some((number * multiplier).round() as u64)
+}
+
+/// Parse `vssadmin list shadowstorage`
+fn parse_shadow_storage(output: &str) -> Vec<ShadowStorage> {
+    let mut storages = Vec::new();
+    let mut current: Option<ShadowStorage> = None;
+
+    for line in output.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once(':') else { continue };
+        let value = value.trim();
+        match key {
+            // "(C:)\\?\Volume{...}\" -> "C:"
+            "For volume" => {
+                storages.extend(current.take());
+                let volume = value
+                    .strip_prefix('(')
+                    .and_then(|v| v.split_once(')'))
+                    .map(|(letter, _)| letter.to_string())
+                    .unwrap_or_default();
+                current = Some(ShadowStorage { volume, used: 0, allocated: 0, maximum: None });
+            }
+            "Used Shadow Copy Storage space" => {
+                if let Some(s) = current.as_mut() {
+                    s.used = parse_size(value).unwrap_or(0);
+                }
+            }
+            "Allocated Shadow Copy Storage space" => {
+                if let Some(s) = current.as_mut() {
+                    s.allocated = parse_size(value).unwrap_or(0);
+                }
+            }
+            "Maximum Shadow Copy Storage space" => {
+                if let Some(s) = current.as_mut() {
+                    s.maximum = parse_size(value);
+                }
+            }
+            _ => {}
+        }
+    }
+    storages.extend(current);
+
+    storages.retain(|s| !s.volume.is_empty());
+    storages
+}
+
+/// Number of shadow copies in `vssadmin list shadows` output
+fn count_shadows(output: &str) -> usize {
+    output.lines().filter(|line| line.trim_start().starts_with("Shadow Copy ID:")).count()
+}
+
+/// A drive letter as vssadmin expects it ("C:")
+fn parse_volume(volume: &str) -> Result<String, String> {
+    let volume = volume.trim_end_matches('\\').to_ascii_uppercase();
+    match volume.as_bytes() {
+        [letter, b':'] if letter.is_ascii_uppercase() => Ok(volume),
+        _ => Err(format!("Not a drive letter: {}", volume)),
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn vssadmin(args: &[&str]) -> Result<String, String> {
+    let output = std::process::Command::new("vssadmin")
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run vssadmin: {}", e))?;
+    // vssadmin reports errors on stdout
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    if !output.status.success() {
+        return Err(format!("vssadmin {} failed: {}", args.join(" "), stdout.trim()));
+    }
+
+    Ok(stdout)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn vssadmin(_args: &[&str]) -> Result<String, String> {
+    Err("Shadow copies are only available on Windows".to_string())
+}
+
+fn require_elevation(feature: &str) -> Result<(), String> {
+    if capabilities::is_elevated() {
+        return Ok(());
+    }
+    Err(OperationError::RequiresElevation { feature: feature.to_string() }.to_string())
+}
+
+fn storage_for(volume: &str) -> Result<ShadowStorage, String> {
+    parse_shadow_storage(&vssadmin(&["list", "shadowstorage", &format!("/for={}", volume)])?)
+        .into_iter()
+        .find(|s| s.volume == volume)
+        .ok_or_else(|| format!("No shadow storage on {}", volume))
+}
+
+/// Shadow storage in use per volume, or None when there is none or it can't be queried
+///
+/// Listing shadow storage already needs elevation, so this is None when not elevated.
+pub fn scan_shadow_copies() -> Option<JunkCategory> {
+    if !cfg!(target_os = "windows") || !capabilities::is_elevated() {
+        return None;
+    }
+    let storages = parse_shadow_storage(&vssadmin(&["list", "shadowstorage"]).ok()?);
+
+    let mut category = JunkCategory {
+        id: "shadow_copies".to_string(),
+        name: "System Restore Points".to_string(),
+        description: "Volume Shadow Copies used by System Restore and Previous Versions. \
+                      Removing them deletes restore points: you can no longer roll the system back to them."
+            .to_string(),
+        items: Vec::new(),
+        total_size: 0,
+        icon: "shadow_copies".to_string(),
+        filtered_size: 0,
+        filtered_count: 0,
+        unreadable_count: 0,
+    };
+
+    for storage in storages.iter().filter(|s| s.used > 0) {
+        let count = vssadmin(&["list", "shadows", &format!("/for={}", storage.volume)])
+            .map(|out| count_shadows(&out))
+            .unwrap_or(0);
+        if count == 0 {
+            continue;
+        }
+        // vssadmin doesn't report per-copy sizes; the oldest holds about an even share
+        let size = storage.used / count as u64;
+
+        category.total_size += size;
+        category.items.push(JunkItem {
+            path: format!("{}{}", SHADOW_SCHEME, storage.volume),
+            name: format!("Oldest restore point on {}", storage.volume),
+            size,
+            description: format!(
+                "Deletes the oldest of {} shadow copies on {} (estimated size). \
+                 WARNING: the restore point is gone for good and System Restore can't return to it.",
+                count, storage.volume
+            ),
+            age_days: None,
+            requires_reboot: false,
+            safety: SafetyLevel::Risky,
+            archive: None,
+            age_basis: AgeBasis::Modified,
+        });
+    }
+
+    (!category.items.is_empty()).then_some(category)
+}
+
+/// Delete the oldest shadow copy behind a `shadow://` item path, returning the bytes freed
+///
+/// The freed size is the drop in used shadow storage; a dry run returns the current estimate.
+pub fn delete_oldest(path: &str, dry_run: bool) -> Result<u64, String> {
+    let volume = parse_volume(
+        path.strip_prefix(SHADOW_SCHEME)
+            .ok_or_else(|| format!("Not a shadow copy item: {}", path))?,
+    )?;
+    require_elevation("Deleting shadow copies")?;
+
+    let before = storage_for(&volume)?;
+    if dry_run {
+        let count = count_shadows(&vssadmin(&["list", "shadows", &format!("/for={}", volume)])?);
+        return Ok(before.used / count.max(1) as u64);
+    }
+
+    vssadmin(&["delete", "shadows", &format!("/for={}", volume), "/oldest", "/quiet"])?;
+
+    let after = storage_for(&volume).map(|s| s.used).unwrap_or(0);
+    Ok(before.used.saturating_sub(after))
+}
+
+/// Cap shadow storage on `volume` at `max_percent` of the volume, returning the bytes freed
+///
+/// Windows discards the oldest shadow copies until the rest fit the new maximum.
+pub fn resize_storage(volume: &str, max_percent: u8) -> Result<u64, String> {
+    let volume = parse_volume(volume)?;
+    if !(1..=100).contains(&max_percent) {
+        return Err(format!("Maximum must be 1-100%, got {}%", max_percent));
+    }
+    require_elevation("Resizing shadow storage")?;
+
+    let before = storage_for(&volume)?;
+    vssadmin(&[
+        "resize",
+        "shadowstorage",
+        &format!("/for={}", volume),
+        &format!("/on={}", volume),
+        &format!("/maxsize={}%", max_percent),
+    ])?;
+
+    let after = storage_for(&volume).map(|s| s.used).unwrap_or(before.used);
+    Ok(before.used.saturating_sub(after))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shadow_storage() {
+        let output = "\
+vssadmin 1.1 - Volume Shadow Copy Service administrative command-line tool
+(C) Copyright 2001-2013 Microsoft Corp.
+
+Shadow Copy Storage association
+   For volume: (C:)\\\\?\\Volume{1b2c3d4e-0000-0000-0000-100000000000}\\
+   Shadow Copy Storage volume: (C:)\\\\?\\Volume{1b2c3d4e-0000-0000-0000-100000000000}\\
+   Used Shadow Copy Storage space: 2.35 GB (1%)
+   Allocated Shadow Copy Storage space: 2.75 GB (1%)
+   Maximum Shadow Copy Storage space: UNBOUNDED (100%)
+
+Shadow Copy Storage association
+   For volume: (D:)\\\\?\\Volume{5f6a7b8c-0000-0000-0000-100000000000}\\
+   Shadow Copy Storage volume: (D:)\\\\?\\Volume{5f6a7b8c-0000-0000-0000-100000000000}\\
+   Used Shadow Copy Storage space: 0 bytes (0%)
+   Allocated Shadow Copy Storage space: 0 bytes (0%)
+   Maximum Shadow Copy Storage space: 512 MB (10%)
+";
+        let storages = parse_shadow_storage(output);
+        assert_eq!(storages.len(), 2);
+        assert_eq!(storages[0].volume, "C:");
+        assert_eq!(storages[0].used, 2_523_293_286);
+        assert_eq!(storages[0].maximum, None);
+        assert_eq!(storages[1].volume, "D:");
+        assert_eq!(storages[1].used, 0);
+        assert_eq!(storages[1].maximum, Some(512 * 1024 * 1024));
+
+        let shadows = "Contents of shadow copy set ID: {a}\n   Shadow Copy ID: {b}\n\nContents of shadow copy set ID: {c}\n   Shadow Copy ID: {d}\n";
+        assert_eq!(count_shadows(shadows), 2);
+
+        assert_eq!(parse_volume("c:\\").unwrap(), "C:");
+        assert!(parse_volume("C: /all").is_err());
+    }
+}
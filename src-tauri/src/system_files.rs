@@ -0,0 +1,320 @@
+// Page/swap and hibernation files
+//
+// pagefile.sys, hiberfil.sys and swapfile.sys are often the largest files on
+// a Windows drive, are held open by the kernel so they can't be deleted, and
+// are the usual reason a shrink stops short (see resize::unmovable). They are
+// reported here with the supported way to shrink or remove each one instead
+// of being offered for deletion. On Linux the active swap areas come from
+// /proc/swaps; on macOS from the swapfiles and sleepimage under /private/var/vm.
+
+use crate::partition::resize::unmovable::UnmovableKind;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use ts_rs::TS;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub enum SystemFileKind {
+    Pagefile,
+    Hibernation,
+    Swapfile,      // Windows swapfile.sys (store apps)
+    LinuxSwapFile,
+    SwapPartition,
+    MacSwap,
+    SleepImage,
+}
+
+/// A supported way to shrink or remove a system file
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SystemFileAction {
+    pub label: String,
+    pub instructions: String,
+    pub commands: Vec<Vec<String>>, // Run in order, each as program and arguments; paths are never spliced into a shell string
+    pub requires_admin: bool,
+    pub requires_reboot: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SystemFileEntry {
+    pub kind: SystemFileKind,
+    pub path: String,
+    #[ts(type = "number")]
+    pub size: u64,
+    #[ts(type = "number | null")]
+    pub used: Option<u64>, // Swap actually in use, where the OS reports it
+    pub description: String,
+    pub actions: Vec<SystemFileAction>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct SystemFilesReport {
+    pub entries: Vec<SystemFileEntry>,
+    #[ts(type = "number")]
+    pub total_size: u64,
+}
+
+impl SystemFileKind {
+    /// The matching shrink blocker, for the Windows files
+    fn unmovable(&self) -> Option<UnmovableKind> {
+        match self {
+            SystemFileKind::Pagefile => Some(UnmovableKind::Pagefile),
+            SystemFileKind::Hibernation => Some(UnmovableKind::Hibernation),
+            SystemFileKind::Swapfile => Some(UnmovableKind::Swapfile),
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            SystemFileKind::Pagefile => {
+                "Virtual memory managed by Windows. It is in use while Windows runs and can't be deleted; move it to another drive or change its size instead."
+            }
+            SystemFileKind::Hibernation => {
+                "Holds the contents of memory while the PC hibernates and for Fast Startup. Turning hibernation off removes it."
+            }
+            SystemFileKind::Swapfile => {
+                "Used by Windows to page out store apps. It follows the page file settings and disappears when the drive has no page file."
+            }
+            SystemFileKind::LinuxSwapFile => {
+                "Active swap file. Deleting it while swap is on fails or corrupts swapped-out memory; turn it off first."
+            }
+            SystemFileKind::SwapPartition => {
+                "Active swap partition. It takes no space on other filesystems; it can only be reclaimed with partition tools after turning it off."
+            }
+            SystemFileKind::MacSwap => "Swap created by macOS on demand. It is removed automatically after a restart.",
+            SystemFileKind::SleepImage => {
+                "Copy of memory written before the Mac sleeps. Its size follows the hibernation mode set with pmset."
+            }
+        }
+    }
+
+    fn actions(&self, path: &str) -> Vec<SystemFileAction> {
+        match self {
+            SystemFileKind::Hibernation => vec![
+                SystemFileAction {
+                    label: "Turn off hibernation".to_string(),
+                    instructions: "Removes hiberfil.sys and disables hibernation and Fast Startup. Run `powercfg /h on` to restore them.".to_string(),
+                    commands: vec![argv(&["powercfg", "/h", "off"])],
+                    requires_admin: true,
+                    requires_reboot: false,
+                },
+                SystemFileAction {
+                    label: "Reduce the hibernation file".to_string(),
+                    instructions: "Keeps Fast Startup but drops full hibernation, shrinking hiberfil.sys to about a fifth of memory.".to_string(),
+                    commands: vec![argv(&["powercfg", "/h", "/type", "reduced"])],
+                    requires_admin: true,
+                    requires_reboot: false,
+                },
+            ],
+            SystemFileKind::Pagefile | SystemFileKind::Swapfile => vec![SystemFileAction {
+                label: "Move or resize the page file".to_string(),
+                instructions: "In Virtual Memory settings (Advanced > Performance Settings > Advanced > Change), set a custom size or \"No paging file\" for this drive and a page file on another drive, then restart.".to_string(),
+                commands: vec![argv(&["SystemPropertiesPerformance.exe"])],
+                requires_admin: true,
+                requires_reboot: true,
+            }],
+            SystemFileKind::LinuxSwapFile => vec![
+                SystemFileAction {
+                    label: "Resize the swap file".to_string(),
+                    instructions: "Turn swap off, reallocate the file at the new size (replace 2G) and turn it back on.".to_string(),
+                    commands: vec![
+                        argv(&["sudo", "swapoff", path]),
+                        argv(&["sudo", "fallocate", "-l", "2G", path]),
+                        argv(&["sudo", "mkswap", path]),
+                        argv(&["sudo", "swapon", path]),
+                    ],
+                    requires_admin: true,
+                    requires_reboot: false,
+                },
+                SystemFileAction {
+                    label: "Remove the swap file".to_string(),
+                    instructions: "Turn swap off and delete the file, then remove its line from /etc/fstab so it isn't activated at boot.".to_string(),
+                    commands: vec![argv(&["sudo", "swapoff", path]), argv(&["sudo", "rm", "--", path])],
+                    requires_admin: true,
+                    requires_reboot: false,
+                },
+            ],
+            SystemFileKind::SwapPartition => vec![SystemFileAction {
+                label: "Stop using the swap partition".to_string(),
+                instructions: "Turn it off and remove it from /etc/fstab; the partition can then be deleted or merged with partition tools.".to_string(),
+                commands: vec![argv(&["sudo", "swapoff", path])],
+                requires_admin: true,
+                requires_reboot: false,
+            }],
+            SystemFileKind::MacSwap => vec![SystemFileAction {
+                label: "Restart".to_string(),
+                instructions: "macOS deletes its swap files at startup; quitting memory-heavy apps keeps them from coming back.".to_string(),
+                commands: Vec::new(),
+                requires_admin: false,
+                requires_reboot: true,
+            }],
+            SystemFileKind::SleepImage => vec![SystemFileAction {
+                label: "Stop writing the sleep image".to_string(),
+                instructions: "Hibernation mode 0 keeps memory powered during sleep instead of saving it to disk (desktops only; laptops lose state on battery drain). Delete the sleepimage file afterwards.".to_string(),
+                commands: vec![argv(&["sudo", "pmset", "-a", "hibernatemode", "0"])],
+                requires_admin: true,
+                requires_reboot: false,
+            }],
+        }
+    }
+}
+
+/// One command line as separate arguments
+fn argv(args: &[&str]) -> Vec<String> {
+    args.iter().map(|a| a.to_string()).collect()
+}
+
+fn entry(kind: SystemFileKind, path: String, size: u64, used: Option<u64>) -> SystemFileEntry {
+    let mut description = kind.description().to_string();
+    if let Some(blocker) = kind.unmovable() {
+        description.push_str(&format!(" It also limits how far the volume can shrink: {}", blocker.remediation()));
+    }
+    SystemFileEntry {
+        actions: kind.actions(&path),
+        kind,
+        path,
+        size,
+        used,
+        description,
+    }
+}
+
+/// Windows system file kind from a path's file name
+fn windows_kind(path: &Path) -> Option<SystemFileKind> {
+    // Split by hand so Windows paths are recognised on any host
+    let path = path.to_string_lossy().to_lowercase();
+    match path.rsplit(['\\', '/']).next()? {
+        "pagefile.sys" => Some(SystemFileKind::Pagefile),
+        "hiberfil.sys" => Some(SystemFileKind::Hibernation),
+        "swapfile.sys" => Some(SystemFileKind::Swapfile),
+        _ => None,
+    }
+}
+
+/// Parse /proc/swaps (sizes in KiB); zram devices are memory, not disk, and are skipped
+fn parse_proc_swaps(content: &str) -> Vec<SystemFileEntry> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [path, kind, size, used, ..] = fields[..] else { return None };
+            if path.starts_with("/dev/zram") {
+                return None;
+            }
+            // Paths with spaces are escaped as \040
+            let path = path.replace("\\040", " ");
+            let kind = match kind {
+                "file" => SystemFileKind::LinuxSwapFile,
+                "partition" => SystemFileKind::SwapPartition,
+                _ => return None,
+            };
+            let size = size.parse::<u64>().ok()? * 1024;
+            let used = used.parse::<u64>().ok().map(|kb| kb * 1024);
+            Some(entry(kind, path, size, used))
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn collect() -> Vec<SystemFileEntry> {
+    use sysinfo::Disks;
+
+    let disks = Disks::new_with_refreshed_list();
+    let mut entries = Vec::new();
+    for disk in disks.list() {
+        let root = disk.mount_point();
+        for name in ["pagefile.sys", "hiberfil.sys", "swapfile.sys"] {
+            let path = root.join(name);
+            // std falls back to the directory entry when the file is locked
+            let Ok(metadata) = std::fs::metadata(&path) else { continue };
+            if let Some(kind) = windows_kind(&path) {
+                entries.push(entry(kind, path.to_string_lossy().to_string(), metadata.len(), None));
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(target_os = "linux")]
+fn collect() -> Vec<SystemFileEntry> {
+    std::fs::read_to_string("/proc/swaps")
+        .map(|content| parse_proc_swaps(&content))
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn collect() -> Vec<SystemFileEntry> {
+    let Ok(dir) = std::fs::read_dir("/private/var/vm") else {
+        return Vec::new();
+    };
+    dir.flatten()
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let kind = if name == "sleepimage" {
+                SystemFileKind::SleepImage
+            } else if name.starts_with("swapfile") {
+                SystemFileKind::MacSwap
+            } else {
+                return None;
+            };
+            let size = e.metadata().ok()?.len();
+            Some(entry(kind, e.path().to_string_lossy().to_string(), size, None))
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn collect() -> Vec<SystemFileEntry> {
+    Vec::new()
+}
+
+/// Page/swap and hibernation files on this machine, largest first
+pub fn system_files_report() -> SystemFilesReport {
+    let mut entries = collect();
+    entries.sort_by(|a, b| b.size.cmp(&a.size));
+    SystemFilesReport {
+        total_size: entries.iter().map(|e| e.size).sum(),
+        entries,
+    }
+}
+
+/// Explanation to show instead of a raw I/O error when `path` is a Windows system file
+pub fn protected_reason(path: &Path) -> Option<String> {
+    let kind = windows_kind(path)?;
+    let action = kind.actions("").into_iter().next()?;
+    Some(format!(
+        "{} can't be deleted while Windows is running. {} ({})",
+        kind.unmovable()?.display_name(),
+        action.label,
+        action.instructions
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_swaps() {
+        let content = "Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+                       /swap\\040file                           file\t\t2097148\t\t1024\t\t-2\n\
+                       /dev/dm-1                               partition\t8388604\t\t0\t\t-3\n\
+                       /dev/zram0                              partition\t4194300\t\t0\t\t100\n";
+        let entries = parse_proc_swaps(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].kind, SystemFileKind::LinuxSwapFile);
+        assert_eq!(entries[0].path, "/swap file");
+        assert_eq!(entries[0].size, 2097148 * 1024);
+        assert_eq!(entries[0].used, Some(1024 * 1024));
+        assert_eq!(entries[0].actions[1].commands[0], vec!["sudo", "swapoff", "/swap file"]);
+        assert_eq!(entries[1].kind, SystemFileKind::SwapPartition);
+
+        assert!(protected_reason(Path::new("C:\\pagefile.sys")).unwrap().contains("page file"));
+        assert!(protected_reason(Path::new("C:\\notes.txt")).is_none());
+    }
+}
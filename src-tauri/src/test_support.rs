@@ -0,0 +1,89 @@
+// Fixtures shared by unit tests
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::partition::{
+    DiskInfo, DiskStatus, FilesystemType, PartitionInfo, PartitionTableType, PartitionType,
+};
+
+pub const GB: u64 = 1024 * 1024 * 1024;
+
+/// A fresh directory under the system temp directory, removed when dropped
+///
+/// Drop also runs when an assertion fails, so a failing test doesn't leave
+/// its files behind.
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+    /// Create `toolkit-<name>-<uuid>` in the temp directory
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("toolkit-{}-{}", name, uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&path).unwrap();
+        TestDir(path)
+    }
+}
+
+impl Deref for TestDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TestDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// An unmounted 10GB ext4 partition at `/dev/<id>`, starting 1MB into the disk
+///
+/// Override what a test cares about with struct update syntax:
+/// `PartitionInfo { number: 2, ..mock_partition("sda2") }`.
+pub fn mock_partition(id: &str) -> PartitionInfo {
+    PartitionInfo {
+        id: id.to_string(),
+        number: 1,
+        device_path: format!("/dev/{}", id),
+        label: None,
+        start_offset: 1024 * 1024,
+        total_size: 10 * GB,
+        used_space: None,
+        partition_type: PartitionType::Primary,
+        filesystem: FilesystemType::Ext4,
+        mount_point: None,
+        is_mounted: false,
+        is_accessible: true,
+        flags: vec![],
+        uuid: None,
+        is_system_volume: false,
+    }
+}
+
+/// A healthy, online 100GB GPT disk holding `partitions`
+pub fn mock_disk(device_path: &str, partitions: Vec<PartitionInfo>) -> DiskInfo {
+    DiskInfo {
+        id: device_path.trim_start_matches("/dev/").to_string(),
+        device_path: device_path.to_string(),
+        model: "Mock Disk".to_string(),
+        total_size: 100 * GB,
+        table_type: PartitionTableType::GPT,
+        partitions,
+        largest_free_block: 0,
+        total_free: 0,
+        serial_number: None,
+        status: DiskStatus {
+            is_online: true,
+            has_errors: false,
+            smart_status: None,
+        },
+    }
+}
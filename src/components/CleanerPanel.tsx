@@ -31,7 +31,9 @@ import {
     FolderOpenRegular,
 } from '@fluentui/react-icons';
 import { invoke } from '@tauri-apps/api/core';
-import { JunkCategory, JunkItem } from '../types/cleaner';
+import { listen } from '@tauri-apps/api/event';
+import { JunkCategory, JunkDeleteProgress, JunkItem } from '../types/cleaner';
+import { ConfirmationToken } from '@/types';
 
 const useStyles = makeStyles({
     container: {
@@ -90,6 +92,8 @@ export const CleanerPanel = () => {
     const [deleting, setDeleting] = useState(false); // Visual state for deleting
     const [selectedItems, setSelectedItems] = useState<Set<string>>(new Set());
     const [cleanDialogOpen, setCleanDialogOpen] = useState(false);
+    const [cleanToken, setCleanToken] = useState<ConfirmationToken | null>(null);
+    const [deleteProgress, setDeleteProgress] = useState<JunkDeleteProgress | null>(null);
     const [errorDialogOpen, setErrorDialogOpen] = useState(false);
     const [cleaningErrors, setCleaningErrors] = useState<string[]>([]);
 
@@ -114,13 +118,12 @@ export const CleanerPanel = () => {
 
             setCategories(result);
 
-            // Auto-select all by default? Or let user choose? 
-            // Let's auto-select "safe" ones. For now, select all.
-            const allPaths = new Set<string>();
+            // Pre-select only items the scan is confident about; the rest need an explicit opt-in
+            const safePaths = new Set<string>();
             result.forEach(cat => {
-                cat.items.forEach(item => allPaths.add(item.path));
+                cat.items.filter(item => item.safety === 'Safe').forEach(item => safePaths.add(item.path));
             });
-            setSelectedItems(allPaths);
+            setSelectedItems(safePaths);
 
         } catch (e) {
             console.error(e);
@@ -130,14 +133,30 @@ export const CleanerPanel = () => {
         }
     };
 
+    // The backend only deletes a selection the user confirmed, via a token bound to exactly these paths
+    const openCleanDialog = async () => {
+        setCleanToken(null);
+        setCleanDialogOpen(true);
+        try {
+            setCleanToken(await invoke<ConfirmationToken>('prepare_clean_junk', { paths: Array.from(selectedItems) }));
+        } catch (e) {
+            console.error(e);
+        }
+    };
+
     const handleClean = async () => {
+        if (!cleanToken) return;
         setCleanDialogOpen(false);
         setLoading(true);
         setDeleting(true);
         setCleaningErrors([]);
+        setDeleteProgress(null);
+        const unlisten = await listen<JunkDeleteProgress>('junk-delete-progress', (event) => {
+            setDeleteProgress(event.payload);
+        });
 
         try {
-            await invoke('clean_junk', { paths: Array.from(selectedItems) });
+            await invoke('clean_junk', { paths: Array.from(selectedItems), confirmation: cleanToken.token });
 
             // Small delay to show completion state
             await new Promise(r => setTimeout(r, 500));
@@ -148,7 +167,10 @@ export const CleanerPanel = () => {
             setCleaningErrors(errorLines);
             setErrorDialogOpen(true);
         } finally {
+            unlisten();
             setDeleting(false);
+            setDeleteProgress(null);
+            setCleanToken(null);
             // Always re-scan to show updated state, even if there were errors
             // This ensures the UI reflects what was actually cleaned
             await handleScan();
@@ -205,9 +227,20 @@ export const CleanerPanel = () => {
                 {deleting ? (
                     <div style={{ display: 'flex', flexDirection: 'column', alignItems: 'center', gap: '10px' }}>
                         <DeleteRegular style={{ fontSize: '48px', color: '#d13438' }} />
-                        <Text size={500}>Deleting {selectedItems.size} items...</Text>
-                        <Text size={300} style={{ color: '#aaa' }}>Freeing up {formatSize(totalSelectedSize)}</Text>
-                        <ProgressBar style={{ width: '200px' }} />
+                        <Text size={500}>
+                            {deleteProgress
+                                ? `Deleting ${deleteProgress.processed} of ${deleteProgress.total} items...`
+                                : `Deleting ${selectedItems.size} items...`}
+                        </Text>
+                        <Text size={300} style={{ color: '#aaa' }}>
+                            {deleteProgress
+                                ? `Freed ${formatSize(deleteProgress.deleted_size)} of ${formatSize(totalSelectedSize)}`
+                                : `Freeing up ${formatSize(totalSelectedSize)}`}
+                        </Text>
+                        <ProgressBar
+                            style={{ width: '200px' }}
+                            value={deleteProgress && deleteProgress.total > 0 ? deleteProgress.processed / deleteProgress.total : undefined}
+                        />
                         <Text size={200} style={{ color: '#aaa', marginTop: '5px' }}>Please wait, this may take a moment</Text>
                     </div>
                 ) : scanning ? (
@@ -234,7 +267,7 @@ export const CleanerPanel = () => {
                                     size="large"
                                     icon={<DeleteRegular />}
                                     style={{ marginTop: '10px', minWidth: '150px' }}
-                                    onClick={() => setCleanDialogOpen(true)}
+                                    onClick={openCleanDialog}
                                     disabled={selectedItems.size === 0}
                                 >
                                     Clean Now
@@ -295,6 +328,18 @@ export const CleanerPanel = () => {
                                                             onChange={(e, data) => toggleItem(item.path, !!data.checked)}
                                                         />
                                                         <span style={{ display: 'flex', alignItems: 'center', gap: '10px' }}>
+                                                            {item.safety !== 'Safe' && (
+                                                                <Text size={200} style={{ color: item.safety === 'Risky' ? '#d13438' : '#c19c00' }}>{item.safety}</Text>
+                                                            )}
+                                                            {item.archive && (
+                                                                <Text
+                                                                    size={200}
+                                                                    style={{ color: '#aaa' }}
+                                                                    title={item.archive.top_entries.map(e => `${e.name} (${formatSize(e.size)})`).join('\n')}
+                                                                >
+                                                                    {item.archive.entry_count} files, {formatSize(item.archive.uncompressed_size)} unpacked
+                                                                </Text>
+                                                            )}
                                                             <Text size={200} style={{ color: '#aaa' }}>{formatSize(item.size)}</Text>
                                                             <Button
                                                                 icon={<FolderOpenRegular />}
@@ -326,7 +371,7 @@ export const CleanerPanel = () => {
                                 Are you sure you want to permanently delete {selectedItems.size} items totaling <strong>{formatSize(totalSelectedSize)}</strong>?
                             </Text>
                             <Text block style={{ marginTop: '10px', color: 'var(--colorPaletteRedForeground1)' }}>
-                                This action cannot be undone.
+                                {cleanToken ? cleanToken.description : 'This action cannot be undone.'}
                             </Text>
                         </DialogContent>
                         <DialogActions>
@@ -334,6 +379,7 @@ export const CleanerPanel = () => {
                             <Button
                                 appearance="primary"
                                 style={{ backgroundColor: '#d13438', color: 'white' }}
+                                disabled={!cleanToken}
                                 onClick={handleClean}
                             >
                                 Delete
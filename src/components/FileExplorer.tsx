@@ -58,7 +58,7 @@ import { CleanerPanel } from './CleanerPanel';
 import ToolshedPanel from './ToolshedPanel';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { FileNode } from '@/types';
+import { ConfirmationToken, FileNode } from '@/types';
 import { FileMetadata } from '@/types/ai-types';
 import { ThemeToggle } from './ThemeToggle';
 
@@ -335,6 +335,7 @@ export const FileExplorer = ({ onToggleAI, isAIPanelOpen, onContextChange }: Fil
     const [deleteDialogOpen, setDeleteDialogOpen] = React.useState(false);
     const [propertiesDialogOpen, setPropertiesDialogOpen] = React.useState(false);
     const [dialogItem, setDialogItem] = React.useState<FileNode | null>(null);
+    const [deleteToken, setDeleteToken] = React.useState<ConfirmationToken | null>(null);
 
     // Compute the actually selected item object (only one supported for now)
     const selectedItem = React.useMemo(() => {
@@ -564,18 +565,26 @@ export const FileExplorer = ({ onToggleAI, isAIPanelOpen, onContextChange }: Fil
         }
     };
 
-    const handleDeleteClick = (item: FileNode) => {
+    const handleDeleteClick = async (item: FileNode) => {
         setDialogItem(item);
+        setDeleteToken(null);
         setDeleteDialogOpen(true);
+        try {
+            // The backend only deletes with a token issued for exactly this path
+            setDeleteToken(await invoke<ConfirmationToken>('prepare_destructive', { op: 'DeleteItem', target: item.path }));
+        } catch (e) {
+            console.error(`Failed to prepare delete: ${e}`);
+        }
     };
 
     const confirmDelete = async () => {
-        if (!dialogItem) return;
+        if (!dialogItem || !deleteToken) return;
         try {
-            await invoke('delete_item', { path: dialogItem.path });
+            await invoke('delete_item', { path: dialogItem.path, confirmation: deleteToken.token });
             fetchData(state.path, true);
             setDeleteDialogOpen(false);
             setDialogItem(null);
+            setDeleteToken(null);
         } catch (e) {
             console.error(`Failed to delete: ${e}`);
             alert(`Failed to delete: ${e}`);
@@ -847,10 +856,15 @@ export const FileExplorer = ({ onToggleAI, isAIPanelOpen, onContextChange }: Fil
                                                     Warning: This is a folder. All contents will be deleted.
                                                 </Text>
                                             )}
+                                            {deleteToken && (
+                                                <Text block style={{ marginTop: '10px' }}>
+                                                    {deleteToken.description}
+                                                </Text>
+                                            )}
                                         </DialogContent>
                                         <DialogActions>
                                             <Button appearance="secondary" onClick={() => setDeleteDialogOpen(false)}>Cancel</Button>
-                                            <Button appearance="primary" style={{ backgroundColor: '#d13438', color: 'white' }} onClick={confirmDelete}>Delete</Button>
+                                            <Button appearance="primary" style={{ backgroundColor: '#d13438', color: 'white' }} disabled={!deleteToken} onClick={confirmDelete}>Delete</Button>
                                         </DialogActions>
                                     </DialogBody>
                                 </DialogSurface>
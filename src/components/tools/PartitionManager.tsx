@@ -24,6 +24,7 @@ import {
   ArrowSwap24Regular,
 } from '@fluentui/react-icons';
 import { invoke } from '@tauri-apps/api/core';
+import { DiskInfo, PartialFailure, PartialFailureError, PartitionInfo } from '@/types';
 import { ResizeDialog } from './partition/ResizeDialog';
 import { SpaceReallocationWizard } from './partition/SpaceReallocationWizard';
 import { SpaceInputDialog } from './partition/SpaceInputDialog';
@@ -88,33 +89,20 @@ const useStyles = makeStyles({
   },
 });
 
-interface DiskInfo {
-  id: string;
-  device_path: string;
-  model: string;
-  total_size: number;
-  table_type: 'MBR' | 'GPT' | 'Unknown';
-  partitions: PartitionInfo[];
-  serial_number?: string;
-  status: {
-    is_online: boolean;
-    has_errors: boolean;
-  };
+interface MoveStep {
+  partition_id: string;
+  partition_label: string | null;
+  from_offset: number;
+  to_offset: number;
+  data_size: number; // Bytes copied out and back in
+  completed: boolean;
 }
 
-interface PartitionInfo {
-  id: string;
-  number: number;
-  device_path: string;
-  label: string | null;
-  start_offset: number;
-  total_size: number;
-  used_space: number | null;
-  partition_type: 'Primary' | 'Extended' | 'Logical' | 'Normal' | 'Unknown';
-  filesystem: 'NTFS' | 'Ext2' | 'Ext3' | 'Ext4' | 'FAT32' | 'ExFAT' | 'APFS' | 'HFSPlus' | 'RAW' | 'Unknown';
-  mount_point: string | null;
-  is_mounted: boolean;
-  flags: string[];
+interface MoveInstructions {
+  tool_recommendation: string | null; // External tool when moves can't run natively
+  steps: MoveStep[];
+  estimated_minutes: number;
+  failure: PartialFailure | null; // Why the moves stopped; later steps stay pending
 }
 
 function formatBytes(bytes: number): string {
@@ -185,8 +173,10 @@ export function PartitionManager() {
     setWizardOpen(true);
   };
 
-  const handleResizeSuccess = () => {
-    loadDisks(); // Reload disk data after successful resize
+  const handleResizeSuccess = (disk: DiskInfo) => {
+    // The resize returns its disk as re-read afterwards; swap it in directly
+    setDisks(prev => prev.map(d => (d.id === disk.id ? disk : d)));
+    setSelectedDisk(current => (current?.id === disk.id ? disk : current));
   };
 
   const handleWizardClose = () => {
@@ -271,6 +261,7 @@ export function PartitionManager() {
                 </Text>
                 <Text size={200} style={{ color: tokens.colorNeutralForeground3 }}>
                   {disk.partitions.length} partition{disk.partitions.length !== 1 ? 's' : ''}
+                  {disk.total_free > 0 && ` • ${formatBytes(disk.total_free)} unallocated (largest block ${formatBytes(disk.largest_free_block)})`}
                 </Text>
               </div>
             </div>
@@ -313,7 +304,7 @@ export function PartitionManager() {
                       <TableCell>{formatBytes(partition.total_size)}</TableCell>
                       <TableCell>{partition.filesystem}</TableCell>
                       <TableCell>{partition.mount_point || '-'}</TableCell>
-                      <TableCell>{partition.is_mounted ? 'Mounted' : 'Not Mounted'}</TableCell>
+                      <TableCell>{!partition.is_accessible ? 'Inaccessible' : partition.is_mounted ? 'Mounted' : 'Not Mounted'}</TableCell>
                       <TableCell>
                         <Button
                           size="small"
@@ -341,7 +332,7 @@ export function PartitionManager() {
 
       {/* Resize Dialog */}
       {selectedPartition && selectedDisk && (
-        <ResizeDialog
+        <ResizeDialog<DiskInfo>
           partition={selectedPartition}
           diskInfo={{ id: selectedDisk.id, total_size: selectedDisk.total_size }}
           open={resizeDialogOpen}
@@ -391,13 +382,29 @@ export function PartitionManager() {
           onExecuteMove={async (moveOperations) => {
             try {
               console.log('Executing move operations:', moveOperations);
-              const instructions = await invoke<string>('execute_partition_moves', {
+              const result = await invoke<MoveInstructions>('execute_partition_moves', {
                 moveOperations
               });
-              alert(instructions);
+              const pending = result.steps.filter((step) => !step.completed);
+              const remaining = pending
+                .map((step) => `${step.partition_label ?? step.partition_id}: move to ${formatBytes(step.to_offset)}`)
+                .join('\n');
+              const tool = result.tool_recommendation ? `\nUse ${result.tool_recommendation} for the remaining moves.` : '';
+              if (result.failure) {
+                // Completed moves stay in effect; the rest are left to the user
+                alert(`Failed to move partitions: ${result.failure.failed_step}: ${result.failure.reason}\n${remaining}${tool}`);
+              } else {
+                alert(pending.length === 0
+                  ? `Moved ${result.steps.length} partition(s) successfully.`
+                  : `${remaining}${tool}`);
+              }
               loadDisks();
             } catch (err) {
-              alert(`Failed to generate move plan: ${err}`);
+              const message = typeof err === 'object' && err !== null && 'partial_failure' in err
+                ? (err as PartialFailureError).message
+                : String(err);
+              alert(`Failed to move partitions: ${message}`);
+              loadDisks();
             }
           }}
         />
@@ -7,6 +7,7 @@ import {
   DialogContent,
   DialogActions,
   Button,
+  Checkbox,
   Text,
   Spinner,
   Field,
@@ -22,21 +23,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { BackupVerificationDialog } from './BackupVerificationDialog';
 import { ConfirmationDialog } from './ConfirmationDialog';
-
-interface PartitionInfo {
-  id: string;
-  number: number;
-  device_path: string;
-  label: string | null;
-  start_offset: number;
-  total_size: number;
-  used_space: number | null;
-  partition_type: 'Primary' | 'Extended' | 'Logical' | 'Normal' | 'Unknown';
-  filesystem: 'NTFS' | 'Ext2' | 'Ext3' | 'Ext4' | 'FAT32' | 'ExFAT' | 'APFS' | 'HFSPlus' | 'RAW' | 'Unknown';
-  mount_point: string | null;
-  is_mounted: boolean;
-  flags: string[];
-}
+import { PartitionInfo, ResizeProgress } from '@/types';
 
 interface ValidationResult {
   is_valid: boolean;
@@ -47,14 +34,9 @@ interface ValidationResult {
   maximum_size: number | null;
   has_adjacent_space: boolean;
   adjacent_space: number;
-}
-
-interface ResizeProgress {
-  phase: 'Validating' | 'CheckingFilesystem' | 'CreatingBackup' | 'ResizingFilesystem' |
-  'UpdatingPartitionTable' | 'ExpandingFilesystem' | 'Verifying' | 'Complete' | 'Error';
-  percent: number;
-  message: string;
-  can_cancel: boolean;
+  requires_unmount: boolean;
+  on_battery: boolean;
+  battery_percent: number | null;
 }
 
 const useStyles = makeStyles({
@@ -108,16 +90,16 @@ const useStyles = makeStyles({
   },
 });
 
-interface ResizeDialogProps {
+interface ResizeDialogProps<D> {
   partition: PartitionInfo;
   diskInfo: { id: string; total_size: number };
   open: boolean;
   onClose: () => void;
-  onSuccess: () => void;
+  onSuccess: (disk: D) => void; // Receives the disk as re-read after the resize
   onReallocate?: () => void;
 }
 
-export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, onReallocate }: ResizeDialogProps) {
+export function ResizeDialog<D>({ partition, diskInfo, open, onClose, onSuccess, onReallocate }: ResizeDialogProps<D>) {
   const styles = useStyles();
   const [mode, setMode] = useState<'expand' | 'shrink'>('expand');
   const [targetSize, setTargetSize] = useState(partition.total_size);
@@ -129,6 +111,7 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
   const [showBackupDialog, setShowBackupDialog] = useState(false);
   const [showConfirmDialog, setShowConfirmDialog] = useState(false);
   const [showReallocateOption, setShowReallocateOption] = useState(false);
+  const [ignoreBattery, setIgnoreBattery] = useState(false);
 
   // Calculate min/max in GB for slider
   const minSizeGB = partition.used_space
@@ -147,6 +130,7 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
       setValidation(null);
       setIsResizing(false);
       setProgress(null);
+      setIgnoreBattery(false);
     }
   }, [open, partition.total_size]);
 
@@ -154,18 +138,12 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
     // Set up progress listener
     const unlisten = listen<ResizeProgress>('resize-progress', (event) => {
       setProgress(event.payload);
-      if (event.payload.phase === 'Complete') {
-        setTimeout(() => {
-          onSuccess();
-          onClose();
-        }, 2000);
-      }
     });
 
     return () => {
       unlisten.then(fn => fn());
     };
-  }, [onSuccess, onClose]);
+  }, []);
 
   const handleValidate = async () => {
     setIsValidating(true);
@@ -229,17 +207,22 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
 
     setIsResizing(true);
     try {
-      if (mode === 'expand') {
-        await invoke('expand_partition', {
+      const disk = mode === 'expand'
+        ? await invoke<D>('expand_partition', {
           partitionId: partition.id,
           targetSize,
-        });
-      } else {
-        await invoke('shrink_partition', {
+          ignoreBattery,
+        })
+        : await invoke<D>('shrink_partition', {
           partitionId: partition.id,
           targetSize,
+          ignoreBattery,
         });
-      }
+      // Leave the Complete message up briefly before handing back the new layout
+      setTimeout(() => {
+        onSuccess(disk);
+        onClose();
+      }, 2000);
     } catch (error) {
       console.error('Resize error:', error);
       setProgress({
@@ -247,11 +230,21 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
         percent: 0,
         message: String(error),
         can_cancel: false,
+        indeterminate: false,
       });
       setIsResizing(false);
     }
   };
 
+  const handleCancelResize = async () => {
+    try {
+      // The resize reports the cancellation itself with an Error update
+      await invoke('cancel_resize', { partitionId: partition.id });
+    } catch (error) {
+      console.error('Cancel error:', error);
+    }
+  };
+
   const handleSliderChange = (_: unknown, data: { value: number }) => {
     setTargetSizeGB(data.value.toFixed(2));
   };
@@ -276,8 +269,10 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
             <DialogContent>
               <div className={styles.progressContainer}>
                 <Text weight="semibold">{progress.message}</Text>
-                <ProgressBar value={progress.percent / 100} />
-                <Text size={200}>{progress.percent.toFixed(0)}% - {progress.phase}</Text>
+                <ProgressBar value={progress.indeterminate ? undefined : progress.percent / 100} />
+                <Text size={200}>
+                  {progress.indeterminate ? progress.phase : `${progress.percent.toFixed(0)}% - ${progress.phase}`}
+                </Text>
                 {progress.phase === 'Error' && (
                   <MessageBar intent="error">
                     <MessageBarBody>{progress.message}</MessageBarBody>
@@ -293,7 +288,7 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
             <DialogActions>
               <Button
                 appearance="secondary"
-                onClick={onClose}
+                onClick={progress.phase === 'Complete' || progress.phase === 'Error' ? onClose : handleCancelResize}
                 disabled={!progress.can_cancel && progress.phase !== 'Complete' && progress.phase !== 'Error'}
               >
                 {progress.phase === 'Complete' || progress.phase === 'Error' ? 'Close' : 'Cancel'}
@@ -389,6 +384,13 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
                     <MessageBarBody>{warning}</MessageBarBody>
                   </MessageBar>
                 ))}
+                {validation.on_battery && (
+                  <Checkbox
+                    checked={ignoreBattery}
+                    onChange={(_, data) => setIgnoreBattery(data.checked === true)}
+                    label="Resize on battery power anyway (a power loss mid-resize can corrupt the partition)"
+                  />
+                )}
                 {validation.is_valid && validation.errors.length === 0 && (
                   <MessageBar intent="success">
                     <MessageBarBody>
@@ -401,7 +403,7 @@ export function ResizeDialog({ partition, diskInfo, open, onClose, onSuccess, on
                 )}
 
                 {/* Show reallocate option if no adjacent space for expansion */}
-                {mode === 'expand' && !validation.is_valid && validation.errors.some(e => e.includes('Not enough adjacent space')) && (
+                {mode === 'expand' && !validation.is_valid && validation.errors.some(e => e.includes('exceeds available contiguous space')) && (
                   <MessageBar intent="info">
                     <MessageBarBody>
                       <Text weight="semibold">Need more space?</Text>
@@ -1,5 +1,15 @@
 import React, { useState } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
+import {
+  ConfirmationToken,
+  PartialFailure,
+  PartialFailureError,
+  PartitionInfo,
+  PlanProgress,
+  PlanValidation,
+  ReallocationPlan,
+} from '@/types';
 import {
   Dialog,
   DialogSurface,
@@ -25,46 +35,6 @@ import {
 } from '@fluentui/react-icons';
 import styles from './SpaceReallocationWizard.module.css';
 
-interface PartitionInfo {
-  id: string;
-  number: number;
-  device_path: string;
-  label: string | null;
-  start_offset: number;
-  total_size: number;
-  used_space: number | null;
-  partition_type: 'Primary' | 'Extended' | 'Logical' | 'Normal' | 'Unknown';
-  filesystem: 'NTFS' | 'Ext2' | 'Ext3' | 'Ext4' | 'FAT32' | 'ExFAT' | 'APFS' | 'HFSPlus' | 'RAW' | 'Unknown';
-  mount_point: string | null;
-  is_mounted: boolean;
-  flags: string[];
-}
-
-interface ReallocationPlan {
-  target_partition_id: string;
-  source_partitions: SourcePartitionPlan[];
-  total_space_freed: number;
-  target_new_size: number;
-  steps: ReallocationStep[];
-  warnings: string[];
-}
-
-interface SourcePartitionPlan {
-  partition_id: string;
-  partition_label: string;
-  current_size: number;
-  used_space?: number;
-  action: { DeleteEntirely?: {} } | { ShrinkAndDelete?: { shrink_to: number } } | { ShrinkOnly?: { new_size: number } };
-}
-
-interface ReallocationStep {
-  step_number: number;
-  title: string;
-  description: string;
-  action_type: 'UserManual' | 'AppAutomated' | 'AppAssistedManual';
-  can_automate: boolean;
-}
-
 interface SpaceReallocationWizardProps {
   open: boolean;
   onClose: () => void;
@@ -72,7 +42,7 @@ interface SpaceReallocationWizardProps {
   desiredSpace: number; // in bytes
 }
 
-type WizardStep = 'input' | 'analyzing' | 'plan' | 'executing' | 'complete' | 'error';
+type WizardStep = 'input' | 'analyzing' | 'plan' | 'confirm' | 'executing' | 'complete' | 'error';
 
 export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = ({
   open,
@@ -84,6 +54,9 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
   const [plan, setPlan] = useState<ReallocationPlan | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [currentExecutingStep, setCurrentExecutingStep] = useState<number>(0);
+  const [planProgress, setPlanProgress] = useState<PlanProgress | null>(null);
+  const [partialFailure, setPartialFailure] = useState<PartialFailure | null>(null);
+  const [deleteTokens, setDeleteTokens] = useState<ConfirmationToken[]>([]);
 
   const formatBytes = (bytes: number): string => {
     const units = ['B', 'KB', 'MB', 'GB', 'TB'];
@@ -110,63 +83,81 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
     }
   };
 
-  const executeStep = async (step: ReallocationStep) => {
-    if (step.action_type === 'UserManual') {
-      // User must do this manually
-      return;
-    }
+  const showFailure = (err: unknown) => {
+    const failure = typeof err === 'object' && err !== null && 'partial_failure' in err
+      ? (err as PartialFailureError)
+      : null;
+    setPartialFailure(failure?.partial_failure ?? null);
+    setError(failure ? failure.message : String(err));
+    setCurrentStep('error');
+  };
 
-    if (step.action_type === 'AppAutomated') {
-      // App can do this automatically
-      if (step.title.toLowerCase().includes('expand')) {
-        // Execute expand
-        await invoke('expand_partition', {
-          partitionId: plan!.target_partition_id,
-          targetSize: plan!.target_new_size,
-        });
+  // Check the plan is still current and, when it deletes partitions, have the
+  // user confirm exactly what each delete destroys before anything runs
+  const reviewPlan = async () => {
+    if (!plan) return;
+
+    setError(null);
+    setPartialFailure(null);
+
+    try {
+      // The disk may have changed since the plan was made; never run a stale plan
+      const validation = await invoke<PlanValidation>('validate_reallocation_plan', { plan });
+      if (!validation.is_valid) {
+        const blocking = validation.issues.filter((issue) => issue.blocking);
+        throw new Error(`The disk changed since this plan was made. Create a new plan.\n${blocking.map((issue) => issue.message).join('\n')}`);
       }
-    }
 
-    if (step.action_type === 'AppAssistedManual') {
-      // App guides but user confirms
-      if (step.title.toLowerCase().includes('delete')) {
-        // Execute delete partition command
-        // The plan's source_partitions contains the partitions to delete
-        for (const source of plan!.source_partitions) {
-          if ('DeleteEntirely' in source.action || 'ShrinkAndDelete' in source.action) {
-            console.log(`Deleting partition: ${source.partition_id}`);
-            await invoke('delete_partition', {
-              partitionId: source.partition_id,
-            });
-          }
+      // Deletes are confirmed per partition, by its current id
+      const current = (id: string) => validation.renumbered[id] ?? id;
+      const tokens: ConfirmationToken[] = [];
+      for (const source of plan.source_partitions) {
+        if (source.action === 'DeleteEntirely') {
+          tokens.push(await invoke<ConfirmationToken>('prepare_destructive', {
+            op: 'DeletePartition',
+            target: current(source.partition_id),
+          }));
         }
       }
+
+      if (tokens.length === 0) {
+        await executePlan([]);
+      } else {
+        setDeleteTokens(tokens);
+        setCurrentStep('confirm');
+      }
+    } catch (err) {
+      showFailure(err);
     }
   };
 
-  const executePlan = async () => {
+  const executePlan = async (tokens: ConfirmationToken[]) => {
     if (!plan) return;
 
     setCurrentStep('executing');
     setCurrentExecutingStep(0);
+    setPlanProgress(null);
 
-    try {
-      for (let i = 0; i < plan.steps.length; i++) {
-        setCurrentExecutingStep(i);
-        const step = plan.steps[i];
+    const unlisten = await listen<PlanProgress>('plan-progress', (event) => {
+      setPlanProgress(event.payload);
+      setCurrentExecutingStep(event.payload.step_index);
+    });
 
-        if (step.can_automate) {
-          await executeStep(step);
-        }
-
-        // Wait a bit for UI update
-        await new Promise(resolve => setTimeout(resolve, 500));
+    try {
+      const confirmations: Record<string, string> = {};
+      for (const token of tokens) {
+        confirmations[token.target] = token.token;
       }
 
+      // All steps run in one backend transaction, which rolls back what it can on failure
+      await invoke('execute_reallocation_plan', { plan, confirmations });
+
       setCurrentStep('complete');
     } catch (err) {
-      setError(String(err));
-      setCurrentStep('error');
+      showFailure(err);
+    } finally {
+      setDeleteTokens([]);
+      unlisten();
     }
   };
 
@@ -175,6 +166,9 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
     setPlan(null);
     setError(null);
     setCurrentExecutingStep(0);
+    setPlanProgress(null);
+    setPartialFailure(null);
+    setDeleteTokens([]);
     onClose();
   };
 
@@ -322,7 +316,7 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
           <Button appearance="secondary" onClick={handleClose}>
             Cancel
           </Button>
-          <Button appearance="primary" onClick={executePlan} disabled={plan.warnings.length === 0}>
+          <Button appearance="primary" onClick={reviewPlan} disabled={plan.warnings.length === 0}>
             {plan.warnings.length > 0 ? 'I have backed up my data - Continue' : 'Execute Plan'}
           </Button>
         </DialogActions>
@@ -330,10 +324,40 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
     );
   };
 
+  const renderConfirmStep = () => (
+    <>
+      <DialogTitle>Confirm Partition Deletion</DialogTitle>
+      <DialogContent className={styles.content}>
+        <MessageBar intent="warning">
+          <Warning24Regular />
+          <Text>The plan deletes these partitions. This cannot be undone.</Text>
+        </MessageBar>
+        <div className={styles.section}>
+          {deleteTokens.map((token) => (
+            <Card key={token.token} className={styles.partitionCard}>
+              <div className={styles.partitionInfo}>
+                <Delete24Regular className={styles.iconDelete} />
+                <Text>{token.description}</Text>
+              </div>
+            </Card>
+          ))}
+        </div>
+      </DialogContent>
+      <DialogActions>
+        <Button appearance="secondary" onClick={() => { setDeleteTokens([]); setCurrentStep('plan'); }}>
+          Back
+        </Button>
+        <Button appearance="primary" onClick={() => executePlan(deleteTokens)}>
+          Delete and Continue
+        </Button>
+      </DialogActions>
+    </>
+  );
+
   const renderExecutingStep = () => {
     if (!plan) return null;
 
-    const progress = ((currentExecutingStep + 1) / plan.steps.length) * 100;
+    const progress = planProgress?.overall_percent ?? 0;
 
     return (
       <>
@@ -344,6 +368,7 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
               Step {currentExecutingStep + 1} of {plan.steps.length}
             </Text>
             <Text>{plan.steps[currentExecutingStep]?.title}</Text>
+            {planProgress && <Text size={200}>{planProgress.message}</Text>}
             <ProgressBar value={progress / 100} />
             <Text size={200}>{progress.toFixed(0)}% complete</Text>
           </div>
@@ -396,8 +421,43 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
       <DialogTitle>Error</DialogTitle>
       <DialogContent className={styles.content}>
         <MessageBar intent="error">
-          <Text>{error || 'An unknown error occurred'}</Text>
+          <Text>{partialFailure ? `${partialFailure.operation} failed at "${partialFailure.failed_step}": ${partialFailure.reason}` : error || 'An unknown error occurred'}</Text>
         </MessageBar>
+        {partialFailure && (
+          <div className={styles.section}>
+            {partialFailure.rolled_back.length > 0 && (
+              <>
+                <Text weight="semibold">Rolled back automatically:</Text>
+                {partialFailure.rolled_back.map((step, idx) => (
+                  <Text key={idx} size={200}>{step}</Text>
+                ))}
+              </>
+            )}
+            {partialFailure.completed.length === 0 ? (
+              <Text>The disk is back to its original layout.</Text>
+            ) : (
+              <>
+                <Text weight="semibold">Still in effect:</Text>
+                {partialFailure.completed.map((step, idx) => (
+                  <Text key={idx} size={200}>{step.description} ({step.before} → {step.after})</Text>
+                ))}
+              </>
+            )}
+            {partialFailure.manual_steps.length > 0 && (
+              <>
+                <Text weight="semibold">To finish by hand:</Text>
+                {partialFailure.manual_steps.map((step, idx) => (
+                  <Text key={idx} size={200}>{step}</Text>
+                ))}
+              </>
+            )}
+            {partialFailure.completed.length > 0 && partialFailure.table_backups.length > 0 && (
+              <Text size={200}>
+                The partition table from before the operation is saved in {partialFailure.table_backups.join(', ')}.
+              </Text>
+            )}
+          </div>
+        )}
       </DialogContent>
       <DialogActions>
         <Button appearance="secondary" onClick={() => setCurrentStep('input')}>
@@ -417,6 +477,7 @@ export const SpaceReallocationWizard: React.FC<SpaceReallocationWizardProps> = (
           {currentStep === 'input' && renderInputStep()}
           {currentStep === 'analyzing' && renderAnalyzingStep()}
           {currentStep === 'plan' && renderPlanStep()}
+          {currentStep === 'confirm' && renderConfirmStep()}
           {currentStep === 'executing' && renderExecutingStep()}
           {currentStep === 'complete' && renderCompleteStep()}
           {currentStep === 'error' && renderErrorStep()}
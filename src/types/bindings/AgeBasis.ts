@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Which timestamp an item's age is measured from
+ */
+export type AgeBasis = "Modified" | "Accessed";
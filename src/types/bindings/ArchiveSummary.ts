@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ArchiveEntry } from "./ArchiveEntry";
+
+/**
+ * What an archive contains, read without extracting it
+ */
+export type ArchiveSummary = { format: string, entry_count: number, uncompressed_size: number, top_entries: Array<ArchiveEntry>, truncated: boolean, };
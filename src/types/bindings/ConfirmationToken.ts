@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DestructiveOp } from "./DestructiveOp";
+
+/**
+ * A single-use permission to run one destructive operation on one target
+ */
+export type ConfirmationToken = { token: string, op: DestructiveOp, target: string, description: string, expires_in_secs: number, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgeBasis } from "./AgeBasis";
+import type { PreviewAction } from "./PreviewAction";
+
+/**
+ * One resolved path of a deletion preview
+ */
+export type DeletionPreviewItem = { path: string, size: number, age_days: number | null, age_basis: AgeBasis, action: PreviewAction, skip_reason: string | null, };
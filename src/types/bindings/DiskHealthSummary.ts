@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthStatus } from "./HealthStatus";
+import type { HealthWarning } from "./HealthWarning";
+import type { SmartStatus } from "./SmartStatus";
+
+/**
+ * Everything the health overview shows for one disk
+ */
+export type DiskHealthSummary = { disk_id: string, device_path: string, model: string, total_size: number, partition_count: number, 
+/**
+ * Used and free space across the disk's mounted volumes
+ */
+used_space: number, free_space: number, smart: SmartStatus | null, temperature: number | null, warnings: Array<HealthWarning>, 
+/**
+ * Worst of the SMART verdict and the warnings; Unknown when SMART couldn't be read and nothing is wrong
+ */
+status: HealthStatus, };
@@ -0,0 +1,52 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskStatus } from "./DiskStatus";
+import type { PartitionInfo } from "./PartitionInfo";
+import type { PartitionTableType } from "./PartitionTableType";
+
+/**
+ * Represents a physical disk
+ */
+export type DiskInfo = { 
+/**
+ * Unique identifier for the disk
+ */
+id: string, 
+/**
+ * Device path (e.g., "/dev/sda" on Linux, "\\\\.\\PhysicalDrive0" on Windows)
+ */
+device_path: string, 
+/**
+ * Disk model name
+ */
+model: string, 
+/**
+ * Total size in bytes
+ */
+total_size: number, 
+/**
+ * Partition table type
+ */
+table_type: PartitionTableType, 
+/**
+ * List of partitions on this disk
+ */
+partitions: Array<PartitionInfo>, 
+/**
+ * Disk serial number (if available)
+ */
+serial_number: string | null, 
+/**
+ * Health status
+ */
+status: DiskStatus, 
+/**
+ * Largest contiguous unallocated region (bytes)
+ * 
+ * This and `total_free` are filled in from the partition layout by the
+ * disk provider; platform code leaves them at 0.
+ */
+largest_free_block: number, 
+/**
+ * Total unallocated space across all gaps (bytes)
+ */
+total_free: number, };
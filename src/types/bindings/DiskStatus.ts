@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SmartStatus } from "./SmartStatus";
+
+/**
+ * Disk health status
+ */
+export type DiskStatus = { 
+/**
+ * Whether the disk is online and accessible
+ */
+is_online: boolean, 
+/**
+ * Whether the disk has any errors
+ */
+has_errors: boolean, 
+/**
+ * SMART status (if available)
+ */
+smart_status: SmartStatus | null, };
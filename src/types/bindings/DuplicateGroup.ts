@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Files with identical contents, found by `find_duplicates`
+ */
+export type DuplicateGroup = { size: number, paths: Array<string>, reclaimable: number, };
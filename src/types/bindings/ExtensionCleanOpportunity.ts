@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SafetyLevel } from "./SafetyLevel";
+
+/**
+ * All files of one extension across the cleaning roots
+ */
+export type ExtensionCleanOpportunity = { extension: string, safety: SafetyLevel, file_count: number, total_size: number, category_ids: Array<string>, paths: Array<string>, };
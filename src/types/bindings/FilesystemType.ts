@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Filesystem type
+ */
+export type FilesystemType = "NTFS" | "Ext2" | "Ext3" | "Ext4" | "Btrfs" | "XFS" | "FAT32" | "ExFAT" | "APFS" | "HFSPlus" | "Swap" | "RAW" | "Unknown";
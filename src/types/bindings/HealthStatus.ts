@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Health status
+ */
+export type HealthStatus = "Good" | "Warning" | "Critical" | "Unknown";
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthStatus } from "./HealthStatus";
+
+/**
+ * One finding about a disk
+ */
+export type HealthWarning = { severity: HealthStatus, message: string, };
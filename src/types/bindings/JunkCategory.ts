@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JunkItem } from "./JunkItem";
+
+export type JunkCategory = { id: string, name: string, description: string, items: Array<JunkItem>, total_size: number, icon: string, filtered_size: number, filtered_count: number, unreadable_count: number, };
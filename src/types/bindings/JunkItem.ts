@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AgeBasis } from "./AgeBasis";
+import type { ArchiveSummary } from "./ArchiveSummary";
+import type { SafetyLevel } from "./SafetyLevel";
+
+export type JunkItem = { path: string, name: string, size: number, description: string, age_days: number | null, requires_reboot: boolean, safety: SafetyLevel, archive: ArchiveSummary | null, age_basis: AgeBasis, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * A region of the disk in a simulated layout
+ */
+export type LayoutSegment = { 
+/**
+ * None for unallocated space
+ */
+partition_id: string | null, label: string, start_offset: number, size: number, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Partition flags
+ */
+export type PartitionFlag = "Boot" | "Hidden" | "System" | "ReadOnly" | "Reserved";
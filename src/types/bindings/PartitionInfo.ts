@@ -0,0 +1,74 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FilesystemType } from "./FilesystemType";
+import type { PartitionFlag } from "./PartitionFlag";
+import type { PartitionType } from "./PartitionType";
+
+/**
+ * Represents a partition on a disk
+ */
+export type PartitionInfo = { 
+/**
+ * Unique identifier for the partition
+ */
+id: string, 
+/**
+ * Partition number (1-based)
+ */
+number: number, 
+/**
+ * Device path (e.g., "/dev/sda1", "D:")
+ */
+device_path: string, 
+/**
+ * Partition label/name (if any)
+ */
+label: string | null, 
+/**
+ * Start offset in bytes
+ */
+start_offset: number, 
+/**
+ * Total size in bytes
+ */
+total_size: number, 
+/**
+ * Used space in bytes (if available)
+ */
+used_space: number | null, 
+/**
+ * Partition type
+ */
+partition_type: PartitionType, 
+/**
+ * Filesystem type
+ */
+filesystem: FilesystemType, 
+/**
+ * Mount point (e.g., "/" on Linux, "C:\" on Windows)
+ */
+mount_point: string | null, 
+/**
+ * Whether the partition is mounted
+ */
+is_mounted: boolean, 
+/**
+ * Whether the mount point responds to a metadata read
+ * (false for lettered/mounted volumes on failing or offline devices)
+ */
+is_accessible: boolean, 
+/**
+ * Partition flags
+ */
+flags: Array<PartitionFlag>, 
+/**
+ * Identifier that follows a partition across renumbering: the GPT
+ * partition GUID (PARTUUID) on Linux/macOS, the volume GUID on Windows.
+ * On MBR disks the PARTUUID is `<disk-signature>-<NN>`, which changes
+ * with the partition number
+ */
+uuid: string | null, 
+/**
+ * Holds the running OS (`/`, or the volume with the Windows directory).
+ * Deleting, shrinking or unmounting it is refused without `force`
+ */
+is_system_volume: boolean, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Type of partition table
+ */
+export type PartitionTableType = "MBR" | "GPT" | "Unknown";
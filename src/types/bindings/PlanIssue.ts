@@ -0,0 +1,13 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Something that changed on disk since a plan was made
+ */
+export type PlanIssue = { partition_id: string, 
+/**
+ * The plan step this makes unsafe, if it maps to one
+ */
+step_number: number | null, message: string, 
+/**
+ * The plan must be recreated before it runs
+ */
+blocking: boolean, };
@@ -0,0 +1,17 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Overall progress of a multi-step plan
+ */
+export type PlanProgress = { 
+/**
+ * 0-based index of the running step
+ */
+step_index: number, step_count: number, step_title: string, 
+/**
+ * Progress of the running step (0-100)
+ */
+step_percent: number, 
+/**
+ * Progress of the whole plan (0-100)
+ */
+overall_percent: number, message: string, };
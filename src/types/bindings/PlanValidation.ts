@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { PlanIssue } from "./PlanIssue";
+
+/**
+ * Whether a plan still matches the disk it was made for
+ */
+export type PlanValidation = { 
+/**
+ * No blocking issues: the plan can run as-is
+ */
+is_valid: boolean, issues: Array<PlanIssue>, 
+/**
+ * Plan partition id -> current id, for partitions renumbered since planning
+ */
+renumbered: { [key in string]?: string }, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * What deleting a path with the given options would do
+ */
+export type PreviewAction = "Delete" | "Trash" | "DeferUntilRestart" | "Reclaim" | "Skip";
@@ -0,0 +1,47 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { LayoutSegment } from "./LayoutSegment";
+import type { ReallocationStep } from "./ReallocationStep";
+import type { ReallocationStrategy } from "./ReallocationStrategy";
+import type { SourcePartitionPlan } from "./SourcePartitionPlan";
+
+/**
+ * Plan for reallocating space from one partition to another
+ */
+export type ReallocationPlan = { 
+/**
+ * The partition the plan is built around: grown for `GrowTarget`, left
+ * as-is for `ShrinkNeighborOnly` (its neighbor shrinks) and `ConsolidateFreeSpace`
+ */
+target_partition_id: string, 
+/**
+ * The target's stable UUID, so it can be found again after renumbering
+ */
+target_partition_uuid: string | null, 
+/**
+ * The target's size when the plan was made (bytes)
+ */
+target_current_size: number, strategy: ReallocationStrategy, 
+/**
+ * The partition(s) that will be shrunk/deleted to free space
+ */
+source_partitions: Array<SourcePartitionPlan>, 
+/**
+ * Total space that will be freed (bytes)
+ */
+total_space_freed: number, 
+/**
+ * New size for target partition after reallocation (bytes)
+ */
+target_new_size: number, 
+/**
+ * Steps the user must follow
+ */
+steps: Array<ReallocationStep>, 
+/**
+ * Warnings about this operation
+ */
+warnings: Array<string>, 
+/**
+ * Disk layout after the plan runs, in offset order
+ */
+simulated_layout: Array<LayoutSegment>, };
@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { StepActionType } from "./StepActionType";
+import type { StepKind } from "./StepKind";
+
+export type ReallocationStep = { step_number: number, title: string, description: string, action_type: StepActionType, can_automate: boolean, kind: StepKind, 
+/**
+ * The partition a delete, shrink or expand acts on
+ */
+partition_id: string | null, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Phases of a resize operation
+ */
+export type ResizePhase = "Validating" | "CheckingFilesystem" | "CreatingBackup" | "ResizingFilesystem" | "UpdatingPartitionTable" | "ExpandingFilesystem" | "Verifying" | "Complete" | "Error";
@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ResizePhase } from "./ResizePhase";
+
+/**
+ * Progress update for a resize operation
+ */
+export type ResizeProgress = { 
+/**
+ * Current operation phase
+ */
+phase: ResizePhase, 
+/**
+ * Overall progress percentage (0-100)
+ */
+percent: number, 
+/**
+ * Current status message
+ */
+message: string, 
+/**
+ * Whether the operation can be cancelled at this point
+ */
+can_cancel: boolean, 
+/**
+ * The tool reports no progress, so `percent` only marks the phase's start
+ */
+indeterminate: boolean, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * Confidence that deleting a junk item won't log the user out or break an app
+ */
+export type SafetyLevel = "Safe" | "Caution" | "Risky";
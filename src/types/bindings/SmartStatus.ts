@@ -0,0 +1,27 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HealthStatus } from "./HealthStatus";
+
+/**
+ * SMART status information
+ */
+export type SmartStatus = { 
+/**
+ * Overall health assessment
+ */
+health: HealthStatus, 
+/**
+ * Temperature in Celsius (if available)
+ */
+temperature: number | null, 
+/**
+ * Power-on hours (if available)
+ */
+power_on_hours: number | null, 
+/**
+ * SSD wear: percentage of rated write endurance used (may exceed 100)
+ */
+percent_used: number | null, 
+/**
+ * Total bytes written over the drive's lifetime (if available)
+ */
+data_written_bytes: number | null, };
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SourcePartitionAction } from "./SourcePartitionAction";
+
+export type SourcePartitionPlan = { partition_id: string, uuid: string | null, partition_label: string, current_size: number, used_space: number | null, action: SourcePartitionAction, };
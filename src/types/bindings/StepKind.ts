@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * What a step does, for estimating how long it takes
+ */
+export type StepKind = "Backup" | "Delete" | "Shrink" | "Expand" | "Create";
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+/**
+ * A supported way to shrink or remove a system file
+ */
+export type SystemFileAction = { label: string, instructions: string, commands: Array<Array<string>>, requires_admin: boolean, requires_reboot: boolean, };
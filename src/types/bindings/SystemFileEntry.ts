@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SystemFileAction } from "./SystemFileAction";
+import type { SystemFileKind } from "./SystemFileKind";
+
+export type SystemFileEntry = { kind: SystemFileKind, path: string, size: number, used: number | null, description: string, actions: Array<SystemFileAction>, };
@@ -1,15 +1,47 @@
-export interface JunkItem {
-    path: string;
-    name: string;
-    size: number;
-    description: string;
+// Generated from the Rust types by ts-rs (`cargo test` in src-tauri)
+export type { AgeBasis } from './bindings/AgeBasis';
+export type { ArchiveSummary } from './bindings/ArchiveSummary';
+export type { DeletionPreviewItem } from './bindings/DeletionPreviewItem';
+export type { ExtensionCleanOpportunity } from './bindings/ExtensionCleanOpportunity';
+export type { JunkCategory } from './bindings/JunkCategory';
+export type { JunkItem } from './bindings/JunkItem';
+export type { PreviewAction } from './bindings/PreviewAction';
+export type { SafetyLevel } from './bindings/SafetyLevel';
+
+import type { AgeBasis } from './bindings/AgeBasis';
+
+export interface CleaningOptions {
+    min_age_days: number | null; // Only include entries older than this
+    dry_run: boolean;
+    skip_errors: boolean;
+    prune_empty_dirs?: boolean; // Remove directories left empty after cleaning
+    preserve_root_dirs?: boolean; // Never remove the cleaning roots themselves (default true)
+    inspect_archives?: boolean; // List the contents of large archives in Downloads
+    use_trash?: boolean; // Move items to the trash / Recycle Bin instead of deleting permanently
+    age_basis?: AgeBasis; // Timestamp min_age_days is measured from (default Modified)
 }
 
-export interface JunkCategory {
+export interface CleaningProfile {
     id: string;
     name: string;
     description: string;
-    items: JunkItem[];
-    total_size: number;
-    icon: string;
+    options: CleaningOptions;
+    category_ids: string[]; // Empty means every category
+    min_item_size: number; // Smaller items are left alone (0 = no limit)
+}
+
+export interface TrashedItem {
+    id: string; // Pass back to restore_trashed
+    name: string;
+    original_path: string;
+    deleted_at: number; // Unix timestamp (seconds)
+    size: number | null; // Null for folders
+    is_dir: boolean;
+}
+
+// Payload of "junk-delete-progress", emitted after each chunk of a cleaning run
+export interface JunkDeleteProgress {
+    processed: number; // Paths handled so far, across all categories of the run
+    total: number;
+    deleted_size: number;
 }
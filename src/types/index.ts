@@ -1,9 +1,81 @@
+// Generated from the Rust types by ts-rs (`cargo test` in src-tauri)
+export type { ConfirmationToken } from './bindings/ConfirmationToken';
+export type { DestructiveOp } from './bindings/DestructiveOp';
+export type { DiskInfo } from './bindings/DiskInfo';
+export type { DiskStatus } from './bindings/DiskStatus';
+export type { DuplicateGroup } from './bindings/DuplicateGroup';
+export type { FilesystemType } from './bindings/FilesystemType';
+export type { LayoutSegment } from './bindings/LayoutSegment';
+export type { PartitionFlag } from './bindings/PartitionFlag';
+export type { PartitionInfo } from './bindings/PartitionInfo';
+export type { PartitionTableType } from './bindings/PartitionTableType';
+export type { PartitionType } from './bindings/PartitionType';
+export type { PlanIssue } from './bindings/PlanIssue';
+export type { PlanProgress } from './bindings/PlanProgress';
+export type { PlanValidation } from './bindings/PlanValidation';
+export type { ReallocationPlan } from './bindings/ReallocationPlan';
+export type { ReallocationStep } from './bindings/ReallocationStep';
+export type { ReallocationStrategy } from './bindings/ReallocationStrategy';
+export type { ResizePhase } from './bindings/ResizePhase';
+export type { ResizeProgress } from './bindings/ResizeProgress';
+export type { SourcePartitionAction } from './bindings/SourcePartitionAction';
+export type { SourcePartitionPlan } from './bindings/SourcePartitionPlan';
+export type { StepKind } from './bindings/StepKind';
+
 export interface FileNode {
     name: string;
     path: string;
-    size: number;
+    size: number; // Recursive total of everything below this node
+    own_size: number; // Files directly inside this directory only (equals size for files)
+    logical_size: number;
+    allocated_size: number;
     is_dir: boolean;
     children?: FileNode[];
     last_modified: number;
     file_count: number;
+    skipped_paths: string[]; // System directories excluded from the scan (root only)
+    size_unknown: boolean; // Size not available without scanning (reported as 0)
+    is_cloud_placeholder: boolean; // Dehydrated OneDrive/iCloud file (no local content)
+    is_estimate: boolean; // On-disk size approximated from cluster size (cluster_estimate scans)
+    summarized?: boolean; // Children dropped to stay within the scan's limits; sizes are complete
+}
+
+// Extrapolated from a shallow sample; show as "~2 min"
+export interface ScanEstimate {
+    path: string;
+    estimated_files: number;
+    estimated_seconds: number;
+    media_type: 'Ssd' | 'Hdd' | 'Unknown';
+    sampled_dirs: number;
+}
+
+// Payload of the `scan-invalidated` event emitted for directories passed to watch_dir
+export interface ScanInvalidated {
+    root: string;
+    paths: string[]; // Changed paths; cached scans of these and their ancestors were dropped
+}
+
+// A completed sub-step of a compound partition operation
+export interface StepRecord {
+    description: string;
+    before: string; // Layout before the step
+    after: string; // Layout after the step
+}
+
+// A split, reallocation plan or move sequence that stopped partway
+export interface PartialFailure {
+    operation: string;
+    failed_step: string;
+    reason: string;
+    completed: StepRecord[]; // Still in effect
+    rolled_back: string[]; // Completed, then undone
+    manual_steps: string[]; // Left for the user to do
+    table_backups: string[]; // Partition tables saved before the first step
+}
+
+// Error of a compound partition command that failed after changing the disk;
+// other command errors are plain strings
+export interface PartialFailureError {
+    message: string;
+    partial_failure: PartialFailure;
 }